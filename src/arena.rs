@@ -0,0 +1,91 @@
+use crate::alloc_api::{AllocError, Allocator, Vec};
+use core::{alloc::Layout, cell::RefCell, ptr::NonNull};
+
+/// Scope of allocations from `A`, such as a reference to a [`SlabAllocator`](crate::SlabAllocator), which are all
+/// freed at once as the arena is [reset](SlabArena::reset) or dropped.
+///
+/// Every allocation is recorded in a list grown in `A`, so request- or frame-scoped work can allocate freely without
+/// freeing each allocation itself. Values moved into the arena are never dropped, only freed.
+///
+/// ```ignore
+/// let arena = SlabArena::new_in(&allocator);
+/// let packet = arena.allocate_value(Packet::new())?;
+/// ```
+pub struct SlabArena<A: Allocator + Clone> {
+    allocations: RefCell<Vec<(NonNull<u8>, Layout), A>>,
+    allocator: A,
+}
+
+impl<A: Allocator + Clone> SlabArena<A> {
+    /// Creates an arena allocating from `allocator`.
+    pub fn new_in(allocator: A) -> Self {
+        Self {
+            allocations: RefCell::new(Vec::new_in(allocator.clone())),
+            allocator,
+        }
+    }
+
+    /// Allocates memory of `layout`, which lives until the arena is reset or dropped.
+    ///
+    /// # Errors
+    ///
+    /// - If the memory, or the record of it, can't be allocated.
+    pub fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let mut allocations = self.allocations.borrow_mut();
+        allocations.try_reserve(1).map_err(|_| AllocError)?;
+
+        let ptr = self.allocator.allocate(layout)?;
+        allocations.push((ptr.cast(), layout));
+
+        Ok(ptr)
+    }
+
+    /// Moves `value` into the arena, returning a reference to it that lives until the arena is reset or dropped.
+    ///
+    /// The value is never dropped, so must not own resources that need releasing.
+    ///
+    /// # Errors
+    ///
+    /// - If the memory, or the record of it, can't be allocated.
+    #[allow(clippy::mut_from_ref)]
+    pub fn allocate_value<T>(&self, value: T) -> Result<&mut T, AllocError> {
+        let mut ptr = self.allocate(Layout::new::<T>())?.cast::<T>();
+
+        // Safety: Memory was just allocated for a `T`, and is only referenced by the returned reference, which lives
+        //         no longer than the memory.
+        unsafe {
+            ptr.write(value);
+            Ok(ptr.as_mut())
+        }
+    }
+
+    /// Number of allocations held by the arena.
+    pub fn allocation_count(&self) -> usize {
+        self.allocations.borrow().len()
+    }
+
+    /// Frees every allocation held by the arena, keeping its record of allocations for reuse.
+    pub fn reset(&mut self) {
+        for (ptr, layout) in self.allocations.get_mut().drain(..) {
+            // Safety: Memory was allocated from the allocator with `layout`, and as the arena is borrowed mutably,
+            //         nothing borrowed from it is still referenced.
+            unsafe {
+                self.allocator.deallocate(ptr, layout);
+            }
+        }
+    }
+}
+
+impl<A: Allocator + Clone> Drop for SlabArena<A> {
+    fn drop(&mut self) {
+        self.reset();
+    }
+}
+
+impl<A: Allocator + Clone> core::fmt::Debug for SlabArena<A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SlabArena")
+            .field("allocation_count", &self.allocation_count())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,180 @@
+use crate::alloc_api::{AllocError, Allocator};
+use core::{alloc::Layout, ptr::NonNull};
+
+/// The backing allocator of a [`SlabAllocator`](crate::SlabAllocator), held once at a fixed address, so its size
+/// classes and side tables share it through [`BackingRef`]s rather than clones of it.
+///
+/// Allocators given by value are moved into memory they allocate themselves, so the slab allocator can be moved
+/// without moving its backing allocator. Allocators of slab allocators created in a const context are borrowed for
+/// the life of the program instead.
+pub(crate) struct Backing<A: Allocator> {
+    allocator: NonNull<A>,
+
+    /// Whether the allocator was moved into memory it allocated itself, so is dropped, and its memory freed, along
+    /// with this.
+    owned: bool,
+}
+
+// Safety: The allocator is owned, or borrowed for the life of the program, and only shared through handles held
+//         alongside this.
+unsafe impl<A: Allocator + Send + Sync> Send for Backing<A> {}
+
+// Safety: Only shared access to the allocator is given.
+unsafe impl<A: Allocator + Sync> Sync for Backing<A> {}
+
+impl<A: Allocator> Backing<A> {
+    const LAYOUT: Layout = Layout::new::<A>();
+
+    /// Moves `allocator` into memory allocated by itself.
+    ///
+    /// # Errors
+    ///
+    /// - If `allocator` can't allocate the memory to hold itself.
+    pub fn new(allocator: A) -> Result<Self, AllocError> {
+        // Zero-sized allocators, such as `Global`, are held at a dangling address, as allocators needn't serve
+        // zero-sized allocations.
+        let memory = if Self::LAYOUT.size() == 0 {
+            NonNull::dangling()
+        } else {
+            allocator.allocate(Self::LAYOUT)?.cast::<A>()
+        };
+
+        // Safety: Memory was just allocated with the layout of `A`, or `A` is zero-sized.
+        unsafe {
+            memory.write(allocator);
+        }
+
+        Ok(Self {
+            allocator: memory,
+            owned: true,
+        })
+    }
+
+    /// Borrows `allocator` for the life of the program, so it can be held in a const context.
+    pub const fn from_static(allocator: &'static A) -> Self {
+        Self {
+            // Safety: References are never null.
+            allocator: unsafe { NonNull::new_unchecked(core::ptr::from_ref(allocator).cast_mut()) },
+            owned: false,
+        }
+    }
+
+    pub fn get(&self) -> &A {
+        // Safety: The allocator lives at least as long as this.
+        unsafe { self.allocator.as_ref() }
+    }
+
+    /// Handle to the allocator, which must be dropped before this.
+    pub const fn handle(&self) -> BackingRef<A> {
+        BackingRef {
+            allocator: self.allocator,
+        }
+    }
+}
+
+impl<A: Allocator> Drop for Backing<A> {
+    fn drop(&mut self) {
+        if !self.owned {
+            return;
+        }
+
+        // Safety: The allocator was moved into this memory, and every handle to it has been dropped. It is moved out
+        //         before its memory is freed, so it can still free that memory.
+        let allocator = unsafe { self.allocator.read() };
+
+        if Self::LAYOUT.size() > 0 {
+            // Safety: Memory was allocated by the allocator with the layout of `A`.
+            unsafe {
+                allocator.deallocate(self.allocator.cast(), Self::LAYOUT);
+            }
+        }
+    }
+}
+
+impl<A: Allocator> core::fmt::Debug for Backing<A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Backing")
+            .field("owned", &self.owned)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Copyable handle to a [`Backing`] allocator, which allocates from it.
+///
+/// Handles are only held by the slab allocator holding the backing allocator, and are dropped before it.
+pub(crate) struct BackingRef<A: Allocator> {
+    allocator: NonNull<A>,
+}
+
+// Safety: Handles only give shared access to the allocator.
+unsafe impl<A: Allocator + Sync> Send for BackingRef<A> {}
+
+// Safety: Handles only give shared access to the allocator.
+unsafe impl<A: Allocator + Sync> Sync for BackingRef<A> {}
+
+impl<A: Allocator> Clone for BackingRef<A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A: Allocator> Copy for BackingRef<A> {}
+
+impl<A: Allocator> BackingRef<A> {
+    fn get(&self) -> &A {
+        // Safety: Handles are dropped before the backing allocator.
+        unsafe { self.allocator.as_ref() }
+    }
+}
+
+// Safety: Every handle allocates from the same allocator, so memory blocks are valid across copies.
+unsafe impl<A: Allocator> Allocator for BackingRef<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.get().allocate(layout)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.get().allocate_zeroed(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Safety: Caller is required to uphold the safety invariants.
+        unsafe { self.get().deallocate(ptr, layout) }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: Caller is required to uphold the safety invariants.
+        unsafe { self.get().grow(ptr, old_layout, new_layout) }
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: Caller is required to uphold the safety invariants.
+        unsafe { self.get().grow_zeroed(ptr, old_layout, new_layout) }
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: Caller is required to uphold the safety invariants.
+        unsafe { self.get().shrink(ptr, old_layout, new_layout) }
+    }
+}
+
+impl<A: Allocator> core::fmt::Debug for BackingRef<A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BackingRef").finish_non_exhaustive()
+    }
+}
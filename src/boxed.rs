@@ -0,0 +1,190 @@
+use crate::alloc_api::{AllocError, Allocator};
+use core::{
+    alloc::Layout,
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    ptr::{self, NonNull},
+};
+
+/// Owned pointer to a `T` allocated from `A`, such as a reference to a [`SlabAllocator`](crate::SlabAllocator),
+/// which returns the object to its size class as it is dropped.
+///
+/// This mirrors `Box<T, A>`, for allocators used with the `allocator-api2` feature, or kept out of the global heap.
+///
+/// ```ignore
+/// let node = SlabBox::new_in(Node::default(), &allocator);
+/// ```
+pub struct SlabBox<T, A: Allocator> {
+    ptr: NonNull<T>,
+    allocator: A,
+}
+
+// Safety: The value is owned by the box, so may be sent along with it.
+unsafe impl<T: Send, A: Allocator + Send> Send for SlabBox<T, A> {}
+
+// Safety: The value is only accessed through shared references to the box.
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for SlabBox<T, A> {}
+
+impl<T, A: Allocator> SlabBox<T, A> {
+    /// Moves `value` into an object allocated from `allocator`.
+    ///
+    /// # Panics
+    ///
+    /// - If the object can't be allocated.
+    pub fn new_in(value: T, allocator: A) -> Self {
+        Self::try_new_in(value, allocator).expect("failed to allocate object")
+    }
+
+    /// Moves `value` into an object allocated from `allocator`.
+    ///
+    /// # Errors
+    ///
+    /// - If the object can't be allocated.
+    pub fn try_new_in(value: T, allocator: A) -> Result<Self, AllocError> {
+        let ptr = allocator.allocate(Layout::new::<T>())?.cast::<T>();
+
+        // Safety: Object was just allocated for a `T`, and is not yet referenced.
+        unsafe {
+            ptr.write(value);
+        }
+
+        Ok(Self { ptr, allocator })
+    }
+
+    /// Moves `value` into an object allocated from `allocator`, pinning it there.
+    ///
+    /// # Panics
+    ///
+    /// - If the object can't be allocated.
+    pub fn pin_in(value: T, allocator: A) -> Pin<Self> {
+        Self::into_pin(Self::new_in(value, allocator))
+    }
+
+    /// Moves `value` into an object allocated from `allocator`, pinning it there.
+    ///
+    /// # Errors
+    ///
+    /// - If the object can't be allocated.
+    pub fn try_pin_in(value: T, allocator: A) -> Result<Pin<Self>, AllocError> {
+        Self::try_new_in(value, allocator).map(Self::into_pin)
+    }
+
+    /// Constructs a value in place in an object allocated from `allocator`, pinning it there, for values that must
+    /// know their address as they are constructed, such as intrusive list nodes.
+    ///
+    /// `init` must initialize the object it is given, returning the reference [`MaybeUninit::write`] returns. If
+    /// `init` panics, the object is freed.
+    ///
+    /// ```ignore
+    /// let node = SlabBox::try_pin_with_in(|object| object.write(Node::new()), &allocator)?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - If the object can't be allocated.
+    ///
+    /// # Panics
+    ///
+    /// - If `init` returns a reference to anything but the object it was given.
+    pub fn try_pin_with_in(
+        init: impl FnOnce(&mut MaybeUninit<T>) -> &mut T,
+        allocator: A,
+    ) -> Result<Pin<Self>, AllocError> {
+        let mut object = SlabBox::try_new_in(MaybeUninit::<T>::uninit(), allocator)?;
+
+        let object_ptr = object.as_mut_ptr();
+        assert!(
+            ptr::from_mut(init(&mut object)) == object_ptr,
+            "object wasn't initialized in place"
+        );
+
+        let (ptr, allocator) = SlabBox::into_raw(object);
+
+        // Safety: Object was allocated from `allocator` by a box, and has been initialized.
+        Ok(Self::into_pin(unsafe {
+            Self::from_raw_in(ptr.cast(), allocator)
+        }))
+    }
+
+    /// Pins the value of the box in its object, which it is never moved out of.
+    pub fn into_pin(this: Self) -> Pin<Self> {
+        // Safety: The value is only ever moved out of the box by value, which a pin never hands out.
+        unsafe { Pin::new_unchecked(this) }
+    }
+
+    /// Rebuilds a box from a pointer returned by [`SlabBox::into_raw`].
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been returned by [`SlabBox::into_raw`] on a box allocated from `allocator`, and not since
+    ///   rebuilt.
+    pub unsafe fn from_raw_in(ptr: NonNull<T>, allocator: A) -> Self {
+        Self { ptr, allocator }
+    }
+
+    /// Consumes the box without dropping its value or freeing its object, returning the pointer to the value and the
+    /// allocator it must be freed to.
+    pub fn into_raw(this: Self) -> (NonNull<T>, A) {
+        let this = ManuallyDrop::new(this);
+
+        // Safety: The allocator is read once, and the box is never dropped.
+        (this.ptr, unsafe { ptr::read(&raw const this.allocator) })
+    }
+
+    /// Moves the value out of the box, freeing its object.
+    pub fn into_inner(this: Self) -> T {
+        let (ptr, allocator) = Self::into_raw(this);
+
+        // Safety: Pointer is to a valid `T`, which is read once, as its object is freed below.
+        let value = unsafe { ptr.read() };
+
+        // Safety: Object was allocated from `allocator` with the layout of `T`.
+        unsafe {
+            allocator.deallocate(ptr.cast(), Layout::new::<T>());
+        }
+
+        value
+    }
+
+    /// The allocator the box was allocated from.
+    pub fn allocator(this: &Self) -> &A {
+        &this.allocator
+    }
+}
+
+// The value is held in its object, so moving the box never moves the value.
+impl<T, A: Allocator> Unpin for SlabBox<T, A> {}
+
+impl<T, A: Allocator> Deref for SlabBox<T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safety: Pointer is to a valid `T`, owned by the box.
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T, A: Allocator> DerefMut for SlabBox<T, A> {
+    fn deref_mut(&mut self) -> &mut T {
+        // Safety: Pointer is to a valid `T`, owned by the box, which is borrowed mutably.
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T, A: Allocator> Drop for SlabBox<T, A> {
+    fn drop(&mut self) {
+        // Safety: Pointer is to a valid `T`, owned by the box, and is never used again.
+        unsafe {
+            self.ptr.drop_in_place();
+            self.allocator
+                .deallocate(self.ptr.cast(), Layout::new::<T>());
+        }
+    }
+}
+
+impl<T: core::fmt::Debug, A: Allocator> core::fmt::Debug for SlabBox<T, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&**self, f)
+    }
+}
@@ -0,0 +1,546 @@
+use crate::{
+    AllocatorHooks, Clock, CorruptionHandler, DEFAULT_PAGE_CACHE_CAPACITY, DEFAULT_PAGE_SIZE,
+    DEFAULT_SIZE_CLASSES, DefaultRawMutex, LeakHandler, MAX_LOCK_SHARD_COUNT, MAX_SIZE_CLASS_COUNT,
+    MAX_SLAB_PAGE_COUNT, MemoryLimit, MemoryTagger, OomHandler, PageProtector, RandomSource,
+    SamplingHandler, SlabAllocator, SlabSelection, Topology,
+    alloc_api::{AllocError, Allocator},
+    panic_on_corruption,
+};
+use core::{marker::PhantomData, time::Duration};
+use lock_api::RawMutex;
+
+/// Settings of a single slab size class, applied as the allocator is built.
+#[derive(Clone, Copy)]
+pub(crate) struct SizeClassConfig {
+    pub object_size: usize,
+    pub slab_page_count: Option<usize>,
+    pub empty_slab_retention: usize,
+    pub slab_limit: usize,
+    pub quarantine_capacity: usize,
+    pub lock_shards: Option<(&'static dyn Topology, usize)>,
+}
+
+impl SizeClassConfig {
+    const DEFAULT: Self = Self {
+        object_size: 0,
+        slab_page_count: None,
+        empty_slab_retention: usize::MAX,
+        slab_limit: usize::MAX,
+        quarantine_capacity: 0,
+        lock_shards: None,
+    };
+}
+
+/// Configures a [`SlabAllocator`] before it is constructed.
+///
+/// Every setting defaults to that of [`SlabAllocator::new_in`], so only the settings that differ need be given.
+///
+/// ```ignore
+/// let allocator = SlabAllocatorBuilder::new_in(frames)
+///     .fallback(heap)
+///     .magazines(&TOPOLOGY)
+///     .size_classes(&[64, 128, 512, 2048])
+///     .slab_page_count::<2048>(4)
+///     .build()?;
+/// ```
+pub struct SlabAllocatorBuilder<
+    A: Allocator,
+    R: RawMutex = DefaultRawMutex,
+    const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE,
+    F: Allocator = A,
+> {
+    allocator: A,
+
+    /// Fallback allocator, or `None` if the backing allocator serves allocations no size class serves.
+    fallback: Option<F>,
+    topology: Option<&'static dyn Topology>,
+    random: Option<&'static dyn RandomSource>,
+    slab_selection: SlabSelection,
+    corruption_handler: CorruptionHandler,
+    oom_handler: Option<OomHandler>,
+    leak_handler: Option<LeakHandler>,
+    checked_deallocation: bool,
+    fallback_tracking: bool,
+    #[cfg(feature = "allocation-sites")]
+    allocation_sites: bool,
+    lazy_shrink: bool,
+    remote_frees: bool,
+    external_headers: bool,
+    metadata_allocator: Option<&'static (dyn Allocator + Sync)>,
+    memory_limit: Option<&'static MemoryLimit>,
+    memory_tagger: Option<&'static dyn MemoryTagger>,
+    guard_pages: Option<(usize, Option<&'static dyn PageProtector>)>,
+    secure_wipe: bool,
+    sampling: Option<(usize, &'static dyn Clock, SamplingHandler)>,
+    hooks: Option<&'static dyn AllocatorHooks>,
+    slab_decay: Option<(&'static dyn Clock, Duration)>,
+    size_classes: [SizeClassConfig; MAX_SIZE_CLASS_COUNT],
+    size_class_count: usize,
+    page_cache_capacity: usize,
+    raw_mutex: PhantomData<R>,
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> SlabAllocatorBuilder<A, R, PAGE_SIZE> {
+    /// Creates a builder for an allocator backed by `allocator`, which also serves allocations no size class
+    /// serves unless a [`fallback`](SlabAllocatorBuilder::fallback) is given.
+    pub fn new_in(allocator: A) -> Self {
+        Self {
+            allocator,
+            fallback: None,
+            topology: None,
+            random: None,
+            slab_selection: SlabSelection::MostRecent,
+            corruption_handler: panic_on_corruption,
+            oom_handler: None,
+            leak_handler: None,
+            checked_deallocation: false,
+            fallback_tracking: false,
+            #[cfg(feature = "allocation-sites")]
+            allocation_sites: false,
+            lazy_shrink: false,
+            remote_frees: false,
+            external_headers: false,
+            metadata_allocator: None,
+            memory_limit: None,
+            memory_tagger: None,
+            guard_pages: None,
+            secure_wipe: false,
+            sampling: None,
+            hooks: None,
+            slab_decay: None,
+            size_classes: [SizeClassConfig::DEFAULT; MAX_SIZE_CLASS_COUNT],
+            size_class_count: 0,
+            page_cache_capacity: DEFAULT_PAGE_CACHE_CAPACITY,
+            raw_mutex: PhantomData,
+        }
+        .size_classes(DEFAULT_SIZE_CLASSES)
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator>
+    SlabAllocatorBuilder<A, R, PAGE_SIZE, F>
+{
+    /// Serves allocations no size class serves from `fallback`. See [`SlabAllocator::with_fallback`].
+    #[must_use]
+    pub fn fallback<G: Allocator>(self, fallback: G) -> SlabAllocatorBuilder<A, R, PAGE_SIZE, G> {
+        SlabAllocatorBuilder {
+            allocator: self.allocator,
+            fallback: Some(fallback),
+            topology: self.topology,
+            random: self.random,
+            slab_selection: self.slab_selection,
+            corruption_handler: self.corruption_handler,
+            oom_handler: self.oom_handler,
+            leak_handler: self.leak_handler,
+            checked_deallocation: self.checked_deallocation,
+            fallback_tracking: self.fallback_tracking,
+            #[cfg(feature = "allocation-sites")]
+            allocation_sites: self.allocation_sites,
+            lazy_shrink: self.lazy_shrink,
+            remote_frees: self.remote_frees,
+            external_headers: self.external_headers,
+            metadata_allocator: self.metadata_allocator,
+            memory_limit: self.memory_limit,
+            memory_tagger: self.memory_tagger,
+            guard_pages: self.guard_pages,
+            secure_wipe: self.secure_wipe,
+            sampling: self.sampling,
+            hooks: self.hooks,
+            slab_decay: self.slab_decay,
+            size_classes: self.size_classes,
+            size_class_count: self.size_class_count,
+            page_cache_capacity: self.page_cache_capacity,
+            raw_mutex: PhantomData,
+        }
+    }
+
+    /// Fronts each size class with a per-CPU magazine layer. See [`SlabAllocator::with_magazines_in`].
+    #[must_use]
+    pub fn magazines(mut self, topology: &'static dyn Topology) -> Self {
+        self.topology = Some(topology);
+        self
+    }
+
+    /// Hands out the objects of each slab in a random order. See [`SlabAllocator::with_randomized_order`].
+    #[must_use]
+    pub fn randomized_order(mut self, random: &'static dyn RandomSource) -> Self {
+        self.random = Some(random);
+        self
+    }
+
+    /// Chooses the slab allocations are served from by `selection`. See [`SlabAllocator::with_slab_selection`].
+    #[must_use]
+    pub fn slab_selection(mut self, selection: SlabSelection) -> Self {
+        self.slab_selection = selection;
+        self
+    }
+
+    /// Reports heap corruption to `handler`. See [`SlabAllocator::with_corruption_handler`].
+    #[must_use]
+    pub fn corruption_handler(mut self, handler: CorruptionHandler) -> Self {
+        self.corruption_handler = handler;
+        self
+    }
+
+    /// Reports backing allocation failures to `handler`. See [`SlabAllocator::with_oom_handler`].
+    #[must_use]
+    pub fn oom_handler(mut self, handler: OomHandler) -> Self {
+        self.oom_handler = Some(handler);
+        self
+    }
+
+    /// Reports objects still allocated as the allocator is dropped to `handler`. See
+    /// [`SlabAllocator::with_leak_handler`].
+    #[must_use]
+    pub fn leak_handler(mut self, handler: LeakHandler) -> Self {
+        self.leak_handler = Some(handler);
+        self
+    }
+
+    /// Verifies every freed pointer. See [`SlabAllocator::with_checked_deallocation`].
+    #[must_use]
+    pub fn checked_deallocation(mut self) -> Self {
+        self.checked_deallocation = true;
+        self
+    }
+
+    /// Records the site of every live allocation. See [`SlabAllocator::with_allocation_sites`].
+    #[cfg(feature = "allocation-sites")]
+    #[must_use]
+    pub fn allocation_sites(mut self) -> Self {
+        self.allocation_sites = true;
+        self
+    }
+
+    /// Records the fallback allocator's allocations. See [`SlabAllocator::with_fallback_tracking`].
+    #[must_use]
+    pub fn fallback_tracking(mut self) -> Self {
+        self.fallback_tracking = true;
+        self
+    }
+
+    /// Keeps shrunk objects in place. See [`SlabAllocator::with_lazy_shrink`].
+    #[must_use]
+    pub fn lazy_shrink(mut self) -> Self {
+        self.lazy_shrink = true;
+        self
+    }
+
+    /// Tags the memory of objects with `tagger` as they are handed out and freed. See
+    /// [`SlabAllocator::with_memory_tagging`].
+    #[must_use]
+    pub fn memory_tagging(mut self, tagger: &'static dyn MemoryTagger) -> Self {
+        self.memory_tagger = Some(tagger);
+        self
+    }
+
+    /// Places objects of size classes of at least `min_object_size` before protected guard pages. See
+    /// [`SlabAllocator::with_guard_pages`].
+    #[must_use]
+    pub fn guard_pages(
+        mut self,
+        min_object_size: usize,
+        protector: &'static dyn PageProtector,
+    ) -> Self {
+        self.guard_pages = Some((min_object_size, Some(protector)));
+        self
+    }
+
+    /// Places objects of size classes of at least `min_object_size` before poisoned guard pages. See
+    /// [`SlabAllocator::with_poisoned_guard_pages`].
+    #[must_use]
+    pub fn poisoned_guard_pages(mut self, min_object_size: usize) -> Self {
+        self.guard_pages = Some((min_object_size, None));
+        self
+    }
+
+    /// Zeroes slabs and pages before they are released. See [`SlabAllocator::with_secure_wipe`].
+    #[must_use]
+    pub fn secure_wipe(mut self) -> Self {
+        self.secure_wipe = true;
+        self
+    }
+
+    /// Reports every `period`th allocation to `handler`. See [`SlabAllocator::with_sampling`].
+    ///
+    /// # Panics
+    ///
+    /// - If `period` is zero.
+    #[must_use]
+    pub fn sampling(
+        mut self,
+        period: usize,
+        clock: &'static dyn Clock,
+        handler: SamplingHandler,
+    ) -> Self {
+        assert!(period > 0, "sampling period must be nonzero");
+
+        self.sampling = Some((period, clock, handler));
+        self
+    }
+
+    /// Tells `hooks` of events within the allocator. See [`SlabAllocator::with_hooks`].
+    #[must_use]
+    pub fn hooks(mut self, hooks: &'static dyn AllocatorHooks) -> Self {
+        self.hooks = Some(hooks);
+        self
+    }
+
+    /// Defers frees to a lock-free queue per size class. See [`SlabAllocator::with_remote_frees`].
+    #[must_use]
+    pub fn remote_frees(mut self) -> Self {
+        self.remote_frees = true;
+        self
+    }
+
+    /// Keeps every slab's header outside of its memory. See [`SlabAllocator::with_external_headers`].
+    #[must_use]
+    pub fn external_headers(mut self) -> Self {
+        self.external_headers = true;
+        self
+    }
+
+    /// Allocates the bookkeeping grown as slabs are created from `allocator`. See
+    /// [`SlabAllocator::with_metadata_allocator`].
+    #[must_use]
+    pub fn metadata_allocator(mut self, allocator: &'static (dyn Allocator + Sync)) -> Self {
+        self.metadata_allocator = Some(allocator);
+        self
+    }
+
+    /// Keeps empty slabs until they have been empty for `decay`. See [`SlabAllocator::with_slab_decay`].
+    #[must_use]
+    pub fn slab_decay(mut self, clock: &'static dyn Clock, decay: Duration) -> Self {
+        self.slab_decay = Some((clock, decay));
+        self
+    }
+
+    /// Bounds the memory held in slabs and pages by `limit`. See [`SlabAllocator::with_memory_limit`].
+    #[must_use]
+    pub fn memory_limit(mut self, limit: &'static MemoryLimit) -> Self {
+        self.memory_limit = Some(limit);
+        self
+    }
+
+    /// Gives the allocator a size class for each of `object_sizes`, rather than the [`DEFAULT_SIZE_CLASSES`]. See
+    /// [`SlabAllocator::with_size_classes_in`].
+    ///
+    /// Size classes of object sizes given before keep their settings.
+    ///
+    /// # Panics
+    ///
+    /// - If `object_sizes` isn't in strictly ascending order, or holds more than [`MAX_SIZE_CLASS_COUNT`] sizes.
+    #[must_use]
+    pub fn size_classes(mut self, object_sizes: &[usize]) -> Self {
+        assert!(object_sizes.len() <= MAX_SIZE_CLASS_COUNT);
+        assert!(object_sizes.is_sorted_by(|object_size, next_size| object_size < next_size));
+
+        let mut size_classes = [SizeClassConfig::DEFAULT; MAX_SIZE_CLASS_COUNT];
+
+        for (config, object_size) in size_classes.iter_mut().zip(object_sizes) {
+            *config = self
+                .configs()
+                .iter()
+                .find(|config| config.object_size == *object_size)
+                .copied()
+                .unwrap_or(SizeClassConfig {
+                    object_size: *object_size,
+                    ..SizeClassConfig::DEFAULT
+                });
+        }
+
+        self.size_classes = size_classes;
+        self.size_class_count = object_sizes.len();
+        self
+    }
+
+    /// Makes every slab of the given size class span `page_count` contiguous pages. See
+    /// [`SlabAllocator::with_slab_page_count`].
+    ///
+    /// # Panics
+    ///
+    /// - If there is no size class of `OBJECT_SIZE`.
+    /// - If `page_count` isn't a power of two, or exceeds [`MAX_SLAB_PAGE_COUNT`].
+    #[must_use]
+    pub fn slab_page_count<const OBJECT_SIZE: usize>(mut self, page_count: usize) -> Self {
+        assert!(page_count.is_power_of_two() && page_count <= MAX_SLAB_PAGE_COUNT);
+
+        self.size_class::<OBJECT_SIZE>().slab_page_count = Some(page_count);
+        self
+    }
+
+    /// Sets the maximum number of empty slabs the given size class keeps. See
+    /// [`SlabAllocator::set_empty_slab_retention`].
+    ///
+    /// # Panics
+    ///
+    /// - If there is no size class of `OBJECT_SIZE`.
+    #[must_use]
+    pub fn empty_slab_retention<const OBJECT_SIZE: usize>(mut self, count: usize) -> Self {
+        self.size_class::<OBJECT_SIZE>().empty_slab_retention = count;
+        self
+    }
+
+    /// Sets the maximum number of slabs the given size class holds at once. See [`SlabAllocator::set_slab_limit`].
+    ///
+    /// # Panics
+    ///
+    /// - If there is no size class of `OBJECT_SIZE`.
+    #[must_use]
+    pub fn slab_limit<const OBJECT_SIZE: usize>(mut self, count: usize) -> Self {
+        self.size_class::<OBJECT_SIZE>().slab_limit = count;
+        self
+    }
+
+    /// Sets how many freed objects of the given size class are held in quarantine. See
+    /// [`SlabAllocator::set_quarantine_capacity`].
+    ///
+    /// # Panics
+    ///
+    /// - If there is no size class of `OBJECT_SIZE`.
+    #[must_use]
+    pub fn quarantine_capacity<const OBJECT_SIZE: usize>(mut self, capacity: usize) -> Self {
+        self.size_class::<OBJECT_SIZE>().quarantine_capacity = capacity;
+        self
+    }
+
+    /// Splits the given size class into `shard_count` lock shards. See [`SlabAllocator::with_lock_shards`].
+    ///
+    /// # Panics
+    ///
+    /// - If there is no size class of `OBJECT_SIZE`.
+    /// - If `shard_count` is zero, or exceeds [`MAX_LOCK_SHARD_COUNT`].
+    #[must_use]
+    pub fn lock_shards<const OBJECT_SIZE: usize>(
+        mut self,
+        topology: &'static dyn Topology,
+        shard_count: usize,
+    ) -> Self {
+        assert!(shard_count > 0 && shard_count <= MAX_LOCK_SHARD_COUNT);
+
+        self.size_class::<OBJECT_SIZE>().lock_shards = Some((topology, shard_count));
+        self
+    }
+
+    /// Sets the maximum number of free pages the page size class keeps. See
+    /// [`SlabAllocator::set_page_cache_capacity`].
+    #[must_use]
+    pub fn page_cache_capacity(mut self, capacity: usize) -> Self {
+        self.page_cache_capacity = capacity;
+        self
+    }
+
+    /// Constructs the allocator.
+    ///
+    /// # Errors
+    ///
+    /// - If the size classes, or a size class' quarantine or lock shards, can't be allocated.
+    ///
+    /// # Panics
+    ///
+    /// - If any object size isn't a multiple of the pointer size, or is too large for a page to hold an object.
+    pub fn build(self) -> Result<SlabAllocator<A, R, PAGE_SIZE, F>, AllocError> {
+        let configs = &self.size_classes[..self.size_class_count];
+        let object_sizes = self.size_classes.map(|config| config.object_size);
+        let mut slab_allocator = SlabAllocator::try_new_with_in(
+            self.allocator,
+            &object_sizes[..self.size_class_count],
+            self.topology,
+        )?
+        .with_fallback_or_backing(self.fallback)
+        .with_slab_selection(self.slab_selection)
+        .with_corruption_handler(self.corruption_handler);
+
+        if let Some(random) = self.random {
+            slab_allocator = slab_allocator.with_randomized_order(random);
+        }
+
+        if let Some(oom_handler) = self.oom_handler {
+            slab_allocator = slab_allocator.with_oom_handler(oom_handler);
+        }
+
+        #[cfg(feature = "allocation-sites")]
+        if self.allocation_sites {
+            slab_allocator = slab_allocator.with_allocation_sites();
+        }
+
+        if self.fallback_tracking {
+            slab_allocator = slab_allocator.with_fallback_tracking();
+        }
+
+        if let Some(leak_handler) = self.leak_handler {
+            slab_allocator = slab_allocator.with_leak_handler(leak_handler);
+        }
+
+        if self.remote_frees {
+            slab_allocator = slab_allocator.with_remote_frees();
+        }
+
+        if self.external_headers {
+            slab_allocator = slab_allocator.with_external_headers();
+        }
+
+        if let Some(metadata_allocator) = self.metadata_allocator {
+            slab_allocator = slab_allocator.with_metadata_allocator(metadata_allocator);
+        }
+
+        if let Some(memory_limit) = self.memory_limit {
+            slab_allocator = slab_allocator.with_memory_limit(memory_limit);
+        }
+
+        if let Some((clock, decay)) = self.slab_decay {
+            slab_allocator = slab_allocator.with_slab_decay(clock, decay);
+        }
+
+        if let Some(memory_tagger) = self.memory_tagger {
+            slab_allocator = slab_allocator.with_memory_tagging(memory_tagger);
+        }
+
+        if self.secure_wipe {
+            slab_allocator = slab_allocator.with_secure_wipe();
+        }
+
+        if let Some((period, clock, handler)) = self.sampling {
+            slab_allocator = slab_allocator.with_sampling(period, clock, handler);
+        }
+
+        if let Some(hooks) = self.hooks {
+            slab_allocator = slab_allocator.with_hooks(hooks);
+        }
+
+        slab_allocator = match self.guard_pages {
+            Some((min_object_size, Some(protector))) => {
+                slab_allocator.with_guard_pages(min_object_size, protector)
+            }
+            Some((min_object_size, None)) => {
+                slab_allocator.with_poisoned_guard_pages(min_object_size)
+            }
+            None => slab_allocator,
+        };
+
+        slab_allocator.checked_deallocation = self.checked_deallocation;
+        slab_allocator.lazy_shrink = self.lazy_shrink;
+        slab_allocator.set_page_cache_capacity(self.page_cache_capacity);
+
+        for config in configs {
+            slab_allocator
+                .expect_size_class_mut(config.object_size)
+                .configure(config)?;
+        }
+
+        Ok(slab_allocator)
+    }
+
+    /// Settings of every size class.
+    fn configs(&self) -> &[SizeClassConfig] {
+        &self.size_classes[..self.size_class_count]
+    }
+
+    /// Settings of the given size class.
+    fn size_class<const OBJECT_SIZE: usize>(&mut self) -> &mut SizeClassConfig {
+        self.size_classes[..self.size_class_count]
+            .iter_mut()
+            .find(|config| config.object_size == OBJECT_SIZE)
+            .expect("no size class of the given object size")
+    }
+}
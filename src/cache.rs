@@ -0,0 +1,187 @@
+use crate::{
+    DefaultRawMutex,
+    alloc_api::{AllocError, Allocator},
+    manager::SlabManager,
+    object_size_for,
+    slab::{DEFAULT_PAGE_SIZE, Slab},
+};
+use core::{alloc::Layout, marker::PhantomData, mem::MaybeUninit, ptr::NonNull};
+use lock_api::RawMutex;
+
+/// Initializes an object when it is first carved out of a slab.
+pub type Constructor<T> = fn(&mut MaybeUninit<T>);
+
+/// Tears down a constructed object before its slab is released.
+pub type Destructor<T> = fn(&mut T);
+
+/// A typed cache of objects, in the style of `kmem_cache`.
+///
+/// When a constructor is provided, every object is constructed once as its slab is created, and objects must be
+/// returned to the cache in their constructed state. The destructor, if any, is run on each free object as its
+/// slab is released.
+///
+/// Slabs are made of pages of `PAGE_SIZE` bytes, which must be a power of two of at least 4 KiB.
+pub struct ObjectCache<
+    T,
+    A: Allocator,
+    R: RawMutex = DefaultRawMutex,
+    const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE,
+> {
+    slabs: SlabManager<A, R, PAGE_SIZE>,
+    constructor: Option<Constructor<T>>,
+    destructor: Option<Destructor<T>>,
+    _marker: PhantomData<T>,
+}
+
+impl<T, A: Allocator, R: RawMutex, const PAGE_SIZE: usize> ObjectCache<T, A, R, PAGE_SIZE> {
+    /// Creates a cache whose objects are handed out uninitialized.
+    ///
+    /// # Panics
+    ///
+    /// - If `T` is too large to be held by a slab.
+    pub fn new_in(allocator: A) -> Self {
+        Self::new_with_hooks_in(None, None, allocator)
+    }
+
+    /// Creates a cache whose objects are initialized by `constructor`.
+    ///
+    /// # Panics
+    ///
+    /// - If `T` is too large to be held by a slab.
+    pub fn with_constructor_in(
+        constructor: Constructor<T>,
+        destructor: Option<Destructor<T>>,
+        allocator: A,
+    ) -> Self {
+        Self::new_with_hooks_in(Some(constructor), destructor, allocator)
+    }
+
+    fn new_with_hooks_in(
+        constructor: Option<Constructor<T>>,
+        destructor: Option<Destructor<T>>,
+        allocator: A,
+    ) -> Self {
+        let object_size =
+            object_size_for(Layout::new::<T>(), PAGE_SIZE).expect("type is too large for a slab");
+
+        #[cfg_attr(not(feature = "poison"), allow(unused_mut))]
+        let mut slabs = SlabManager::new_in(object_size, allocator);
+
+        // Constructed objects must keep their state while they are free.
+        #[cfg(feature = "poison")]
+        if constructor.is_some() {
+            slabs.disable_poisoning();
+        }
+
+        Self {
+            slabs,
+            constructor,
+            destructor,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Takes an object from the cache.
+    ///
+    /// If the cache has a constructor, the object is in its constructed state.
+    pub fn allocate(&self) -> Result<NonNull<T>, AllocError> {
+        let object = self.slabs.next_object_with(|slab| {
+            if let Some(constructor) = self.constructor {
+                for object in slab.objects() {
+                    // Safety: Object is suitably sized and aligned for `T`, and is not yet referenced.
+                    constructor(unsafe { object.cast::<MaybeUninit<T>>().as_mut() });
+                }
+            }
+        })?;
+
+        Ok(object.cast())
+    }
+}
+
+impl<T, A: Allocator, R: RawMutex, const PAGE_SIZE: usize> ObjectCache<T, A, R, PAGE_SIZE> {
+    /// Number of free objects held by the cache.
+    pub fn remaining_object_count(&self) -> usize {
+        self.slabs.remaining_object_count()
+    }
+
+    /// Releases every slab with no allocated objects to the backing allocator, returning the number of bytes
+    /// released.
+    ///
+    /// The destructor, if any, is run on each object of the released slabs.
+    pub fn trim(&self) -> usize {
+        self.slabs
+            .reclaim_empty_with(|slab| self.destroy_free_objects(slab))
+    }
+
+    /// Number of empty slabs held by the cache.
+    pub fn empty_slab_count(&self) -> usize {
+        self.slabs.empty_slab_count()
+    }
+
+    /// Maximum number of empty slabs the cache keeps before releasing them to the backing allocator.
+    pub fn empty_slab_retention(&self) -> usize {
+        self.slabs.empty_slab_retention()
+    }
+
+    /// Sets the maximum number of empty slabs the cache keeps.
+    ///
+    /// By default every empty slab is kept until [`ObjectCache::trim`] is called.
+    pub fn set_empty_slab_retention(&self, count: usize) {
+        self.slabs.set_empty_slab_retention(count);
+    }
+
+    /// Runs the destructor, if any, on every free object of `slab`.
+    fn destroy_free_objects(&self, slab: &Slab) {
+        if let Some(destructor) = self.destructor {
+            for object in slab.free_objects() {
+                // Safety: Free objects are in their constructed state, and are no longer referenced.
+                destructor(unsafe { object.cast::<T>().as_mut() });
+            }
+        }
+    }
+
+    /// Returns an object to the cache.
+    ///
+    /// # Safety
+    ///
+    /// - `object_ptr` must point to an object that originated from this cache.
+    /// - If the cache has a constructor, the object must be in its constructed state.
+    ///
+    /// With the `zeroize-on-free` feature, objects of caches without a constructor are zeroed. Objects of caches
+    /// with a constructor are left in their constructed state.
+    pub unsafe fn deallocate(&self, object_ptr: NonNull<T>) {
+        #[cfg(feature = "zeroize-on-free")]
+        if self.constructor.is_none() {
+            // Safety: Caller is required to ensure the object is allocated, so it is no longer referenced.
+            unsafe {
+                object_ptr.cast::<u8>().write_bytes(0, size_of::<T>());
+            }
+        }
+
+        // Safety: Caller is required to ensure object belongs to this cache.
+        unsafe {
+            self.slabs
+                .return_object_with(object_ptr.cast(), |slab| self.destroy_free_objects(slab));
+        }
+    }
+}
+
+impl<T, A: Allocator, R: RawMutex, const PAGE_SIZE: usize> Drop
+    for ObjectCache<T, A, R, PAGE_SIZE>
+{
+    fn drop(&mut self) {
+        for slab in self.slabs.lists().slabs() {
+            self.destroy_free_objects(&slab);
+        }
+    }
+}
+
+impl<T, A: Allocator, R: RawMutex, const PAGE_SIZE: usize> core::fmt::Debug
+    for ObjectCache<T, A, R, PAGE_SIZE>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ObjectCache")
+            .field("slabs", &self.slabs)
+            .finish()
+    }
+}
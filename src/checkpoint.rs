@@ -0,0 +1,121 @@
+use crate::{
+    alloc_api::{AllocError, Allocator, Vec},
+    slab::Slab,
+};
+
+/// Free bitmap of a slab, as of a checkpoint.
+struct SlabCheckpoint<A: Allocator> {
+    address: usize,
+    free_bitmap: Vec<u64, A>,
+}
+
+/// Free bitmaps of the slabs of a size class, as of a checkpoint, ordered by the slabs' addresses.
+pub(crate) struct SizeClassCheckpoint<A: Allocator> {
+    object_size: usize,
+    slabs: Vec<SlabCheckpoint<A>, A>,
+}
+
+impl<A: Allocator + Clone> SizeClassCheckpoint<A> {
+    pub fn new_in(object_size: usize, allocator: A) -> Self {
+        Self {
+            object_size,
+            slabs: Vec::new_in(allocator),
+        }
+    }
+
+    /// Records the free bitmap of `slab`.
+    ///
+    /// # Errors
+    ///
+    /// - If the bitmap can't be allocated.
+    pub fn record(&mut self, slab: &Slab) -> Result<(), AllocError> {
+        let mut free_bitmap = Vec::new_in(self.slabs.allocator().clone());
+
+        for word in slab.free_bitmap_words() {
+            free_bitmap.try_reserve(1).map_err(|_| AllocError)?;
+            free_bitmap.push(word);
+        }
+
+        let address = slab.memory().addr().get();
+        let index = self
+            .slabs
+            .binary_search_by_key(&address, |slab| slab.address)
+            .unwrap_or_else(|index| index);
+
+        self.slabs.try_reserve(1).map_err(|_| AllocError)?;
+        self.slabs.insert(
+            index,
+            SlabCheckpoint {
+                address,
+                free_bitmap,
+            },
+        );
+
+        Ok(())
+    }
+}
+
+impl<A: Allocator> SizeClassCheckpoint<A> {
+    pub fn object_size(&self) -> usize {
+        self.object_size
+    }
+
+    /// Free bitmap of `slab` as of the checkpoint, or `None` if the slab was created since.
+    pub fn free_bitmap_of(&self, slab: &Slab) -> Option<&[u64]> {
+        self.slabs
+            .binary_search_by_key(&slab.memory().addr().get(), |slab| slab.address)
+            .ok()
+            .map(|index| self.slabs[index].free_bitmap.as_slice())
+    }
+
+    fn slab_count(&self) -> usize {
+        self.slabs.len()
+    }
+}
+
+/// Snapshot of which objects of an allocator's size classes are allocated, taken by
+/// [`SlabAllocator::checkpoint`](crate::SlabAllocator::checkpoint), so that every object allocated since can be freed
+/// at once by [`SlabAllocator::rollback`](crate::SlabAllocator::rollback).
+///
+/// Only the free bitmap of each slab is recorded, so a checkpoint costs a few words per slab.
+pub struct Checkpoint<A: Allocator> {
+    size_classes: Vec<SizeClassCheckpoint<A>, A>,
+}
+
+impl<A: Allocator> Checkpoint<A> {
+    pub(crate) fn new_in(allocator: A) -> Self {
+        Self {
+            size_classes: Vec::new_in(allocator),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// - If the size class' checkpoint can't be recorded.
+    pub(crate) fn push(&mut self, size_class: SizeClassCheckpoint<A>) -> Result<(), AllocError> {
+        self.size_classes.try_reserve(1).map_err(|_| AllocError)?;
+        self.size_classes.push(size_class);
+
+        Ok(())
+    }
+
+    pub(crate) fn size_classes(&self) -> &[SizeClassCheckpoint<A>] {
+        &self.size_classes
+    }
+}
+
+impl<A: Allocator> core::fmt::Debug for Checkpoint<A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Checkpoint")
+            .field("size_class_count", &self.size_classes.len())
+            .field(
+                "slab_count",
+                &self
+                    .size_classes
+                    .iter()
+                    .map(SizeClassCheckpoint::slab_count)
+                    .sum::<usize>(),
+            )
+            .finish()
+    }
+}
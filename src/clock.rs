@@ -0,0 +1,9 @@
+use core::time::Duration;
+
+/// Monotonic clock, used to tell how long empty slabs have been idle.
+///
+/// The epoch is arbitrary, such as the time since boot, but the time returned must never decrease.
+pub trait Clock: Sync {
+    /// Returns the current time, relative to the clock's epoch.
+    fn now(&self) -> Duration;
+}
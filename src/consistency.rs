@@ -0,0 +1,259 @@
+use core::fmt;
+
+/// Maximum number of inconsistencies a [`ConsistencyReport`] holds the details of. Further inconsistencies are only
+/// counted.
+pub const MAX_REPORTED_INCONSISTENCIES: usize = 16;
+
+/// Broken invariant of an allocator's internal state, found by
+/// [`SlabAllocator::check_consistency`](crate::SlabAllocator::check_consistency).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Inconsistency {
+    /// A slab's count of free objects differs from the number of objects marked free in its bitmap.
+    FreeCountMismatch {
+        /// Object size of the size class holding the slab.
+        object_size: usize,
+
+        /// Base address of the slab.
+        slab_address: usize,
+
+        /// Number of free objects recorded by the slab.
+        free_count: usize,
+
+        /// Number of objects marked free in the slab's bitmap.
+        free_bits: usize,
+    },
+
+    /// Bits past a slab's last object are set in its free bitmap.
+    StrayFreeBits {
+        /// Object size of the size class holding the slab.
+        object_size: usize,
+
+        /// Base address of the slab.
+        slab_address: usize,
+    },
+
+    /// A slab holds objects of another size than its size class.
+    WrongObjectSize {
+        /// Object size of the size class holding the slab.
+        object_size: usize,
+
+        /// Base address of the slab.
+        slab_address: usize,
+
+        /// Object size recorded by the slab.
+        slab_object_size: usize,
+    },
+
+    /// A slab's header names another list than the one holding it.
+    MisfiledSlab {
+        /// Object size of the size class holding the slab.
+        object_size: usize,
+
+        /// Base address of the slab.
+        slab_address: usize,
+    },
+
+    /// A slab held by a size class can't be found by its address, so its objects can't be freed.
+    UnindexedSlab {
+        /// Object size of the size class holding the slab.
+        object_size: usize,
+
+        /// Base address of the slab.
+        slab_address: usize,
+    },
+
+    /// The memory of two slabs overlaps.
+    OverlappingSlabs {
+        /// Object size of the size class holding the first slab.
+        object_size: usize,
+
+        /// Base address of the first slab.
+        slab_address: usize,
+
+        /// Object size of the size class holding the second slab.
+        other_object_size: usize,
+
+        /// Base address of the second slab.
+        other_slab_address: usize,
+    },
+
+    /// A size class' count of free objects differs from the sum of its slabs' free objects.
+    RemainingCountMismatch {
+        /// Object size of the size class.
+        object_size: usize,
+
+        /// Number of free objects recorded by the size class.
+        recorded: usize,
+
+        /// Number of free objects counted over the size class' slabs.
+        counted: usize,
+    },
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FreeCountMismatch {
+                object_size,
+                slab_address,
+                free_count,
+                free_bits,
+            } => write!(
+                f,
+                "{object_size}-byte slab at {slab_address:#x} records {free_count} free objects, but its bitmap \
+                 marks {free_bits}"
+            ),
+
+            Self::StrayFreeBits {
+                object_size,
+                slab_address,
+            } => write!(
+                f,
+                "{object_size}-byte slab at {slab_address:#x} marks objects past its last as free"
+            ),
+
+            Self::WrongObjectSize {
+                object_size,
+                slab_address,
+                slab_object_size,
+            } => write!(
+                f,
+                "{object_size}-byte size class holds a slab of {slab_object_size}-byte objects at \
+                 {slab_address:#x}"
+            ),
+
+            Self::MisfiledSlab {
+                object_size,
+                slab_address,
+            } => write!(
+                f,
+                "{object_size}-byte slab at {slab_address:#x} is held on another list than it names"
+            ),
+
+            Self::UnindexedSlab {
+                object_size,
+                slab_address,
+            } => write!(
+                f,
+                "{object_size}-byte slab at {slab_address:#x} can't be found by its address"
+            ),
+
+            Self::OverlappingSlabs {
+                object_size,
+                slab_address,
+                other_object_size,
+                other_slab_address,
+            } => write!(
+                f,
+                "{object_size}-byte slab at {slab_address:#x} overlaps {other_object_size}-byte slab at \
+                 {other_slab_address:#x}"
+            ),
+
+            Self::RemainingCountMismatch {
+                object_size,
+                recorded,
+                counted,
+            } => write!(
+                f,
+                "{object_size}-byte size class records {recorded} free objects, but its slabs hold {counted}"
+            ),
+        }
+    }
+}
+
+/// Result of [`SlabAllocator::check_consistency`](crate::SlabAllocator::check_consistency): the inconsistencies found
+/// in an allocator's internal state, the first [`MAX_REPORTED_INCONSISTENCIES`] of which are held in detail.
+///
+/// The report is built without allocating, so it can be taken from crash handlers. With [`fmt::Display`], it is
+/// written as a summary line followed by a line for each inconsistency held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ConsistencyReport {
+    inconsistencies: [Option<Inconsistency>; MAX_REPORTED_INCONSISTENCIES],
+    inconsistency_count: usize,
+    checked_slab_count: usize,
+    locked_shard_count: usize,
+}
+
+impl ConsistencyReport {
+    pub(crate) const fn new() -> Self {
+        Self {
+            inconsistencies: [None; MAX_REPORTED_INCONSISTENCIES],
+            inconsistency_count: 0,
+            checked_slab_count: 0,
+            locked_shard_count: 0,
+        }
+    }
+
+    pub(crate) fn record(&mut self, inconsistency: Inconsistency) {
+        if let Some(slot) = self.inconsistencies.get_mut(self.inconsistency_count) {
+            *slot = Some(inconsistency);
+        }
+
+        self.inconsistency_count += 1;
+    }
+
+    pub(crate) fn record_checked_slab(&mut self) {
+        self.checked_slab_count += 1;
+    }
+
+    pub(crate) fn record_locked_shard(&mut self) {
+        self.locked_shard_count += 1;
+    }
+
+    /// Whether no inconsistency was found. Lock shards that were held as the check ran weren't checked; see
+    /// [`ConsistencyReport::locked_shard_count`].
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistency_count == 0
+    }
+
+    /// Inconsistencies held in detail, in the order they were found.
+    pub fn inconsistencies(&self) -> impl Iterator<Item = &Inconsistency> {
+        self.inconsistencies.iter().flatten()
+    }
+
+    /// Number of inconsistencies found, including those beyond the ones held in detail.
+    pub fn inconsistency_count(&self) -> usize {
+        self.inconsistency_count
+    }
+
+    /// Number of slabs checked.
+    pub fn checked_slab_count(&self) -> usize {
+        self.checked_slab_count
+    }
+
+    /// Number of lock shards skipped, as their slabs were locked as the check ran.
+    pub fn locked_shard_count(&self) -> usize {
+        self.locked_shard_count
+    }
+}
+
+impl fmt::Display for ConsistencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} inconsistencies in {} slabs",
+            self.inconsistency_count, self.checked_slab_count
+        )?;
+
+        if self.locked_shard_count > 0 {
+            write!(f, " ({} locked shards skipped)", self.locked_shard_count)?;
+        }
+
+        for inconsistency in self.inconsistencies() {
+            write!(f, "\n{inconsistency}")?;
+        }
+
+        if self.inconsistency_count > MAX_REPORTED_INCONSISTENCIES {
+            write!(
+                f,
+                "\n{} more",
+                self.inconsistency_count - MAX_REPORTED_INCONSISTENCIES
+            )?;
+        }
+
+        Ok(())
+    }
+}
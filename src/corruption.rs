@@ -0,0 +1,135 @@
+use crate::ObjectHandle;
+use core::{fmt, ptr::NonNull};
+
+/// Heap corruption detected by an allocator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Corruption {
+    /// An object was freed while it was already free.
+    DoubleFree {
+        /// The object being freed.
+        object_ptr: NonNull<u8>,
+    },
+
+    /// A free object was written to, detected as it was handed out again.
+    WriteAfterFree {
+        /// The object being allocated.
+        object_ptr: NonNull<u8>,
+    },
+
+    /// A pointer that doesn't point to one of the allocator's objects was freed.
+    ForeignPointer {
+        /// The pointer being freed.
+        object_ptr: NonNull<u8>,
+    },
+
+    /// The canary bytes around an object were overwritten, detected as it was freed.
+    RedzoneOverwritten {
+        /// The object being freed.
+        object_ptr: NonNull<u8>,
+    },
+
+    /// The poisoned guard page following an object was overwritten, detected as it was freed. See
+    /// [`SlabAllocator::with_poisoned_guard_pages`](crate::SlabAllocator::with_poisoned_guard_pages).
+    GuardPageOverwritten {
+        /// The object being freed.
+        object_ptr: NonNull<u8>,
+    },
+
+    /// A handle whose object has since been freed, or that was never allocated, was resolved or freed.
+    StaleHandle {
+        /// The handle being resolved or freed.
+        handle: ObjectHandle,
+    },
+}
+
+impl fmt::Display for Corruption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DoubleFree { object_ptr } => {
+                write!(f, "object at {object_ptr:p} was freed while already free")
+            }
+
+            Self::WriteAfterFree { object_ptr } => {
+                write!(
+                    f,
+                    "object at {object_ptr:p} was written to after being freed"
+                )
+            }
+
+            Self::ForeignPointer { object_ptr } => {
+                write!(
+                    f,
+                    "pointer {object_ptr:p} was not allocated by this allocator"
+                )
+            }
+
+            Self::RedzoneOverwritten { object_ptr } => {
+                write!(f, "redzone around object at {object_ptr:p} was overwritten")
+            }
+
+            Self::GuardPageOverwritten { object_ptr } => {
+                write!(
+                    f,
+                    "guard page after object at {object_ptr:p} was overwritten"
+                )
+            }
+
+            Self::StaleHandle { handle } => {
+                write!(f, "handle {:#x} is stale", handle.into_raw())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Corruption {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            Self::DoubleFree { object_ptr } => defmt::write!(
+                f,
+                "object at {} was freed while already free",
+                object_ptr.as_ptr()
+            ),
+
+            Self::WriteAfterFree { object_ptr } => defmt::write!(
+                f,
+                "object at {} was written to after being freed",
+                object_ptr.as_ptr()
+            ),
+
+            Self::ForeignPointer { object_ptr } => defmt::write!(
+                f,
+                "pointer {} was not allocated by this allocator",
+                object_ptr.as_ptr()
+            ),
+
+            Self::RedzoneOverwritten { object_ptr } => defmt::write!(
+                f,
+                "redzone around object at {} was overwritten",
+                object_ptr.as_ptr()
+            ),
+
+            Self::GuardPageOverwritten { object_ptr } => defmt::write!(
+                f,
+                "guard page after object at {} was overwritten",
+                object_ptr.as_ptr()
+            ),
+
+            Self::StaleHandle { handle } => {
+                defmt::write!(f, "handle {=u32:#x} is stale", handle.into_raw())
+            }
+        }
+    }
+}
+
+/// Called when heap corruption is detected.
+///
+/// If the handler returns, a corrupted free is abandoned, leaving the allocator's state as it was, while a
+/// corrupted allocation proceeds.
+pub type CorruptionHandler = fn(Corruption);
+
+/// The default [`CorruptionHandler`], which panics.
+pub fn panic_on_corruption(corruption: Corruption) {
+    panic!("heap corruption detected: {corruption}");
+}
@@ -0,0 +1,99 @@
+use core::{
+    cell::UnsafeCell,
+    hint::spin_loop,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use critical_section::RestoreState;
+use lock_api::{GuardNoSend, RawMutex};
+
+/// Lock that holds a critical section for as long as it is locked.
+///
+/// On single-core targets, a critical section typically disables interrupts, so an interrupt handler can never find a
+/// lock held by the context it interrupted, and the allocator can be called from both thread and interrupt context.
+/// The critical section is provided by the [`critical_section`] implementation of the target, as set with
+/// `critical_section::set_impl!`. The lock still spins on a flag while held elsewhere, so it remains sound on
+/// multi-core targets.
+///
+/// Critical sections must be released in the reverse order they were acquired, so locks of this type must be unlocked
+/// in the reverse order they were locked, which the allocator always does.
+///
+/// ```ignore
+/// let allocator = SlabAllocator::<_, CriticalSectionRawMutex>::new_in(heap);
+/// ```
+pub struct CriticalSectionRawMutex {
+    locked: AtomicBool,
+    restore_state: UnsafeCell<RestoreState>,
+}
+
+// Safety: The restore state is only accessed by the holder of the lock.
+unsafe impl Sync for CriticalSectionRawMutex {}
+
+// Safety: The lock is held for as long as the flag is set, which is only ever set by one context at a time.
+unsafe impl RawMutex for CriticalSectionRawMutex {
+    const INIT: Self = Self {
+        locked: AtomicBool::new(false),
+        restore_state: UnsafeCell::new(RestoreState::invalid()),
+    };
+
+    // The critical section must be released by the context that acquired it.
+    type GuardMarker = GuardNoSend;
+
+    fn lock(&self) {
+        // Safety: The critical section is released as the lock is unlocked.
+        let restore_state = unsafe { critical_section::acquire() };
+
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            spin_loop();
+        }
+
+        // Safety: Lock is held, so no other context accesses the restore state.
+        unsafe {
+            *self.restore_state.get() = restore_state;
+        }
+    }
+
+    fn try_lock(&self) -> bool {
+        // Safety: The critical section is released as the lock is unlocked, or below if it can't be locked.
+        let restore_state = unsafe { critical_section::acquire() };
+
+        if self
+            .locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            // Safety: Lock is held, so no other context accesses the restore state.
+            unsafe {
+                *self.restore_state.get() = restore_state;
+            }
+
+            true
+        } else {
+            // Safety: Critical section was acquired above, and no other was acquired since.
+            unsafe {
+                critical_section::release(restore_state);
+            }
+
+            false
+        }
+    }
+
+    unsafe fn unlock(&self) {
+        // Safety: Caller is required to hold the lock, so no other context accesses the restore state.
+        let restore_state = unsafe { *self.restore_state.get() };
+        self.locked.store(false, Ordering::Release);
+
+        // Safety: Critical section was acquired as the lock was locked, and locks are unlocked in the reverse order
+        //         they were locked.
+        unsafe {
+            critical_section::release(restore_state);
+        }
+    }
+
+    fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+}
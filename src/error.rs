@@ -0,0 +1,31 @@
+use crate::alloc_api::AllocError;
+use core::fmt;
+
+/// Error returned by [`SlabAllocator::try_allocate`](crate::SlabAllocator::try_allocate).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TryAllocError {
+    /// Serving the allocation would have waited on a lock or on another allocation, which may be held by the
+    /// interrupted context. The allocation may succeed once that context has run.
+    WouldBlock,
+
+    /// Memory is exhausted, or the allocation's layout can't be served.
+    Exhausted,
+}
+
+impl From<AllocError> for TryAllocError {
+    fn from(_: AllocError) -> Self {
+        Self::Exhausted
+    }
+}
+
+impl fmt::Display for TryAllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WouldBlock => f.write_str("allocation would block"),
+            Self::Exhausted => f.write_str("memory allocation failed"),
+        }
+    }
+}
+
+impl core::error::Error for TryAllocError {}
@@ -0,0 +1,157 @@
+use crate::alloc_api::{AllocError, Allocator};
+use core::{
+    alloc::Layout,
+    ptr::NonNull,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+};
+
+/// Backing allocator wrapper failing chosen allocations, for tests of the paths taken as memory runs out.
+///
+/// Allocations succeed until failures are injected, either of a single allocation with
+/// [`FailingAllocator::fail_nth`], or of allocations at random with [`FailingAllocator::fail_randomly`]. A shared
+/// reference to the failing allocator should be given to slab allocators, so failures can be injected while they hold
+/// it.
+///
+/// ```ignore
+/// let failing = FailingAllocator::new(Global);
+/// let slab_allocator = SlabAllocator::<_>::new_in(&failing);
+///
+/// failing.fail_nth(1);
+/// assert!(slab_allocator.allocate(layout).is_err());
+/// ```
+#[derive(Debug)]
+pub struct FailingAllocator<A: Allocator> {
+    allocator: A,
+    allocation_count: AtomicUsize,
+    failure_count: AtomicUsize,
+    /// Index of the allocation to fail, or `usize::MAX` if none.
+    failing_index: AtomicUsize,
+    /// One in how many allocations fail at random, or zero if none.
+    failure_rate: AtomicU64,
+    random_state: AtomicU64,
+}
+
+impl<A: Allocator> FailingAllocator<A> {
+    /// Wraps `allocator`, with every allocation succeeding.
+    pub const fn new(allocator: A) -> Self {
+        Self {
+            allocator,
+            allocation_count: AtomicUsize::new(0),
+            failure_count: AtomicUsize::new(0),
+            failing_index: AtomicUsize::new(usize::MAX),
+            failure_rate: AtomicU64::new(0),
+            random_state: AtomicU64::new(0),
+        }
+    }
+
+    /// The wrapped allocator.
+    pub fn inner(&self) -> &A {
+        &self.allocator
+    }
+
+    /// Fails the `n`th allocation from now, counting from one.
+    ///
+    /// # Panics
+    ///
+    /// - If `n` is zero.
+    pub fn fail_nth(&self, n: usize) {
+        assert!(n > 0);
+
+        self.failing_index.store(
+            self.allocation_count.load(Ordering::Relaxed) + (n - 1),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Fails allocations at random, each with a chance of one in `one_in`, in a sequence determined by `seed`.
+    ///
+    /// # Panics
+    ///
+    /// - If `one_in` is zero.
+    pub fn fail_randomly(&self, seed: u64, one_in: u64) {
+        assert!(one_in > 0);
+
+        // Xorshift never leaves a state of zero.
+        self.random_state.store(seed.max(1), Ordering::Relaxed);
+        self.failure_rate.store(one_in, Ordering::Relaxed);
+    }
+
+    /// Stops failing allocations.
+    pub fn stop_failing(&self) {
+        self.failing_index.store(usize::MAX, Ordering::Relaxed);
+        self.failure_rate.store(0, Ordering::Relaxed);
+    }
+
+    /// Number of allocations made, including those that failed.
+    pub fn allocation_count(&self) -> usize {
+        self.allocation_count.load(Ordering::Relaxed)
+    }
+
+    /// Number of allocations failed.
+    pub fn failure_count(&self) -> usize {
+        self.failure_count.load(Ordering::Relaxed)
+    }
+
+    /// Counts an allocation, returning whether it should fail.
+    fn should_fail(&self) -> bool {
+        let index = self.allocation_count.fetch_add(1, Ordering::Relaxed);
+
+        let fails_nth = self
+            .failing_index
+            .compare_exchange(index, usize::MAX, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok();
+
+        let failure_rate = self.failure_rate.load(Ordering::Relaxed);
+        let fails_randomly = failure_rate > 0 && {
+            let (Ok(state) | Err(state)) =
+                self.random_state
+                    .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |state| {
+                        Some(xorshift(state))
+                    });
+
+            xorshift(state).is_multiple_of(failure_rate)
+        };
+
+        let fails = fails_nth || fails_randomly;
+
+        if fails {
+            self.failure_count.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fails
+    }
+}
+
+// Safety: Allocations that don't fail are served by the wrapped allocator.
+unsafe impl<A: Allocator> Allocator for FailingAllocator<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if self.should_fail() {
+            return Err(AllocError);
+        }
+
+        self.allocator.allocate(layout)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if self.should_fail() {
+            return Err(AllocError);
+        }
+
+        self.allocator.allocate_zeroed(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Safety: Caller is required to maintain safety invariants.
+        unsafe {
+            self.allocator.deallocate(ptr, layout);
+        }
+    }
+}
+
+/// Advances a xorshift generator from `state`.
+const fn xorshift(mut state: u64) -> u64 {
+    state ^= state << 13;
+    state ^= state >> 7;
+    state ^= state << 17;
+    state
+}
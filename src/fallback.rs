@@ -0,0 +1,221 @@
+use crate::{
+    FallbackStats, LeakHandler, TryAllocError,
+    alloc_api::{AllocError, Allocator, Vec},
+    leak,
+    stats::Counters,
+};
+use core::{
+    alloc::Layout,
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use lock_api::{Mutex, RawMutex};
+
+/// Allocation served by the fallback allocator, as recorded by a [`FallbackRegistry`].
+struct FallbackAllocation {
+    address: usize,
+    layout: Layout,
+}
+
+impl FallbackAllocation {
+    fn contains(&self, address: usize) -> bool {
+        (self.address..self.address + self.layout.size()).contains(&address)
+    }
+}
+
+/// Side table of the allocations served by the fallback allocator, with their layouts, so they can be counted,
+/// owned and reported as leaked like the objects of the size classes.
+///
+/// Allocations are kept ordered by address, so the allocation holding a pointer is found by binary search.
+pub(crate) struct FallbackRegistry<A: Allocator, R: RawMutex> {
+    allocations: Mutex<R, Vec<FallbackAllocation, A>>,
+    counters: Counters,
+    live_bytes: AtomicUsize,
+    peak_live_bytes: AtomicUsize,
+    leak_handler: Option<LeakHandler>,
+}
+
+impl<A: Allocator, R: RawMutex> FallbackRegistry<A, R> {
+    pub fn new_in(allocator: A) -> Self {
+        Self {
+            allocations: Mutex::new(Vec::new_in(allocator)),
+            counters: Counters::new(),
+            live_bytes: AtomicUsize::new(0),
+            peak_live_bytes: AtomicUsize::new(0),
+            leak_handler: None,
+        }
+    }
+
+    /// Reports allocations still recorded as the registry is dropped to `handler`.
+    pub fn set_leak_handler(&mut self, handler: LeakHandler) {
+        self.leak_handler = Some(handler);
+    }
+
+    /// Records the allocation at `ptr`, just served with `layout`.
+    ///
+    /// # Errors
+    ///
+    /// - If the table can't grow to hold the allocation.
+    pub fn insert(&self, ptr: NonNull<u8>, layout: Layout) -> Result<(), AllocError> {
+        Self::insert_into(&mut self.allocations.lock(), ptr, layout)?;
+        self.record_allocation(layout);
+
+        Ok(())
+    }
+
+    /// Records the allocation at `ptr`, just served with `layout`, without waiting on the table's lock.
+    ///
+    /// # Errors
+    ///
+    /// - [`TryAllocError::WouldBlock`] if the table's lock is held.
+    /// - [`TryAllocError::Exhausted`] if the table can't grow to hold the allocation.
+    pub fn try_insert(&self, ptr: NonNull<u8>, layout: Layout) -> Result<(), TryAllocError> {
+        let mut allocations = self
+            .allocations
+            .try_lock()
+            .ok_or(TryAllocError::WouldBlock)?;
+
+        Self::insert_into(&mut allocations, ptr, layout)?;
+        self.record_allocation(layout);
+
+        Ok(())
+    }
+
+    fn insert_into(
+        allocations: &mut Vec<FallbackAllocation, A>,
+        ptr: NonNull<u8>,
+        layout: Layout,
+    ) -> Result<(), AllocError> {
+        let address = ptr.addr().get();
+        let index = allocations.partition_point(|allocation| allocation.address < address);
+
+        allocations.try_reserve(1).map_err(|_| AllocError)?;
+        allocations.insert(index, FallbackAllocation { address, layout });
+
+        Ok(())
+    }
+
+    /// Forgets the allocation at `ptr` as it is freed, returning whether it was recorded.
+    pub fn remove(&self, ptr: NonNull<u8>) -> bool {
+        let Some(layout) = Self::remove_from(&mut self.allocations.lock(), ptr) else {
+            return false;
+        };
+
+        self.counters.record_deallocations(1);
+        self.live_bytes.fetch_sub(layout.size(), Ordering::Relaxed);
+
+        true
+    }
+
+    /// Whether an allocation starting at `ptr` is recorded.
+    pub fn is_recorded(&self, ptr: NonNull<u8>) -> bool {
+        self.allocations
+            .lock()
+            .binary_search_by_key(&ptr.addr().get(), |allocation| allocation.address)
+            .is_ok()
+    }
+
+    /// Replaces the recorded allocation at `old_ptr` with its reallocation at `new_ptr` of `new_layout`.
+    ///
+    /// The old allocation's entry is forgotten before the new one is recorded, so the table never needs to grow.
+    pub fn replace(&self, old_ptr: NonNull<u8>, new_ptr: NonNull<u8>, new_layout: Layout) {
+        let mut allocations = self.allocations.lock();
+        let Some(old_layout) = Self::remove_from(&mut allocations, old_ptr) else {
+            return;
+        };
+
+        // Space was freed by the removal above, so this never fails.
+        let _ = Self::insert_into(&mut allocations, new_ptr, new_layout);
+        drop(allocations);
+
+        self.live_bytes
+            .fetch_sub(old_layout.size(), Ordering::Relaxed);
+        self.record_live_bytes(new_layout.size());
+    }
+
+    fn remove_from(
+        allocations: &mut Vec<FallbackAllocation, A>,
+        ptr: NonNull<u8>,
+    ) -> Option<Layout> {
+        let index = allocations
+            .binary_search_by_key(&ptr.addr().get(), |allocation| allocation.address)
+            .ok()?;
+
+        Some(allocations.remove(index).layout)
+    }
+
+    /// Whether `ptr` points into a recorded allocation.
+    pub fn contains(&self, ptr: NonNull<u8>) -> bool {
+        let address = ptr.addr().get();
+        let allocations = self.allocations.lock();
+        let index = allocations.partition_point(|allocation| allocation.address <= address);
+
+        index
+            .checked_sub(1)
+            .is_some_and(|index| allocations[index].contains(address))
+    }
+
+    fn record_allocation(&self, layout: Layout) {
+        self.counters.record_allocation();
+        self.record_live_bytes(layout.size());
+    }
+
+    fn record_live_bytes(&self, size: usize) {
+        let live_bytes = self.live_bytes.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak_live_bytes
+            .fetch_max(live_bytes, Ordering::Relaxed);
+    }
+
+    /// Number of bytes allocated to hold the table.
+    pub fn metadata_bytes(&self) -> usize {
+        self.allocations.lock().capacity() * size_of::<FallbackAllocation>()
+    }
+
+    pub fn stats(&self) -> FallbackStats {
+        FallbackStats {
+            live_allocation_count: self.allocations.lock().len(),
+            live_bytes: self.live_bytes.load(Ordering::Relaxed),
+            allocation_count: self.counters.allocation_count(),
+            deallocation_count: self.counters.deallocation_count(),
+            peak_live_bytes: self.peak_live_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Zeroes the cumulative counters, and lowers the peak to the bytes currently allocated.
+    pub fn reset_stats(&self) {
+        self.counters.reset();
+        self.peak_live_bytes
+            .store(self.live_bytes.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+}
+
+impl<A: Allocator, R: RawMutex> Drop for FallbackRegistry<A, R> {
+    fn drop(&mut self) {
+        let allocations = self.allocations.get_mut();
+
+        // Allocations are reported by size, each size once, as that of its first allocation.
+        for (index, allocation) in allocations.iter().enumerate() {
+            let size = allocation.layout.size();
+            if allocations[..index]
+                .iter()
+                .any(|allocation| allocation.layout.size() == size)
+            {
+                continue;
+            }
+
+            let count = allocations[index..]
+                .iter()
+                .filter(|allocation| allocation.layout.size() == size)
+                .count();
+            leak::report_leak(self.leak_handler, size, count);
+        }
+    }
+}
+
+impl<A: Allocator, R: RawMutex> core::fmt::Debug for FallbackRegistry<A, R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FallbackRegistry")
+            .field("stats", &self.stats())
+            .finish_non_exhaustive()
+    }
+}
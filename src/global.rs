@@ -0,0 +1,96 @@
+use crate::{DEFAULT_PAGE_SIZE, DefaultRawMutex, SlabAllocator, Topology, alloc_api::Allocator};
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    ptr::{NonNull, null_mut},
+};
+use lock_api::RawMutex;
+use spin::Once;
+
+/// A [`SlabAllocator`] that can be installed as the `#[global_allocator]`.
+///
+/// The slab allocator is created on first use, over the backing allocator returned by `init`. This allows the
+/// global allocator to be declared in a `static`, even though its backing allocator may not be constructible in a
+/// const context. See [`global_slab_allocator!`](crate::global_slab_allocator).
+pub struct GlobalSlabAllocator<
+    A: Allocator,
+    R: RawMutex = DefaultRawMutex,
+    const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE,
+> {
+    init: fn() -> A,
+    topology: Option<&'static dyn Topology>,
+    allocator: Once<SlabAllocator<A, R, PAGE_SIZE>>,
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> GlobalSlabAllocator<A, R, PAGE_SIZE> {
+    /// Creates a global allocator whose backing allocator is provided by `init`.
+    pub const fn new(init: fn() -> A) -> Self {
+        Self {
+            init,
+            topology: None,
+            allocator: Once::new(),
+        }
+    }
+
+    /// Creates a global allocator whose size classes are fronted by per-CPU magazines.
+    pub const fn with_magazines(init: fn() -> A, topology: &'static dyn Topology) -> Self {
+        Self {
+            init,
+            topology: Some(topology),
+            allocator: Once::new(),
+        }
+    }
+
+    /// The underlying slab allocator, creating it if this is the first use.
+    pub fn allocator(&self) -> &SlabAllocator<A, R, PAGE_SIZE> {
+        self.allocator.call_once(|| {
+            let allocator = (self.init)();
+
+            match self.topology {
+                Some(topology) => SlabAllocator::with_magazines_in(allocator, topology),
+                None => SlabAllocator::new_in(allocator),
+            }
+        })
+    }
+}
+
+// Safety: Allocations are served by `SlabAllocator`, which upholds the `Allocator` contract.
+unsafe impl<A: Allocator + Send + Sync, R: RawMutex + Sync, const PAGE_SIZE: usize> GlobalAlloc
+    for GlobalSlabAllocator<A, R, PAGE_SIZE>
+{
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.allocator()
+            .allocate(layout)
+            .map_or(null_mut(), |ptr| ptr.cast::<u8>().as_ptr())
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.allocator()
+            .allocate_zeroed(layout)
+            .map_or(null_mut(), |ptr| ptr.cast::<u8>().as_ptr())
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // Safety: Caller is required to ensure `ptr` was allocated by this allocator with `layout`, so is non-null.
+        let ptr = unsafe { NonNull::new_unchecked(ptr) };
+
+        // Safety: Caller is required to ensure `ptr` was allocated by this allocator with `layout`.
+        unsafe {
+            self.allocator().deallocate(ptr, layout);
+        }
+    }
+}
+
+/// Declares a [`GlobalSlabAllocator`] as the `#[global_allocator]`.
+///
+/// `init` is called once, on the first allocation, to create the backing allocator.
+///
+/// ```ignore
+/// slab_allocator::global_slab_allocator!(static ALLOCATOR: PageAllocator = PageAllocator::new);
+/// ```
+#[macro_export]
+macro_rules! global_slab_allocator {
+    ($vis:vis static $name:ident: $allocator:ty = $init:expr $(;)?) => {
+        #[global_allocator]
+        $vis static $name: $crate::GlobalSlabAllocator<$allocator> = $crate::GlobalSlabAllocator::new($init);
+    };
+}
@@ -0,0 +1,191 @@
+use crate::{
+    Corruption, MemoryLimit,
+    alloc_api::{AllocError, Allocator},
+};
+use core::{
+    alloc::Layout,
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Hook making pages inaccessible, so that the guard pages following objects placed on pages of their own fault as
+/// they are overrun. See [`SlabAllocator::with_guard_pages`](crate::SlabAllocator::with_guard_pages).
+///
+/// Pages are protected as they are taken from the backing allocator, and unprotected before they are returned to it.
+pub trait PageProtector: Sync {
+    /// Makes `page` inaccessible, such as by unmapping it or clearing its permissions.
+    ///
+    /// # Safety
+    ///
+    /// - `page` must be a whole page held by the allocator, which isn't accessed until it is unprotected.
+    unsafe fn protect(&self, page: NonNull<[u8]>);
+
+    /// Makes `page`, previously protected, readable and writable again.
+    ///
+    /// # Safety
+    ///
+    /// - `page` must have been protected by [`PageProtector::protect`].
+    unsafe fn unprotect(&self, page: NonNull<[u8]>);
+}
+
+/// Byte filling guard pages that aren't protected, checked as their objects are freed.
+const GUARD_BYTE: u8 = 0xF7;
+
+/// Places each object of the size classes it serves at the end of a page of its own, followed by a guard page that is
+/// either protected or poisoned, so overflows past the end of objects are caught.
+///
+/// The object's page and its guard page are allocated together from the backing allocator, and released to it as the
+/// object is freed.
+pub(crate) struct GuardPages<const PAGE_SIZE: usize> {
+    min_object_size: usize,
+    protector: Option<&'static dyn PageProtector>,
+    live_object_count: AtomicUsize,
+}
+
+impl<const PAGE_SIZE: usize> GuardPages<PAGE_SIZE> {
+    // Safety: Layout is known to be valid.
+    const LAYOUT: Layout = unsafe { Layout::from_size_align_unchecked(2 * PAGE_SIZE, PAGE_SIZE) };
+
+    /// Guards the objects of size classes of at least `min_object_size`, protecting their guard pages with `protector`,
+    /// or poisoning them if `None`.
+    pub const fn new(
+        min_object_size: usize,
+        protector: Option<&'static dyn PageProtector>,
+    ) -> Self {
+        Self {
+            min_object_size,
+            protector,
+            live_object_count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Whether objects of the size class of `object_size` are guarded. Pages are never guarded.
+    pub fn serves(&self, object_size: usize) -> bool {
+        object_size >= self.min_object_size && object_size < PAGE_SIZE
+    }
+
+    /// Number of guarded objects currently allocated.
+    pub fn live_object_count(&self) -> usize {
+        self.live_object_count.load(Ordering::Relaxed)
+    }
+
+    /// Allocates an object of `object_size` bytes, aligned to `align`, at the end of a page of its own.
+    ///
+    /// The object is placed by the size it was requested with rather than that of its size class, so it ends as close
+    /// to the guard page as `align` allows, and even small overflows reach the guard page.
+    ///
+    /// # Errors
+    ///
+    /// - If the pages can't be allocated from `allocator`, or would exceed `memory_limit`.
+    /// - If `allocator` doesn't align the pages to the page size, as the pages of objects are found from their
+    ///   address as they are freed.
+    pub fn allocate<A: Allocator>(
+        &self,
+        allocator: &A,
+        memory_limit: Option<&MemoryLimit>,
+        object_size: usize,
+        align: usize,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if let Some(memory_limit) = memory_limit {
+            memory_limit.acquire(Self::LAYOUT.size())?;
+        }
+
+        let pages = if zeroed {
+            allocator.allocate_zeroed(Self::LAYOUT)
+        } else {
+            allocator.allocate(Self::LAYOUT)
+        };
+
+        let pages = match pages {
+            Ok(pages) if pages.cast::<u8>().addr().get().is_multiple_of(PAGE_SIZE) => {
+                pages.cast::<u8>()
+            }
+
+            result => {
+                if let Ok(pages) = result {
+                    // Safety: Pages were just allocated with the layout, and were never handed out.
+                    unsafe {
+                        allocator.deallocate(pages.cast(), Self::LAYOUT);
+                    }
+                }
+
+                if let Some(memory_limit) = memory_limit {
+                    memory_limit.release(Self::LAYOUT.size());
+                }
+
+                return Err(AllocError);
+            }
+        };
+
+        // Safety: The guard page is the second page of the allocation.
+        let guard_page = unsafe { pages.add(PAGE_SIZE) };
+        match self.protector {
+            // Safety: Guard page was just allocated, and is never accessed until it is unprotected.
+            Some(protector) => unsafe {
+                protector.protect(NonNull::slice_from_raw_parts(guard_page, PAGE_SIZE));
+            },
+
+            // Safety: Guard page lies within the allocation.
+            None => unsafe { guard_page.write_bytes(GUARD_BYTE, PAGE_SIZE) },
+        }
+
+        self.live_object_count.fetch_add(1, Ordering::Relaxed);
+
+        let offset = (PAGE_SIZE - object_size) & !(align - 1);
+
+        // Safety: Object lies within the first page of the allocation.
+        Ok(NonNull::slice_from_raw_parts(
+            unsafe { pages.add(offset) },
+            object_size,
+        ))
+    }
+
+    /// Deallocates the object at `object_ptr`, releasing its pages to `allocator`.
+    ///
+    /// # Errors
+    ///
+    /// - [`Corruption::GuardPageOverwritten`] if the object's guard page is poisoned and was overwritten, in which
+    ///   case its pages are not released.
+    ///
+    /// # Safety
+    ///
+    /// - `object_ptr` must point to an allocated object of this guard, whose pages were allocated from `allocator`.
+    pub unsafe fn deallocate<A: Allocator>(
+        &self,
+        allocator: &A,
+        memory_limit: Option<&MemoryLimit>,
+        object_ptr: NonNull<u8>,
+    ) -> Result<(), Corruption> {
+        // Safety: Caller is required to ensure the object lies within the first of its pages, which were aligned to the
+        //         page size as they were allocated.
+        let pages = unsafe { object_ptr.sub(object_ptr.addr().get() % PAGE_SIZE) };
+        // Safety: The guard page is the second page of the allocation.
+        let guard_page = NonNull::slice_from_raw_parts(unsafe { pages.add(PAGE_SIZE) }, PAGE_SIZE);
+
+        match self.protector {
+            // Safety: Caller is required to ensure the object is allocated, so its guard page was protected.
+            Some(protector) => unsafe { protector.unprotect(guard_page) },
+
+            None => {
+                // Safety: Guard page lies within the object's pages, which are allocated.
+                let guard_bytes = unsafe { guard_page.as_ref() };
+                if guard_bytes.iter().any(|byte| *byte != GUARD_BYTE) {
+                    return Err(Corruption::GuardPageOverwritten { object_ptr });
+                }
+            }
+        }
+
+        // Safety: Pages were allocated from `allocator` with the layout.
+        unsafe {
+            allocator.deallocate(pages, Self::LAYOUT);
+        }
+
+        if let Some(memory_limit) = memory_limit {
+            memory_limit.release(Self::LAYOUT.size());
+        }
+
+        self.live_object_count.fetch_sub(1, Ordering::Relaxed);
+        Ok(())
+    }
+}
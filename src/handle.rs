@@ -0,0 +1,250 @@
+use crate::{
+    MAX_SIZE_CLASS_COUNT,
+    alloc_api::{AllocError, Allocator, Vec},
+    slab::SlabHeader,
+};
+use core::ptr::NonNull;
+
+const SIZE_CLASS_BITS: u32 = 5;
+const SLAB_NUMBER_BITS: u32 = 12;
+const OBJECT_INDEX_BITS: u32 = 9;
+const GENERATION_BITS: u32 = 6;
+
+const _: () = {
+    assert!(SIZE_CLASS_BITS + SLAB_NUMBER_BITS + OBJECT_INDEX_BITS + GENERATION_BITS == u32::BITS);
+    assert!(MAX_SIZE_CLASS_COUNT <= (1 << SIZE_CLASS_BITS));
+};
+
+/// Compact reference to an object allocated by
+/// [`SlabAllocator::allocate_handle`](crate::SlabAllocator::allocate_handle), packing the index of its size class,
+/// the number of its slab, the index of the object within its slab, and the object's generation into 32 bits.
+///
+/// Handles are resolved to their objects by [`SlabAllocator::resolve`](crate::SlabAllocator::resolve), so data
+/// structures can hold 32-bit references, and object graphs can be serialized without their addresses. With the
+/// `serde` feature, handles are serialized as their raw value.
+///
+/// Each object's generation is advanced as it is freed by handle, so stale handles to freed objects are detected
+/// rather than resolving to whichever object took their place. As generations wrap around, a handle kept across 64
+/// frees of its object's slot goes undetected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct ObjectHandle(u32);
+
+impl ObjectHandle {
+    const SLAB_NUMBER_SHIFT: u32 = Self::OBJECT_INDEX_SHIFT + OBJECT_INDEX_BITS;
+    const SIZE_CLASS_SHIFT: u32 = Self::SLAB_NUMBER_SHIFT + SLAB_NUMBER_BITS;
+    const OBJECT_INDEX_SHIFT: u32 = GENERATION_BITS;
+
+    /// Packs a handle, if each part fits its bits.
+    pub(crate) fn new(
+        size_class_index: usize,
+        slab_number: usize,
+        object_index: usize,
+        generation: usize,
+    ) -> Option<Self> {
+        let fits = |value: usize, bits: u32| value < (1 << bits);
+        if !fits(size_class_index, SIZE_CLASS_BITS)
+            || !fits(slab_number, SLAB_NUMBER_BITS)
+            || !fits(object_index, OBJECT_INDEX_BITS)
+            || !fits(generation, GENERATION_BITS)
+        {
+            return None;
+        }
+
+        let raw = (size_class_index << Self::SIZE_CLASS_SHIFT)
+            | (slab_number << Self::SLAB_NUMBER_SHIFT)
+            | (object_index << Self::OBJECT_INDEX_SHIFT)
+            | generation;
+
+        u32::try_from(raw).ok().map(Self)
+    }
+
+    /// Rebuilds a handle from the value returned by [`ObjectHandle::into_raw`].
+    pub const fn from_raw(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    /// The raw value of the handle.
+    pub const fn into_raw(self) -> u32 {
+        self.0
+    }
+
+    fn part(self, shift: u32, bits: u32) -> usize {
+        // Parts are at most 32 bits wide, so always fit `usize`.
+        #[allow(clippy::as_conversions)]
+        {
+            ((self.0 >> shift) & ((1 << bits) - 1)) as usize
+        }
+    }
+
+    /// Index of the object's size class, in ascending order of object size.
+    pub(crate) fn size_class_index(self) -> usize {
+        self.part(Self::SIZE_CLASS_SHIFT, SIZE_CLASS_BITS)
+    }
+
+    /// Number of the object's slab within its size class.
+    pub(crate) fn slab_number(self) -> usize {
+        self.part(Self::SLAB_NUMBER_SHIFT, SLAB_NUMBER_BITS)
+    }
+
+    /// Index of the object within its slab.
+    pub(crate) fn object_index(self) -> usize {
+        self.part(Self::OBJECT_INDEX_SHIFT, OBJECT_INDEX_BITS)
+    }
+
+    /// Generation of the object the handle was allocated for.
+    fn generation(self) -> usize {
+        self.part(0, GENERATION_BITS)
+    }
+}
+
+/// Slab number, with the generation of each object of the slab numbered by it.
+struct NumberedSlab<A: Allocator> {
+    /// Header of the numbered slab, with the number of handles to its objects, or `None` if the number is free.
+    slab: Option<(NonNull<SlabHeader>, usize)>,
+
+    /// Generation of each object, which is kept as the number is freed, so stale handles are still detected once it
+    /// is reused.
+    generations: Vec<u8, A>,
+}
+
+/// Numbers of the slabs of a size class holding objects referenced by handles.
+///
+/// Each slab is numbered for as long as handles to its objects are allocated, which keeps it from being released, so
+/// numbers are only reused once a slab is no longer referenced.
+pub(crate) struct SlabNumbers<A: Allocator> {
+    numbers: Vec<NumberedSlab<A>, A>,
+
+    /// Number of the slab most recently numbered, which most handles are allocated from.
+    last_number: usize,
+}
+
+// Safety: Headers are only accessed through the numbers with the owning size class' lock held.
+unsafe impl<A: Allocator + Send> Send for SlabNumbers<A> {}
+
+impl<A: Allocator> SlabNumbers<A> {
+    pub fn new_in(allocator: A) -> Self {
+        Self {
+            numbers: Vec::new_in(allocator),
+            last_number: 0,
+        }
+    }
+
+    /// Counts one fewer handle to the slab numbered `slab_number`, advancing the generation of its object at
+    /// `object_index`, and unnumbering the slab once no handles to it are left.
+    pub fn release(&mut self, slab_number: usize, object_index: usize) {
+        let number = &mut self.numbers[slab_number];
+        let generation = &mut number.generations[object_index];
+        *generation = (*generation + 1) & ((1 << GENERATION_BITS) - 1);
+
+        if let Some((_, handle_count)) = &mut number.slab {
+            *handle_count -= 1;
+            if *handle_count == 0 {
+                number.slab = None;
+            }
+        }
+    }
+
+    /// Unnumbers every slab, advancing the generation of each of their objects, so every handle allocated is stale.
+    pub fn clear(&mut self) {
+        for number in &mut self.numbers {
+            if number.slab.take().is_some() {
+                for generation in &mut number.generations {
+                    *generation = (*generation + 1) & ((1 << GENERATION_BITS) - 1);
+                }
+            }
+        }
+    }
+
+    /// Header of the slab of `handle`'s object, or `None` if the handle is stale or was never allocated.
+    pub fn get(&self, handle: ObjectHandle) -> Option<NonNull<SlabHeader>> {
+        let number = self.numbers.get(handle.slab_number())?;
+        let generation = number.generations.get(handle.object_index())?;
+
+        (usize::from(*generation) == handle.generation())
+            .then_some(number.slab)
+            .flatten()
+            .map(|(header, _)| header)
+    }
+
+    /// Number of bytes allocated to hold the numbers and generations.
+    pub fn metadata_bytes(&self) -> usize {
+        (self.numbers.capacity() * size_of::<NumberedSlab<A>>())
+            + self
+                .numbers
+                .iter()
+                .map(|number| number.generations.capacity())
+                .sum::<usize>()
+    }
+}
+
+impl<A: Allocator + Clone> SlabNumbers<A> {
+    /// Number of the slab headed by `header`, numbering it if it isn't yet, and counting another handle to it, with
+    /// the generation of its object at `object_index`.
+    ///
+    /// # Errors
+    ///
+    /// - If the slab can't be numbered.
+    pub fn acquire(
+        &mut self,
+        header: NonNull<SlabHeader>,
+        object_index: usize,
+    ) -> Result<(usize, usize), AllocError> {
+        let is_header = |number: &NumberedSlab<A>| {
+            number
+                .slab
+                .is_some_and(|(slab_header, _)| slab_header == header)
+        };
+
+        let number_index = if self.numbers.get(self.last_number).is_some_and(is_header) {
+            self.last_number
+        } else if let Some(number_index) = self.numbers.iter().position(is_header) {
+            number_index
+        } else {
+            self.number(header)?
+        };
+
+        let number = &mut self.numbers[number_index];
+        if let Some((_, handle_count)) = &mut number.slab {
+            *handle_count += 1;
+        }
+        self.last_number = number_index;
+
+        Ok((number_index, usize::from(number.generations[object_index])))
+    }
+
+    /// Numbers the slab headed by `header`, reusing a free number if there is one.
+    fn number(&mut self, header: NonNull<SlabHeader>) -> Result<usize, AllocError> {
+        // Safety: Slab holds the object a handle is being allocated for, so can't be released.
+        let object_count = unsafe { header.as_ref() }.object_count();
+
+        let number_index = match self.numbers.iter().position(|number| number.slab.is_none()) {
+            Some(number_index) => number_index,
+            None => {
+                self.numbers.try_reserve(1).map_err(|_| AllocError)?;
+                self.numbers.push(NumberedSlab {
+                    slab: None,
+                    generations: Vec::new_in(self.numbers.allocator().clone()),
+                });
+
+                self.numbers.len() - 1
+            }
+        };
+
+        let generations = &mut self.numbers[number_index].generations;
+        if generations.len() < object_count {
+            generations
+                .try_reserve_exact(object_count - generations.len())
+                .map_err(|_| AllocError)?;
+            generations.resize(object_count, 0);
+        }
+
+        self.numbers[number_index].slab = Some((header, 0));
+        Ok(number_index)
+    }
+}
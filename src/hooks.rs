@@ -0,0 +1,31 @@
+use core::{alloc::Layout, ptr::NonNull};
+
+/// Callbacks for events within a [`SlabAllocator`](crate::SlabAllocator), so integrators can do their own accounting,
+/// tracing or debugging. See [`SlabAllocator::with_hooks`](crate::SlabAllocator::with_hooks).
+///
+/// Every callback does nothing by default, so only the events of interest need be implemented. Callbacks may be made
+/// while the allocator's locks are held, so they must not allocate from, or otherwise call into, the allocator that
+/// made them.
+pub trait AllocatorHooks: Sync {
+    /// Called as a slab of `object_size`-byte objects spanning `slab` is created, before any of its objects are
+    /// handed out.
+    fn on_slab_created(&self, object_size: usize, slab: NonNull<[u8]>) {
+        let _ = (object_size, slab);
+    }
+
+    /// Called as the slab of `object_size`-byte objects spanning `slab` is destroyed, before its memory is released to
+    /// the backing allocator.
+    fn on_slab_destroyed(&self, object_size: usize, slab: NonNull<[u8]>) {
+        let _ = (object_size, slab);
+    }
+
+    /// Called as an allocation of `layout`, which no size class serves, falls through to the fallback allocator.
+    fn on_fallthrough(&self, layout: Layout) {
+        let _ = layout;
+    }
+
+    /// Called as an allocation of `layout` fails, before the failure is returned.
+    fn on_alloc_failure(&self, layout: Layout) {
+        let _ = layout;
+    }
+}
@@ -0,0 +1,50 @@
+use core::fmt;
+
+/// Objects of a size class still allocated as their allocator was dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct Leak {
+    /// Object size of the size class, the page size for the page size class, or the size of the leaked allocations of
+    /// the fallback allocator, if they are [tracked](crate::SlabAllocator::with_fallback_tracking).
+    pub object_size: usize,
+
+    /// Number of objects still allocated.
+    pub object_count: usize,
+}
+
+impl fmt::Display for Leak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} objects of the {}-byte size class were leaked",
+            self.object_count, self.object_size
+        )
+    }
+}
+
+/// Called for each size class with objects still allocated as its allocator is dropped, before the slabs holding them
+/// are released, and for each size of tracked allocations of the fallback allocator still allocated.
+///
+/// Leaks are ignored unless a handler is set, as an allocator dropped while unwinding from a panic would otherwise
+/// abort. Debug builds may set [`panic_on_leak`] to assert a clean shutdown.
+pub type LeakHandler = fn(Leak);
+
+/// A [`LeakHandler`] that panics.
+pub fn panic_on_leak(leak: Leak) {
+    panic!("allocator dropped with live objects: {leak}");
+}
+
+/// Reports `object_count` objects of the size class of `object_size` leaked to `handler`.
+pub(crate) fn report_leak(handler: Option<LeakHandler>, object_size: usize, object_count: usize) {
+    if object_count == 0 {
+        return;
+    }
+
+    if let Some(handler) = handler {
+        handler(Leak {
+            object_size,
+            object_count,
+        });
+    }
+}
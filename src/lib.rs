@@ -1,379 +1,3726 @@
 #![cfg_attr(not(test), no_std)]
-#![feature(allocator_api, slice_ptr_get)]
+#![cfg_attr(not(feature = "allocator-api2"), feature(allocator_api))]
 
 #[cfg(test)]
 mod tests;
 
-extern crate alloc;
+mod arena;
+pub use arena::SlabArena;
+
+mod backing;
+
+mod boxed;
+pub use boxed::SlabBox;
+
+mod checkpoint;
+pub use checkpoint::Checkpoint;
+
+mod builder;
+pub use builder::SlabAllocatorBuilder;
+
+mod cache;
+pub use cache::{Constructor, Destructor, ObjectCache};
+
+#[cfg(feature = "critical-section")]
+mod critical_section;
+#[cfg(feature = "critical-section")]
+pub use critical_section::CriticalSectionRawMutex;
+
+mod clock;
+pub use clock::Clock;
+
+mod consistency;
+pub use consistency::{ConsistencyReport, Inconsistency, MAX_REPORTED_INCONSISTENCIES};
+
+mod corruption;
+pub use corruption::{Corruption, CorruptionHandler, panic_on_corruption};
+
+mod error;
+pub use error::TryAllocError;
+
+#[cfg(feature = "test-utils")]
+mod failing;
+#[cfg(feature = "test-utils")]
+pub use failing::FailingAllocator;
+
+mod fallback;
+
+mod global;
+pub use global::GlobalSlabAllocator;
+
+mod guard;
+pub use guard::PageProtector;
+
+mod handle;
+pub use handle::ObjectHandle;
+
+mod hooks;
+pub use hooks::AllocatorHooks;
+
+mod leak;
+pub use leak::{Leak, LeakHandler, panic_on_leak};
+
+mod limit;
+pub use limit::MemoryLimit;
+
+mod list;
+
+mod local;
+pub use local::{LocalRawMutex, LocalSlabAllocator};
+
+mod magazine;
+pub use magazine::{DEPOT_CAPACITY, MAGAZINE_CAPACITY, Topology};
+
+mod manager;
+pub use manager::SlabManager;
+
+mod metadata;
+
+mod numa;
+pub use numa::{NumaSlabAllocator, NumaTopology};
+
+mod oom;
+pub use oom::{OomHandler, OutOfMemory};
+
+mod page_class;
+pub use page_class::DEFAULT_PAGE_CACHE_CAPACITY;
+
+#[cfg(feature = "test-utils")]
+mod page_arena;
+#[cfg(feature = "test-utils")]
+pub use page_arena::PageArena;
+
+mod page_source;
+pub use page_source::{PageSource, PageSourceAllocator};
+
+mod quarantine;
+
+mod random;
+pub use random::RandomSource;
+
+mod reclaim;
+pub use reclaim::MemoryReclaim;
 
-use alloc::{
-    alloc::{AllocError, Allocator},
-    vec::Vec,
+mod region;
+pub use region::RegionAllocator;
+
+mod remote;
+
+#[cfg(feature = "redzone")]
+mod redzone;
+
+mod sampling;
+use sampling::Sampler;
+pub use sampling::{AllocationSample, SamplingHandler};
+
+mod selection;
+pub use selection::SlabSelection;
+
+mod shared;
+pub use shared::SharedSlabAllocator;
+
+#[cfg(feature = "allocation-sites")]
+mod sites;
+#[cfg(feature = "allocation-sites")]
+pub use sites::{AllocationSite, AllocationSiteStats};
+
+mod size_classes;
+pub use size_classes::{DefaultSizeClasses, SizeClasses};
+
+mod slab;
+pub use slab::{DEFAULT_PAGE_SIZE, MAX_SLAB_PAGE_COUNT, Slab};
+
+mod stats;
+pub use stats::{
+    FallbackStats, OccupancyHistogram, SIZE_CLASS_COUNT, SizeClassStats, SlabAllocatorStats,
 };
-use core::{alloc::Layout, cmp::max, ops::Range, ptr::NonNull};
-use spin::RwLock;
 
-const fn objects_per_page<const OBJECT_SIZE: usize>() -> usize {
-    0x1000 / OBJECT_SIZE
-}
+mod tagging;
+pub use tagging::MemoryTagger;
+#[cfg(all(target_arch = "aarch64", target_feature = "mte"))]
+pub use tagging::Mte;
 
-struct Slab<const OBJECT_SIZE: usize, A: Allocator> {
-    bitmap: u64,
-    memory: NonNull<[u8]>,
-    inner: A,
-}
+#[cfg(feature = "test-utils")]
+mod tracking;
+#[cfg(feature = "test-utils")]
+pub use tracking::{TrackedAllocation, TrackingAllocator, TrackingEvent};
 
-impl<const OBJECT_SIZE: usize, A: Allocator> Slab<OBJECT_SIZE, A> {
-    // Safety: Layout is known to be valid.
-    const LAYOUT: Layout = unsafe { Layout::from_size_align_unchecked(0x1000, 0x1000) };
+mod tree;
 
-    pub fn new_in(allocator: A) -> Result<Self, AllocError> {
-        assert!(OBJECT_SIZE >= 64);
-        assert!(OBJECT_SIZE < 0x1000);
-        assert!(OBJECT_SIZE.is_power_of_two());
+extern crate alloc;
 
-        // `objects_per_page()` will never overflow `u32`.
-        #[allow(clippy::cast_possible_truncation, clippy::as_conversions)]
-        let objects_per_page = objects_per_page::<OBJECT_SIZE>() as u32;
+/// The allocator API in use: `core`'s unstable API by default, or `allocator-api2`'s on stable toolchains.
+mod alloc_api {
+    #[cfg(not(feature = "allocator-api2"))]
+    pub use alloc::{
+        alloc::{AllocError, Allocator},
+        vec::Vec,
+    };
+
+    #[cfg(feature = "allocator-api2")]
+    pub use allocator_api2::{
+        alloc::{AllocError, Allocator},
+        vec::Vec,
+    };
+
+    #[cfg(all(test, not(feature = "allocator-api2")))]
+    pub use alloc::alloc::Global;
+
+    #[cfg(all(test, feature = "allocator-api2"))]
+    pub use allocator_api2::alloc::Global;
+}
 
-        Ok(Self {
-            bitmap: 1u64.unbounded_shl(objects_per_page).wrapping_sub(1),
-            memory: allocator.allocate(Self::LAYOUT)?,
-            inner: allocator,
-        })
+/// Lock used to guard allocator state when no other lock is specified.
+///
+/// Allocators called from interrupt context should instead use a lock that masks interrupts while held, such as
+/// `CriticalSectionRawMutex` of the `critical-section` feature.
+pub type DefaultRawMutex = spin::Mutex<()>;
+
+use alloc_api::{AllocError, Allocator, Vec};
+use backing::{Backing, BackingRef};
+use builder::SizeClassConfig;
+use checkpoint::SizeClassCheckpoint;
+use core::{
+    alloc::Layout,
+    cmp::{max, min},
+    iter,
+    num::NonZero,
+    pin::Pin,
+    ptr::{self, NonNull},
+    slice,
+    time::Duration,
+};
+use fallback::FallbackRegistry;
+use guard::GuardPages;
+use handle::SlabNumbers;
+use lock_api::{Mutex, RawMutex};
+use magazine::MagazineLayer;
+use page_class::PageClass;
+use remote::RemoteFreeQueue;
+#[cfg(feature = "allocation-sites")]
+use sites::SiteRegistry;
+use slab::{MIN_OBJECT_SIZE, SlabHeader};
+use spin::Once;
+use stats::Counters;
+
+/// Size of the slab objects needed to hold values of `layout`, if it can be served by a slab of `page_size` pages.
+fn object_size_for(layout: Layout, page_size: usize) -> Option<usize> {
+    let object_size = max(
+        max(layout.size().next_power_of_two(), layout.align()),
+        MIN_OBJECT_SIZE,
+    );
+
+    if object_size < page_size {
+        Some(object_size)
+    } else {
+        None
     }
+}
+
+/// Object sizes of the size classes a [`SlabAllocator`] has unless others are given, in ascending order.
+///
+/// Besides the power-of-two size classes, intermediate size classes of three quarters of the next power of two
+/// reduce the memory wasted rounding objects up, for objects aligned to no more than a quarter of it.
+pub const DEFAULT_SIZE_CLASSES: &[usize] = &[
+    #[cfg(feature = "small-size-classes")]
+    8,
+    #[cfg(feature = "small-size-classes")]
+    16,
+    #[cfg(feature = "small-size-classes")]
+    32,
+    64,
+    96,
+    128,
+    192,
+    256,
+    384,
+    512,
+    768,
+    1024,
+    1536,
+    2048,
+];
+
+/// Maximum number of slab size classes of a [`SlabAllocator`].
+pub const MAX_SIZE_CLASS_COUNT: usize = 32;
+
+/// Maximum number of lock shards a size class can be split into. See [`SlabAllocator::with_lock_shards`].
+pub const MAX_LOCK_SHARD_COUNT: usize = 256;
+
+/// Whether slabs of `page_size` pages can hold objects of `object_size`: a multiple of the pointer size, with room
+/// for at least one object in a single page.
+const fn is_valid_object_size(object_size: usize, page_size: usize) -> bool {
+    object_size >= MIN_OBJECT_SIZE
+        && object_size.is_multiple_of(size_of::<usize>())
+        && object_size < page_size
+        && slab::object_count(object_size, page_size, false) > 0
+}
 
-    /// Range of addresses that are covered by this slab.
-    pub fn memory_range(&self) -> Range<usize> {
-        let start_address = self.memory.addr().get();
-        start_address..(start_address + self.memory.len())
+/// Whether `object_sizes` can be the size classes of slabs of `page_size` pages: no more than
+/// [`MAX_SIZE_CLASS_COUNT`] valid object sizes, in strictly ascending order.
+const fn are_valid_size_classes(object_sizes: &[usize], page_size: usize) -> bool {
+    if object_sizes.len() > MAX_SIZE_CLASS_COUNT {
+        return false;
     }
 
-    /// Currently remaining (free) objects in this slab.
-    pub fn remaining_object_count(&self) -> usize {
-        // `u64::count_ones()` will never overflow a `usize`.
-        #[allow(clippy::cast_possible_truncation, clippy::as_conversions)]
+    let mut class_index = 0;
+    while class_index < object_sizes.len() {
+        if !is_valid_object_size(object_sizes[class_index], page_size)
+            || (class_index > 0 && object_sizes[class_index - 1] >= object_sizes[class_index])
         {
-            self.bitmap.count_ones() as usize
+            return false;
         }
-    }
 
-    /// Whether the slab is empty.
-    pub fn is_empty(&self) -> bool {
-        self.remaining_object_count() == 0
+        class_index += 1;
     }
 
-    pub fn next_object(&mut self) -> Option<NonNull<[u8]>> {
-        (!self.is_empty()).then(|| {
-            // `u64::trailing_zeros()` will never overflow a `usize`.
-            #[allow(clippy::cast_possible_truncation, clippy::as_conversions)]
-            let object_index = self.bitmap.trailing_zeros() as usize;
-
-            debug_assert!((self.bitmap & (1 << object_index)) > 0);
+    true
+}
 
-            // Clear the bit in the bitmap.
-            self.bitmap &= !(1 << object_index);
+/// Alignment of every object of the size class of `object_size`.
+///
+/// Objects are packed against the end of their slab, which is aligned to at least a page, so each object is aligned
+/// to the largest power of two dividing the object size.
+fn object_align(object_size: usize) -> usize {
+    1 << object_size.trailing_zeros()
+}
 
-            let byte_index_start = object_index * OBJECT_SIZE;
-            let byte_index_end = byte_index_start + OBJECT_SIZE;
+/// Size of the slot needed to hold an object of `layout`.
+fn slot_size_for(layout: Layout) -> usize {
+    // With redzones, objects are held within larger slots.
+    #[cfg(feature = "redzone")]
+    {
+        redzone::slot_size(layout)
+    }
 
-            // Safety: Indexes are checked to be within bounds.
-            unsafe {
-                self.memory
-                    .get_unchecked_mut(byte_index_start..byte_index_end)
-            }
-        })
+    #[cfg(not(feature = "redzone"))]
+    {
+        layout.size()
     }
+}
 
-    /// # Safety
-    ///
-    /// - `object_ptr` must point to an object that originated from this slab.
-    pub unsafe fn return_object(&mut self, object_ptr: NonNull<u8>) {
-        debug_assert!(self.memory_range().contains(&object_ptr.addr().get()));
+/// Empty slice aligned for `layout`, handed out for zero-size allocations without touching any slab or allocator.
+fn dangling_for(layout: Layout) -> NonNull<[u8]> {
+    // Alignments are never zero.
+    let ptr = NonNull::without_provenance(NonZero::new(layout.align()).unwrap());
 
-        // Safety:
-        // - `object` is checked to be contained by `self.memory`.
-        // - `object`, lying within `self.memory`, points to the same allocation.
-        let byte_offset = unsafe { object_ptr.byte_offset_from_unsigned(self.memory) };
-        let object_index = byte_offset >> OBJECT_SIZE.trailing_zeros();
+    NonNull::slice_from_raw_parts(ptr, 0)
+}
 
-        debug_assert!((self.bitmap & (1 << object_index)) == 0);
+/// Number of objects returned to a size class at once by [`SlabAllocator::deallocate_many`].
+const DEALLOCATION_BATCH_SIZE: usize = 32;
 
-        // Set the bit in the bitmap.
-        self.bitmap |= 1 << object_index;
+/// Scrubs an object freed without being deallocated, as its deallocation would have.
+#[cfg_attr(not(feature = "zeroize-on-free"), allow(unused_variables))]
+fn scrub_object(object: NonNull<[u8]>) {
+    // Safety: Objects are only scrubbed once freed, so are no longer referenced.
+    #[cfg(feature = "zeroize-on-free")]
+    unsafe {
+        object.cast::<u8>().write_bytes(0, object.len());
     }
 }
 
-impl<const OBJECT_SIZE: usize, A: Allocator> Drop for Slab<OBJECT_SIZE, A> {
-    fn drop(&mut self) {
-        // Safety: `self` is being dropped, `self.slab` will no longer be used.
-        unsafe {
-            self.inner
-                .deallocate(self.memory.as_non_null_ptr(), Self::LAYOUT);
-        }
-    }
+/// Whether the object at `ptr` is aligned for `layout`, so it can be resized in place to it. Objects of size classes
+/// serving [aligned objects](SizeClass::allocate_aligned) aren't all aligned alike.
+fn is_aligned_for(ptr: NonNull<u8>, layout: Layout) -> bool {
+    ptr.addr().get().is_multiple_of(layout.align())
 }
 
-impl<const OBJECT_SIZE: usize, A: Allocator> core::fmt::Debug for Slab<OBJECT_SIZE, A> {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("Slab")
-            .field("remaining_object_count", &self.remaining_object_count())
-            .field("memory", &self.memory)
-            .finish()
-    }
+/// Whether an object's redzones can be moved to fit `new_layout`, which requires the leading redzone to be unchanged.
+fn can_move_redzones(old_layout: Layout, new_layout: Layout) -> bool {
+    !cfg!(feature = "redzone") || old_layout.align() == new_layout.align()
 }
 
-struct SlabManager<const OBJECT_SIZE: usize, A: Allocator> {
-    slabs: Vec<Slab<OBJECT_SIZE, A>, A>,
-    remaining_object_count: usize,
-    inner: A,
+/// A single size class: a slab manager, optionally fronted by a per-CPU magazine layer and a remote-free queue.
+///
+/// Hot size classes may be split into lock shards, each a slab manager of its own, so processors allocating from
+/// different shards don't contend for the same locks. Each slab records its shard, so objects are returned to the
+/// shard they were allocated from.
+///
+/// Size classes are stored contiguously, so each is aligned to its own cache line (see [`slab::CACHE_LINE_SIZE`]) to
+/// keep the locks and counters of neighbouring size classes from false sharing.
+#[repr(align(64))]
+struct SizeClass<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> {
+    slabs: SlabManager<A, R, PAGE_SIZE>,
+
+    /// Lock shards beyond the first, which is `slabs`.
+    shards: Vec<SlabManager<A, R, PAGE_SIZE>, A>,
+    shard_topology: Option<&'static dyn Topology>,
+    magazines: Option<MagazineLayer<A, R>>,
+    remote_frees: Option<RemoteFreeQueue>,
+
+    /// Numbers of the slabs holding objects allocated by handle.
+    slab_numbers: Mutex<R, SlabNumbers<A>>,
+    leak_handler: Option<LeakHandler>,
+    counters: Counters,
 }
 
-impl<const SIZE_BITS: usize, A: Allocator + Clone> SlabManager<SIZE_BITS, A> {
-    pub fn new_in(allocator: A) -> Self {
+impl<A: Allocator + Clone, R: RawMutex, const PAGE_SIZE: usize> SizeClass<A, R, PAGE_SIZE> {
+    pub fn new_in(
+        object_size: usize,
+        allocator: A,
+        topology: Option<&'static dyn Topology>,
+    ) -> Self {
         Self {
-            slabs: Vec::new_in(allocator.clone()),
-            remaining_object_count: 0,
-            inner: allocator,
+            slabs: SlabManager::new_in(object_size, allocator.clone()),
+            shards: Vec::new_in(allocator.clone()),
+            shard_topology: None,
+            magazines: topology.map(|topology| MagazineLayer::new_in(topology, allocator.clone())),
+            remote_frees: None,
+            slab_numbers: Mutex::new(SlabNumbers::new_in(allocator)),
+            leak_handler: None,
+            counters: Counters::new(),
         }
     }
 
-    pub fn next_object(&mut self) -> Result<NonNull<[u8]>, AllocError> {
-        if self.is_empty() {
-            let mut new_slab = Slab::new_in(self.inner.clone())?;
+    pub fn set_leak_handler(&mut self, handler: LeakHandler) {
+        self.leak_handler = Some(handler);
+    }
+
+    /// Records which objects are allocated, returning the objects held after being freed to their slabs first, so
+    /// they're recorded as free.
+    ///
+    /// # Errors
+    ///
+    /// - If the checkpoint can't be allocated.
+    pub fn checkpoint<C: Allocator + Clone>(
+        &self,
+        allocator: C,
+    ) -> Result<SizeClassCheckpoint<C>, AllocError> {
+        self.flush_freed_objects();
+
+        let mut checkpoint = SizeClassCheckpoint::new_in(self.object_size(), allocator);
+
+        for shard in self.shards() {
+            for slab in shard.lists().slabs() {
+                checkpoint.record(&slab)?;
+            }
+        }
 
-            debug_assert!(!new_slab.is_empty());
+        Ok(checkpoint)
+    }
 
-            // Safety: Slab was just allocated.
-            let object = unsafe { new_slab.next_object().unwrap_unchecked() };
+    pub fn enable_remote_frees(&mut self) {
+        self.remote_frees = Some(RemoteFreeQueue::new());
+    }
 
-            self.remaining_object_count += new_slab.remaining_object_count();
+    /// Splits the size class into `shard_count` lock shards, with the settings of the first, allocating from the
+    /// shard of the current processor of `topology`.
+    ///
+    /// # Errors
+    ///
+    /// - If the shards, or their quarantines, can't be allocated.
+    ///
+    /// # Panics
+    ///
+    /// - If `shard_count` is zero, or exceeds [`MAX_LOCK_SHARD_COUNT`].
+    /// - If any slab has already been created.
+    pub fn set_lock_shards(
+        &mut self,
+        topology: &'static dyn Topology,
+        shard_count: usize,
+    ) -> Result<(), AllocError> {
+        assert!(shard_count > 0 && shard_count <= MAX_LOCK_SHARD_COUNT);
+        assert!(self.shards().all(|shard| shard.lists().slab_count() == 0));
+
+        self.shards.clear();
+        self.shards
+            .try_reserve_exact(shard_count - 1)
+            .map_err(|_| AllocError)?;
+
+        for shard in 1..shard_count {
+            // Shard count is bounded by `MAX_LOCK_SHARD_COUNT`, so will never truncate.
+            #[allow(clippy::cast_possible_truncation, clippy::as_conversions)]
+            self.shards.push(self.slabs.new_shard(shard as u8)?);
+        }
 
-            self.slabs.push(new_slab);
+        self.shard_topology = Some(topology);
+        Ok(())
+    }
 
-            Ok(object)
-        } else {
-            let object = self.slabs.iter_mut().find_map(Slab::next_object).unwrap();
+    /// Applies the settings of `config`, before any slab is created.
+    pub fn configure(&mut self, config: &SizeClassConfig) -> Result<(), AllocError> {
+        if let Some(slab_page_count) = config.slab_page_count {
+            self.slabs.set_slab_page_count(slab_page_count);
+        }
 
-            self.remaining_object_count -= 1;
+        self.set_empty_slab_retention(config.empty_slab_retention);
+        self.set_slab_limit(config.slab_limit);
+        self.set_quarantine_capacity(config.quarantine_capacity)?;
 
-            Ok(object)
+        match config.lock_shards {
+            Some((topology, shard_count)) => self.set_lock_shards(topology, shard_count),
+            None => Ok(()),
         }
     }
-}
 
-impl<const SIZE_BITS: usize, A: Allocator> SlabManager<SIZE_BITS, A> {
-    pub fn remaining_object_count(&self) -> usize {
-        self.remaining_object_count
+    pub fn allocate(&self) -> Result<NonNull<[u8]>, AllocError> {
+        if let Some(magazines) = &self.magazines
+            && let Some(object_ptr) = magazines.pop()
+        {
+            self.counters.record_allocation();
+            return Ok(NonNull::slice_from_raw_parts(
+                object_ptr,
+                self.object_size(),
+            ));
+        }
+
+        self.drain_remote_frees();
+
+        let object = self.shard().next_object()?;
+        self.counters.record_allocation();
+
+        Ok(object)
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.remaining_object_count() == 0
+    pub fn allocate_zeroed(&self) -> Result<NonNull<[u8]>, AllocError> {
+        if let Some(magazines) = &self.magazines
+            && let Some(object_ptr) = magazines.pop()
+        {
+            // Safety: Object was just taken from the magazine layer, and so isn't referenced.
+            unsafe {
+                object_ptr.write_bytes(0, self.object_size());
+            }
+
+            self.counters.record_allocation();
+            return Ok(NonNull::slice_from_raw_parts(
+                object_ptr,
+                self.object_size(),
+            ));
+        }
+
+        self.drain_remote_frees();
+
+        let object = self.shard().next_zeroed_object()?;
+        self.counters.record_allocation();
+
+        Ok(object)
     }
 
-    /// # Safety
+    /// Allocates an object aligned to `align`, beyond the objects' own alignment, from whichever slab of the current
+    /// processor's shard holds a free one, zeroed if `zeroed`.
     ///
-    /// - `object_ptr` must point to an object that originated from this slab manager.
-    pub unsafe fn return_object(&mut self, object_ptr: NonNull<u8>) {
-        let slab = self
-            .slabs
-            .iter_mut()
-            .find(|slab| slab.memory_range().contains(&object_ptr.addr().get()));
-        debug_assert!(slab.is_some());
+    /// Aligned objects bypass the magazine layer, as cached objects are rarely aligned to more than their own
+    /// alignment, and are searched for through every slab, so are slower to allocate than other objects.
+    pub fn allocate_aligned(
+        &self,
+        align: usize,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.drain_remote_frees();
+
+        let object = self.shard().next_aligned_object(align, zeroed)?;
+        self.counters.record_allocation();
+
+        Ok(object)
+    }
 
-        // Safety: Caller is required to ensure object belongs to this slab manager.
-        unsafe {
-            slab.unwrap_unchecked().return_object(object_ptr);
+    pub fn try_allocate_aligned(&self, align: usize) -> Result<NonNull<[u8]>, TryAllocError> {
+        let object = self.shard().try_next_aligned_object(align)?;
+        self.counters.record_allocation();
+
+        Ok(object)
+    }
+
+    /// Ensures at least `object_count` free objects are held in the slabs of the current processor's shard.
+    pub fn reserve(&self, object_count: usize) -> Result<(), AllocError> {
+        self.shard().reserve(object_count)
+    }
+
+    pub fn try_allocate(&self) -> Result<NonNull<[u8]>, TryAllocError> {
+        if let Some(magazines) = &self.magazines
+            && let Some(object_ptr) = magazines.try_pop()
+        {
+            self.counters.record_allocation();
+            return Ok(NonNull::slice_from_raw_parts(
+                object_ptr,
+                self.object_size(),
+            ));
         }
 
-        self.remaining_object_count += 1;
+        let object = self.shard().try_next_object()?;
+        self.counters.record_allocation();
+
+        Ok(object)
     }
-}
 
-impl<const OBJECT_SIZE: usize, A: Allocator> core::fmt::Debug for SlabManager<OBJECT_SIZE, A> {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("SlabManager")
-            .field("remaining_object_count", &self.remaining_object_count())
-            .field("slabs", &self.slabs)
-            .finish()
+    pub fn allocate_many(&self, objects: &mut [NonNull<[u8]>]) -> usize {
+        let mut cached_count = 0;
+
+        if let Some(magazines) = &self.magazines {
+            for object in objects.iter_mut() {
+                let Some(object_ptr) = magazines.pop() else {
+                    break;
+                };
+
+                *object = NonNull::slice_from_raw_parts(object_ptr, self.object_size());
+                cached_count += 1;
+            }
+        }
+
+        if cached_count < objects.len() {
+            self.drain_remote_frees();
+        }
+
+        let object_count = cached_count + self.shard().next_objects(&mut objects[cached_count..]);
+        self.counters.record_allocations(object_count);
+
+        object_count
     }
 }
 
-pub struct SlabAllocator<A: Allocator> {
-    slab_64: RwLock<SlabManager<64, A>>,
-    slab_128: RwLock<SlabManager<128, A>>,
-    slab_256: RwLock<SlabManager<256, A>>,
-    slab_512: RwLock<SlabManager<512, A>>,
-    slab_1024: RwLock<SlabManager<1024, A>>,
-    slab_2048: RwLock<SlabManager<2048, A>>,
-    inner: A,
-}
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> SizeClass<A, R, PAGE_SIZE> {
+    /// Every lock shard, starting with `slabs`.
+    fn shards(&self) -> impl Iterator<Item = &SlabManager<A, R, PAGE_SIZE>> {
+        iter::once(&self.slabs).chain(&self.shards)
+    }
 
-impl<A: Allocator + Clone> SlabAllocator<A> {
-    pub fn new_in(allocator: A) -> Self {
-        Self {
-            slab_64: RwLock::new(SlabManager::new_in(allocator.clone())),
-            slab_128: RwLock::new(SlabManager::new_in(allocator.clone())),
-            slab_256: RwLock::new(SlabManager::new_in(allocator.clone())),
-            slab_512: RwLock::new(SlabManager::new_in(allocator.clone())),
-            slab_1024: RwLock::new(SlabManager::new_in(allocator.clone())),
-            slab_2048: RwLock::new(SlabManager::new_in(allocator.clone())),
-            inner: allocator,
+    /// Every lock shard, starting with `slabs`, for configuring them.
+    pub fn shards_mut(&mut self) -> impl Iterator<Item = &mut SlabManager<A, R, PAGE_SIZE>> {
+        iter::once(&mut self.slabs).chain(&mut self.shards)
+    }
+
+    /// Lock shard serving the current processor.
+    fn shard(&self) -> &SlabManager<A, R, PAGE_SIZE> {
+        match self.shard_topology {
+            Some(topology) => self.shard_at(topology.current_cpu() % (self.shards.len() + 1)),
+            None => &self.slabs,
         }
     }
-}
 
-impl<A: Allocator> SlabAllocator<A> {
-    pub fn remaining_object_count<const OBJECT_SIZE: usize>(&self) -> usize {
-        assert!(OBJECT_SIZE >= 64);
-        assert!(OBJECT_SIZE < 0x1000);
-        assert!(OBJECT_SIZE.is_power_of_two());
+    fn shard_at(&self, index: usize) -> &SlabManager<A, R, PAGE_SIZE> {
+        match index.checked_sub(1) {
+            Some(index) => &self.shards[index],
+            None => &self.slabs,
+        }
+    }
 
-        match OBJECT_SIZE {
-            64 => {
-                let slab_64 = self.slab_64.read();
-                slab_64.remaining_object_count()
-            }
+    /// Lock shard holding the slab of `object_ptr`.
+    ///
+    /// # Safety
+    ///
+    /// - `object_ptr` must point to an allocated object that originated from this size class.
+    unsafe fn owning_shard(&self, object_ptr: NonNull<u8>) -> &SlabManager<A, R, PAGE_SIZE> {
+        if self.shards.is_empty() {
+            return &self.slabs;
+        }
 
-            128 => {
-                let slab_128 = self.slab_128.read();
-                slab_128.remaining_object_count()
-            }
+        // Safety: Caller is required to ensure the object originated from this size class.
+        let Some(header) = (unsafe { self.header_of(object_ptr) }) else {
+            // Foreign pointers are reported as the first shard fails to find their slab.
+            return &self.slabs;
+        };
 
-            256 => {
-                let slab_256 = self.slab_256.read();
-                slab_256.remaining_object_count()
-            }
+        // Safety: Caller is required to ensure the object is allocated, so its slab can't be released.
+        self.shard_at(unsafe { header.as_ref() }.shard())
+    }
 
-            512 => {
-                let slab_512 = self.slab_512.read();
-                slab_512.remaining_object_count()
-            }
+    /// Header of the slab holding `object_ptr`, or `None` if headers are external and no slab of any lock shard holds
+    /// it.
+    ///
+    /// # Safety
+    ///
+    /// - `object_ptr` must point to an allocated object that originated from this size class.
+    unsafe fn header_of(&self, object_ptr: NonNull<u8>) -> Option<NonNull<SlabHeader>> {
+        if self.slabs.has_external_headers() {
+            self.external_header_of(object_ptr)
+        } else {
+            // Safety: Caller is required to ensure the object originated from this size class.
+            Some(unsafe { SlabHeader::containing(object_ptr, self.slabs.slab_size()) })
+        }
+    }
 
-            1024 => {
-                let slab_1024 = self.slab_1024.read();
-                slab_1024.remaining_object_count()
-            }
+    /// Header of the slab holding `ptr`, if headers are external and `ptr` points into a slab of any lock shard.
+    fn external_header_of(&self, ptr: NonNull<u8>) -> Option<NonNull<SlabHeader>> {
+        self.shards()
+            .find_map(|shard| shard.external_header_of(ptr))
+    }
 
-            2048 => {
-                let slab_2048 = self.slab_2048.read();
-                slab_2048.remaining_object_count()
+    /// Returns objects to the slabs of their shards, locking each shard once for every run of its objects.
+    ///
+    /// # Safety
+    ///
+    /// - Every pointer of `object_ptrs` must point to a distinct allocated object that originated from this size
+    ///   class.
+    unsafe fn return_objects(&self, object_ptrs: &[NonNull<u8>]) {
+        // Safety: Caller is required to ensure objects are allocated, and originated from this size class.
+        let same_shard = |object_ptr: &NonNull<u8>, next_ptr: &NonNull<u8>| unsafe {
+            ptr::eq(self.owning_shard(*object_ptr), self.owning_shard(*next_ptr))
+        };
+
+        for run in object_ptrs.chunk_by(same_shard) {
+            // Safety: Caller is required to ensure objects are allocated, and originated from this size class.
+            unsafe {
+                self.owning_shard(run[0]).return_objects(run);
             }
+        }
+    }
 
-            _ => unimplemented!(),
+    /// # Safety
+    ///
+    /// - `object_ptr` must point to an allocated object that originated from this size class.
+    unsafe fn return_object(&self, object_ptr: NonNull<u8>) {
+        // Safety: Caller is required to uphold the safety invariants.
+        unsafe {
+            self.owning_shard(object_ptr).return_object(object_ptr);
         }
     }
-}
 
-// Safety:
-// Memory blocks are not freed unless:
-// - `Allocator::deallocate` is called.
-// - `Self` is dropped.
-unsafe impl<A: Allocator + Clone> Allocator for SlabAllocator<A> {
-    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-        let allocation_size = max(layout.size().next_power_of_two(), layout.align());
-        debug_assert!(allocation_size.is_power_of_two());
+    pub fn object_size(&self) -> usize {
+        self.slabs.object_size()
+    }
 
-        match allocation_size {
-            64 => {
-                let mut slab_64 = self.slab_64.write();
-                slab_64.next_object()
-            }
+    pub fn has_external_headers(&self) -> bool {
+        self.slabs.has_external_headers()
+    }
 
-            128 => {
-                let mut slab_128 = self.slab_128.write();
-                slab_128.next_object()
-            }
+    pub fn remaining_object_count(&self) -> usize {
+        self.shards().map(SlabManager::remaining_object_count).sum()
+    }
 
-            256 => {
-                let mut slab_256 = self.slab_256.write();
-                slab_256.next_object()
-            }
+    pub fn cached_object_count(&self) -> usize {
+        self.magazines
+            .as_ref()
+            .map_or(0, MagazineLayer::cached_object_count)
+    }
 
-            512 => {
-                let mut slab_512 = self.slab_512.write();
-                slab_512.next_object()
-            }
+    pub fn empty_slab_count(&self) -> usize {
+        self.shards().map(SlabManager::empty_slab_count).sum()
+    }
 
-            1024 => {
-                let mut slab_1024 = self.slab_1024.write();
-                slab_1024.next_object()
-            }
+    pub fn empty_slab_retention(&self) -> usize {
+        self.slabs.empty_slab_retention()
+    }
 
-            2048 => {
-                let mut slab_2048 = self.slab_2048.write();
-                slab_2048.next_object()
-            }
+    /// Sets the maximum number of empty slabs kept by each lock shard.
+    pub fn set_empty_slab_retention(&self, count: usize) {
+        for shard in self.shards() {
+            shard.set_empty_slab_retention(count);
+        }
+    }
+
+    pub fn slab_limit(&self) -> usize {
+        self.slabs.slab_limit()
+    }
 
-            _ => self.inner.allocate(layout),
+    /// Sets the maximum number of slabs held by each lock shard.
+    pub fn set_slab_limit(&self, count: usize) {
+        for shard in self.shards() {
+            shard.set_slab_limit(count);
         }
     }
 
-    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
-        let allocation_size = max(layout.size().next_power_of_two(), layout.align());
-        debug_assert!(allocation_size.is_power_of_two());
+    /// # Safety
+    ///
+    /// - Every pointer of `object_ptrs` must point to a distinct object that originated from this size class.
+    pub unsafe fn deallocate_many(&self, object_ptrs: &[NonNull<u8>]) {
+        self.counters.record_deallocations(object_ptrs.len());
 
-        match allocation_size {
-            64 => {
-                let mut slab_64 = self.slab_64.write();
+        #[cfg(feature = "zeroize-on-free")]
+        for object_ptr in object_ptrs {
+            // Safety: Caller is required to ensure the objects are allocated, so they are no longer referenced.
+            unsafe {
+                object_ptr.write_bytes(0, self.object_size());
+            }
+        }
 
-                // Safety: Object size matches this slab size, and so is guaranteed to originate from it.
-                unsafe {
-                    slab_64.return_object(ptr);
-                }
+        let Some(magazines) = &self.magazines else {
+            // Safety: Caller is required to ensure objects belong to this size class.
+            unsafe {
+                self.release_objects(object_ptrs);
             }
 
-            128 => {
-                let mut slab_128 = self.slab_128.write();
+            return;
+        };
 
-                // Safety: Object size matches this slab size, and so is guaranteed to originate from it.
+        for object_ptr in object_ptrs {
+            if let Err(object_ptr) = magazines.push(*object_ptr) {
+                // Safety: Caller is required to ensure object belongs to this size class.
                 unsafe {
-                    slab_128.return_object(ptr);
+                    self.release_objects(slice::from_ref(&object_ptr));
                 }
             }
+        }
+    }
 
-            256 => {
-                let mut slab_256 = self.slab_256.write();
-
-                // Safety: Object size matches this slab size, and so is guaranteed to originate from it.
-                unsafe {
-                    slab_256.return_object(ptr);
-                }
+    /// Returns objects that missed the magazine layer to the slabs, deferring them to the remote-free queue if
+    /// there is one.
+    ///
+    /// # Safety
+    ///
+    /// - Every pointer of `object_ptrs` must point to a distinct free object that originated from this size class.
+    unsafe fn release_objects(&self, object_ptrs: &[NonNull<u8>]) {
+        let Some(remote_frees) = &self.remote_frees else {
+            // Safety: Caller is required to ensure objects belong to this size class.
+            unsafe {
+                self.return_objects(object_ptrs);
             }
 
-            512 => {
-                let mut slab_512 = self.slab_512.write();
+            return;
+        };
 
-                // Safety: Object size matches this slab size, and so is guaranteed to originate from it.
-                unsafe {
-                    slab_512.return_object(ptr);
-                }
+        for object_ptr in object_ptrs {
+            // Safety: Caller is required to ensure the object is free, and objects are large enough and suitably
+            //         aligned to hold a pointer.
+            unsafe {
+                remote_frees.push(*object_ptr);
             }
+        }
+    }
 
-            1024 => {
-                let mut slab_1024 = self.slab_1024.write();
+    /// Returns every object of the remote-free queue to the slabs, in batches.
+    fn drain_remote_frees(&self) {
+        let Some(remote_frees) = &self.remote_frees else {
+            return;
+        };
 
-                // Safety: Object size matches this slab size, and so is guaranteed to originate from it.
-                unsafe {
-                    slab_1024.return_object(ptr);
-                }
-            }
+        let mut object_ptrs = [NonNull::dangling(); DEALLOCATION_BATCH_SIZE];
+        let mut object_count = 0;
 
-            2048 => {
-                let mut slab_2048 = self.slab_2048.write();
+        remote_frees.drain(|object_ptr| {
+            object_ptrs[object_count] = object_ptr;
+            object_count += 1;
 
-                // Safety: Object size matches this slab size, and so is guaranteed to originate from it.
+            if object_count == object_ptrs.len() {
+                // Safety: Queued objects are free, and originated from this size class.
                 unsafe {
-                    slab_2048.return_object(ptr);
+                    self.return_objects(&object_ptrs);
                 }
+
+                object_count = 0;
             }
+        });
 
-            _ => {
-                // Safety: Caller is required to maintain safety invariants.
+        // Safety: Queued objects are free, and originated from this size class.
+        unsafe {
+            self.return_objects(&object_ptrs[..object_count]);
+        }
+    }
+
+    pub fn remote_free_count(&self) -> usize {
+        self.remote_frees.as_ref().map_or(0, RemoteFreeQueue::len)
+    }
+
+    pub fn quarantined_object_count(&self) -> usize {
+        self.shards()
+            .map(SlabManager::quarantined_object_count)
+            .sum()
+    }
+
+    /// Sets how many freed objects are held in the quarantine of each lock shard.
+    pub fn set_quarantine_capacity(&self, capacity: usize) -> Result<(), AllocError> {
+        self.shards()
+            .try_for_each(|shard| shard.set_quarantine_capacity(capacity))
+    }
+
+    pub fn flush_quarantine(&self) {
+        for shard in self.shards() {
+            shard.flush_quarantine();
+        }
+    }
+
+    /// Number of slabs held by every lock shard.
+    fn slab_count(&self) -> usize {
+        self.shards().map(|shard| shard.lists().slab_count()).sum()
+    }
+
+    /// Number of objects held by the slabs, whether allocated or not.
+    pub fn object_capacity(&self) -> usize {
+        self.slab_count() * self.slabs.objects_per_slab()
+    }
+
+    /// Number of objects currently allocated, excluding those cached, queued or quarantined after being freed.
+    pub fn live_object_count(&self) -> usize {
+        self.object_capacity()
+            .saturating_sub(self.remaining_object_count())
+            .saturating_sub(self.cached_object_count())
+            .saturating_sub(self.remote_free_count())
+            .saturating_sub(self.quarantined_object_count())
+    }
+
+    pub fn stats(&self) -> SizeClassStats {
+        let slab_count = self.slab_count();
+        let peak_slab_count = self
+            .shards()
+            .map(SlabManager::peak_slab_count)
+            .sum::<usize>();
+
+        SizeClassStats {
+            object_size: self.object_size(),
+            live_object_count: self.live_object_count(),
+            object_capacity: slab_count * self.slabs.objects_per_slab(),
+            slab_count,
+            page_count: slab_count * self.slabs.slab_page_count(),
+            allocation_count: self.counters.allocation_count(),
+            deallocation_count: self.counters.deallocation_count(),
+            wasted_bytes: self.counters.wasted_bytes(),
+            peak_live_object_count: self.counters.peak_live_object_count(),
+            peak_page_count: peak_slab_count * self.slabs.slab_page_count(),
+            slab_creation_count: self.shards().map(SlabManager::slab_creation_count).sum(),
+        }
+    }
+
+    pub fn reset_stats(&self) {
+        self.counters.reset();
+
+        for shard in self.shards() {
+            shard.reset_stats();
+        }
+    }
+
+    /// Number of bytes allocated for bookkeeping, outside of the slabs themselves.
+    pub fn metadata_bytes(&self) -> usize {
+        self.shards()
+            .map(SlabManager::metadata_bytes)
+            .sum::<usize>()
+            + (self.shards.capacity() * size_of::<SlabManager<A, R, PAGE_SIZE>>())
+            + self
+                .magazines
+                .as_ref()
+                .map_or(0, MagazineLayer::metadata_bytes)
+            + self.slab_numbers.lock().metadata_bytes()
+    }
+
+    /// Returns every object cached in the magazine layer to the slabs.
+    pub fn flush_magazines(&self) {
+        if let Some(magazines) = &self.magazines {
+            magazines.drain(|object_ptr| {
+                // Safety: Cached objects originated from this size class.
                 unsafe {
-                    self.inner.deallocate(ptr, layout);
+                    self.return_object(object_ptr);
                 }
-            }
+            });
+        }
+    }
+
+    /// Marks every object free at once, discarding the objects held by the magazine layer, remote-free queue and
+    /// quarantine rather than returning them one by one.
+    ///
+    /// # Safety
+    ///
+    /// - No object of this size class may be referenced, nor the size class be used concurrently.
+    pub unsafe fn reset(&self) {
+        self.counters.record_deallocations(self.live_object_count());
+
+        if let Some(magazines) = &self.magazines {
+            magazines.drain(|_| {});
+        }
+
+        if let Some(remote_frees) = &self.remote_frees {
+            remote_frees.drain(|_| {});
         }
+
+        self.slab_numbers.lock().clear();
+
+        for shard in self.shards() {
+            // Safety: Caller is required to ensure no object is referenced, nor the size class used concurrently.
+            unsafe {
+                shard.reset(scrub_object);
+            }
+        }
+    }
+
+    /// Frees every object allocated since `checkpoint` was recorded, other than those allocated at the checkpoint,
+    /// then freed and allocated again since.
+    ///
+    /// # Safety
+    ///
+    /// - `checkpoint` must have been recorded from this size class.
+    /// - No object allocated since the checkpoint may be referenced, nor the size class be used concurrently.
+    pub unsafe fn roll_back<C: Allocator>(&self, checkpoint: &SizeClassCheckpoint<C>) {
+        self.flush_freed_objects();
+
+        let freed_count = self
+            .shards()
+            .map(|shard| {
+                // Safety: Caller is required to uphold the safety invariants.
+                unsafe { shard.roll_back(|slab| checkpoint.free_bitmap_of(slab), scrub_object) }
+            })
+            .sum();
+
+        self.counters.record_deallocations(freed_count);
+    }
+
+    /// Returns the objects held by the magazine layer, remote-free queue and quarantine after being freed to the
+    /// slabs.
+    fn flush_freed_objects(&self) {
+        self.flush_magazines();
+        self.drain_remote_frees();
+        self.flush_quarantine();
+    }
+
+    /// Flushes the magazine layer, remote-free queue and quarantine, then releases every empty slab, returning the
+    /// number of bytes released.
+    pub fn trim(&self) -> usize {
+        self.flush_freed_objects();
+        self.shards().map(SlabManager::reclaim_empty).sum()
+    }
+
+    /// Returns the remote-free queue, quarantine and the magazine depot's objects to the slabs, then releases empty
+    /// slabs beyond the retention, returning the number of bytes released.
+    pub fn maintain(&self) -> usize {
+        // Returned objects may empty slabs beyond the retention, which are released as they are returned.
+        let held_bytes = || self.slab_count() * self.slabs.slab_size();
+        let initial_held_bytes = held_bytes();
+
+        self.drain_remote_frees();
+        self.flush_quarantine();
+
+        if let Some(magazines) = &self.magazines {
+            magazines.drain_depot(|object_ptr| {
+                // Safety: Cached objects originated from this size class.
+                unsafe {
+                    self.return_object(object_ptr);
+                }
+            });
+        }
+
+        for shard in self.shards() {
+            shard.release_excess_empty_slabs();
+        }
+
+        initial_held_bytes.saturating_sub(held_bytes())
+    }
+
+    /// Flushes the magazine layer, remote-free queue and quarantine, then releases empty slabs until at least
+    /// `target_bytes` have been released, or none are left, returning the number of bytes released.
+    pub fn reclaim(&self, target_bytes: usize) -> usize {
+        self.flush_magazines();
+        self.drain_remote_frees();
+        self.flush_quarantine();
+
+        let mut released_bytes = 0;
+
+        for shard in self.shards() {
+            if released_bytes >= target_bytes {
+                break;
+            }
+
+            released_bytes += shard.reclaim_empty_up_to(target_bytes - released_bytes);
+        }
+
+        released_bytes
+    }
+
+    /// Counts the slabs of every lock shard by the share of their objects that are allocated.
+    pub fn occupancy_histogram(&self) -> OccupancyHistogram {
+        let mut histogram = OccupancyHistogram::default();
+
+        for shard in self.shards() {
+            shard.record_occupancy(&mut histogram);
+        }
+
+        histogram
+    }
+
+    /// Whether `object_ptr` points to an object of one of the slabs of any lock shard.
+    pub fn owns(&self, object_ptr: NonNull<u8>) -> bool {
+        self.shards().any(|shard| shard.owns(object_ptr))
+    }
+
+    /// Whether `ptr` points into the memory of one of the slabs of any lock shard.
+    pub fn contains(&self, ptr: NonNull<u8>) -> bool {
+        self.shards().any(|shard| shard.contains(ptr))
+    }
+
+    /// Writes a map of every slab's objects, under a line giving the object size for each lock shard.
+    pub fn write_map(&self, writer: &mut impl core::fmt::Write) -> core::fmt::Result {
+        self.shards().try_for_each(|shard| shard.write_map(writer))
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> Drop for SizeClass<A, R, PAGE_SIZE> {
+    fn drop(&mut self) {
+        leak::report_leak(
+            self.leak_handler,
+            self.object_size(),
+            self.live_object_count(),
+        );
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> core::fmt::Debug
+    for SizeClass<A, R, PAGE_SIZE>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SizeClass")
+            .field("slabs", &self.slabs)
+            .field("shards", &self.shards)
+            .field("remote_free_count", &self.remote_free_count())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Size class of a [`SlabAllocator`] backed by `A`, which it shares by reference.
+type BackedSizeClass<A, R, const PAGE_SIZE: usize> = SizeClass<BackingRef<A>, R, PAGE_SIZE>;
+
+/// Chunk of no more than a page of size classes of a [`SlabAllocator`].
+type SizeClassChunk<A, R, const PAGE_SIZE: usize> =
+    Vec<BackedSizeClass<A, R, PAGE_SIZE>, BackingRef<A>>;
+
+/// Chunked size-class table of a [`SlabAllocator`].
+type SizeClassTable<A, R, const PAGE_SIZE: usize> =
+    Vec<SizeClassChunk<A, R, PAGE_SIZE>, BackingRef<A>>;
+
+/// Creates the size-class table of a [`SlabAllocator`] from its backing allocator.
+type CreateSizeClasses<A, R, const PAGE_SIZE: usize> =
+    fn(BackingRef<A>) -> Result<SizeClassTable<A, R, PAGE_SIZE>, AllocError>;
+
+/// Slab allocator serving small allocations from per-size-class slabs, backed by `A`.
+///
+/// Internal state is guarded by locks built on the raw mutex `R`, allowing kernels to supply their own locking
+/// discipline (for instance, a spinlock that disables interrupts).
+///
+/// Slabs are made of pages of `PAGE_SIZE` bytes, which must be a power of two of at least 4 KiB, such as the 16 KiB
+/// or 64 KiB pages of some architectures. Allocations of exactly one page are served by the page size class, and
+/// every other allocation no size class serves by the fallback allocator `F`, which defaults to the backing
+/// allocator. See [`SlabAllocator::with_fallback`]. Zero-size allocations are served by neither: they're handed out
+/// as dangling pointers aligned for their layout, and freeing them does nothing.
+///
+/// The backing allocator is held once, and shared by reference with the size classes, so it needn't be [`Clone`].
+pub struct SlabAllocator<
+    A: Allocator,
+    R: RawMutex = DefaultRawMutex,
+    const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE,
+    F: Allocator = A,
+> {
+    /// Size classes in ascending order of object size, in chunks of no more than a page each, so they can be held by
+    /// backing allocators that only serve single pages.
+    ///
+    /// Allocators [created in a const context](SlabAllocator::const_new_in) create their size classes with
+    /// `create_size_classes` on first use.
+    size_classes: Once<SizeClassTable<A, R, PAGE_SIZE>>,
+    create_size_classes: CreateSizeClasses<A, R, PAGE_SIZE>,
+    pages: PageClass<BackingRef<A>, R, PAGE_SIZE>,
+    corruption_handler: CorruptionHandler,
+    checked_deallocation: bool,
+    lazy_shrink: bool,
+    memory_limit: Option<&'static MemoryLimit>,
+    memory_tagger: Option<&'static dyn MemoryTagger>,
+    guard_pages: Option<GuardPages<PAGE_SIZE>>,
+    sampler: Option<Sampler>,
+    hooks: Option<&'static dyn AllocatorHooks>,
+    fallback_registry: Option<FallbackRegistry<BackingRef<A>, R>>,
+    #[cfg(feature = "allocation-sites")]
+    site_registry: Option<SiteRegistry<BackingRef<A>, R>>,
+
+    /// Fallback allocator, or `None` if allocations no size class serves are made by the backing allocator.
+    fallback: Option<F>,
+
+    /// Backing allocator shared by every other field, so declared last to be dropped after them.
+    inner: Backing<A>,
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> SlabAllocator<A, R, PAGE_SIZE> {
+    /// Creates an allocator with the [`DEFAULT_SIZE_CLASSES`].
+    ///
+    /// # Panics
+    ///
+    /// - If the size classes can't be allocated.
+    pub fn new_in(allocator: A) -> Self {
+        Self::with_size_classes_in(allocator, DEFAULT_SIZE_CLASSES)
+    }
+
+    /// Creates an allocator with a per-CPU magazine layer in front of each size class.
+    ///
+    /// Freed objects are cached by the freeing processor and handed out again without taking the
+    /// size class' lock. Cached objects are only returned to their slabs by [`SlabAllocator::flush_magazines`].
+    ///
+    /// # Panics
+    ///
+    /// - If the size classes can't be allocated.
+    pub fn with_magazines_in(allocator: A, topology: &'static dyn Topology) -> Self {
+        Self::try_new_with_in(allocator, DEFAULT_SIZE_CLASSES, Some(topology))
+            .expect("failed to allocate size classes")
+    }
+
+    /// Creates an allocator with a size class for each of `object_sizes`, rather than the
+    /// [`DEFAULT_SIZE_CLASSES`].
+    ///
+    /// Each allocation is served by the smallest size class whose objects fit and are aligned for its layout. As
+    /// objects are packed against the end of their slab, they are aligned to the largest power of two dividing their
+    /// size. The size classes are held in memory from the backing allocator.
+    ///
+    /// # Panics
+    ///
+    /// - If `object_sizes` isn't in strictly ascending order, or holds more than [`MAX_SIZE_CLASS_COUNT`] sizes.
+    /// - If any object size isn't a multiple of the pointer size, or is too large for a page to hold an object.
+    /// - If the size classes can't be allocated.
+    pub fn with_size_classes_in(allocator: A, object_sizes: &[usize]) -> Self {
+        Self::try_new_with_in(allocator, object_sizes, None)
+            .expect("failed to allocate size classes")
+    }
+
+    /// Creates an allocator with the size classes of `S`, which are checked at compile time. See
+    /// [`SlabAllocator::with_size_classes_in`].
+    ///
+    /// Only the size classes of `S` are held by the allocator, so declaring only the size classes in use keeps its
+    /// footprint to a minimum.
+    ///
+    /// # Panics
+    ///
+    /// - If the size classes can't be allocated.
+    pub fn with_const_size_classes_in<S: SizeClasses>(allocator: A) -> Self {
+        const {
+            assert!(
+                are_valid_size_classes(S::OBJECT_SIZES, PAGE_SIZE),
+                "invalid size classes"
+            );
+        }
+
+        Self::with_size_classes_in(allocator, S::OBJECT_SIZES)
+    }
+
+    fn try_new_with_in(
+        allocator: A,
+        object_sizes: &[usize],
+        topology: Option<&'static dyn Topology>,
+    ) -> Result<Self, AllocError> {
+        let inner = Backing::new(allocator)?;
+        let size_classes = Self::new_size_class_table(inner.handle(), object_sizes, topology)?;
+
+        Ok(Self {
+            size_classes: Once::initialized(size_classes),
+            create_size_classes: Self::new_default_size_class_table,
+            pages: PageClass::new_in(inner.handle()),
+            corruption_handler: panic_on_corruption,
+            checked_deallocation: false,
+            lazy_shrink: false,
+            memory_limit: None,
+            memory_tagger: None,
+            guard_pages: None,
+            sampler: None,
+            hooks: None,
+            fallback_registry: None,
+            #[cfg(feature = "allocation-sites")]
+            site_registry: None,
+            fallback: None,
+            inner,
+        })
+    }
+
+    fn new_size_class_table(
+        allocator: BackingRef<A>,
+        object_sizes: &[usize],
+        topology: Option<&'static dyn Topology>,
+    ) -> Result<SizeClassTable<A, R, PAGE_SIZE>, AllocError> {
+        assert!(
+            are_valid_size_classes(object_sizes, PAGE_SIZE),
+            "invalid size classes"
+        );
+
+        let chunk_len = max(PAGE_SIZE / size_of::<BackedSizeClass<A, R, PAGE_SIZE>>(), 1);
+        let mut size_classes = Vec::new_in(allocator);
+        size_classes
+            .try_reserve_exact(object_sizes.len().div_ceil(chunk_len))
+            .map_err(|_| AllocError)?;
+
+        for chunk_object_sizes in object_sizes.chunks(chunk_len) {
+            let mut chunk = Vec::new_in(allocator);
+            chunk
+                .try_reserve_exact(chunk_object_sizes.len())
+                .map_err(|_| AllocError)?;
+            chunk.extend(
+                chunk_object_sizes
+                    .iter()
+                    .map(|object_size| SizeClass::new_in(*object_size, allocator, topology)),
+            );
+
+            size_classes.push(chunk);
+        }
+
+        Ok(size_classes)
+    }
+
+    fn new_default_size_class_table(
+        allocator: BackingRef<A>,
+    ) -> Result<SizeClassTable<A, R, PAGE_SIZE>, AllocError> {
+        Self::new_size_class_table(allocator, DEFAULT_SIZE_CLASSES, None)
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> SlabAllocator<A, R, PAGE_SIZE> {
+    /// Creates an allocator with the [`DEFAULT_SIZE_CLASSES`] without calling the backing allocator, so it can be
+    /// declared in a `static`.
+    ///
+    /// The size classes are allocated by the first allocation, which fails if they can't be. Until then, the
+    /// allocator holds no memory and reports no size classes, though configuring or querying a size class creates
+    /// them.
+    ///
+    /// ```ignore
+    /// static HEAP: SlabAllocator<FrameAllocator> = SlabAllocator::const_new_in(&FrameAllocator);
+    /// ```
+    pub const fn const_new_in(allocator: &'static A) -> Self {
+        let inner = Backing::from_static(allocator);
+
+        Self {
+            size_classes: Once::new(),
+            create_size_classes: Self::new_default_size_class_table,
+            pages: PageClass::with_cache_allocator_in(inner.handle(), inner.handle()),
+            corruption_handler: panic_on_corruption,
+            checked_deallocation: false,
+            lazy_shrink: false,
+            memory_limit: None,
+            memory_tagger: None,
+            guard_pages: None,
+            sampler: None,
+            hooks: None,
+            fallback_registry: None,
+            #[cfg(feature = "allocation-sites")]
+            site_registry: None,
+            fallback: None,
+            inner,
+        }
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator>
+    SlabAllocator<A, R, PAGE_SIZE, F>
+{
+    /// Serves allocations no size class serves from `fallback`, rather than the allocator backing the slabs.
+    ///
+    /// This allows slabs to be made of pages from one allocator, such as a frame allocator, while larger allocations
+    /// are made by another, such as a general-purpose heap.
+    ///
+    /// Allocations previously made by the current fallback allocator must not be deallocated by the returned
+    /// allocator, so this is intended to be called before any allocations are made.
+    #[must_use]
+    pub fn with_fallback<G: Allocator>(self, fallback: G) -> SlabAllocator<A, R, PAGE_SIZE, G> {
+        self.with_fallback_or_backing(Some(fallback))
+    }
+
+    /// Serves allocations no size class serves from `fallback`, or from the backing allocator if `None`.
+    fn with_fallback_or_backing<G: Allocator>(
+        self,
+        fallback: Option<G>,
+    ) -> SlabAllocator<A, R, PAGE_SIZE, G> {
+        let Self {
+            size_classes,
+            create_size_classes,
+            pages,
+            corruption_handler,
+            checked_deallocation,
+            lazy_shrink,
+            memory_limit,
+            memory_tagger,
+            guard_pages,
+            sampler,
+            hooks,
+            fallback_registry,
+            #[cfg(feature = "allocation-sites")]
+            site_registry,
+            inner,
+            fallback: _,
+        } = self;
+
+        SlabAllocator {
+            size_classes,
+            create_size_classes,
+            pages,
+            corruption_handler,
+            checked_deallocation,
+            lazy_shrink,
+            memory_limit,
+            memory_tagger,
+            guard_pages,
+            sampler,
+            hooks,
+            fallback_registry,
+            #[cfg(feature = "allocation-sites")]
+            site_registry,
+            inner,
+            fallback,
+        }
+    }
+
+    /// Hands out the objects of each slab in a random order drawn from `random`, rather than lowest first, so heap
+    /// layouts are not trivially predictable.
+    ///
+    /// Objects cached in the magazine layer are still handed out most-recently-freed first.
+    #[must_use]
+    pub fn with_randomized_order(mut self, random: &'static dyn RandomSource) -> Self {
+        for slabs in self
+            .size_class_chunks_mut()
+            .iter_mut()
+            .flatten()
+            .flat_map(SizeClass::shards_mut)
+        {
+            slabs.set_random_source(random);
+        }
+
+        self
+    }
+
+    /// Chooses the slab each size class serves allocations from once its active slab is exhausted by `selection`,
+    /// rather than the partial slab most recently relisted.
+    #[must_use]
+    pub fn with_slab_selection(mut self, selection: SlabSelection) -> Self {
+        for slabs in self
+            .size_class_chunks_mut()
+            .iter_mut()
+            .flatten()
+            .flat_map(SizeClass::shards_mut)
+        {
+            slabs.set_slab_selection(selection);
+        }
+
+        self
+    }
+
+    /// Reports heap corruption detected by the allocator to `handler`, rather than panicking.
+    ///
+    /// Objects freed while already free are always detected as they are returned to their slabs, though objects
+    /// cached in the magazine layer or quarantine are only returned once they are flushed or evicted. The `poison`
+    /// and `redzone` features detect further corruption.
+    #[must_use]
+    pub fn with_corruption_handler(mut self, handler: CorruptionHandler) -> Self {
+        for slabs in self
+            .size_class_chunks_mut()
+            .iter_mut()
+            .flatten()
+            .flat_map(SizeClass::shards_mut)
+        {
+            slabs.set_corruption_handler(handler);
+        }
+        self.corruption_handler = handler;
+
+        self
+    }
+
+    /// Reports failures of the backing allocator to provide slabs or pages to `handler`, before the allocations that
+    /// needed them fail.
+    ///
+    /// The handler is called with the failing size class' lock held, so it must not call back into this allocator.
+    #[must_use]
+    pub fn with_oom_handler(mut self, handler: OomHandler) -> Self {
+        for slabs in self
+            .size_class_chunks_mut()
+            .iter_mut()
+            .flatten()
+            .flat_map(SizeClass::shards_mut)
+        {
+            slabs.set_oom_handler(handler);
+        }
+        self.pages.set_oom_handler(handler);
+
+        self
+    }
+
+    /// Tells `hooks` of events within the allocator: slabs being created and destroyed, allocations falling through to
+    /// the fallback allocator, and allocations failing.
+    ///
+    /// Slab events are told with the size class' lock held, so the hooks must not call back into this allocator.
+    /// Pages of the page size class and [guarded](SlabAllocator::with_guard_pages) objects aren't held in slabs, so
+    /// aren't told of.
+    #[must_use]
+    pub fn with_hooks(mut self, hooks: &'static dyn AllocatorHooks) -> Self {
+        for slabs in self
+            .size_class_chunks_mut()
+            .iter_mut()
+            .flatten()
+            .flat_map(SizeClass::shards_mut)
+        {
+            slabs.set_hooks(hooks);
+        }
+        self.hooks = Some(hooks);
+
+        self
+    }
+
+    /// Reports the objects of each size class, and the [tracked](SlabAllocator::with_fallback_tracking) allocations
+    /// of the fallback allocator, still allocated as the allocator is dropped to `handler`, such as [`panic_on_leak`],
+    /// rather than ignoring them.
+    ///
+    /// Objects freed to the magazine layer, remote-free queue or quarantine aren't leaked. The slabs holding leaked
+    /// objects are still released, so leaked objects must no longer be referenced.
+    #[must_use]
+    pub fn with_leak_handler(mut self, handler: LeakHandler) -> Self {
+        for size_class in self.size_class_chunks_mut().iter_mut().flatten() {
+            size_class.set_leak_handler(handler);
+        }
+        self.pages.set_leak_handler(handler);
+
+        if let Some(fallback_registry) = &mut self.fallback_registry {
+            fallback_registry.set_leak_handler(handler);
+        }
+
+        self
+    }
+
+    /// Verifies every freed pointer points to an object of the size class its layout maps to, reporting foreign
+    /// pointers to the corruption handler rather than corrupting a slab.
+    ///
+    /// Each check binary-searches the size class' slabs by address with its lock held, so this is intended for
+    /// debugging.
+    #[must_use]
+    pub fn with_checked_deallocation(mut self) -> Self {
+        self.checked_deallocation = true;
+        self
+    }
+
+    /// Records the site of every live allocation in a side table allocated from the backing allocator, so that live
+    /// allocations can be reported by where they were made with [`SlabAllocator::allocation_sites`], as a leak
+    /// profiler would.
+    ///
+    /// Allocations are recorded with the source location of the call to the allocator, such as
+    /// [`Allocator::allocate`], or with the site supplied to [`SlabAllocator::allocate_at`]. Allocations made through
+    /// collections and other wrappers are recorded at the wrapper's call to the allocator, so are best tagged through
+    /// [`SlabAllocator::allocate_at`]. Reallocations keep the site of the original allocation. Handles aren't
+    /// recorded, nor are allocations whose record can't be allocated, or that were made by
+    /// [`SlabAllocator::try_allocate`] while the table was locked.
+    ///
+    /// Each allocation and free takes the table's lock and searches it by address, so this is intended for debugging,
+    /// and to be called before any allocations are made.
+    #[cfg(feature = "allocation-sites")]
+    #[must_use]
+    pub fn with_allocation_sites(mut self) -> Self {
+        self.site_registry = Some(SiteRegistry::new_in(self.inner.handle()));
+        self
+    }
+
+    /// Records every allocation served by the fallback allocator, with its layout, in a side table allocated from the
+    /// backing allocator, so that the fallback allocator's allocations are [owned](SlabAllocator::owns), reported to
+    /// the [leak handler](SlabAllocator::with_leak_handler) by size, and counted by
+    /// [`SlabAllocator::fallback_stats`], as the size classes' objects are.
+    ///
+    /// Freeing a pointer the table doesn't hold with a layout served by the fallback allocator is reported to the
+    /// corruption handler as a foreign pointer. Each allocation and free takes the table's lock and searches it by
+    /// address, so this is intended for debugging.
+    ///
+    /// Allocations made by the fallback allocator before tracking began aren't recorded, so this is intended to be
+    /// called before any allocations are made.
+    #[must_use]
+    pub fn with_fallback_tracking(mut self) -> Self {
+        let mut fallback_registry = FallbackRegistry::new_in(self.inner.handle());
+        if let Some(leak_handler) = self.pages.leak_handler() {
+            fallback_registry.set_leak_handler(leak_handler);
+        }
+
+        self.fallback_registry = Some(fallback_registry);
+        self
+    }
+
+    /// Keeps objects shrunk into a smaller size class in place, rather than moving them to the smaller size class.
+    ///
+    /// This avoids copying objects when memory is not tight, at the cost of the memory the smaller size class
+    /// would have saved.
+    #[must_use]
+    pub fn with_lazy_shrink(mut self) -> Self {
+        self.lazy_shrink = true;
+        self
+    }
+
+    /// Tags the memory of objects served by size classes with `tagger` as they are handed out and freed, so that
+    /// accesses through dangling pointers are caught by hardware such as ARM's Memory Tagging Extension.
+    ///
+    /// Pointers handed out carry their object's tag, and are stripped of it as they're freed, resized or queried. See
+    /// [`MemoryTagger`].
+    #[must_use]
+    pub fn with_memory_tagging(mut self, tagger: &'static dyn MemoryTagger) -> Self {
+        self.memory_tagger = Some(tagger);
+        self
+    }
+
+    /// Places each object of size classes of at least `min_object_size` at the end of a page of its own, followed by
+    /// a guard page made inaccessible by `protector`, so overflows past the end of objects fault, as with page-heap
+    /// debugging.
+    ///
+    /// Each guarded object takes two pages from the backing allocator, which are released as it is freed, so this is
+    /// meant for debugging the larger size classes, such as those of 1024 and 2048 bytes. Guarded objects aren't held
+    /// in slabs, so they aren't [owned](SlabAllocator::owns), counted in the stats of their size class, released by
+    /// [`SlabAllocator::reset`], or served as [handles](SlabAllocator::allocate_handle).
+    ///
+    /// The backing allocator must align the pages to the page size, as guarded objects are found from their address as
+    /// they are freed. Unlike slabs, guarded objects aren't aligned within over-sized allocations, but fail to
+    /// allocate.
+    #[must_use]
+    pub fn with_guard_pages(
+        mut self,
+        min_object_size: usize,
+        protector: &'static dyn PageProtector,
+    ) -> Self {
+        self.guard_pages = Some(GuardPages::new(min_object_size, Some(protector)));
+        self
+    }
+
+    /// Places each object of size classes of at least `min_object_size` at the end of a page of its own, as with
+    /// [`SlabAllocator::with_guard_pages`], though its guard page is filled with a poison pattern rather than made
+    /// inaccessible, for platforms without page protection.
+    ///
+    /// Overflows aren't caught as they happen, but as the object is freed, when an overwritten guard page is reported
+    /// to the corruption handler as [`Corruption::GuardPageOverwritten`].
+    #[must_use]
+    pub fn with_poisoned_guard_pages(mut self, min_object_size: usize) -> Self {
+        self.guard_pages = Some(GuardPages::new(min_object_size, None));
+        self
+    }
+
+    /// Reports every `period`th allocation to `handler`, with its layout, the size class that served it, and the time
+    /// it was made as measured by `clock`, so heap usage can be profiled in production without tracing every
+    /// allocation.
+    ///
+    /// Allocations are counted across every size class and the fallback allocator, so the sampled allocations are
+    /// representative of the whole heap. Zero-size allocations and reallocations made in place aren't counted, while
+    /// reallocations that move their object count as an allocation of the new layout.
+    ///
+    /// # Panics
+    ///
+    /// - If `period` is zero.
+    #[must_use]
+    pub fn with_sampling(
+        mut self,
+        period: usize,
+        clock: &'static dyn Clock,
+        handler: SamplingHandler,
+    ) -> Self {
+        assert!(period > 0, "sampling period must be nonzero");
+
+        self.sampler = Some(Sampler::new(period, clock, handler));
+        self
+    }
+
+    /// Bounds the memory held in slabs and pages by `limit`, which may be shared with other allocators.
+    ///
+    /// Allocations needing a new slab or page that would exceed the limit first release every empty slab and cached
+    /// page, then fail if the limit would still be exceeded. Allocations served by the fallback allocator aren't
+    /// bounded.
+    #[must_use]
+    pub fn with_memory_limit(mut self, limit: &'static MemoryLimit) -> Self {
+        for slabs in self
+            .size_class_chunks_mut()
+            .iter_mut()
+            .flatten()
+            .flat_map(SizeClass::shards_mut)
+        {
+            slabs.set_memory_limit(limit);
+        }
+        self.pages.set_memory_limit(limit);
+        self.memory_limit = Some(limit);
+
+        self
+    }
+
+    /// Zeroes the memory of every slab and page before it is released to the backing allocator, whether by trimming
+    /// or as the allocator is dropped, so the data of freed objects doesn't persist in recycled memory once a heap is
+    /// torn down.
+    ///
+    /// The memory is zeroed with volatile writes, so the zeroing isn't elided by the compiler. Objects still allocated
+    /// as the allocator is dropped are leaked rather than released, so aren't wiped, nor are allocations served by the
+    /// fallback allocator or [guarded](SlabAllocator::with_guard_pages).
+    #[must_use]
+    pub fn with_secure_wipe(mut self) -> Self {
+        for slabs in self
+            .size_class_chunks_mut()
+            .iter_mut()
+            .flatten()
+            .flat_map(SizeClass::shards_mut)
+        {
+            slabs.set_secure_wipe();
+        }
+        self.pages.set_secure_wipe();
+
+        self
+    }
+
+    /// Keeps empty slabs beyond a size class' [retention](SlabAllocator::set_empty_slab_retention) until they have
+    /// been empty for `decay`, as measured by `clock`, so short dips in allocations don't release slabs that will be
+    /// needed again.
+    ///
+    /// Slabs that have decayed are released by the next free that empties a slab of their size class, or by
+    /// [`SlabAllocator::maintain`]. [`SlabAllocator::trim`] and [`MemoryReclaim::reclaim`] release empty slabs
+    /// regardless of how long they have been empty.
+    #[must_use]
+    pub fn with_slab_decay(mut self, clock: &'static dyn Clock, decay: Duration) -> Self {
+        for slabs in self
+            .size_class_chunks_mut()
+            .iter_mut()
+            .flatten()
+            .flat_map(SizeClass::shards_mut)
+        {
+            slabs.set_slab_decay(clock, decay);
+        }
+
+        self
+    }
+
+    /// Defers frees that miss the magazine layer (or every free, without one) to a lock-free queue per size class,
+    /// rather than returning them to their slabs as they are freed.
+    ///
+    /// A free performed on another processor than the one allocating from the size class then never waits on the
+    /// size class' locks. Queued objects are returned to their slabs in bulk by the next allocation from the size
+    /// class that misses the magazine layer, or by [`SlabAllocator::trim`].
+    #[must_use]
+    pub fn with_remote_frees(mut self) -> Self {
+        for size_class in self.size_class_chunks_mut().iter_mut().flatten() {
+            size_class.enable_remote_frees();
+        }
+
+        self
+    }
+
+    /// Keeps the header of every slab outside of the slab's memory, for slabs whose memory must hold nothing but
+    /// objects, such as memory visible to devices.
+    ///
+    /// Each header, with its bitmaps, is allocated separately from the backing allocator, which must then serve
+    /// allocations smaller than a page, unless a [metadata allocator](SlabAllocator::with_metadata_allocator) is
+    /// given. As an object's slab can no longer be found from its address alone, each size
+    /// class keeps an index of its slabs by address, which frees look up with the size class' lock held. Slabs then
+    /// hold every object that fits their memory.
+    ///
+    /// # Panics
+    ///
+    /// - If any size class has already created a slab.
+    #[must_use]
+    pub fn with_external_headers(mut self) -> Self {
+        for slabs in self
+            .size_class_chunks_mut()
+            .iter_mut()
+            .flatten()
+            .flat_map(SizeClass::shards_mut)
+        {
+            slabs.set_external_headers();
+        }
+
+        self
+    }
+
+    /// Allocates the bookkeeping each size class grows as it creates slabs from `allocator`, rather than the backing
+    /// allocator: the headers of its slabs, with [external headers](SlabAllocator::with_external_headers). Slabs are
+    /// found by address through their headers, so in-slab headers need no bookkeeping beyond the slabs themselves.
+    ///
+    /// Bookkeeping then never competes with slabs for the backing allocator's memory, nor asks it for allocations
+    /// smaller than a page, as a raw frame allocator can't serve. The size classes and their quarantines and magazines
+    /// are still allocated from the backing allocator as the allocator is configured.
+    ///
+    /// # Panics
+    ///
+    /// - If any size class has already created a slab.
+    #[must_use]
+    pub fn with_metadata_allocator(mut self, allocator: &'static (dyn Allocator + Sync)) -> Self {
+        for slabs in self
+            .size_class_chunks_mut()
+            .iter_mut()
+            .flatten()
+            .flat_map(SizeClass::shards_mut)
+        {
+            slabs.set_metadata_allocator(allocator);
+        }
+
+        self
+    }
+
+    /// Makes every slab of the given size class span `page_count` contiguous pages, rather than the default.
+    ///
+    /// Larger slabs hold more objects each, amortizing the slab header and the backing allocation over more objects.
+    /// By default, slabs of small objects span a single page, while those of objects too large to fit their header in
+    /// the leftover bytes of a page span the fewest pages amortizing it to no more than an eighth of each slab.
+    /// Slabs are aligned to their size; should the backing allocator not honor such alignments, each slab takes twice
+    /// its size from it to be aligned within. With lazy shrinking,
+    /// objects are only kept in place within size classes of the same slab size.
+    ///
+    /// # Panics
+    ///
+    /// - If `page_count` isn't a power of two, or exceeds [`MAX_SLAB_PAGE_COUNT`].
+    /// - If the size class has already created a slab.
+    #[must_use]
+    pub fn with_slab_page_count<const OBJECT_SIZE: usize>(mut self, page_count: usize) -> Self {
+        for slabs in self.expect_size_class_mut(OBJECT_SIZE).shards_mut() {
+            slabs.set_slab_page_count(page_count);
+        }
+
+        self
+    }
+
+    /// Makes the slabs of every size class span a single page, for backing allocators that may only serve single
+    /// pages.
+    fn with_single_page_slabs(mut self) -> Self {
+        for size_class in self.size_class_chunks_mut().iter_mut().flatten() {
+            for slabs in size_class.shards_mut() {
+                slabs.set_slab_page_count(1);
+            }
+        }
+
+        self
+    }
+
+    /// Splits the given size class into `shard_count` lock shards, each with slabs and locks of its own, so
+    /// processors allocating from a hot size class don't all contend for the same locks.
+    ///
+    /// Each allocation is served by the shard of the current processor of `topology`, modulo the shard count, and
+    /// each object is returned to the shard it was allocated from. The size class' settings, such as its slab limit,
+    /// empty slab retention and quarantine capacity, apply to each shard.
+    ///
+    /// # Panics
+    ///
+    /// - If `shard_count` is zero, or exceeds [`MAX_LOCK_SHARD_COUNT`].
+    /// - If the size class has already created a slab.
+    /// - If the shards can't be allocated.
+    #[must_use]
+    pub fn with_lock_shards<const OBJECT_SIZE: usize>(
+        mut self,
+        topology: &'static dyn Topology,
+        shard_count: usize,
+    ) -> Self {
+        self.expect_size_class_mut(OBJECT_SIZE)
+            .set_lock_shards(topology, shard_count)
+            .expect("failed to allocate lock shards");
+
+        self
+    }
+
+    /// Ensures at least `object_count` objects of the given size class can be allocated without allocating from the
+    /// backing allocator, creating slabs up front as needed.
+    ///
+    /// This establishes capacity for contexts that must not wait on the backing allocator, such as interrupt
+    /// handlers. Objects cached in the magazine layer aren't counted, and reserved slabs are empty slabs like any
+    /// other, so they are released by [`SlabAllocator::trim`] or beyond the size class'
+    /// [empty slab retention](SlabAllocator::set_empty_slab_retention). Size classes split into
+    /// [lock shards](SlabAllocator::with_lock_shards) only reserve objects in the current processor's shard.
+    ///
+    /// # Errors
+    ///
+    /// - If a slab can't be created, such as when the size class' slab limit is reached. Slabs already created are
+    ///   kept.
+    pub fn reserve<const OBJECT_SIZE: usize>(&self, object_count: usize) -> Result<(), AllocError> {
+        self.create_size_classes()?;
+        self.expect_size_class(OBJECT_SIZE).reserve(object_count)
+    }
+
+    /// Allocates an object of `layout` into each element of `objects`, returning the number of objects allocated.
+    ///
+    /// Objects of a size class are claimed from its slabs in bulk, rather than one at a time. Fewer objects than
+    /// `objects.len()` are allocated only if memory is exhausted, in which case only the allocated prefix of
+    /// `objects` is written.
+    #[cfg_attr(feature = "allocation-sites", track_caller)]
+    pub fn allocate_many(&self, layout: Layout, objects: &mut [NonNull<[u8]>]) -> usize {
+        let object_count = self.allocate_many_objects(layout, objects);
+        if object_count < objects.len() {
+            self.report_failure(layout);
+        }
+
+        #[cfg(feature = "allocation-sites")]
+        {
+            let site = AllocationSite::caller();
+            for object in &objects[..object_count] {
+                self.record_site(*object, layout, site);
+            }
+        }
+
+        object_count
+    }
+
+    fn allocate_many_objects(&self, layout: Layout, objects: &mut [NonNull<[u8]>]) -> usize {
+        if layout.size() == 0 {
+            objects.fill(dangling_for(layout));
+            return objects.len();
+        }
+
+        if self.create_size_classes().is_err() {
+            return 0;
+        }
+
+        let Some(allocation_size) = self.allocation_size_for(layout) else {
+            let object_count = objects
+                .iter_mut()
+                .map_while(|object| {
+                    *object = self.allocate_fallback(layout, false).ok()?;
+                    Some(())
+                })
+                .count();
+
+            self.sample(layout, None, object_count);
+            return object_count;
+        };
+
+        let allocate_many = |objects: &mut [NonNull<[u8]>]| match self.size_class(allocation_size) {
+            Some(_) if self.guard_pages_for(allocation_size).is_some() => objects
+                .iter_mut()
+                .map_while(|object| {
+                    *object = self.allocate_slot(allocation_size, layout, false).ok()?;
+                    Some(())
+                })
+                .count(),
+            Some(size_class) if layout.align() > object_align(allocation_size) => objects
+                .iter_mut()
+                .map_while(|object| {
+                    *object = size_class.allocate_aligned(layout.align(), false).ok()?;
+                    Some(())
+                })
+                .count(),
+            Some(size_class) => size_class.allocate_many(objects),
+            None => objects
+                .iter_mut()
+                .map_while(|object| {
+                    *object = self.pages.allocate(false).ok()?;
+                    Some(())
+                })
+                .count(),
+        };
+
+        let mut object_count = allocate_many(objects);
+        if object_count < objects.len() && self.reclaim_for_retry() {
+            object_count += allocate_many(&mut objects[object_count..]);
+        }
+
+        self.record_waste(allocation_size, layout, object_count);
+        self.sample(layout, Some(allocation_size), object_count);
+
+        for object in &mut objects[..object_count] {
+            *object = self.tag_object(*object);
+        }
+
+        #[cfg(feature = "redzone")]
+        for object in &mut objects[..object_count] {
+            // Safety: Slots were just allocated, and are large enough to hold the object and its redzones.
+            *object = unsafe { redzone::guard(*object, layout) };
+        }
+
+        object_count
+    }
+
+    /// Attempts to allocate an object of `layout` without waiting on any of the allocator's locks, for contexts that
+    /// may have interrupted the allocator, such as NMI or exception handlers.
+    ///
+    /// Objects are served from the magazine layer and the size class' slabs as by [`Allocator::allocate`], though
+    /// queued remote frees aren't drained and no memory is reclaimed under a memory limit. Slabs, pages, and
+    /// allocations served by the fallback allocator are still allocated from the backing or fallback allocator, which
+    /// must itself be safe to call from such contexts.
+    ///
+    /// # Errors
+    ///
+    /// - [`TryAllocError::WouldBlock`] if a lock is held, the active slab can't be replaced while other allocations
+    ///   are claiming from it, or the size classes of an allocator [created in a const
+    ///   context](SlabAllocator::const_new_in) have yet to be created by an allocation that may wait.
+    /// - [`TryAllocError::Exhausted`] if memory is exhausted.
+    #[cfg_attr(feature = "allocation-sites", track_caller)]
+    pub fn try_allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, TryAllocError> {
+        let object = self.try_allocate_object(layout).inspect_err(|error| {
+            if *error == TryAllocError::Exhausted {
+                self.report_failure(layout);
+            }
+        })?;
+
+        // Allocations are only recorded if the table's lock is free, so recording never waits.
+        #[cfg(feature = "allocation-sites")]
+        if let Some(site_registry) = &self.site_registry
+            && layout.size() != 0
+        {
+            let _ =
+                site_registry.try_insert(object.cast(), layout.size(), AllocationSite::caller());
+        }
+
+        Ok(object)
+    }
+
+    fn try_allocate_object(&self, layout: Layout) -> Result<NonNull<[u8]>, TryAllocError> {
+        if layout.size() == 0 {
+            return Ok(dangling_for(layout));
+        }
+
+        // Size classes are only created by allocations that may wait, as another context may be creating them.
+        if !self.size_classes.is_completed() {
+            return Err(TryAllocError::WouldBlock);
+        }
+
+        let Some(allocation_size) = self.allocation_size_for(layout) else {
+            let object = self.try_allocate_fallback(layout)?;
+            self.sample(layout, None, 1);
+
+            return Ok(object);
+        };
+
+        let object = if let Some(guard_pages) = self.guard_pages_for(allocation_size) {
+            guard_pages.allocate(
+                self.inner.get(),
+                self.memory_limit,
+                slot_size_for(layout),
+                layout.align(),
+                false,
+            )?
+        } else {
+            match self.size_class(allocation_size) {
+                Some(size_class) if layout.align() > object_align(allocation_size) => {
+                    size_class.try_allocate_aligned(layout.align())
+                }
+                Some(size_class) => size_class.try_allocate(),
+                None => self.pages.try_allocate(false),
+            }?
+        };
+        let object = self.tag_object(object);
+        self.record_waste(allocation_size, layout, 1);
+        self.sample(layout, Some(allocation_size), 1);
+
+        // Safety: Slot was just allocated, and is large enough to hold the object and its redzones.
+        #[cfg(feature = "redzone")]
+        let object = unsafe { redzone::guard(object, layout) };
+
+        Ok(object)
+    }
+
+    /// Allocates an object of `layout` as [`Allocator::allocate`] does, recording it as made at `site` if
+    /// [allocation sites are tracked](SlabAllocator::with_allocation_sites).
+    ///
+    /// # Errors
+    ///
+    /// - If the object can't be allocated.
+    #[cfg(feature = "allocation-sites")]
+    pub fn allocate_at(
+        &self,
+        layout: Layout,
+        site: AllocationSite,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let object = self
+            .allocate_object(layout, false)
+            .inspect_err(|_| self.report_failure(layout))?;
+        self.record_site(object, layout, site);
+
+        Ok(object)
+    }
+
+    /// Live allocations grouped by the site they were made at, in descending order of live bytes, in a list allocated
+    /// from the backing allocator. Empty unless [allocation sites are
+    /// tracked](SlabAllocator::with_allocation_sites).
+    ///
+    /// # Errors
+    ///
+    /// - If the list can't be allocated.
+    #[cfg(feature = "allocation-sites")]
+    pub fn allocation_sites(&self) -> Result<Vec<AllocationSiteStats, &A>, AllocError> {
+        match &self.site_registry {
+            Some(site_registry) => site_registry.stats_in(self.inner.get()),
+            None => Ok(Vec::new_in(self.inner.get())),
+        }
+    }
+
+    /// Records `object`, just allocated with `layout`, as made at `site`, if allocation sites are tracked.
+    /// Allocations whose record can't be allocated go unrecorded, rather than failing.
+    #[cfg(feature = "allocation-sites")]
+    fn record_site(&self, object: NonNull<[u8]>, layout: Layout, site: AllocationSite) {
+        if let Some(site_registry) = &self.site_registry
+            && layout.size() != 0
+        {
+            let _ = site_registry.insert(object.cast(), layout.size(), site);
+        }
+    }
+
+    /// Site the allocation at `ptr` was recorded as made at, or the caller's location if it wasn't.
+    #[cfg(feature = "allocation-sites")]
+    #[track_caller]
+    fn site_of(&self, ptr: NonNull<u8>) -> AllocationSite {
+        match self
+            .site_registry
+            .as_ref()
+            .and_then(|site_registry| site_registry.site_of(ptr))
+        {
+            Some(site) => site,
+            None => AllocationSite::caller(),
+        }
+    }
+
+    /// Moves the record of the allocation at `old_ptr` to `object`, its reallocation with `layout`, keeping its site.
+    #[cfg(feature = "allocation-sites")]
+    fn record_reallocation(
+        &self,
+        old_ptr: NonNull<u8>,
+        object: NonNull<[u8]>,
+        layout: Layout,
+        site: AllocationSite,
+    ) {
+        let Some(site_registry) = &self.site_registry else {
+            return;
+        };
+
+        if layout.size() == 0 {
+            site_registry.remove(old_ptr);
+        } else {
+            let _ = site_registry.replace(old_ptr, object.cast(), layout.size(), site);
+        }
+    }
+
+    /// Allocates an object of `layout` from its size class, or from the fallback allocator if no size class serves
+    /// it.
+    fn allocate_object(&self, layout: Layout, zeroed: bool) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.size() == 0 {
+            return Ok(dangling_for(layout));
+        }
+
+        self.create_size_classes()?;
+
+        let Some(allocation_size) = self.allocation_size_for(layout) else {
+            let object = self.allocate_fallback(layout, zeroed)?;
+            self.sample(layout, None, 1);
+
+            return Ok(object);
+        };
+
+        let object = self.tag_object(self.allocate_slot(allocation_size, layout, zeroed)?);
+        self.record_waste(allocation_size, layout, 1);
+        self.sample(layout, Some(allocation_size), 1);
+
+        // Safety: Slot was just allocated, and is large enough to hold the object and its redzones.
+        #[cfg(feature = "redzone")]
+        let object = unsafe { redzone::guard(object, layout) };
+
+        Ok(object)
+    }
+
+    /// Allocates an object of `layout`, returning a compact [handle](ObjectHandle) to it rather than a pointer.
+    ///
+    /// The object spans its whole slot, without redzones, and is found again by [`SlabAllocator::resolve`]. Its slab
+    /// is numbered within its size class for as long as handles to its objects are allocated.
+    ///
+    /// # Errors
+    ///
+    /// - If the object can't be allocated.
+    /// - If `layout` isn't served by a slab size class, or its size class is
+    ///   [guarded](SlabAllocator::with_guard_pages), or the object's slab or index within it doesn't fit a handle,
+    ///   as with the objects of slabs of more than 512 objects.
+    pub fn allocate_handle(&self, layout: Layout) -> Result<ObjectHandle, AllocError> {
+        self.create_size_classes()?;
+
+        let allocation_size = self.allocation_size_for(layout).ok_or(AllocError)?;
+        let (size_class_index, size_class) = self
+            .size_class_chunks()
+            .iter()
+            .flatten()
+            .enumerate()
+            .find(|(_, size_class)| size_class.object_size() == allocation_size)
+            .filter(|_| self.guard_pages_for(allocation_size).is_none())
+            .ok_or(AllocError)?;
+
+        let slot = self
+            .allocate_slot(allocation_size, layout, false)?
+            .cast::<u8>();
+
+        // Safety: Slot was just allocated from the size class.
+        let handle = unsafe { size_class.header_of(slot) }.and_then(|header| {
+            // Safety: Slot is allocated, so its slab can't be released.
+            let object_index = unsafe { header.as_ref() }.object_index_of(slot);
+            let mut slab_numbers = size_class.slab_numbers.lock();
+            let (slab_number, generation) = slab_numbers.acquire(header, object_index).ok()?;
+
+            let handle = ObjectHandle::new(size_class_index, slab_number, object_index, generation);
+            if handle.is_none() {
+                slab_numbers.release(slab_number, object_index);
+            }
+
+            handle
+        });
+
+        let Some(handle) = handle else {
+            // Safety: Slot was allocated from the size class above, and was never handed out.
+            unsafe {
+                self.deallocate_slots(slice::from_ref(&slot), allocation_size);
+            }
+
+            return Err(AllocError);
+        };
+
+        // Handles are resolved to pointers carrying the tag held by their object's memory.
+        self.tag_object(NonNull::slice_from_raw_parts(slot, allocation_size));
+
+        self.record_waste(allocation_size, layout, 1);
+        self.sample(layout, Some(allocation_size), 1);
+        Ok(handle)
+    }
+
+    /// Allocates a slot of `allocation_size` bytes from its size class, to hold an object of `layout`.
+    fn allocate_slot(
+        &self,
+        allocation_size: usize,
+        layout: Layout,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let align = layout.align();
+        let allocate = || {
+            if let Some(guard_pages) = self.guard_pages_for(allocation_size) {
+                return guard_pages.allocate(
+                    self.inner.get(),
+                    self.memory_limit,
+                    slot_size_for(layout),
+                    align,
+                    zeroed,
+                );
+            }
+
+            match self.size_class(allocation_size) {
+                Some(size_class) if align > object_align(allocation_size) => {
+                    size_class.allocate_aligned(align, zeroed)
+                }
+                Some(size_class) if zeroed => size_class.allocate_zeroed(),
+                Some(size_class) => size_class.allocate(),
+                None => self.pages.allocate(zeroed),
+            }
+        };
+
+        allocate().or_else(|error| {
+            if self.reclaim_for_retry() {
+                allocate()
+            } else {
+                Err(error)
+            }
+        })
+    }
+
+    /// Allocator serving allocations no size class serves.
+    fn fallback(&self) -> &dyn Allocator {
+        match &self.fallback {
+            Some(fallback) => fallback,
+            None => self.inner.get(),
+        }
+    }
+
+    /// Allocates an object of `layout` from the fallback allocator, as no size class serves it, recording it if
+    /// [fallback allocations are tracked](SlabAllocator::with_fallback_tracking).
+    fn allocate_fallback(&self, layout: Layout, zeroed: bool) -> Result<NonNull<[u8]>, AllocError> {
+        let object = self.allocate_untracked_fallback(layout, zeroed)?;
+
+        if let Some(fallback_registry) = &self.fallback_registry
+            && let Err(error) = fallback_registry.insert(object.cast(), layout)
+        {
+            // Safety: Object was just allocated with `layout`, and was never handed out.
+            unsafe {
+                self.fallback().deallocate(object.cast(), layout);
+            }
+
+            return Err(error);
+        }
+
+        Ok(object)
+    }
+
+    /// Allocates an object of `layout` from the fallback allocator, recording it without waiting on the lock of the
+    /// fallback allocations' table if they are tracked.
+    fn try_allocate_fallback(&self, layout: Layout) -> Result<NonNull<[u8]>, TryAllocError> {
+        let object = self.allocate_untracked_fallback(layout, false)?;
+
+        if let Some(fallback_registry) = &self.fallback_registry
+            && let Err(error) = fallback_registry.try_insert(object.cast(), layout)
+        {
+            // Safety: Object was just allocated with `layout`, and was never handed out.
+            unsafe {
+                self.fallback().deallocate(object.cast(), layout);
+            }
+
+            return Err(error);
+        }
+
+        Ok(object)
+    }
+
+    fn allocate_untracked_fallback(
+        &self,
+        layout: Layout,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let _span = self.report_fallthrough(layout);
+
+        if zeroed {
+            self.fallback().allocate_zeroed(layout)
+        } else {
+            self.fallback().allocate(layout)
+        }
+    }
+
+    /// Logs an allocation of `layout` falling through to the fallback allocator, and tells the
+    /// [hooks](SlabAllocator::with_hooks) of it.
+    ///
+    /// Returns the guard of the fallthrough's tracing span, which is held while the fallback allocator is called.
+    #[must_use]
+    fn report_fallthrough(&self, layout: Layout) -> impl Sized {
+        #[cfg(feature = "tracing")]
+        let span =
+            tracing::debug_span!("fallthrough", size = layout.size(), align = layout.align())
+                .entered();
+        #[cfg(not(feature = "tracing"))]
+        let span = ();
+
+        #[cfg(feature = "log")]
+        log::debug!("{layout:?} falls through to the fallback allocator");
+        #[cfg(feature = "defmt")]
+        defmt::debug!(
+            "{}-byte allocation aligned to {} falls through to the fallback allocator",
+            layout.size(),
+            layout.align()
+        );
+
+        if let Some(hooks) = self.hooks {
+            hooks.on_fallthrough(layout);
+        }
+
+        span
+    }
+
+    /// Frees an object of `layout` to the fallback allocator, forgetting it if fallback allocations are tracked.
+    ///
+    /// Pointers the table of tracked fallback allocations doesn't hold are reported to the corruption handler as
+    /// foreign, and aren't freed.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated by the fallback allocator with `layout`.
+    unsafe fn deallocate_fallback(&self, ptr: NonNull<u8>, layout: Layout) {
+        if let Some(fallback_registry) = &self.fallback_registry
+            && !fallback_registry.remove(ptr)
+        {
+            (self.corruption_handler)(Corruption::ForeignPointer { object_ptr: ptr });
+            return;
+        }
+
+        // Safety: Caller is required to ensure `ptr` was allocated by the fallback allocator with `layout`.
+        unsafe {
+            self.fallback().deallocate(ptr, layout);
+        }
+    }
+
+    /// Grows or shrinks the object at `ptr` within the fallback allocator by `reallocate`, updating its record to
+    /// `new_layout` if fallback allocations are tracked.
+    ///
+    /// Pointers the table of tracked fallback allocations doesn't hold are reported to the corruption handler as
+    /// foreign, and aren't reallocated.
+    ///
+    /// # Safety
+    ///
+    /// - `reallocate` must be safe to call, reallocating `ptr` within the fallback allocator.
+    unsafe fn reallocate_fallback(
+        &self,
+        ptr: NonNull<u8>,
+        new_layout: Layout,
+        reallocate: impl FnOnce(&dyn Allocator) -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let Some(fallback_registry) = &self.fallback_registry else {
+            return reallocate(self.fallback());
+        };
+
+        if !fallback_registry.is_recorded(ptr) {
+            (self.corruption_handler)(Corruption::ForeignPointer { object_ptr: ptr });
+            return Err(AllocError);
+        }
+
+        let new_object = reallocate(self.fallback())?;
+        fallback_registry.replace(ptr, new_object.cast(), new_layout);
+
+        Ok(new_object)
+    }
+
+    /// Records the bytes lost to rounding as `object_count` objects of `layout` are served from the size class of
+    /// `allocation_size`.
+    fn record_waste(&self, allocation_size: usize, layout: Layout, object_count: usize) {
+        let wasted_bytes = (allocation_size - layout.size()) * object_count;
+
+        match self.size_class(allocation_size) {
+            Some(size_class) => size_class.counters.record_waste(wasted_bytes),
+            None => self.pages.record_waste(wasted_bytes),
+        }
+    }
+
+    /// Counts `object_count` allocations of `layout` served by the size class of `allocation_size`, or by the fallback
+    /// allocator if `None`, if allocations are [sampled](SlabAllocator::with_sampling).
+    fn sample(&self, layout: Layout, allocation_size: Option<usize>, object_count: usize) {
+        if let Some(sampler) = &self.sampler {
+            sampler.record(layout, allocation_size, object_count);
+        }
+    }
+
+    /// Tells the [hooks](SlabAllocator::with_hooks) of a failed allocation of `layout`.
+    fn report_failure(&self, layout: Layout) {
+        if let Some(hooks) = self.hooks {
+            hooks.on_alloc_failure(layout);
+        }
+    }
+
+    /// Releases every empty slab and cached page after an allocation failed under a memory limit, as the limit may
+    /// only have been reached because of memory held by other size classes. Returns whether the allocation should be
+    /// retried.
+    fn reclaim_for_retry(&self) -> bool {
+        self.memory_limit.is_some() && self.trim() > 0
+    }
+
+    /// Deallocates every object of `ptrs`, which were all allocated with `layout`.
+    ///
+    /// Objects of a size class are returned to its slabs in batches, locking the slab lists at most once per batch.
+    ///
+    /// # Safety
+    ///
+    /// - Every pointer of `ptrs` must point to a distinct object allocated by this allocator with `layout`.
+    pub unsafe fn deallocate_many(&self, ptrs: &[NonNull<u8>], layout: Layout) {
+        if layout.size() == 0 {
+            return;
+        }
+
+        #[cfg(feature = "allocation-sites")]
+        if let Some(site_registry) = &self.site_registry {
+            for ptr in ptrs {
+                site_registry.remove(*ptr);
+            }
+        }
+
+        let Some(allocation_size) = self.allocation_size_for(layout) else {
+            for ptr in ptrs {
+                // Safety: Caller is required to maintain safety invariants.
+                unsafe {
+                    self.deallocate_fallback(*ptr, layout);
+                }
+            }
+
+            return;
+        };
+
+        let mut slot_ptrs = [NonNull::dangling(); DEALLOCATION_BATCH_SIZE];
+        let mut slot_count = 0;
+
+        for ptr in ptrs {
+            // Safety: Caller is required to maintain safety invariants.
+            let Some((slot_ptr, slot_size)) =
+                (unsafe { self.check_slot(*ptr, layout, allocation_size) })
+            else {
+                continue;
+            };
+
+            // Objects shrunk in place are held by larger size classes, so are returned on their own.
+            if slot_size != allocation_size {
+                // Safety: Slot was checked to be of the size class of `slot_size`.
+                unsafe {
+                    self.deallocate_slots(slice::from_ref(&slot_ptr), slot_size);
+                }
+
+                continue;
+            }
+
+            slot_ptrs[slot_count] = slot_ptr;
+            slot_count += 1;
+
+            if slot_count == slot_ptrs.len() {
+                // Safety: Slots were checked to be of the size class of `allocation_size`.
+                unsafe {
+                    self.deallocate_slots(&slot_ptrs, allocation_size);
+                }
+
+                slot_count = 0;
+            }
+        }
+
+        // Safety: Slots were checked to be of the size class of `allocation_size`.
+        unsafe {
+            self.deallocate_slots(&slot_ptrs[..slot_count], allocation_size);
+        }
+    }
+}
+
+impl<S: PageSource + Clone, R: RawMutex, const PAGE_SIZE: usize>
+    SlabAllocator<PageSourceAllocator<S, PAGE_SIZE>, R, PAGE_SIZE>
+{
+    /// Creates an allocator whose slabs are made of frames from `source`, accessed at `virtual_offset` bytes above
+    /// their physical addresses.
+    ///
+    /// Allocations no size class serves are made of frames from `source` too. See [`PageSourceAllocator`].
+    ///
+    /// Slabs span a single page, as `source` needn't allocate contiguous frames. Sources that do can be given larger
+    /// slabs with [`SlabAllocator::with_slab_page_count`].
+    ///
+    /// # Safety
+    ///
+    /// - Every frame `source` allocates must be mapped, readable and writable, at `virtual_offset` bytes above its
+    ///   physical address, for as long as it is allocated.
+    pub unsafe fn from_page_source(source: S, virtual_offset: usize) -> Self {
+        // Safety: Caller is required to uphold the mapping's safety invariants.
+        Self::new_in(unsafe { PageSourceAllocator::new(source, virtual_offset) })
+            .with_single_page_slabs()
+    }
+
+    /// Creates an allocator whose slabs are made of frames from `source`, accessed at their physical addresses as
+    /// offsets from `mapping`, so pointers to them keep the mapping's provenance.
+    ///
+    /// Allocations no size class serves are made of frames from `source` too. See
+    /// [`PageSourceAllocator::with_mapping`].
+    ///
+    /// As with [`SlabAllocator::from_page_source`], slabs span a single page.
+    ///
+    /// # Safety
+    ///
+    /// - Every frame `source` allocates must be readable and writable through `mapping`, at its physical address as
+    ///   an offset from it, for as long as it is allocated.
+    pub unsafe fn from_page_mapping(source: S, mapping: NonNull<u8>) -> Self {
+        // Safety: Caller is required to uphold the mapping's safety invariants.
+        Self::new_in(unsafe { PageSourceAllocator::with_mapping(source, mapping) })
+            .with_single_page_slabs()
+    }
+}
+
+impl<R: RawMutex, const PAGE_SIZE: usize>
+    SlabAllocator<RegionAllocator<R, PAGE_SIZE>, R, PAGE_SIZE>
+{
+    /// Creates an allocator whose slabs are carved out of `region`, rather than allocated from a backing allocator.
+    ///
+    /// Allocations no size class serves are carved out of the region too, rounded up to whole pages. See
+    /// [`RegionAllocator`].
+    ///
+    /// # Safety
+    ///
+    /// - `region` must be valid for reads and writes, and must not be accessed other than through this allocator
+    ///   for as long as it or any of its allocations are in use.
+    ///
+    /// # Panics
+    ///
+    /// - If `region` can't hold its bookkeeping and at least one page.
+    pub unsafe fn from_region(region: NonNull<[u8]>) -> Self {
+        // Safety: Caller is required to uphold the region's safety invariants.
+        Self::new_in(unsafe { RegionAllocator::new(region) })
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator>
+    SlabAllocator<A, R, PAGE_SIZE, F>
+{
+    /// The object `handle` refers to.
+    ///
+    /// Stale handles, whose objects have since been freed, are reported to the corruption handler, as are handles that
+    /// were never allocated, and `None` is returned.
+    pub fn resolve(&self, handle: ObjectHandle) -> Option<NonNull<[u8]>> {
+        let (_, header) = self.handle_slab(handle)?;
+
+        // Safety: Slab is numbered, so holds allocated objects and can't be released, and the object index was
+        //         checked to lie within it.
+        let object = unsafe { SlabHeader::object(header, handle.object_index()) };
+
+        Some(match self.memory_tagger {
+            Some(tagger) => {
+                NonNull::slice_from_raw_parts(tagger.load_tag(object.cast()), object.len())
+            }
+            None => object,
+        })
+    }
+
+    /// Frees the object `handle` refers to.
+    ///
+    /// Stale handles, whose objects have since been freed, are reported to the corruption handler, as are handles that
+    /// were never allocated, and nothing is freed.
+    ///
+    /// # Safety
+    ///
+    /// - `handle`'s object must no longer be referenced.
+    pub unsafe fn deallocate_handle(&self, handle: ObjectHandle) {
+        let Some((size_class, header)) = self.handle_slab(handle) else {
+            return;
+        };
+
+        // Safety: Slab is numbered, so can't be released until the object is freed below.
+        let slot = unsafe { SlabHeader::object(header, handle.object_index()) }.cast::<u8>();
+        size_class
+            .slab_numbers
+            .lock()
+            .release(handle.slab_number(), handle.object_index());
+
+        // Safety: Handle isn't stale, so its object is allocated, and it originated from the size class.
+        unsafe {
+            self.deallocate_slots(slice::from_ref(&slot), size_class.object_size());
+        }
+    }
+
+    /// Size class and slab header of the object `handle` refers to, reporting stale handles to the corruption
+    /// handler.
+    fn handle_slab(
+        &self,
+        handle: ObjectHandle,
+    ) -> Option<(&BackedSizeClass<A, R, PAGE_SIZE>, NonNull<SlabHeader>)> {
+        let slab = self
+            .size_class_chunks()
+            .iter()
+            .flatten()
+            .nth(handle.size_class_index())
+            .and_then(|size_class| {
+                let header = size_class.slab_numbers.lock().get(handle)?;
+                Some((size_class, header))
+            })
+            .filter(|(_, header)| {
+                // Safety: Slab is numbered, so holds allocated objects and can't be released.
+                handle.object_index() < unsafe { header.as_ref() }.object_count()
+            });
+
+        if slab.is_none() {
+            (self.corruption_handler)(Corruption::StaleHandle { handle });
+        }
+
+        slab
+    }
+
+    /// Number of free objects held by the slabs of the given size class.
+    ///
+    /// Objects cached in the magazine layer, waiting in the remote-free queue or held in quarantine are not included.
+    pub fn remaining_object_count<const OBJECT_SIZE: usize>(&self) -> usize {
+        self.expect_size_class(OBJECT_SIZE).remaining_object_count()
+    }
+
+    /// Number of objects currently allocated from every size class, including the page size class.
+    ///
+    /// Freed objects not yet returned to their slabs, whether cached in the magazine layer, waiting in the
+    /// remote-free queue or held in quarantine, are not included, nor are allocations served by the fallback
+    /// allocator. See [`SlabAllocator::allocated_object_count`] for a single size class.
+    pub fn outstanding_allocation_count(&self) -> usize {
+        self.size_class_chunks()
+            .iter()
+            .flatten()
+            .map(SizeClass::live_object_count)
+            .sum::<usize>()
+            + self.pages.stats().live_object_count
+            + self
+                .guard_pages
+                .as_ref()
+                .map_or(0, GuardPages::live_object_count)
+    }
+
+    /// Whether no objects are currently allocated from any size class, so the allocator can be dropped without
+    /// leaking. See [`SlabAllocator::outstanding_allocation_count`].
+    pub fn is_fully_free(&self) -> bool {
+        self.outstanding_allocation_count() == 0
+    }
+
+    /// Number of objects of the given size class currently allocated.
+    ///
+    /// Freed objects not yet returned to their slabs, whether cached in the magazine layer, waiting in the
+    /// remote-free queue or held in quarantine, are not included.
+    pub fn allocated_object_count<const OBJECT_SIZE: usize>(&self) -> usize {
+        self.expect_size_class(OBJECT_SIZE).live_object_count()
+    }
+
+    /// Number of objects held by the slabs of the given size class, whether allocated or not.
+    pub fn total_object_count<const OBJECT_SIZE: usize>(&self) -> usize {
+        self.expect_size_class(OBJECT_SIZE).object_capacity()
+    }
+
+    /// Counts the slabs of the given size class by the share of their objects that are allocated.
+    ///
+    /// Objects freed to the magazine layer, remote-free queue or quarantine still count as allocated, as they are
+    /// yet to be returned to their slabs.
+    pub fn occupancy_histogram<const OBJECT_SIZE: usize>(&self) -> OccupancyHistogram {
+        self.expect_size_class(OBJECT_SIZE).occupancy_histogram()
+    }
+
+    /// Number of contiguous pages spanned by each slab of the given size class.
+    pub fn slab_page_count<const OBJECT_SIZE: usize>(&self) -> usize {
+        self.expect_size_class(OBJECT_SIZE).slabs.slab_page_count()
+    }
+
+    /// Number of freed objects of the given size class waiting in its remote-free queue.
+    pub fn remote_free_count<const OBJECT_SIZE: usize>(&self) -> usize {
+        self.expect_size_class(OBJECT_SIZE).remote_free_count()
+    }
+
+    /// Number of free objects of the given size class held in the magazine layer.
+    pub fn cached_object_count<const OBJECT_SIZE: usize>(&self) -> usize {
+        self.expect_size_class(OBJECT_SIZE).cached_object_count()
+    }
+
+    /// Number of empty slabs held by the given size class.
+    pub fn empty_slab_count<const OBJECT_SIZE: usize>(&self) -> usize {
+        self.expect_size_class(OBJECT_SIZE).empty_slab_count()
+    }
+
+    /// Maximum number of empty slabs the given size class keeps before releasing them to the backing allocator.
+    pub fn empty_slab_retention<const OBJECT_SIZE: usize>(&self) -> usize {
+        self.expect_size_class(OBJECT_SIZE).empty_slab_retention()
+    }
+
+    /// Sets the maximum number of empty slabs the given size class keeps.
+    ///
+    /// By default every empty slab is kept until [`SlabAllocator::trim`] is called. Once a limit is set, frees that
+    /// empty a slab release empty slabs beyond the limit to the backing allocator. Keeping a few empty slabs avoids
+    /// repeatedly allocating and releasing pages under bursty workloads.
+    pub fn set_empty_slab_retention<const OBJECT_SIZE: usize>(&self, count: usize) {
+        self.expect_size_class(OBJECT_SIZE)
+            .set_empty_slab_retention(count)
+    }
+
+    /// Maximum number of slabs the given size class holds at once.
+    pub fn slab_limit<const OBJECT_SIZE: usize>(&self) -> usize {
+        self.expect_size_class(OBJECT_SIZE).slab_limit()
+    }
+
+    /// Sets the maximum number of slabs the given size class holds at once, bounding its memory to `count` times
+    /// its slab size.
+    ///
+    /// By default size classes grow without limit. Once the limit is reached, allocations from the size class fail
+    /// until its slabs have free objects again, so a single size class can't exhaust the memory of every other.
+    /// Slabs already held beyond a lowered limit are kept until they empty and are released.
+    pub fn set_slab_limit<const OBJECT_SIZE: usize>(&self, count: usize) {
+        self.expect_size_class(OBJECT_SIZE).set_slab_limit(count);
+    }
+
+    /// Number of freed objects of the given size class held in quarantine.
+    pub fn quarantined_object_count<const OBJECT_SIZE: usize>(&self) -> usize {
+        self.expect_size_class(OBJECT_SIZE)
+            .quarantined_object_count()
+    }
+
+    /// Sets how many freed objects of the given size class are held in quarantine before they can be reused.
+    ///
+    /// Delaying reuse makes use-after-free bugs more likely to touch memory that is no longer handed out (and, with
+    /// the `poison` feature, to be detected). Objects cached in the magazine layer are reused immediately, so
+    /// quarantine is best used without magazines. Any objects currently held are returned to their slabs.
+    pub fn set_quarantine_capacity<const OBJECT_SIZE: usize>(
+        &self,
+        capacity: usize,
+    ) -> Result<(), AllocError> {
+        self.expect_size_class(OBJECT_SIZE)
+            .set_quarantine_capacity(capacity)
+    }
+
+    /// Maximum number of free pages the page size class keeps for reuse, rather than releasing them to the backing
+    /// allocator.
+    pub fn page_cache_capacity(&self) -> usize {
+        self.pages.capacity()
+    }
+
+    /// Sets the maximum number of free pages the page size class keeps for reuse, releasing any cached pages beyond
+    /// it.
+    ///
+    /// Defaults to [`DEFAULT_PAGE_CACHE_CAPACITY`].
+    pub fn set_page_cache_capacity(&self, capacity: usize) {
+        self.pages.set_capacity(capacity);
+    }
+
+    /// Number of free pages currently kept for reuse by the page size class.
+    pub fn cached_page_count(&self) -> usize {
+        self.pages.cached_page_count()
+    }
+
+    /// Returns every quarantined object to its slab.
+    pub fn flush_quarantine(&self) {
+        for size_class in self.size_class_chunks().iter().flatten() {
+            size_class.flush_quarantine();
+        }
+    }
+
+    /// Chunks of the size-class table, or none if the size classes have yet to be created.
+    fn size_class_chunks(&self) -> &[SizeClassChunk<A, R, PAGE_SIZE>] {
+        self.size_classes.get().map_or(&[], Vec::as_slice)
+    }
+
+    /// Chunks of the size-class table, for configuring the size classes, creating them if they have yet to be.
+    ///
+    /// # Panics
+    ///
+    /// - If the size classes can't be allocated.
+    fn size_class_chunks_mut(&mut self) -> &mut [SizeClassChunk<A, R, PAGE_SIZE>] {
+        self.create_size_classes()
+            .expect("failed to allocate size classes");
+
+        self.size_classes
+            .get_mut()
+            .map_or(&mut [], Vec::as_mut_slice)
+    }
+
+    /// Creates the size classes of an allocator [created in a const context](SlabAllocator::const_new_in), if they
+    /// have yet to be created.
+    fn create_size_classes(&self) -> Result<(), AllocError> {
+        self.size_classes
+            .try_call_once(|| (self.create_size_classes)(self.inner.handle()))
+            .map(|_| ())
+    }
+
+    /// The size class of `object_size`, if there is one.
+    fn size_class(&self, object_size: usize) -> Option<&BackedSizeClass<A, R, PAGE_SIZE>> {
+        let chunk = self.size_class_chunks().iter().find(|chunk| {
+            chunk
+                .last()
+                .is_some_and(|size_class| size_class.object_size() >= object_size)
+        })?;
+
+        chunk
+            .binary_search_by_key(&object_size, SizeClass::object_size)
+            .ok()
+            .map(|class_index| &chunk[class_index])
+    }
+
+    /// The size class of `object_size`.
+    ///
+    /// # Panics
+    ///
+    /// - If the size classes can't be allocated.
+    /// - If there is no size class of `object_size`.
+    fn expect_size_class(&self, object_size: usize) -> &BackedSizeClass<A, R, PAGE_SIZE> {
+        self.create_size_classes()
+            .expect("failed to allocate size classes");
+
+        self.size_class(object_size)
+            .expect("no size class of the given object size")
+    }
+
+    /// The size class of `object_size`, for configuring it.
+    ///
+    /// # Panics
+    ///
+    /// - If there is no size class of `object_size`.
+    fn expect_size_class_mut(
+        &mut self,
+        object_size: usize,
+    ) -> &mut BackedSizeClass<A, R, PAGE_SIZE> {
+        self.size_class_chunks_mut()
+            .iter_mut()
+            .flatten()
+            .find(|size_class| size_class.object_size() == object_size)
+            .expect("no size class of the given object size")
+    }
+
+    /// Size of each slab of the size class of `object_size`, if it is a slab size class.
+    fn slab_size_of(&self, object_size: usize) -> Option<usize> {
+        self.size_class(object_size)
+            .map(|size_class| size_class.slabs.slab_size())
+    }
+
+    /// Guard serving the size class of `object_size`, if its objects are [guarded](SlabAllocator::with_guard_pages).
+    fn guard_pages_for(&self, object_size: usize) -> Option<&GuardPages<PAGE_SIZE>> {
+        self.guard_pages
+            .as_ref()
+            .filter(|guard_pages| guard_pages.serves(object_size))
+    }
+
+    /// Size of the objects `layout` is routed to, or `None` if it is served by the fallback allocator.
+    ///
+    /// Allocations are served by the smallest size class whose objects fit and are aligned for them, then by the
+    /// smallest size class whose objects fit and whose slabs hold objects aligned for them, or by the page size class
+    /// if no size class fits them and they round up to a page.
+    fn allocation_size_for(&self, layout: Layout) -> Option<usize> {
+        let slot_size = slot_size_for(layout);
+        let fitting_size_classes = || {
+            self.size_class_chunks()
+                .iter()
+                .flatten()
+                .skip_while(|size_class| size_class.object_size() < slot_size)
+        };
+
+        fitting_size_classes()
+            .find(|size_class| object_align(size_class.object_size()) >= layout.align())
+            .or_else(|| {
+                fitting_size_classes()
+                    .find(|size_class| size_class.slabs.has_aligned_objects(layout.align()))
+            })
+            .map(SizeClass::object_size)
+            .or_else(|| {
+                (max(slot_size.next_power_of_two(), layout.align()) == PAGE_SIZE)
+                    .then_some(PAGE_SIZE)
+            })
+    }
+
+    /// The slot holding the object at `ptr`, allocated with `layout`.
+    fn slot_of(ptr: NonNull<u8>, layout: Layout) -> NonNull<u8> {
+        // With redzones, objects are held within larger slots.
+        #[cfg(feature = "redzone")]
+        {
+            redzone::slot_of(ptr, layout)
+        }
+
+        #[cfg(not(feature = "redzone"))]
+        {
+            let _ = layout;
+            ptr
+        }
+    }
+
+    /// Tags the memory of `object`, just allocated from a size class, if objects are tagged, returning the object to
+    /// hand out.
+    fn tag_object(&self, object: NonNull<[u8]>) -> NonNull<[u8]> {
+        match self.memory_tagger {
+            Some(tagger) => NonNull::slice_from_raw_parts(tagger.tag(object), object.len()),
+            None => object,
+        }
+    }
+
+    /// Strips the tag from `ptr`, if objects are tagged and `ptr` was allocated from the size class of
+    /// `allocation_size`, rather than by the fallback allocator.
+    fn untag(&self, ptr: NonNull<u8>, allocation_size: Option<usize>) -> NonNull<u8> {
+        match (self.memory_tagger, allocation_size) {
+            (Some(tagger), Some(_)) => tagger.untag(ptr),
+            _ => ptr,
+        }
+    }
+
+    /// Whether `ptr` points to an object of a size class that `layout` could have been allocated from.
+    ///
+    /// Objects shrunk in place are held by larger size classes than their layout maps to, though always of the same
+    /// slab size.
+    fn owns_object(&self, ptr: NonNull<u8>, layout: Layout, allocation_size: usize) -> bool {
+        let slot_ptr = Self::slot_of(ptr, layout);
+        let slab_size = self.slab_size_of(allocation_size);
+
+        self.size_class_chunks()
+            .iter()
+            .flatten()
+            .filter(|size_class| size_class.object_size() >= allocation_size)
+            .filter(|size_class| Some(size_class.slabs.slab_size()) == slab_size)
+            .any(|size_class| size_class.owns(slot_ptr))
+    }
+
+    /// Size of the slot holding the object at `ptr`, which may be larger than `allocation_size` if the object was
+    /// shrunk in place.
+    ///
+    /// If deallocation is checked and `ptr` is foreign, it is reported to the corruption handler and `None` is
+    /// returned.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated by this allocator with `layout`, which maps to a size class.
+    unsafe fn slot_size_of(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        allocation_size: usize,
+    ) -> Option<usize> {
+        if self.checked_deallocation && !self.owns_object(ptr, layout, allocation_size) {
+            (self.corruption_handler)(Corruption::ForeignPointer { object_ptr: ptr });
+            return None;
+        }
+
+        // Objects are only held by size classes of the same slab size as the size class their layout maps to.
+        let slab_size = self.slab_size_of(allocation_size)?;
+        let slot_ptr = Self::slot_of(ptr, layout);
+
+        let header = if self.size_class(allocation_size)?.has_external_headers() {
+            let header = self
+                .size_class_chunks()
+                .iter()
+                .flatten()
+                .filter(|size_class| size_class.slabs.slab_size() == slab_size)
+                .find_map(|size_class| size_class.external_header_of(slot_ptr));
+
+            let Some(header) = header else {
+                (self.corruption_handler)(Corruption::ForeignPointer { object_ptr: ptr });
+                return None;
+            };
+
+            header
+        } else {
+            // Safety: Caller is required to ensure the object was allocated from a size class, and so lies in a slab
+            //         of `slab_size` bytes.
+            unsafe { SlabHeader::containing(slot_ptr, slab_size) }
+        };
+
+        // Safety: Caller is required to ensure the object is allocated, so its slab can't be released.
+        Some(unsafe { header.as_ref() }.object_size())
+    }
+
+    /// Grows an object, in place if it fits within its slot.
+    ///
+    /// If `zeroed`, the bytes past the end of `old_layout` are zeroed. Objects grown in place only have those bytes
+    /// zeroed, rather than the whole object.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated by this allocator with `old_layout`.
+    /// - `new_layout` must be no smaller than `old_layout`.
+    unsafe fn grow_object(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Zero-size allocations are dangling, so hold nothing to move.
+        if old_layout.size() == 0 {
+            return if zeroed {
+                self.allocate_zeroed(new_layout)
+            } else {
+                self.allocate(new_layout)
+            };
+        }
+
+        let old_allocation_size = self.allocation_size_for(old_layout);
+        let new_allocation_size = self.allocation_size_for(new_layout);
+        let ptr = self.untag(ptr, old_allocation_size);
+
+        // Safety: Caller is required to ensure `ptr` was allocated with `old_layout`.
+        if let Some(slot_size) =
+            unsafe { self.served_slot_size_of(ptr, old_layout, old_allocation_size) }?
+        {
+            // Guarded objects end against their guard page, so are moved rather than grown in place.
+            if new_allocation_size.is_some_and(|new_allocation_size| {
+                new_allocation_size <= slot_size
+                    && self.guard_pages_for(slot_size).is_none()
+                    && self.slab_size_of(new_allocation_size) == self.slab_size_of(slot_size)
+            }) && can_move_redzones(old_layout, new_layout)
+                && is_aligned_for(ptr, new_layout)
+            {
+                // Safety: The new layout fits within the object's slot.
+                let object =
+                    unsafe { self.resize_in_place(ptr, old_layout, new_layout, slot_size)? };
+
+                if zeroed {
+                    // Safety: Bytes past the end of `old_layout` lie within the object, and aren't yet in use.
+                    unsafe {
+                        ptr.add(old_layout.size())
+                            .write_bytes(0, object.len() - old_layout.size());
+                    }
+                }
+
+                return Ok(object);
+            }
+        } else if new_allocation_size.is_none() {
+            let _span = self.report_fallthrough(new_layout);
+
+            // Safety: Caller is required to maintain safety invariants.
+            return unsafe {
+                self.reallocate_fallback(ptr, new_layout, |fallback| {
+                    if zeroed {
+                        fallback.grow_zeroed(ptr, old_layout, new_layout)
+                    } else {
+                        fallback.grow(ptr, old_layout, new_layout)
+                    }
+                })
+            };
+        }
+
+        let new_object = self.allocate_object(new_layout, zeroed)?;
+
+        // Safety: Caller is required to maintain safety invariants.
+        Ok(unsafe { self.move_object(ptr, old_layout, new_layout, new_object) })
+    }
+
+    /// Shrinks an object, in place if its slot fits the new layout.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated by this allocator with `old_layout`.
+    /// - `new_layout` must be no larger than `old_layout`.
+    unsafe fn shrink_object(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if new_layout.size() == 0 {
+            // Safety: Caller is required to ensure `ptr` was allocated with `old_layout`.
+            unsafe {
+                self.deallocate(ptr, old_layout);
+            }
+
+            return Ok(dangling_for(new_layout));
+        }
+
+        let old_allocation_size = self.allocation_size_for(old_layout);
+        let new_allocation_size = self.allocation_size_for(new_layout);
+        let ptr = self.untag(ptr, old_allocation_size);
+
+        // Safety: Caller is required to ensure `ptr` was allocated with `old_layout`.
+        if let Some(slot_size) =
+            unsafe { self.served_slot_size_of(ptr, old_layout, old_allocation_size) }?
+        {
+            // Objects are only kept in a larger slab size class than their layout maps to when shrinking lazily, and
+            // only if both size classes share a slab size, so the object's slab can be found from its new layout.
+            // Guarded objects end against their guard page, so are moved rather than shrunk in place.
+            let fits_slot = self.guard_pages_for(slot_size).is_none()
+                && new_allocation_size.is_some_and(|new_allocation_size| {
+                    new_allocation_size == slot_size
+                        || (self.lazy_shrink
+                            && self.size_class(slot_size).is_some()
+                            && self.size_class(new_allocation_size).is_some()
+                            && self.guard_pages_for(new_allocation_size).is_none()
+                            && self.slab_size_of(new_allocation_size)
+                                == self.slab_size_of(slot_size))
+                });
+
+            if fits_slot
+                && can_move_redzones(old_layout, new_layout)
+                && is_aligned_for(ptr, new_layout)
+            {
+                // Safety: The new layout fits within the object's slot.
+                return unsafe { self.resize_in_place(ptr, old_layout, new_layout, slot_size) };
+            }
+        } else if new_allocation_size.is_none() {
+            // Safety: Caller is required to maintain safety invariants.
+            return unsafe {
+                self.reallocate_fallback(ptr, new_layout, |fallback| {
+                    fallback.shrink(ptr, old_layout, new_layout)
+                })
+            };
+        }
+
+        let new_object = self.allocate(new_layout)?;
+
+        // Safety: Caller is required to maintain safety invariants.
+        Ok(unsafe { self.move_object(ptr, old_layout, new_layout, new_object) })
+    }
+
+    /// Frees an object of non-zero size, allocated with `layout`.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated by this allocator with `layout`.
+    unsafe fn deallocate_object(&self, ptr: NonNull<u8>, layout: Layout) {
+        let Some(allocation_size) = self.allocation_size_for(layout) else {
+            // Safety: Caller is required to maintain safety invariants.
+            unsafe {
+                self.deallocate_fallback(ptr, layout);
+            }
+
+            return;
+        };
+
+        // Safety: Caller is required to maintain safety invariants.
+        if let Some((slot_ptr, slot_size)) =
+            unsafe { self.check_slot(ptr, layout, allocation_size) }
+        {
+            // Safety: Slot was checked to be of the size class of `slot_size`.
+            unsafe {
+                self.deallocate_slots(slice::from_ref(&slot_ptr), slot_size);
+            }
+        }
+    }
+
+    /// Finds the slot holding the object at `ptr` as it is deallocated, returning the slot and its size.
+    ///
+    /// Foreign pointers (if deallocation is checked) and overwritten redzones are reported to the corruption handler,
+    /// in which case `None` is returned and the object must not be deallocated.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated by this allocator with `layout`, which maps to a size class.
+    unsafe fn check_slot(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        allocation_size: usize,
+    ) -> Option<(NonNull<u8>, usize)> {
+        let ptr = self.untag(ptr, Some(allocation_size));
+
+        // Safety: Caller is required to ensure `ptr` was allocated with `layout`.
+        let Ok(Some(slot_size)) =
+            (unsafe { self.served_slot_size_of(ptr, layout, Some(allocation_size)) })
+        else {
+            return None;
+        };
+
+        // Guarded slots are sized by the objects they hold, rather than by their size class.
+        #[cfg(feature = "redzone")]
+        let redzone_slot_size = if self.guard_pages_for(slot_size).is_some() {
+            slot_size_for(layout)
+        } else {
+            slot_size
+        };
+
+        // Safety: Caller is required to ensure `ptr` was allocated with `layout`, and so was guarded.
+        #[cfg(feature = "redzone")]
+        let ptr = match unsafe { redzone::check(ptr, redzone_slot_size, layout) } {
+            Ok(slot_ptr) => slot_ptr,
+
+            Err(corruption) => {
+                (self.corruption_handler)(corruption);
+                return None;
+            }
+        };
+
+        Some((ptr, slot_size))
+    }
+
+    /// Returns slots to the size class of `slot_size`.
+    ///
+    /// # Safety
+    ///
+    /// - Every pointer of `slot_ptrs` must point to a distinct allocated slot of the size class of `slot_size`.
+    unsafe fn deallocate_slots(&self, slot_ptrs: &[NonNull<u8>], slot_size: usize) {
+        // Slots are tagged afresh, so pointers to them from before they were freed no longer match their tags.
+        if let Some(tagger) = self.memory_tagger {
+            let is_guarded = self.guard_pages_for(slot_size).is_some();
+
+            for slot_ptr in slot_ptrs {
+                // Guarded slots are placed by the size of their objects, so end with their page rather than a slot of
+                // the size class.
+                let slot_len = if is_guarded {
+                    PAGE_SIZE - (slot_ptr.addr().get() % PAGE_SIZE)
+                } else {
+                    slot_size
+                };
+
+                tagger.tag(NonNull::slice_from_raw_parts(*slot_ptr, slot_len));
+            }
+        }
+
+        // Safety: Caller is required to ensure the slots originate from the size class.
+        unsafe {
+            match (self.guard_pages_for(slot_size), self.size_class(slot_size)) {
+                (Some(guard_pages), _) => {
+                    for slot_ptr in slot_ptrs {
+                        if let Err(corruption) =
+                            guard_pages.deallocate(self.inner.get(), self.memory_limit, *slot_ptr)
+                        {
+                            (self.corruption_handler)(corruption);
+                        }
+                    }
+                }
+                (None, Some(size_class)) => size_class.deallocate_many(slot_ptrs),
+                (None, None) => {
+                    for slot_ptr in slot_ptrs {
+                        self.pages.deallocate(*slot_ptr);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Size of the slot holding the object at `ptr`, or `None` if it is served by the fallback allocator.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated by this allocator with `layout`, which maps to `allocation_size`.
+    ///
+    /// # Errors
+    ///
+    /// - If deallocation is checked and `ptr` is foreign, in which case it is reported to the corruption handler.
+    unsafe fn served_slot_size_of(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        allocation_size: Option<usize>,
+    ) -> Result<Option<usize>, AllocError> {
+        match allocation_size {
+            None => Ok(None),
+            Some(allocation_size) if allocation_size == PAGE_SIZE => Ok(Some(PAGE_SIZE)),
+            Some(allocation_size) if self.guard_pages_for(allocation_size).is_some() => {
+                Ok(Some(allocation_size))
+            }
+
+            // Safety: Caller is required to ensure `ptr` was allocated with `layout`.
+            Some(allocation_size) => unsafe { self.slot_size_of(ptr, layout, allocation_size) }
+                .map(Some)
+                .ok_or(AllocError),
+        }
+    }
+
+    /// Moves an object to `new_object`, a new allocation of `new_layout`.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated by this allocator with `old_layout`.
+    /// - `new_object` must have just been allocated by this allocator with `new_layout`.
+    unsafe fn move_object(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        new_object: NonNull<[u8]>,
+    ) -> NonNull<[u8]> {
+        // Safety:
+        // - Caller is required to ensure `ptr` is valid for `old_layout`.
+        // - Caller is required to ensure `new_object` was just allocated, so doesn't overlap `ptr`.
+        unsafe {
+            ptr.copy_to_nonoverlapping(
+                new_object.cast(),
+                min(old_layout.size(), new_layout.size()),
+            );
+            self.deallocate(ptr, old_layout);
+        }
+
+        new_object
+    }
+
+    /// Resizes an object within its slot.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated by this allocator with `old_layout`, and be held in a slot of `slot_size`.
+    /// - `new_layout` must map to a size class no larger than `slot_size`.
+    unsafe fn resize_in_place(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        slot_size: usize,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // With redzones, the trailing redzone moves with the end of the object.
+        #[cfg(feature = "redzone")]
+        {
+            // Safety: Caller is required to ensure `ptr` was allocated with `old_layout`, and so was guarded.
+            let slot_ptr = match unsafe { redzone::check(ptr, slot_size, old_layout) } {
+                Ok(slot_ptr) => slot_ptr,
+
+                Err(corruption) => {
+                    (self.corruption_handler)(corruption);
+                    return Err(AllocError);
+                }
+            };
+
+            let slot = self.tag_object(NonNull::slice_from_raw_parts(slot_ptr, slot_size));
+
+            // Safety: Slot belongs to the object being resized.
+            Ok(unsafe { redzone::guard(slot, new_layout) })
+        }
+
+        #[cfg(not(feature = "redzone"))]
+        {
+            let _ = (old_layout, new_layout);
+            Ok(self.tag_object(NonNull::slice_from_raw_parts(ptr, slot_size)))
+        }
+    }
+
+    /// Snapshot of the state and activity of every size class.
+    pub fn stats(&self) -> SlabAllocatorStats {
+        let mut stats = SlabAllocatorStats::new();
+
+        for size_class in self.size_class_chunks().iter().flatten() {
+            stats.push(size_class.stats());
+        }
+
+        stats.push(self.pages.stats());
+        stats
+    }
+
+    /// Snapshot of the allocations served by the fallback allocator, or `None` unless they are
+    /// [tracked](SlabAllocator::with_fallback_tracking).
+    pub fn fallback_stats(&self) -> Option<FallbackStats> {
+        self.fallback_registry.as_ref().map(FallbackRegistry::stats)
+    }
+
+    /// Publishes a snapshot of every size class to the `metrics` facade, as described by
+    /// [`SlabAllocatorStats::record_metrics`]. Call periodically, such as before the recorder is scraped.
+    #[cfg(feature = "metrics")]
+    pub fn record_metrics(&self) {
+        self.stats().record_metrics();
+    }
+
+    /// Object size of the size class serving allocations of `layout`, or `None` if they are served by the fallback
+    /// allocator.
+    ///
+    /// Allocations are served by the smallest size class whose objects fit and are aligned for them, then by the
+    /// smallest size class whose objects fit and whose slabs hold objects aligned for them, or by the page size class,
+    /// of an object size of `PAGE_SIZE`, if they round up to a page. The difference from the layout's size is lost to
+    /// rounding.
+    pub fn size_class_for(&self, layout: Layout) -> Option<usize> {
+        self.allocation_size_for(layout)
+    }
+
+    /// Number of bytes usable by the allocation at `ptr`, which is rounded up from its layout's size to fill its
+    /// slot, as with the length of the memory returned as it was allocated.
+    ///
+    /// Allocations served by the fallback allocator report only their layout's size, as do zero-size allocations,
+    /// which have no slot, [guarded](SlabAllocator::with_guard_pages) allocations, which end against their guard page,
+    /// and all allocations with the `redzone` feature, since the rest of their slots are canaries.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been allocated by this allocator with `layout`.
+    pub unsafe fn usable_size(&self, ptr: NonNull<u8>, layout: Layout) -> usize {
+        if cfg!(feature = "redzone") || layout.size() == 0 {
+            return layout.size();
+        }
+
+        let allocation_size = self.allocation_size_for(layout);
+        if allocation_size
+            .is_some_and(|allocation_size| self.guard_pages_for(allocation_size).is_some())
+        {
+            return layout.size();
+        }
+        let ptr = self.untag(ptr, allocation_size);
+
+        // Safety: Caller is required to ensure `ptr` was allocated with `layout`.
+        unsafe { self.served_slot_size_of(ptr, layout, allocation_size) }
+            .ok()
+            .flatten()
+            .unwrap_or(layout.size())
+    }
+
+    /// Whether `ptr` points into a slab of any size class, or into an allocation of the fallback allocator if they
+    /// are [tracked](SlabAllocator::with_fallback_tracking).
+    ///
+    /// This allows frees to be routed between allocators by ownership. Pages of the page size class aren't held in
+    /// slabs, so aren't owned, nor are untracked allocations of the fallback allocator.
+    pub fn owns(&self, ptr: NonNull<u8>) -> bool {
+        let slab_ptr = self.memory_tagger.map_or(ptr, |tagger| tagger.untag(ptr));
+
+        self.size_class_chunks()
+            .iter()
+            .flatten()
+            .any(|size_class| size_class.contains(slab_ptr))
+            || self
+                .fallback_registry
+                .as_ref()
+                .is_some_and(|fallback_registry| fallback_registry.contains(ptr))
+    }
+
+    /// Renders every slab as a map of its objects, grouped by size class, for debugging over a serial console or
+    /// similar.
+    ///
+    /// Each size class is written as a line of its object size and slab count, followed by a block for each slab with
+    /// a character for every object, `#` if allocated or `.` if free, in rows of 64. Objects freed to the magazine
+    /// layer, remote-free queue or quarantine are shown as allocated. Size classes whose slabs are locked, such as by
+    /// the context that panicked, are marked as locked rather than waited on. Size classes split into lock shards
+    /// are written as a line and blocks for each shard.
+    ///
+    /// ```text
+    /// 64: 1 slab
+    /// ######........................................................
+    /// 128: 0 slabs
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - If `writer` fails.
+    pub fn dump_map(&self, writer: &mut impl core::fmt::Write) -> core::fmt::Result {
+        self.size_class_chunks()
+            .iter()
+            .flatten()
+            .try_for_each(|size_class| size_class.write_map(writer))
+    }
+
+    /// Validates the allocator's internal invariants, returning a report of every inconsistency found, for use from
+    /// assertions and crash handlers.
+    ///
+    /// Every slab's count of free objects is checked against its free bitmap, and its object size and list against
+    /// the size class and list holding it. Slabs must be found by their addresses, and no two slabs may overlap,
+    /// within or across size classes. Each size class' count of free objects must match the sum over its slabs.
+    ///
+    /// The check never allocates or waits on a lock: lock shards whose slabs are locked, such as by the context that
+    /// crashed, are skipped and counted in the report. Results are only meaningful while no other context allocates
+    /// or frees, as counts may otherwise be observed mid-update.
+    pub fn check_consistency(&self) -> ConsistencyReport {
+        let mut report = ConsistencyReport::new();
+        let shards = || {
+            self.size_class_chunks()
+                .iter()
+                .flatten()
+                .flat_map(SizeClass::shards)
+        };
+
+        for (index, shard) in shards().enumerate() {
+            shard.check_consistency(&mut report);
+
+            for other in shards().skip(index + 1) {
+                shard.check_overlap(other, &mut report);
+            }
+        }
+
+        report
+    }
+
+    /// Zeroes the cumulative counters of every size class, and of the fallback allocator's allocations if they are
+    /// tracked, and lowers their peaks to the current number of live objects, pages and bytes, so statistics can be
+    /// gathered over a window of time.
+    ///
+    /// Live allocations are untouched.
+    pub fn reset_stats(&self) {
+        for size_class in self.size_class_chunks().iter().flatten() {
+            size_class.reset_stats();
+        }
+
+        self.pages.reset_stats();
+
+        if let Some(fallback_registry) = &self.fallback_registry {
+            fallback_registry.reset_stats();
+        }
+    }
+
+    /// Total number of bytes held from the backing allocator, in the pages of slabs and of the page size class, and
+    /// in the allocator's own bookkeeping.
+    ///
+    /// Allocations served by the fallback allocator aren't counted, though the table
+    /// [tracking](SlabAllocator::with_fallback_tracking) them is, as is the table of allocation sites.
+    pub fn total_backing_bytes(&self) -> usize {
+        let table_bytes = (self.size_classes.get().map_or(0, Vec::capacity)
+            * size_of::<SizeClassChunk<A, R, PAGE_SIZE>>())
+            + self
+                .size_class_chunks()
+                .iter()
+                .map(|chunk| chunk.capacity() * size_of::<BackedSizeClass<A, R, PAGE_SIZE>>())
+                .sum::<usize>();
+
+        let size_class_bytes = self
+            .size_class_chunks()
+            .iter()
+            .flatten()
+            .map(|size_class| {
+                (size_class.stats().page_count * PAGE_SIZE) + size_class.metadata_bytes()
+            })
+            .sum::<usize>();
+
+        let page_bytes = (self.pages.stats().page_count * PAGE_SIZE) + self.pages.metadata_bytes();
+        let fallback_registry_bytes = self
+            .fallback_registry
+            .as_ref()
+            .map_or(0, FallbackRegistry::metadata_bytes);
+
+        #[cfg(feature = "allocation-sites")]
+        let site_registry_bytes = self
+            .site_registry
+            .as_ref()
+            .map_or(0, SiteRegistry::metadata_bytes);
+        #[cfg(not(feature = "allocation-sites"))]
+        let site_registry_bytes = 0;
+
+        table_bytes + size_class_bytes + page_bytes + fallback_registry_bytes + site_registry_bytes
+    }
+
+    /// Returns every object cached in the magazine layer to its slab.
+    pub fn flush_magazines(&self) {
+        for size_class in self.size_class_chunks().iter().flatten() {
+            size_class.flush_magazines();
+        }
+    }
+
+    /// Performs periodic housekeeping, intended to be called from a timer or idle loop, returning the number of bytes
+    /// released to the backing allocator.
+    ///
+    /// For each size class, queued remote frees and quarantined objects are returned to their slabs, as are objects
+    /// cached in the magazine depot, which other processors would otherwise hold on to. Processor-local magazines are
+    /// kept, so the fast path stays warm. Empty slabs beyond the size class'
+    /// [retention](SlabAllocator::set_empty_slab_retention) are then released.
+    ///
+    /// Only objects that are queued or cached are visited, never the slabs themselves, so the work done is bounded by
+    /// the frees made since the last call rather than by the size of the heap. [`SlabAllocator::trim`] releases more
+    /// memory, at the cost of emptying every cache.
+    pub fn maintain(&self) -> usize {
+        self.size_class_chunks()
+            .iter()
+            .flatten()
+            .map(SizeClass::maintain)
+            .sum()
+    }
+
+    /// Frees every object allocated from the size classes at once, without deallocating each individually, for
+    /// arena-like uses where everything allocated is discarded together. Slabs are kept for reuse, which a following
+    /// [`SlabAllocator::trim`] releases.
+    ///
+    /// Allocations of whole pages or larger, and those served by the fallback allocator, aren't tracked by the slabs,
+    /// so must still be deallocated individually. Every [`ObjectHandle`] allocated becomes stale.
+    ///
+    /// # Safety
+    ///
+    /// - No object allocated from the size classes may be referenced or deallocated afterwards.
+    /// - The allocator must not be used concurrently.
+    pub unsafe fn reset(&self) {
+        for size_class in self.size_class_chunks().iter().flatten() {
+            // Safety: Caller is required to uphold the safety invariants.
+            unsafe {
+                size_class.reset();
+            }
+        }
+    }
+
+    /// Frees every object allocated from the size classes of `checkpoint` since it was taken, at once.
+    ///
+    /// Objects allocated at the checkpoint are kept, even if they were freed and their memory allocated again since.
+    ///
+    /// # Safety
+    ///
+    /// - `checkpoint` must have been taken from this allocator.
+    /// - No object allocated since the checkpoint may be referenced or deallocated afterwards, and every
+    ///   [`ObjectHandle`] allocated since must have been deallocated.
+    /// - The allocator must not be used concurrently.
+    pub unsafe fn rollback<C: Allocator>(&self, checkpoint: &Checkpoint<C>) {
+        for size_class_checkpoint in checkpoint.size_classes() {
+            // Safety: Caller is required to uphold the safety invariants.
+            unsafe {
+                self.expect_size_class(size_class_checkpoint.object_size())
+                    .roll_back(size_class_checkpoint);
+            }
+        }
+    }
+
+    /// Releases every slab with no allocated objects to the backing allocator, returning the number of bytes
+    /// released.
+    ///
+    /// Objects cached in the magazine layer or quarantine are flushed to their slabs first, so they don't keep
+    /// slabs alive.
+    pub fn trim(&self) -> usize {
+        self.size_class_chunks()
+            .iter()
+            .flatten()
+            .map(SizeClass::trim)
+            .sum::<usize>()
+            + self.pages.trim()
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator>
+    SlabAllocator<A, R, PAGE_SIZE, F>
+{
+    /// Moves `value` into an object of its size class, pinning it there for the life of the returned box. See
+    /// [`SlabBox::try_pin_with_in`] to construct values in place.
+    ///
+    /// # Errors
+    ///
+    /// - If the object can't be allocated.
+    pub fn allocate_pinned<T>(&self, value: T) -> Result<Pin<SlabBox<T, &Self>>, AllocError> {
+        SlabBox::try_pin_in(value, self)
+    }
+
+    /// Records which objects of every size class are allocated, so every object allocated since can be freed at
+    /// once by [`SlabAllocator::rollback`], such as to undo the allocations of a failed step of boot, or of a test.
+    ///
+    /// Objects freed but held by the magazine layer, remote-free queue or quarantine are returned to their slabs.
+    /// Allocations of whole pages or larger, and those served by the fallback allocator, aren't recorded.
+    ///
+    /// # Errors
+    ///
+    /// - If the checkpoint can't be allocated.
+    pub fn checkpoint(&self) -> Result<Checkpoint<&A>, AllocError> {
+        self.checkpoint_where(|_| true)
+    }
+
+    /// Records which objects are allocated, as with [`SlabAllocator::checkpoint`], of only the size classes serving
+    /// `layouts`.
+    ///
+    /// # Errors
+    ///
+    /// - If the checkpoint can't be allocated.
+    pub fn checkpoint_of(&self, layouts: &[Layout]) -> Result<Checkpoint<&A>, AllocError> {
+        self.checkpoint_where(|object_size| {
+            layouts
+                .iter()
+                .any(|layout| self.size_class_for(*layout) == Some(object_size))
+        })
+    }
+
+    /// Records which objects are allocated of the size classes whose object sizes match `is_recorded`.
+    fn checkpoint_where(
+        &self,
+        is_recorded: impl Fn(usize) -> bool,
+    ) -> Result<Checkpoint<&A>, AllocError> {
+        self.create_size_classes()?;
+
+        let mut checkpoint = Checkpoint::new_in(self.inner.get());
+
+        for size_class in self.size_class_chunks().iter().flatten() {
+            if is_recorded(size_class.object_size()) {
+                checkpoint.push(size_class.checkpoint(self.inner.get())?)?;
+            }
+        }
+
+        Ok(checkpoint)
+    }
+}
+
+// Safety:
+// Memory blocks are not freed unless:
+// - `Allocator::deallocate` is called.
+// - `Self` is dropped.
+unsafe impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator> Allocator
+    for SlabAllocator<A, R, PAGE_SIZE, F>
+{
+    #[cfg_attr(feature = "allocation-sites", track_caller)]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let object = self
+            .allocate_object(layout, false)
+            .inspect_err(|_| self.report_failure(layout))?;
+
+        #[cfg(feature = "allocation-sites")]
+        self.record_site(object, layout, AllocationSite::caller());
+
+        Ok(object)
+    }
+
+    #[cfg_attr(feature = "allocation-sites", track_caller)]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let object = self
+            .allocate_object(layout, true)
+            .inspect_err(|_| self.report_failure(layout))?;
+
+        #[cfg(feature = "allocation-sites")]
+        self.record_site(object, layout, AllocationSite::caller());
+
+        Ok(object)
+    }
+
+    #[cfg_attr(feature = "allocation-sites", track_caller)]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        #[cfg(feature = "allocation-sites")]
+        let site = self.site_of(ptr);
+
+        // Safety: Caller is required to maintain safety invariants.
+        let object = unsafe { self.grow_object(ptr, old_layout, new_layout, false) }
+            .inspect_err(|_| self.report_failure(new_layout))?;
+
+        #[cfg(feature = "allocation-sites")]
+        self.record_reallocation(ptr, object, new_layout, site);
+
+        Ok(object)
+    }
+
+    #[cfg_attr(feature = "allocation-sites", track_caller)]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        #[cfg(feature = "allocation-sites")]
+        let site = self.site_of(ptr);
+
+        // Safety: Caller is required to maintain safety invariants.
+        let object = unsafe { self.grow_object(ptr, old_layout, new_layout, true) }
+            .inspect_err(|_| self.report_failure(new_layout))?;
+
+        #[cfg(feature = "allocation-sites")]
+        self.record_reallocation(ptr, object, new_layout, site);
+
+        Ok(object)
+    }
+
+    #[cfg_attr(feature = "allocation-sites", track_caller)]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        #[cfg(feature = "allocation-sites")]
+        let site = self.site_of(ptr);
+
+        // Safety: Caller is required to maintain safety invariants.
+        let object = unsafe { self.shrink_object(ptr, old_layout, new_layout) }
+            .inspect_err(|_| self.report_failure(new_layout))?;
+
+        #[cfg(feature = "allocation-sites")]
+        self.record_reallocation(ptr, object, new_layout, site);
+
+        Ok(object)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Zero-size allocations are dangling, so have nothing to free.
+        if layout.size() == 0 {
+            return;
+        }
+
+        #[cfg(feature = "allocation-sites")]
+        if let Some(site_registry) = &self.site_registry {
+            site_registry.remove(ptr);
+        }
+
+        // Safety: Caller is required to maintain safety invariants.
+        unsafe {
+            self.deallocate_object(ptr, layout);
+        }
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator> core::fmt::Debug
+    for SlabAllocator<A, R, PAGE_SIZE, F>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SlabAllocator")
+            .field("page_size", &PAGE_SIZE)
+            .field("size_classes", &DebugSizeClasses(self.size_class_chunks()))
+            .field("pages", &self.pages)
+            .field("checked_deallocation", &self.checked_deallocation)
+            .field("lazy_shrink", &self.lazy_shrink)
+            .field("memory_limit", &self.memory_limit)
+            .field("fallback_registry", &self.fallback_registry)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Formats the chunked size-class table as a single list.
+struct DebugSizeClasses<'a, A: Allocator, R: RawMutex, const PAGE_SIZE: usize>(
+    &'a [Vec<SizeClass<A, R, PAGE_SIZE>, A>],
+);
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> core::fmt::Debug
+    for DebugSizeClasses<'_, A, R, PAGE_SIZE>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.0.iter().flatten()).finish()
     }
 }
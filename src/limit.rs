@@ -0,0 +1,62 @@
+use crate::alloc_api::AllocError;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Limit on the memory one or more allocators may hold in slabs and pages.
+///
+/// Memory is counted as slabs and pages are taken from the backing allocator, and uncounted as they are released to
+/// it. The allocators' own bookkeeping, and allocations served by their fallback allocators, aren't counted. See
+/// [`SlabAllocator::with_memory_limit`](crate::SlabAllocator::with_memory_limit).
+#[derive(Debug)]
+pub struct MemoryLimit {
+    limit: AtomicUsize,
+    held_bytes: AtomicUsize,
+}
+
+impl MemoryLimit {
+    /// Creates a limit of `limit` bytes.
+    pub const fn new(limit: usize) -> Self {
+        Self {
+            limit: AtomicUsize::new(limit),
+            held_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Maximum number of bytes that may be held.
+    pub fn limit(&self) -> usize {
+        self.limit.load(Ordering::Relaxed)
+    }
+
+    /// Sets the maximum number of bytes that may be held.
+    ///
+    /// Lowering the limit below the bytes already held releases nothing, but no more memory is taken until enough is
+    /// released.
+    pub fn set_limit(&self, limit: usize) {
+        self.limit.store(limit, Ordering::Relaxed);
+    }
+
+    /// Number of bytes currently held.
+    pub fn held_bytes(&self) -> usize {
+        self.held_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Counts `byte_count` more bytes as held.
+    ///
+    /// # Errors
+    ///
+    /// - If holding `byte_count` more bytes would exceed the limit, in which case nothing is counted.
+    pub(crate) fn acquire(&self, byte_count: usize) -> Result<(), AllocError> {
+        self.held_bytes
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |held_bytes| {
+                held_bytes
+                    .checked_add(byte_count)
+                    .filter(|held_bytes| *held_bytes <= self.limit())
+            })
+            .map(|_| ())
+            .map_err(|_| AllocError)
+    }
+
+    /// Counts `byte_count` bytes as no longer held.
+    pub(crate) fn release(&self, byte_count: usize) {
+        self.held_bytes.fetch_sub(byte_count, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::slab::{Slab, SlabHeader};
+use core::ptr::NonNull;
+
+/// Doubly linked list of slabs, threaded through their headers, so slabs can be moved between lists without
+/// allocating.
+pub(crate) struct SlabChain {
+    head: Option<NonNull<SlabHeader>>,
+    len: usize,
+}
+
+// Safety: Headers are only linked and unlinked with the owning manager's lists locked.
+unsafe impl Send for SlabChain {}
+
+impl SlabChain {
+    pub const fn new() -> Self {
+        Self { head: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Header of the slab most recently pushed.
+    pub fn front(&self) -> Option<NonNull<SlabHeader>> {
+        self.head
+    }
+
+    /// Links the slab headed by `header` in at the front of the list.
+    ///
+    /// # Safety
+    ///
+    /// - `header` must point to the header of a live slab that isn't linked into any list.
+    pub unsafe fn push_front(&mut self, header: NonNull<SlabHeader>) {
+        // Safety: Caller is required to ensure `header` is valid. Headers linked into the list are live, and the
+        //         list is locked, as it is borrowed mutably.
+        unsafe {
+            header.as_ref().set_links(None, self.head);
+
+            if let Some(head) = self.head {
+                head.as_ref().set_links(Some(header), head.as_ref().next());
+            }
+        }
+
+        self.head = Some(header);
+        self.len += 1;
+    }
+
+    /// Unlinks the slab headed by `header`, returning the pointer it was linked in with.
+    ///
+    /// # Safety
+    ///
+    /// - `header` must be linked into this list.
+    pub unsafe fn remove(&mut self, header: &SlabHeader) -> NonNull<SlabHeader> {
+        let (prev, next) = (header.prev(), header.next());
+
+        // The pointer is taken from the link to the header rather than `header`, so it keeps the provenance of the
+        // pointer it was pushed with.
+        let header_ptr = match prev {
+            Some(prev) => {
+                // Safety: Headers linked into the list are live, and the list is locked, as it is borrowed mutably.
+                unsafe {
+                    let prev = prev.as_ref();
+                    let header_ptr = prev.next();
+                    prev.set_links(prev.prev(), next);
+
+                    header_ptr
+                }
+            }
+
+            None => core::mem::replace(&mut self.head, next),
+        }
+        .expect("slab is not linked into the list");
+
+        debug_assert!(header_ptr.addr() == NonNull::from(header).addr());
+
+        // Safety: Headers linked into the list are live, and the list is locked, as it is borrowed mutably.
+        unsafe {
+            if let Some(next) = next {
+                next.as_ref().set_links(prev, next.as_ref().next());
+            }
+
+            header.set_links(None, None);
+        }
+
+        self.len -= 1;
+
+        header_ptr
+    }
+
+    /// Unlinks the slab most recently pushed.
+    pub fn pop_front(&mut self) -> Option<NonNull<SlabHeader>> {
+        let head = self.head?;
+
+        // Safety: Headers linked into the list are live, and `head` is linked into this list.
+        Some(unsafe { self.remove(head.as_ref()) })
+    }
+
+    /// Iterates the slabs, most recently pushed first.
+    pub fn iter(&self) -> impl Iterator<Item = Slab> {
+        core::iter::successors(self.head, |header| {
+            // Safety: Headers linked into the list are live.
+            unsafe { header.as_ref() }.next()
+        })
+        .map(|header| {
+            // Safety: Headers linked into the list belong to live slabs.
+            unsafe { Slab::from_header(header) }
+        })
+    }
+}
+
+impl core::fmt::Debug for SlabChain {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
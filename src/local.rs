@@ -0,0 +1,50 @@
+use crate::{DEFAULT_PAGE_SIZE, SlabAllocator};
+use core::cell::Cell;
+use lock_api::{GuardNoSend, RawMutex};
+
+/// Lock for allocators that are only ever used by one context, such as on single-core targets without interrupt
+/// handlers that allocate, or as per-thread arenas.
+///
+/// Locking only sets a flag, with no atomic operations. As the flag is a [`Cell`], allocators using this lock are not
+/// `Sync`, so they can't be shared between threads.
+///
+/// # Panics
+///
+/// - If the lock is locked while already held, such as by a handler called with the allocator's locks held that
+///   calls back into the allocator, as no other context could ever release it.
+pub struct LocalRawMutex {
+    locked: Cell<bool>,
+}
+
+// Safety: The lock is held for as long as the flag is set, and the flag can only be set by the one context using it.
+unsafe impl RawMutex for LocalRawMutex {
+    const INIT: Self = Self {
+        locked: Cell::new(false),
+    };
+
+    type GuardMarker = GuardNoSend;
+
+    fn lock(&self) {
+        assert!(self.try_lock(), "lock is already held by this context");
+    }
+
+    fn try_lock(&self) -> bool {
+        !self.locked.replace(true)
+    }
+
+    unsafe fn unlock(&self) {
+        self.locked.set(false);
+    }
+
+    fn is_locked(&self) -> bool {
+        self.locked.get()
+    }
+}
+
+/// A [`SlabAllocator`] for a single context, guarded by [`LocalRawMutex`] rather than a spinlock.
+///
+/// ```ignore
+/// let allocator = LocalSlabAllocator::<Heap>::new_in(heap);
+/// ```
+pub type LocalSlabAllocator<A, const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE, F = A> =
+    SlabAllocator<A, LocalRawMutex, PAGE_SIZE, F>;
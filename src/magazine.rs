@@ -0,0 +1,245 @@
+use crate::alloc_api::{Allocator, Vec};
+use core::{mem, ptr::NonNull};
+use lock_api::{Mutex, RawMutex};
+
+/// Number of objects a single magazine can hold.
+pub const MAGAZINE_CAPACITY: usize = 16;
+
+/// Maximum number of full magazines held by a depot before frees fall through to the slab layer.
+pub const DEPOT_CAPACITY: usize = 8;
+
+/// Describes the processors an allocator is shared between.
+pub trait Topology: Sync {
+    /// Total number of processors.
+    fn cpu_count(&self) -> usize;
+
+    /// Index of the processor the caller is executing on.
+    ///
+    /// The returned value must always be less than [`Topology::cpu_count`].
+    fn current_cpu(&self) -> usize;
+}
+
+/// Fixed-capacity stack of free objects.
+struct Magazine {
+    rounds: [NonNull<u8>; MAGAZINE_CAPACITY],
+    len: usize,
+}
+
+// Safety: Magazines only hold objects that aren't referenced by anything else.
+unsafe impl Send for Magazine {}
+
+impl Magazine {
+    const fn new() -> Self {
+        Self {
+            rounds: [NonNull::dangling(); MAGAZINE_CAPACITY],
+            len: 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == MAGAZINE_CAPACITY
+    }
+
+    fn pop(&mut self) -> Option<NonNull<u8>> {
+        self.len = self.len.checked_sub(1)?;
+
+        Some(self.rounds[self.len])
+    }
+
+    fn push(&mut self, object_ptr: NonNull<u8>) -> Result<(), NonNull<u8>> {
+        if self.is_full() {
+            Err(object_ptr)
+        } else {
+            self.rounds[self.len] = object_ptr;
+            self.len += 1;
+
+            Ok(())
+        }
+    }
+}
+
+/// Magazines owned by a single processor.
+///
+/// `previous` is always either full or empty.
+struct CpuCache {
+    loaded: Magazine,
+    previous: Magazine,
+}
+
+impl CpuCache {
+    const fn new() -> Self {
+        Self {
+            loaded: Magazine::new(),
+            previous: Magazine::new(),
+        }
+    }
+}
+
+/// Central store of magazines shared by all processors.
+struct Depot<A: Allocator> {
+    full: Vec<Magazine, A>,
+    empty: Vec<Magazine, A>,
+}
+
+/// Per-processor object cache sitting in front of a single size class.
+pub(crate) struct MagazineLayer<A: Allocator, R: RawMutex> {
+    topology: &'static dyn Topology,
+    cpus: Vec<Mutex<R, CpuCache>, A>,
+    depot: Mutex<R, Depot<A>>,
+}
+
+impl<A: Allocator + Clone, R: RawMutex> MagazineLayer<A, R> {
+    pub fn new_in(topology: &'static dyn Topology, allocator: A) -> Self {
+        let mut cpus = Vec::new_in(allocator.clone());
+        cpus.resize_with(topology.cpu_count(), || Mutex::new(CpuCache::new()));
+
+        Self {
+            topology,
+            cpus,
+            depot: Mutex::new(Depot {
+                full: Vec::new_in(allocator.clone()),
+                empty: Vec::new_in(allocator),
+            }),
+        }
+    }
+}
+
+impl<A: Allocator, R: RawMutex> MagazineLayer<A, R> {
+    fn current_cpu_cache(&self) -> &Mutex<R, CpuCache> {
+        &self.cpus[self.topology.current_cpu()]
+    }
+
+    /// Takes a cached object for the current processor, if one is available.
+    pub fn pop(&self) -> Option<NonNull<u8>> {
+        let mut cpu_cache = self.current_cpu_cache().lock();
+        let CpuCache { loaded, previous } = &mut *cpu_cache;
+
+        if let Some(object_ptr) = loaded.pop() {
+            return Some(object_ptr);
+        }
+
+        if previous.is_full() {
+            mem::swap(loaded, previous);
+            return loaded.pop();
+        }
+
+        let mut depot = self.depot.lock();
+        let full_magazine = depot.full.pop()?;
+        let empty_magazine = mem::replace(loaded, full_magazine);
+
+        // An empty magazine holds no objects, so it can be discarded if the depot can't grow.
+        if depot.empty.try_reserve(1).is_ok() {
+            depot.empty.push(empty_magazine);
+        }
+
+        loaded.pop()
+    }
+
+    /// Takes a cached object for the current processor, if one is available without waiting on a lock.
+    pub fn try_pop(&self) -> Option<NonNull<u8>> {
+        let mut cpu_cache = self.current_cpu_cache().try_lock()?;
+        let CpuCache { loaded, previous } = &mut *cpu_cache;
+
+        if let Some(object_ptr) = loaded.pop() {
+            return Some(object_ptr);
+        }
+
+        if previous.is_full() {
+            mem::swap(loaded, previous);
+            return loaded.pop();
+        }
+
+        None
+    }
+
+    /// Caches a freed object for the current processor.
+    ///
+    /// The object is handed back if the cache can't hold it, and must instead be returned to the slab layer.
+    pub fn push(&self, object_ptr: NonNull<u8>) -> Result<(), NonNull<u8>> {
+        let mut cpu_cache = self.current_cpu_cache().lock();
+        let CpuCache { loaded, previous } = &mut *cpu_cache;
+
+        let Err(object_ptr) = loaded.push(object_ptr) else {
+            return Ok(());
+        };
+
+        if previous.is_empty() {
+            mem::swap(loaded, previous);
+            return loaded.push(object_ptr);
+        }
+
+        let mut depot = self.depot.lock();
+        if depot.full.len() >= DEPOT_CAPACITY || depot.full.try_reserve(1).is_err() {
+            return Err(object_ptr);
+        }
+
+        let empty_magazine = depot.empty.pop().unwrap_or_else(Magazine::new);
+        let full_magazine = mem::replace(loaded, empty_magazine);
+        depot.full.push(full_magazine);
+
+        loaded.push(object_ptr)
+    }
+
+    /// Removes every cached object, passing each to `return_object`.
+    pub fn drain(&self, mut return_object: impl FnMut(NonNull<u8>)) {
+        for cpu_cache in &self.cpus {
+            let mut cpu_cache = cpu_cache.lock();
+
+            while let Some(object_ptr) = cpu_cache.loaded.pop() {
+                return_object(object_ptr);
+            }
+
+            while let Some(object_ptr) = cpu_cache.previous.pop() {
+                return_object(object_ptr);
+            }
+        }
+
+        self.drain_depot(return_object);
+    }
+
+    /// Removes every object cached in the depot's full magazines, passing each to `return_object`, while leaving the
+    /// processor caches in place.
+    pub fn drain_depot(&self, mut return_object: impl FnMut(NonNull<u8>)) {
+        let mut depot = self.depot.lock();
+        let Depot { full, empty } = &mut *depot;
+
+        for mut magazine in full.drain(..) {
+            while let Some(object_ptr) = magazine.pop() {
+                return_object(object_ptr);
+            }
+
+            if empty.try_reserve(1).is_ok() {
+                empty.push(magazine);
+            }
+        }
+    }
+
+    /// Number of objects currently held across all processor caches and the depot.
+    pub fn cached_object_count(&self) -> usize {
+        let cpu_cached_count: usize = self
+            .cpus
+            .iter()
+            .map(|cpu_cache| {
+                let cpu_cache = cpu_cache.lock();
+                cpu_cache.loaded.len + cpu_cache.previous.len
+            })
+            .sum();
+
+        let depot = self.depot.lock();
+        let depot_cached_count: usize = depot.full.iter().map(|magazine| magazine.len).sum();
+
+        cpu_cached_count + depot_cached_count
+    }
+
+    /// Number of bytes allocated for the processor caches and depot.
+    pub fn metadata_bytes(&self) -> usize {
+        let depot = self.depot.lock();
+
+        (self.cpus.capacity() * size_of::<Mutex<R, CpuCache>>())
+            + ((depot.full.capacity() + depot.empty.capacity()) * size_of::<Magazine>())
+    }
+}
@@ -0,0 +1,1748 @@
+use crate::{
+    AllocatorHooks, Clock, ConsistencyReport, Corruption, CorruptionHandler, Inconsistency,
+    MemoryLimit, OccupancyHistogram, OomHandler, OutOfMemory, RandomSource, SlabSelection,
+    TryAllocError,
+    alloc_api::{AllocError, Allocator},
+    corruption::panic_on_corruption,
+    is_valid_object_size,
+    list::SlabChain,
+    metadata::MetadataAllocator,
+    quarantine::Quarantine,
+    slab::{
+        DEFAULT_PAGE_SIZE, MAX_SLAB_PAGE_COUNT, Slab, SlabHeader, SlabList, color_count,
+        default_slab_page_count, is_valid_page_size, object_count,
+    },
+    tree::SlabTree,
+};
+use core::{
+    hint::spin_loop,
+    ptr::{NonNull, null_mut},
+    slice,
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+    time::Duration,
+};
+use lock_api::{Mutex, MutexGuard, RawMutex};
+
+/// The slabs of a manager: the active slab, and every other slab kept on a list by occupancy.
+///
+/// Slabs with free objects are exactly those on the partial and empty lists, so a replacement for the active slab is
+/// taken straight from them, without trying full slabs. The lists are linked through the slabs' headers, so moving
+/// slabs between them never allocates.
+pub(crate) struct SlabLists {
+    active: Option<Slab>,
+    full: SlabChain,
+    partial: SlabChain,
+    empty: SlabChain,
+
+    /// Every slab, ordered by base address, so slabs can be found by address.
+    tree: SlabTree,
+
+    /// Clock stamping slabs as they are put on the empty list, if empty slabs decay.
+    clock: Option<&'static dyn Clock>,
+
+    /// Whether slabs are zeroed before they are released.
+    wipe: bool,
+
+    /// Hooks told of every slab as it is created and destroyed.
+    hooks: Option<&'static dyn AllocatorHooks>,
+
+    /// Allocator of the external headers, if not the backing allocator.
+    metadata: Option<&'static (dyn Allocator + Sync)>,
+}
+
+impl SlabLists {
+    /// Total number of slabs, including the active slab.
+    pub fn slab_count(&self) -> usize {
+        usize::from(self.active.is_some()) + self.full.len() + self.partial.len() + self.empty.len()
+    }
+
+    /// Number of bytes allocated for the lists.
+    fn metadata_bytes(&self) -> usize {
+        self.slabs()
+            .map(|slab| slab.external_header_size())
+            .sum::<usize>()
+    }
+
+    /// Number of slabs held on the given list.
+    pub fn len(&self, list: SlabList) -> usize {
+        match list {
+            SlabList::Active => usize::from(self.active.is_some()),
+            SlabList::Full => self.full.len(),
+            SlabList::Partial => self.partial.len(),
+            SlabList::Empty => self.empty.len(),
+        }
+    }
+
+    /// Iterates every slab, including the active slab.
+    pub fn slabs(&self) -> impl Iterator<Item = Slab> {
+        self.active
+            .as_ref()
+            .map(|slab| {
+                // Safety: The active slab is live.
+                unsafe { Slab::from_header(slab.header_ptr()) }
+            })
+            .into_iter()
+            .chain(self.full.iter())
+            .chain(self.partial.iter())
+            .chain(self.empty.iter())
+    }
+
+    fn list_mut(&mut self, list: SlabList) -> &mut SlabChain {
+        match list {
+            SlabList::Full => &mut self.full,
+            SlabList::Partial => &mut self.partial,
+            SlabList::Empty => &mut self.empty,
+            SlabList::Active => unreachable!("the active slab is not held on a list"),
+        }
+    }
+
+    /// Checks every slab against its free bitmap, the list holding it and the tree, recording inconsistencies in
+    /// `report`. Returns the number of free objects over the slabs.
+    fn check_consistency(
+        &self,
+        object_size: usize,
+        slab_size: usize,
+        report: &mut ConsistencyReport,
+    ) -> usize {
+        let active = self.active.as_ref().map(|slab| {
+            // Safety: The active slab is live.
+            (SlabList::Active, unsafe {
+                Slab::from_header(slab.header_ptr())
+            })
+        });
+        let listed_slabs = active
+            .into_iter()
+            .chain(self.full.iter().map(|slab| (SlabList::Full, slab)))
+            .chain(self.partial.iter().map(|slab| (SlabList::Partial, slab)))
+            .chain(self.empty.iter().map(|slab| (SlabList::Empty, slab)));
+
+        let mut free_count = 0;
+        for (list, slab) in listed_slabs {
+            report.record_checked_slab();
+
+            let slab_address = slab.memory().addr().get();
+            let header = slab.header();
+            if header.object_size() != object_size {
+                report.record(Inconsistency::WrongObjectSize {
+                    object_size,
+                    slab_address,
+                    slab_object_size: header.object_size(),
+                });
+            }
+
+            if header.list() != list {
+                report.record(Inconsistency::MisfiledSlab {
+                    object_size,
+                    slab_address,
+                });
+            }
+
+            if !self.has_slab_at(slab_address) {
+                report.record(Inconsistency::UnindexedSlab {
+                    object_size,
+                    slab_address,
+                });
+            }
+
+            let (free_bits, has_stray_bits) = slab.count_free_bits();
+            if slab.remaining_object_count() != free_bits {
+                report.record(Inconsistency::FreeCountMismatch {
+                    object_size,
+                    slab_address,
+                    free_count: slab.remaining_object_count(),
+                    free_bits,
+                });
+            }
+
+            if has_stray_bits {
+                report.record(Inconsistency::StrayFreeBits {
+                    object_size,
+                    slab_address,
+                });
+            }
+
+            free_count += slab.remaining_object_count();
+        }
+
+        // Slabs are ordered by address, so overlapping slabs are neighbours.
+        for slab in self.slabs() {
+            let slab_address = slab.memory().addr().get();
+            if let Some(next_address) = self.slab_after(slab_address)
+                && next_address - slab_address < slab_size
+            {
+                report.record(Inconsistency::OverlappingSlabs {
+                    object_size,
+                    slab_address,
+                    other_object_size: object_size,
+                    other_slab_address: next_address,
+                });
+            }
+        }
+
+        free_count
+    }
+
+    /// Base address of a slab of these lists overlapping `start..end`, if any.
+    fn slab_overlapping(&self, start: usize, end: usize, slab_size: usize) -> Option<usize> {
+        // Slabs of the same lists never overlap, so only the last slab starting before `end` can reach `start`.
+        self.tree
+            .last_before(end)
+            .map(|header| {
+                // Safety: Slabs held in the tree are live.
+                unsafe { header.as_ref() }.address()
+            })
+            .filter(|address| address + slab_size > start)
+    }
+
+    /// Base address of the first slab of these lists starting after `address`, if any.
+    fn slab_after(&self, address: usize) -> Option<usize> {
+        self.tree.first_after(address).map(|header| {
+            // Safety: Slabs held in the tree are live.
+            unsafe { header.as_ref() }.address()
+        })
+    }
+
+    /// Whether a slab of these lists starts at `address`.
+    pub fn has_slab_at(&self, address: usize) -> bool {
+        self.tree.get(address).is_some()
+    }
+
+    /// Header of the slab starting at `address`, if the slab's header is external and the slab is held by these
+    /// lists.
+    pub fn external_header_at(&self, address: usize) -> Option<NonNull<SlabHeader>> {
+        self.tree.get(address).filter(|header| {
+            // Safety: Slabs held in the tree are live.
+            unsafe { header.as_ref() }.is_external()
+        })
+    }
+
+    /// Links a newly created slab into the tree, so it can be found by address.
+    fn insert_address(&mut self, slab: &Slab) {
+        // Safety: The slab was just created, so isn't linked into the tree, and slabs never share their memory.
+        unsafe {
+            self.tree.insert(slab.header_ptr());
+        }
+    }
+
+    /// Allocator of the external headers of slabs backed by `backing`.
+    fn metadata<'a, A: Allocator>(&self, backing: &'a A) -> MetadataAllocator<&'a A> {
+        match self.metadata {
+            Some(metadata) => MetadataAllocator::Separate(metadata),
+            None => MetadataAllocator::Backing(backing),
+        }
+    }
+
+    /// Unlinks a slab from the tree, and releases it to the backing allocator `backing`.
+    ///
+    /// # Safety
+    ///
+    /// - `slab` must have been taken off these lists, and none of its objects may be used again.
+    /// - `backing` must be the allocator the slabs of these lists were created with.
+    unsafe fn release_slab(&mut self, slab: Slab, backing: &impl Allocator) {
+        // Safety: Caller is required to ensure the slab was held by these lists, so is linked into the tree, and is
+        //         no longer used.
+        unsafe {
+            self.tree.remove(slab.header());
+            self.destroy_slab(slab, backing);
+        }
+    }
+
+    /// Releases a slab of these lists to the backing allocator `backing`, telling the hooks of it.
+    ///
+    /// # Safety
+    ///
+    /// - `slab` must have been taken off these lists, and none of its objects may be used again.
+    /// - `backing` must be the allocator the slabs of these lists were created with.
+    unsafe fn destroy_slab(&self, slab: Slab, backing: &impl Allocator) {
+        if let Some(hooks) = self.hooks {
+            hooks.on_slab_destroyed(slab.header().object_size(), slab.memory());
+        }
+
+        // Safety: Caller is required to ensure the slab is no longer used, and was created with `backing`.
+        unsafe {
+            slab.release_in(backing, &self.metadata(backing), self.wipe);
+        }
+    }
+
+    /// Releases every slab of these lists to the backing allocator `backing`.
+    ///
+    /// # Safety
+    ///
+    /// - None of the slabs' objects may be used again.
+    /// - `backing` must be the allocator the slabs of these lists were created with.
+    unsafe fn destroy_all(&mut self, backing: &impl Allocator) {
+        for list in [SlabList::Full, SlabList::Partial, SlabList::Empty] {
+            while let Some(header) = self.list_mut(list).pop_front() {
+                // Safety: The slab was taken off its list, and caller is required to ensure it is no longer used.
+                unsafe {
+                    self.destroy_slab(Slab::from_header(header), backing);
+                }
+            }
+        }
+
+        if let Some(slab) = self.active.take() {
+            // Safety: Caller is required to ensure the active slab is no longer used.
+            unsafe {
+                self.destroy_slab(slab, backing);
+            }
+        }
+
+        self.tree = SlabTree::new();
+    }
+
+    fn push_slab(&mut self, list: SlabList, slab: Slab) {
+        let clock = self.clock.filter(|_| list == SlabList::Empty);
+
+        // Safety: Lists are locked, as `self` is borrowed mutably, and the slab was taken off any list it was on.
+        unsafe {
+            if let Some(clock) = clock {
+                slab.header().set_empty_since(clock.now());
+            }
+
+            slab.header().set_list(list);
+            self.list_mut(list).push_front(slab.header_ptr());
+        }
+    }
+
+    /// Takes the slab headed by `header` off `list`.
+    ///
+    /// # Safety
+    ///
+    /// - `header` must belong to a slab held on `list`.
+    unsafe fn take_slab(&mut self, list: SlabList, header: &SlabHeader) -> Slab {
+        // Safety: Caller is required to ensure the slab is held on `list`.
+        let header = unsafe { self.list_mut(list).remove(header) };
+
+        // Safety: Slabs held on the lists are live.
+        unsafe { Slab::from_header(header) }
+    }
+
+    /// Takes the best slab to serve allocations from, preferring partial slabs over empty slabs, and choosing
+    /// between partial slabs by `selection`.
+    fn take_allocatable_slab(&mut self, selection: SlabSelection) -> Option<Slab> {
+        if selection == SlabSelection::Fullest
+            && let Some(fullest) = self.partial.iter().min_by_key(Slab::remaining_object_count)
+        {
+            // Safety: The slab is held on the partial list.
+            return Some(unsafe { self.take_slab(SlabList::Partial, fullest.header()) });
+        }
+
+        [SlabList::Partial, SlabList::Empty]
+            .into_iter()
+            .find_map(|list| {
+                let header = self.list_mut(list).pop_front()?;
+
+                // Safety: Slabs held on the lists are live.
+                Some(unsafe { Slab::from_header(header) })
+            })
+    }
+
+    /// Moves the slab headed by `header` to the list matching its current occupancy.
+    ///
+    /// # Safety
+    ///
+    /// - `header` must belong to a slab held by these lists.
+    unsafe fn relist(&mut self, header: &SlabHeader) {
+        let list = header.list();
+        if list == SlabList::Active {
+            // The active slab is relisted when it is retired.
+            return;
+        }
+
+        let occupancy = header.occupancy();
+        if list != occupancy {
+            // Safety: Caller is required to ensure the slab is held by these lists, so it is held on its list.
+            let slab = unsafe { self.take_slab(list, header) };
+            self.push_slab(occupancy, slab);
+        }
+    }
+}
+
+impl core::fmt::Debug for SlabLists {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SlabLists")
+            .field("active", &self.active)
+            .field("full", &self.full)
+            .field("partial", &self.partial)
+            .field("empty", &self.empty)
+            .finish()
+    }
+}
+
+/// Manages the slabs of a single object size, handing out their objects.
+///
+/// Each size class of a [`SlabAllocator`](crate::SlabAllocator) is served by a manager, though managers can also be
+/// used on their own as the building block of custom allocators, such as a cache of a single object size. Objects are
+/// taken with [`SlabManager::next_object`] and returned with [`SlabManager::return_object`], and slabs are allocated
+/// from, and released to, the backing allocator `A` as needed. Slabs still held as the manager is dropped are
+/// released, along with any objects still allocated from them.
+///
+/// Allocations are claimed from the active slab's bitmap without locking, and frees are returned to their slab's
+/// bitmap without locking. The lists are only locked to replace an exhausted active slab, or when a free changes
+/// a slab's occupancy.
+///
+/// The active slab is the slab that served the last allocation, so allocations never search the slabs while it has
+/// free objects. Only replacing it chooses between slabs, by the manager's [`SlabSelection`].
+pub struct SlabManager<A: Allocator, R: RawMutex, const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE> {
+    active: AtomicPtr<SlabHeader>,
+    epoch: AtomicUsize,
+    readers: [AtomicUsize; 2],
+    remaining_object_count: AtomicUsize,
+    empty_slab_retention: AtomicUsize,
+    slab_limit: AtomicUsize,
+    slab_decay: Duration,
+    slab_creation_count: AtomicUsize,
+    peak_slab_count: AtomicUsize,
+    lists: Mutex<R, SlabLists>,
+    quarantine_capacity: AtomicUsize,
+    quarantine: Mutex<R, Quarantine>,
+    object_size: usize,
+    slab_page_count: usize,
+    shard: u8,
+    external_headers: bool,
+    random: Option<&'static dyn RandomSource>,
+    slab_selection: SlabSelection,
+    memory_limit: Option<&'static MemoryLimit>,
+    corruption_handler: CorruptionHandler,
+    oom_handler: Option<OomHandler>,
+    #[cfg(feature = "poison")]
+    poison: bool,
+    inner: A,
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> SlabManager<A, R, PAGE_SIZE> {
+    /// Creates a manager of slabs of `object_size`-byte objects, allocated from `allocator`.
+    ///
+    /// No slab is created until the first object is taken.
+    ///
+    /// # Panics
+    ///
+    /// - If `object_size` is smaller than a pointer or not a multiple of its size, or a slab of a single page can't
+    ///   hold an object of `object_size`.
+    pub fn new_in(object_size: usize, allocator: A) -> Self {
+        const { assert!(is_valid_page_size(PAGE_SIZE)) };
+        assert!(
+            is_valid_object_size(object_size, PAGE_SIZE),
+            "invalid object size"
+        );
+
+        Self {
+            active: AtomicPtr::new(null_mut()),
+            epoch: AtomicUsize::new(0),
+            readers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            remaining_object_count: AtomicUsize::new(0),
+            empty_slab_retention: AtomicUsize::new(usize::MAX),
+            slab_limit: AtomicUsize::new(usize::MAX),
+            slab_decay: Duration::ZERO,
+            slab_creation_count: AtomicUsize::new(0),
+            peak_slab_count: AtomicUsize::new(0),
+            lists: Mutex::new(SlabLists {
+                active: None,
+                full: SlabChain::new(),
+                partial: SlabChain::new(),
+                empty: SlabChain::new(),
+                tree: SlabTree::new(),
+                clock: None,
+                wipe: false,
+                hooks: None,
+                metadata: None,
+            }),
+            quarantine_capacity: AtomicUsize::new(0),
+            quarantine: Mutex::new(Quarantine::new()),
+            object_size,
+            slab_page_count: default_slab_page_count(object_size, PAGE_SIZE, false),
+            shard: 0,
+            external_headers: false,
+            random: None,
+            slab_selection: SlabSelection::MostRecent,
+            memory_limit: None,
+            corruption_handler: panic_on_corruption,
+            oom_handler: None,
+            #[cfg(feature = "poison")]
+            poison: true,
+            inner: allocator,
+        }
+    }
+
+    /// Makes every slab span `page_count` contiguous pages, rather than the fewest pages that keep the header and
+    /// leftover bytes of each slab within an eighth of it.
+    ///
+    /// # Panics
+    ///
+    /// - If `page_count` isn't a power of two, or exceeds [`MAX_SLAB_PAGE_COUNT`].
+    /// - If any slab has already been created.
+    pub fn set_slab_page_count(&mut self, page_count: usize) {
+        assert!(page_count.is_power_of_two() && page_count <= MAX_SLAB_PAGE_COUNT);
+        assert!(self.lists.get_mut().slab_count() == 0);
+
+        self.slab_page_count = page_count;
+    }
+
+    /// Keeps the header of every slab outside of its memory, finding the header of an object's slab through the tree
+    /// of slabs by address, rather than its address alone.
+    ///
+    /// # Panics
+    ///
+    /// - If any slab has already been created.
+    pub fn set_external_headers(&mut self) {
+        let lists = self.lists.get_mut();
+        assert!(lists.slab_count() == 0);
+
+        self.external_headers = true;
+
+        // Slabs needn't be enlarged to amortize headers kept outside of them.
+        if self.slab_page_count == default_slab_page_count(self.object_size, PAGE_SIZE, false) {
+            self.slab_page_count = default_slab_page_count(self.object_size, PAGE_SIZE, true);
+        }
+    }
+
+    /// Allocates external headers from `allocator` rather than the backing allocator.
+    ///
+    /// # Panics
+    ///
+    /// - If any slab has already been created.
+    pub fn set_metadata_allocator(&mut self, allocator: &'static (dyn Allocator + Sync)) {
+        let lists = self.lists.get_mut();
+        assert!(lists.slab_count() == 0);
+
+        lists.metadata = Some(allocator);
+    }
+
+    /// Hands out objects from each slab in a random order drawn from `random`, rather than lowest first.
+    pub fn set_random_source(&mut self, random: &'static dyn RandomSource) {
+        self.random = Some(random);
+    }
+
+    /// Chooses the slab allocations are served from once the active slab is exhausted by `selection`.
+    pub fn set_slab_selection(&mut self, selection: SlabSelection) {
+        self.slab_selection = selection;
+    }
+
+    /// Counts every slab against `limit`, failing to create slabs that would exceed it.
+    pub fn set_memory_limit(&mut self, limit: &'static MemoryLimit) {
+        self.memory_limit = Some(limit);
+    }
+
+    /// Keeps empty slabs beyond the retention until they have been empty for `decay`, as measured by `clock`, rather
+    /// than releasing them as soon as they are emptied.
+    pub fn set_slab_decay(&mut self, clock: &'static dyn Clock, decay: Duration) {
+        self.lists.get_mut().clock = Some(clock);
+        self.slab_decay = decay;
+    }
+
+    /// Zeroes every slab before it is released to the backing allocator.
+    pub fn set_secure_wipe(&mut self) {
+        self.lists.get_mut().wipe = true;
+    }
+
+    /// Tells `hooks` of every slab as it is created and destroyed.
+    pub fn set_hooks(&mut self, hooks: &'static dyn AllocatorHooks) {
+        self.lists.get_mut().hooks = Some(hooks);
+    }
+
+    /// Reports heap corruption detected by the manager to `handler`.
+    pub fn set_corruption_handler(&mut self, handler: CorruptionHandler) {
+        self.corruption_handler = handler;
+    }
+
+    /// Reports failures of the backing allocator to provide a slab to `handler`.
+    pub fn set_oom_handler(&mut self, handler: OomHandler) {
+        self.oom_handler = Some(handler);
+    }
+
+    /// Stops free objects from being poisoned, for objects whose state must be kept while they are free.
+    #[cfg(feature = "poison")]
+    pub fn disable_poisoning(&mut self) {
+        self.poison = false;
+    }
+
+    /// Takes the next free object, creating a slab if none is free.
+    ///
+    /// # Errors
+    ///
+    /// - If a slab can't be created, such as when the slab limit or memory limit is reached, or the backing allocator
+    ///   is exhausted.
+    pub fn next_object(&self) -> Result<NonNull<[u8]>, AllocError> {
+        self.next_object_with(|_| {})
+    }
+
+    /// Takes the next free object, calling `init_slab` on any slab created to satisfy the request, before any of its
+    /// objects are handed out.
+    ///
+    /// # Errors
+    ///
+    /// - If a slab can't be created.
+    pub fn next_object_with(&self, init_slab: impl Fn(&Slab)) -> Result<NonNull<[u8]>, AllocError> {
+        self.take_object(&init_slab).map(|(object, _)| object)
+    }
+
+    /// Takes the next free object, zeroed.
+    ///
+    /// Objects that haven't been handed out since their slab was created are known to be zeroed already.
+    ///
+    /// # Errors
+    ///
+    /// - If a slab can't be created.
+    pub fn next_zeroed_object(&self) -> Result<NonNull<[u8]>, AllocError> {
+        let (object, is_clean) = self.take_object(&|_| {})?;
+
+        if !is_clean {
+            // Safety: Object was just claimed, and so isn't referenced.
+            unsafe {
+                object.cast::<u8>().write_bytes(0, object.len());
+            }
+        }
+
+        Ok(object)
+    }
+
+    /// Takes the next free object, and whether it is known to be zeroed.
+    fn take_object(&self, init_slab: &impl Fn(&Slab)) -> Result<(NonNull<[u8]>, bool), AllocError> {
+        loop {
+            if let Some((object, is_clean)) = self.claim_active_object() {
+                self.remaining_object_count.fetch_sub(1, Ordering::Relaxed);
+
+                return Ok((object, self.prepare_object(object, is_clean)));
+            }
+
+            self.replace_active_slab(init_slab)?;
+        }
+    }
+
+    /// Fills `objects` with free objects, claiming as many as possible from the active slab at once.
+    ///
+    /// Returns the number of objects taken, which is less than `objects.len()` only if a slab couldn't be created.
+    pub fn next_objects(&self, objects: &mut [NonNull<[u8]>]) -> usize {
+        let mut object_count = 0;
+
+        while object_count < objects.len() {
+            let claimed_objects = &mut objects[object_count..];
+            let claimed_count = self.claim_active_objects(claimed_objects);
+
+            self.remaining_object_count
+                .fetch_sub(claimed_count, Ordering::Relaxed);
+
+            for object in &claimed_objects[..claimed_count] {
+                self.prepare_object(*object, false);
+            }
+
+            object_count += claimed_count;
+
+            if object_count < objects.len() && self.replace_active_slab(&|_| {}).is_err() {
+                break;
+            }
+        }
+
+        object_count
+    }
+
+    /// Takes the next free object without waiting on the manager's lock, or on allocations claiming from the active
+    /// slab.
+    ///
+    /// # Errors
+    ///
+    /// - [`TryAllocError::WouldBlock`] if the lock is held, or the active slab can't be replaced while other
+    ///   allocations are claiming from it.
+    /// - [`TryAllocError::Exhausted`] if a slab can't be created.
+    pub fn try_next_object(&self) -> Result<NonNull<[u8]>, TryAllocError> {
+        loop {
+            if let Some((object, is_clean)) = self.claim_active_object() {
+                self.remaining_object_count.fetch_sub(1, Ordering::Relaxed);
+                self.prepare_object(object, is_clean);
+
+                return Ok(object);
+            }
+
+            let mut lists = self.lists.try_lock().ok_or(TryAllocError::WouldBlock)?;
+
+            // Retiring the active slab waits for every allocation claiming from it, which may have been interrupted.
+            if self
+                .readers
+                .iter()
+                .any(|readers| readers.load(Ordering::SeqCst) > 0)
+            {
+                return Err(TryAllocError::WouldBlock);
+            }
+
+            self.replace_active_slab_locked(&mut lists, &|_| {})?;
+        }
+    }
+
+    /// Takes a free object aligned to `align`, which exceeds the objects' own alignment, zeroed if `zeroed`. See
+    /// [`SlabManager::has_aligned_objects`].
+    ///
+    /// # Errors
+    ///
+    /// - If no slab holds a free object aligned to `align`, and a slab can't be created.
+    pub fn next_aligned_object(
+        &self,
+        align: usize,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.take_aligned_object(&mut self.lists.lock(), align, zeroed)
+    }
+
+    /// Takes a free object aligned to `align` without waiting on the manager's lock.
+    ///
+    /// # Errors
+    ///
+    /// - [`TryAllocError::WouldBlock`] if the lock is held.
+    /// - [`TryAllocError::Exhausted`] if no slab holds a free object aligned to `align`, and a slab can't be created.
+    pub fn try_next_aligned_object(&self, align: usize) -> Result<NonNull<[u8]>, TryAllocError> {
+        let mut lists = self.lists.try_lock().ok_or(TryAllocError::WouldBlock)?;
+        Ok(self.take_aligned_object(&mut lists, align, false)?)
+    }
+
+    /// Takes a free object aligned to `align` from any slab holding one, or else from a new slab, with the lists
+    /// already locked.
+    fn take_aligned_object(
+        &self,
+        lists: &mut SlabLists,
+        align: usize,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let claimed = lists.slabs().find_map(|slab| {
+            // Safety: Slabs held on the lists are live while the lists are locked.
+            let claimed = unsafe { SlabHeader::next_aligned_object(slab.header_ptr(), align) }?;
+            Some((slab, claimed))
+        });
+
+        let (object, is_clean) = match claimed {
+            Some((slab, claimed)) => {
+                // Safety: Slab is held by these lists.
+                unsafe {
+                    lists.relist(slab.header());
+                }
+
+                claimed
+            }
+
+            None => {
+                // Slabs of the first color hold objects aligned to any alignment the manager has aligned objects
+                // for.
+                let new_slab = self.create_slab_colored(lists, &|_| {}, 0)?;
+
+                // Safety: The slab was just created.
+                let claimed =
+                    unsafe { SlabHeader::next_aligned_object(new_slab.header_ptr(), align) };
+                lists.push_slab(new_slab.header().occupancy(), new_slab);
+
+                claimed.ok_or(AllocError)?
+            }
+        };
+
+        self.remaining_object_count.fetch_sub(1, Ordering::Relaxed);
+
+        if self.prepare_object(object, is_clean) || !zeroed {
+            return Ok(object);
+        }
+
+        // Safety: Object was just claimed, and so isn't referenced.
+        unsafe {
+            object.cast::<u8>().write_bytes(0, object.len());
+        }
+
+        Ok(object)
+    }
+
+    /// Replaces the active slab if it is exhausted, creating a new slab if no other slab has free objects.
+    fn replace_active_slab(&self, init_slab: &impl Fn(&Slab)) -> Result<(), AllocError> {
+        self.replace_active_slab_locked(&mut self.lists.lock(), init_slab)
+    }
+
+    /// Replaces the active slab if it is exhausted, with the lists already locked.
+    fn replace_active_slab_locked(
+        &self,
+        lists: &mut SlabLists,
+        init_slab: &impl Fn(&Slab),
+    ) -> Result<(), AllocError> {
+        // Another allocation may have already replaced the exhausted active slab.
+        if lists.active.as_ref().is_some_and(|slab| !slab.is_full()) {
+            return Ok(());
+        }
+
+        let new_slab = match lists.take_allocatable_slab(self.slab_selection) {
+            Some(slab) => slab,
+            None => self.create_slab(lists, init_slab)?,
+        };
+
+        // Safety: Lists are locked.
+        unsafe {
+            new_slab.header().set_list(SlabList::Active);
+        }
+
+        self.active
+            .store(new_slab.header_ptr().as_ptr(), Ordering::SeqCst);
+
+        if let Some(retired_slab) = lists.active.replace(new_slab) {
+            // Allocations may still be claiming objects from the retired slab, so its occupancy is only final once
+            // they have finished.
+            self.synchronize();
+
+            let occupancy = retired_slab.header().occupancy();
+            lists.push_slab(occupancy, retired_slab);
+        }
+
+        Ok(())
+    }
+
+    /// Ensures at least `object_count` free objects are held in slabs, creating slabs as needed, so that many
+    /// objects can be allocated without allocating from the backing allocator.
+    ///
+    /// # Errors
+    ///
+    /// - If a slab can't be created, in which case the slabs already created are kept.
+    pub fn reserve(&self, object_count: usize) -> Result<(), AllocError> {
+        let mut lists = self.lists.lock();
+
+        while self.remaining_object_count() < object_count {
+            let new_slab = self.create_slab(&mut lists, &|_| {})?;
+            lists.push_slab(SlabList::Empty, new_slab);
+        }
+
+        Ok(())
+    }
+
+    /// Creates a slab, making room for it on every list, and calls `init_slab` on it.
+    ///
+    /// # Errors
+    ///
+    /// - If the slab limit or memory limit has been reached, or the backing allocator is exhausted.
+    fn create_slab(
+        &self,
+        lists: &mut SlabLists,
+        init_slab: &impl Fn(&Slab),
+    ) -> Result<Slab, AllocError> {
+        // Successive slabs cycle through the colors, so their objects don't all contend for the same cache sets.
+        let color = self.slab_creation_count.load(Ordering::Relaxed)
+            % color_count(self.object_size, self.slab_size(), self.external_headers);
+
+        self.create_slab_colored(lists, init_slab, color)
+    }
+
+    /// Creates a slab of the given `color`, as with [`SlabManager::create_slab`].
+    fn create_slab_colored(
+        &self,
+        lists: &mut SlabLists,
+        init_slab: &impl Fn(&Slab),
+        color: usize,
+    ) -> Result<Slab, AllocError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "create_slab",
+            object_size = self.object_size,
+            slab_page_count = self.slab_page_count
+        )
+        .entered();
+
+        let slab_count = lists.slab_count() + 1;
+        if slab_count > self.slab_limit() {
+            return Err(AllocError);
+        }
+
+        if let Some(memory_limit) = self.memory_limit {
+            memory_limit.acquire(self.slab_size())?;
+        }
+
+        let new_slab = match Slab::new_in::<PAGE_SIZE>(
+            self.object_size,
+            self.slab_page_count,
+            color,
+            self.shard,
+            self.external_headers,
+            &self.inner,
+            &lists.metadata(&self.inner),
+        ) {
+            Ok(new_slab) => new_slab,
+            Err(error) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("backing allocator failed to allocate a slab");
+                #[cfg(feature = "log")]
+                log::warn!(
+                    "backing allocator failed to allocate a slab of {}-byte objects",
+                    self.object_size
+                );
+                #[cfg(feature = "defmt")]
+                defmt::warn!(
+                    "backing allocator failed to allocate a slab of {}-byte objects",
+                    self.object_size
+                );
+
+                self.release_memory(self.slab_size());
+
+                if let Some(oom_handler) = self.oom_handler {
+                    oom_handler(OutOfMemory {
+                        object_size: self.object_size,
+                        size: self.slab_size(),
+                    });
+                }
+
+                return Err(error);
+            }
+        };
+
+        #[cfg(feature = "poison")]
+        if self.poison {
+            for object in new_slab.objects() {
+                // Safety: The slab was just created, so none of its objects are referenced.
+                unsafe {
+                    crate::slab::poison_object(object);
+                }
+            }
+        }
+
+        init_slab(&new_slab);
+
+        if let Some(hooks) = lists.hooks {
+            hooks.on_slab_created(self.object_size, new_slab.memory());
+        }
+
+        #[cfg(feature = "log")]
+        log::debug!(
+            "created a slab of {} {}-byte objects, now holding {slab_count} slabs",
+            new_slab.remaining_object_count(),
+            self.object_size
+        );
+        #[cfg(feature = "defmt")]
+        defmt::debug!(
+            "created a slab of {} {}-byte objects, now holding {} slabs",
+            new_slab.remaining_object_count(),
+            self.object_size,
+            slab_count
+        );
+
+        self.slab_creation_count.fetch_add(1, Ordering::Relaxed);
+        self.peak_slab_count
+            .fetch_max(slab_count, Ordering::Relaxed);
+
+        self.remaining_object_count
+            .fetch_add(new_slab.remaining_object_count(), Ordering::Relaxed);
+
+        lists.insert_address(&new_slab);
+        Ok(new_slab)
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> SlabManager<A, R, PAGE_SIZE> {
+    /// Claims an object from the active slab, without locking.
+    fn claim_active_object(&self) -> Option<(NonNull<[u8]>, bool)> {
+        self.read_active_slab(|header| {
+            // Safety: The active slab can't be retired until this reader has finished.
+            unsafe { SlabHeader::next_object(header, self.random) }
+        })
+        .flatten()
+    }
+
+    /// Claims objects from the active slab into `objects` until either is exhausted, without locking.
+    ///
+    /// Returns the number of objects claimed.
+    fn claim_active_objects(&self, objects: &mut [NonNull<[u8]>]) -> usize {
+        self.read_active_slab(|header| {
+            objects
+                .iter_mut()
+                .map_while(|object| {
+                    // Safety: The active slab can't be retired until this reader has finished.
+                    *object = unsafe { SlabHeader::next_object(header, self.random) }?.0;
+                    Some(())
+                })
+                .count()
+        })
+        .unwrap_or(0)
+    }
+
+    /// Calls `read` on the active slab, if there is one, preventing it from being retired until `read` returns.
+    fn read_active_slab<T>(&self, read: impl FnOnce(NonNull<SlabHeader>) -> T) -> Option<T> {
+        let readers = loop {
+            let epoch = self.epoch.load(Ordering::SeqCst);
+            let readers = &self.readers[epoch % 2];
+            readers.fetch_add(1, Ordering::SeqCst);
+
+            // If the epoch advanced, the active slab may have been retired without waiting for this reader.
+            if self.epoch.load(Ordering::SeqCst) == epoch {
+                break readers;
+            }
+
+            readers.fetch_sub(1, Ordering::Release);
+        };
+
+        let result = NonNull::new(self.active.load(Ordering::SeqCst)).map(read);
+
+        readers.fetch_sub(1, Ordering::Release);
+
+        result
+    }
+
+    /// Checks the poison of a just-claimed object, returning whether it is known to be zeroed.
+    fn prepare_object(&self, object: NonNull<[u8]>, is_clean: bool) -> bool {
+        #[cfg(feature = "poison")]
+        if self.poison {
+            // Safety: Object was poisoned when its slab was created or it was freed, and was just claimed.
+            if let Err(corruption) = unsafe { crate::slab::check_poison(object) } {
+                (self.corruption_handler)(corruption);
+            }
+
+            // Objects are poisoned after being zeroed, so must be zeroed again as they're handed out.
+            // Safety: Object was just claimed, and so isn't referenced.
+            #[cfg(feature = "zeroize-on-free")]
+            unsafe {
+                object.cast::<u8>().write_bytes(0, object.len());
+            }
+
+            return cfg!(feature = "zeroize-on-free");
+        }
+
+        #[cfg(not(feature = "poison"))]
+        let _ = object;
+
+        is_clean
+    }
+
+    /// Waits until every allocation that could have observed the previous active slab has finished.
+    fn synchronize(&self) {
+        let epoch = self.epoch.fetch_add(1, Ordering::SeqCst);
+
+        while self.readers[epoch % 2].load(Ordering::SeqCst) > 0 {
+            spin_loop();
+        }
+    }
+
+    /// Number of free objects held in slabs.
+    pub fn remaining_object_count(&self) -> usize {
+        self.remaining_object_count.load(Ordering::Relaxed)
+    }
+
+    /// Cumulative number of slabs created.
+    pub fn slab_creation_count(&self) -> usize {
+        self.slab_creation_count.load(Ordering::Relaxed)
+    }
+
+    /// Peak number of slabs held at once.
+    pub fn peak_slab_count(&self) -> usize {
+        self.peak_slab_count.load(Ordering::Relaxed)
+    }
+
+    /// Writes a map of every slab's objects, under a line giving the object size. See [`Slab::write_map`].
+    pub fn write_map(&self, writer: &mut impl core::fmt::Write) -> core::fmt::Result {
+        // The lists may be locked by the context writing the map, such as from a panic handler.
+        let Some(lists) = self.lists.try_lock() else {
+            return writeln!(writer, "{}: locked", self.object_size);
+        };
+
+        let slab_count = lists.slab_count();
+        let plural = if slab_count == 1 { "" } else { "s" };
+        writeln!(writer, "{}: {slab_count} slab{plural}", self.object_size)?;
+
+        lists.slabs().try_for_each(|slab| slab.write_map(writer))
+    }
+
+    /// Checks the slabs and the count of free objects, recording inconsistencies in `report`. See
+    /// [`SlabAllocator::check_consistency`](crate::SlabAllocator::check_consistency).
+    pub(crate) fn check_consistency(&self, report: &mut ConsistencyReport) {
+        // The lists may be locked by the context checking them, such as from a crash handler.
+        let Some(lists) = self.lists.try_lock() else {
+            report.record_locked_shard();
+            return;
+        };
+
+        let counted = lists.check_consistency(self.object_size, self.slab_size(), report);
+        let recorded = self.remaining_object_count();
+        if recorded != counted {
+            report.record(Inconsistency::RemainingCountMismatch {
+                object_size: self.object_size,
+                recorded,
+                counted,
+            });
+        }
+    }
+
+    /// Records every slab of this manager overlapping a slab of `other` in `report`. Managers whose lists are locked
+    /// are skipped.
+    pub(crate) fn check_overlap(&self, other: &Self, report: &mut ConsistencyReport) {
+        let (Some(lists), Some(other_lists)) = (self.lists.try_lock(), other.lists.try_lock())
+        else {
+            return;
+        };
+
+        for slab in lists.slabs() {
+            let slab_address = slab.memory().addr().get();
+            if let Some(other_slab_address) = other_lists.slab_overlapping(
+                slab_address,
+                slab_address + self.slab_size(),
+                other.slab_size(),
+            ) {
+                report.record(Inconsistency::OverlappingSlabs {
+                    object_size: self.object_size,
+                    slab_address,
+                    other_object_size: other.object_size,
+                    other_slab_address,
+                });
+            }
+        }
+    }
+
+    /// Counts every slab in `histogram` by the share of its objects that are allocated.
+    pub fn record_occupancy(&self, histogram: &mut OccupancyHistogram) {
+        let object_count = self.objects_per_slab();
+
+        for slab in self.lists().slabs() {
+            histogram.record(object_count - slab.remaining_object_count(), object_count);
+        }
+    }
+
+    /// Zeroes the count of slabs created, and lowers the peak to the current number of slabs.
+    pub(crate) fn reset_stats(&self) {
+        let lists = self.lists();
+
+        self.slab_creation_count.store(0, Ordering::Relaxed);
+        self.peak_slab_count
+            .store(lists.slab_count(), Ordering::Relaxed);
+    }
+
+    /// Number of bytes allocated for the slab lists and quarantine, outside of the slabs themselves.
+    pub(crate) fn metadata_bytes(&self) -> usize {
+        // The quarantine is locked before the lists as objects are evicted from it, so the lists must be unlocked
+        // before the quarantine is locked.
+        let lists_bytes = self.lists().metadata_bytes();
+        lists_bytes + self.quarantine.lock().metadata_bytes()
+    }
+
+    /// Locks and returns the slab lists.
+    pub(crate) fn lists(&self) -> MutexGuard<'_, R, SlabLists> {
+        self.lists.lock()
+    }
+
+    /// Number of empty slabs currently held.
+    pub fn empty_slab_count(&self) -> usize {
+        self.lists().len(SlabList::Empty)
+    }
+
+    /// Maximum number of empty slabs kept when a free empties a slab.
+    pub fn empty_slab_retention(&self) -> usize {
+        self.empty_slab_retention.load(Ordering::Relaxed)
+    }
+
+    /// Sets the maximum number of empty slabs kept when a free empties a slab. Slabs beyond this are released to
+    /// the backing allocator.
+    pub fn set_empty_slab_retention(&self, count: usize) {
+        self.empty_slab_retention.store(count, Ordering::Relaxed);
+    }
+
+    /// Maximum number of slabs held at once.
+    pub fn slab_limit(&self) -> usize {
+        self.slab_limit.load(Ordering::Relaxed)
+    }
+
+    /// Sets the maximum number of slabs held at once. Slabs already held beyond this are kept, but no more are
+    /// created until enough are released.
+    pub fn set_slab_limit(&self, count: usize) {
+        self.slab_limit.store(count, Ordering::Relaxed);
+    }
+
+    /// Releases every empty slab to the backing allocator, returning the number of bytes released.
+    pub fn reclaim_empty(&self) -> usize {
+        self.reclaim_empty_with(|_| {})
+    }
+
+    /// Releases every empty slab to the backing allocator, calling `release_slab` on each slab before it is released.
+    ///
+    /// Returns the number of bytes released.
+    pub fn reclaim_empty_with(&self, release_slab: impl Fn(&Slab)) -> usize {
+        self.release_empty_slabs(&mut self.lists(), 0, usize::MAX, None, release_slab)
+    }
+
+    /// Releases empty slabs beyond the empty slab retention to the backing allocator, returning the number of bytes
+    /// released.
+    pub fn release_excess_empty_slabs(&self) -> usize {
+        let mut lists = self.lists();
+        let now = lists.clock.map(Clock::now);
+
+        self.release_empty_slabs(
+            &mut lists,
+            self.empty_slab_retention(),
+            usize::MAX,
+            now,
+            |_| {},
+        )
+    }
+
+    /// Releases empty slabs to the backing allocator until at least `target_bytes` have been released, or none are
+    /// left, returning the number of bytes released.
+    pub fn reclaim_empty_up_to(&self, target_bytes: usize) -> usize {
+        self.release_empty_slabs(&mut self.lists(), 0, target_bytes, None, |_| {})
+    }
+
+    /// Releases empty slabs until at most `retained_count` remain, or at least `target_bytes` have been released,
+    /// returning the number of bytes released.
+    ///
+    /// If the current time `now` is given, only slabs that have been empty for the decay period are released.
+    fn release_empty_slabs(
+        &self,
+        lists: &mut SlabLists,
+        retained_count: usize,
+        target_bytes: usize,
+        now: Option<Duration>,
+        release_slab: impl Fn(&Slab),
+    ) -> usize {
+        #[cfg(feature = "tracing")]
+        let span = (lists.empty.len() > retained_count).then(|| {
+            tracing::debug_span!(
+                "reclaim",
+                object_size = self.object_size,
+                released_bytes = tracing::field::Empty
+            )
+            .entered()
+        });
+
+        let mut released_bytes = 0;
+
+        // The active slab is never on the empty list, and slabs on the empty list have no allocated objects, so
+        // nothing can be claiming from them. However, the frees that emptied a slab may not have finished with its
+        // header yet.
+        let mut cursor = lists.empty.front();
+
+        while let Some(header) = cursor {
+            if lists.empty.len() <= retained_count || released_bytes >= target_bytes {
+                break;
+            }
+
+            // Safety: Slabs held on the lists are live while the lists are locked.
+            let header = unsafe { header.as_ref() };
+            cursor = header.next();
+
+            if !header.is_releasable()
+                || now.is_some_and(|now| now.saturating_sub(header.empty_since()) < self.slab_decay)
+            {
+                continue;
+            }
+
+            // Safety: The slab is held on the empty list.
+            let slab = unsafe { lists.take_slab(SlabList::Empty, header) };
+            debug_assert!(slab.header().occupancy() == SlabList::Empty);
+
+            release_slab(&slab);
+
+            self.remaining_object_count
+                .fetch_sub(slab.remaining_object_count(), Ordering::Relaxed);
+            released_bytes += slab.memory().len();
+
+            // Safety: The slab was taken off the empty list, and has no allocated objects.
+            unsafe {
+                lists.release_slab(slab, &self.inner);
+            }
+        }
+
+        self.release_memory(released_bytes);
+
+        #[cfg(feature = "tracing")]
+        if let Some(span) = span {
+            span.record("released_bytes", released_bytes);
+        }
+
+        released_bytes
+    }
+
+    /// Uncounts `byte_count` bytes of released slabs from the memory limit, if there is one.
+    fn release_memory(&self, byte_count: usize) {
+        if let Some(memory_limit) = self.memory_limit {
+            memory_limit.release(byte_count);
+        }
+    }
+
+    /// Returns an object to its slab, or to the quarantine if objects are quarantined.
+    ///
+    /// Slabs emptied by the return are kept up to the [empty slab
+    /// retention](SlabManager::set_empty_slab_retention), and released beyond it.
+    ///
+    /// # Safety
+    ///
+    /// - `object_ptr` must point to an allocated object taken from this slab manager, which is no longer used.
+    pub unsafe fn return_object(&self, object_ptr: NonNull<u8>) {
+        // Safety: Caller is required to uphold the safety invariants.
+        unsafe {
+            self.return_object_with(object_ptr, |_| {});
+        }
+    }
+
+    /// Size of each object.
+    pub fn object_size(&self) -> usize {
+        self.object_size
+    }
+
+    /// Number of contiguous pages spanned by each slab.
+    pub fn slab_page_count(&self) -> usize {
+        self.slab_page_count
+    }
+
+    /// Size of each slab's memory.
+    pub fn slab_size(&self) -> usize {
+        self.slab_page_count * PAGE_SIZE
+    }
+
+    /// Number of allocatable objects in each slab.
+    pub fn objects_per_slab(&self) -> usize {
+        object_count(self.object_size, self.slab_size(), self.external_headers)
+    }
+
+    /// Whether slabs hold objects aligned to `align`, beyond the objects' own alignment.
+    ///
+    /// Objects are packed against the end of their slab, so in slabs of the first color, every object a multiple of
+    /// `align` bytes from the end is aligned to it. Slabs of a single page with in-slab headers hold none aligned to
+    /// the page, as the header takes its start.
+    pub fn has_aligned_objects(&self, align: usize) -> bool {
+        let object_align = 1 << self.object_size.trailing_zeros();
+
+        align <= self.slab_size() && (align / align.min(object_align)) <= self.objects_per_slab()
+    }
+
+    /// Whether every slab's header is kept outside of its memory.
+    pub fn has_external_headers(&self) -> bool {
+        self.external_headers
+    }
+
+    /// Base address of the slab `ptr` would lie in, if it pointed into one of this manager's slabs.
+    fn slab_address_of(&self, ptr: NonNull<u8>) -> usize {
+        ptr.addr().get() & !(self.slab_size() - 1)
+    }
+
+    /// Header of the slab holding `ptr`, if headers are external and `ptr` points into one of this manager's slabs.
+    pub(crate) fn external_header_of(&self, ptr: NonNull<u8>) -> Option<NonNull<SlabHeader>> {
+        self.lists().external_header_at(self.slab_address_of(ptr))
+    }
+
+    /// Whether `object_ptr` points to an object of one of this manager's slabs.
+    pub fn owns(&self, object_ptr: NonNull<u8>) -> bool {
+        let slab_address = self.slab_address_of(object_ptr);
+        let lists = self.lists();
+
+        let header = match lists.external_header_at(slab_address) {
+            Some(header) => header,
+
+            // Safety: The pointer is within one of this manager's slabs, whose header is at its start.
+            None if lists.has_slab_at(slab_address) => unsafe {
+                SlabHeader::containing(object_ptr, self.slab_size())
+            },
+
+            None => return false,
+        };
+
+        // Safety: The slab can't be released while the lists are locked.
+        let header = unsafe { header.as_ref() };
+
+        // Objects are offset by their slab's color, so only the slab holding the pointer can tell.
+        header.is_object_offset(object_ptr.addr().get() - slab_address)
+    }
+
+    /// Whether `ptr` points into the memory of one of this manager's slabs.
+    pub fn contains(&self, ptr: NonNull<u8>) -> bool {
+        self.lists().has_slab_at(self.slab_address_of(ptr))
+    }
+
+    /// Number of freed objects held back from reuse.
+    pub fn quarantine_capacity(&self) -> usize {
+        self.quarantine_capacity.load(Ordering::Relaxed)
+    }
+
+    /// Sets the number of freed objects held back from reuse, returning any currently held objects to their slabs.
+    ///
+    /// # Errors
+    ///
+    /// - If the quarantine can't be allocated.
+    pub fn set_quarantine_capacity(&self, capacity: usize) -> Result<(), AllocError> {
+        let mut quarantine = self.quarantine.lock();
+
+        let evict = |object_ptr| {
+            // Safety: Quarantined objects originated from this slab manager.
+            unsafe {
+                self.release_object(object_ptr, |_| {});
+            }
+        };
+
+        // Safety: The quarantine is only ever allocated from the backing allocator.
+        let result = unsafe { quarantine.set_capacity(capacity, evict, &self.inner) };
+
+        self.quarantine_capacity
+            .store(quarantine.capacity(), Ordering::Relaxed);
+
+        result
+    }
+
+    /// Number of freed objects currently held back from reuse.
+    pub fn quarantined_object_count(&self) -> usize {
+        self.quarantine.lock().len()
+    }
+
+    /// Returns every quarantined object to its slab.
+    pub fn flush_quarantine(&self) {
+        self.quarantine.lock().drain(|object_ptr| {
+            // Safety: Quarantined objects originated from this slab manager.
+            unsafe {
+                self.release_object(object_ptr, |_| {});
+            }
+        });
+    }
+
+    /// Marks every object of every slab free at once, discarding the quarantine, and calling `scrub` on each object
+    /// that was allocated before it is marked free. Slabs are kept, with every slab but the active slab moved to the
+    /// empty list.
+    ///
+    /// # Safety
+    ///
+    /// - No object of this slab manager may be referenced, nor the manager be used concurrently.
+    pub(crate) unsafe fn reset(&self, scrub: impl Fn(NonNull<[u8]>)) {
+        // Quarantined objects are still marked allocated, and are freed along with every other object.
+        self.quarantine.lock().drain(|_| {});
+
+        let mut lists = self.lists();
+        let mut freed_count = 0;
+
+        let mut reset_slab = |slab: &Slab| {
+            for object in slab.allocated_objects() {
+                // Safety: Caller is required to ensure no object is referenced.
+                unsafe {
+                    self.discard_object(object, &scrub);
+                }
+            }
+
+            // Safety: Caller is required to ensure no object is referenced, nor the slab accessed concurrently.
+            freed_count += unsafe { slab.reset() };
+        };
+
+        if let Some(active) = &lists.active {
+            reset_slab(active);
+        }
+
+        for list in [SlabList::Full, SlabList::Partial] {
+            while let Some(header) = lists.list_mut(list).pop_front() {
+                // Safety: Slabs held on the lists are live.
+                let slab = unsafe { Slab::from_header(header) };
+                reset_slab(&slab);
+                lists.push_slab(SlabList::Empty, slab);
+            }
+        }
+
+        self.remaining_object_count
+            .fetch_add(freed_count, Ordering::Relaxed);
+    }
+
+    /// Marks every object allocated since a checkpoint free, given the free bitmap of each slab as of the
+    /// checkpoint by `free_bitmap_of`, or `None` for slabs created since, and calling `scrub` on each object before
+    /// it is marked free. Returns the number of objects freed.
+    ///
+    /// # Safety
+    ///
+    /// - No object allocated since the checkpoint may be referenced, nor the manager be used concurrently.
+    pub(crate) unsafe fn roll_back<'a>(
+        &self,
+        free_bitmap_of: impl Fn(&Slab) -> Option<&'a [u64]>,
+        scrub: impl Fn(NonNull<[u8]>),
+    ) -> usize {
+        let mut lists = self.lists();
+        let mut freed_count = 0;
+
+        for slab in lists.slabs() {
+            for object in slab.objects_allocated_since(free_bitmap_of(&slab)) {
+                // Safety: Caller is required to ensure objects allocated since the checkpoint aren't referenced.
+                unsafe {
+                    self.discard_object(object, &scrub);
+                }
+
+                // Safety: Object belongs to this slab, and is allocated.
+                match unsafe { slab.header().return_object(object.cast()) } {
+                    Ok(_) => freed_count += 1,
+                    Err(corruption) => (self.corruption_handler)(corruption),
+                }
+            }
+        }
+
+        // Slabs are relisted once every object is freed, as the active slab is never relisted, and empty slabs have
+        // no objects to free.
+        for list in [SlabList::Full, SlabList::Partial] {
+            let mut cursor = lists.list_mut(list).front();
+
+            while let Some(header) = cursor {
+                // Safety: Slabs held on the lists are live while the lists are locked.
+                let header = unsafe { header.as_ref() };
+                cursor = header.next();
+
+                // Safety: Slab is held by these lists.
+                unsafe {
+                    lists.relist(header);
+                }
+            }
+        }
+
+        self.remaining_object_count
+            .fetch_add(freed_count, Ordering::Relaxed);
+
+        let now = lists.clock.map(Clock::now);
+        self.release_empty_slabs(
+            &mut lists,
+            self.empty_slab_retention(),
+            usize::MAX,
+            now,
+            |_| {},
+        );
+
+        freed_count
+    }
+
+    /// Scrubs an object freed without being returned with `scrub`, then poisons it.
+    ///
+    /// # Safety
+    ///
+    /// - `object` must not be referenced.
+    unsafe fn discard_object(&self, object: NonNull<[u8]>, scrub: &impl Fn(NonNull<[u8]>)) {
+        scrub(object);
+
+        #[cfg(feature = "poison")]
+        if self.poison {
+            // Safety: Caller is required to ensure the object isn't referenced.
+            unsafe {
+                crate::slab::poison_object(object);
+            }
+        }
+    }
+
+    /// Returns an object, calling `release_slab` on any slab released as a result.
+    ///
+    /// # Safety
+    ///
+    /// - `object_ptr` must point to an allocated object taken from this slab manager, which is no longer used.
+    pub unsafe fn return_object_with(&self, object_ptr: NonNull<u8>, release_slab: impl Fn(&Slab)) {
+        // Safety: Caller is required to uphold the safety invariants.
+        unsafe {
+            self.return_objects_with(slice::from_ref(&object_ptr), release_slab);
+        }
+    }
+
+    /// Returns objects to their slabs, as with [`SlabManager::return_object`], locking the lists at most once for the
+    /// whole batch.
+    ///
+    /// # Safety
+    ///
+    /// - Every pointer of `object_ptrs` must point to a distinct allocated object taken from this slab manager, which
+    ///   is no longer used.
+    pub unsafe fn return_objects(&self, object_ptrs: &[NonNull<u8>]) {
+        // Safety: Caller is required to uphold the safety invariants.
+        unsafe {
+            self.return_objects_with(object_ptrs, |_| {});
+        }
+    }
+
+    /// Returns objects, calling `release_slab` on any slab released as a result.
+    ///
+    /// The lists are locked at most once for the whole batch.
+    ///
+    /// # Safety
+    ///
+    /// - Every pointer of `object_ptrs` must point to a distinct allocated object taken from this slab manager, which
+    ///   is no longer used.
+    pub unsafe fn return_objects_with(
+        &self,
+        object_ptrs: &[NonNull<u8>],
+        release_slab: impl Fn(&Slab),
+    ) {
+        for &object_ptr in object_ptrs {
+            debug_assert!(self.owns(object_ptr));
+
+            #[cfg(feature = "poison")]
+            if self.poison {
+                // Safety: Caller is required to ensure the object is allocated, so it is no longer referenced.
+                unsafe {
+                    crate::slab::poison_object(NonNull::slice_from_raw_parts(
+                        object_ptr,
+                        self.object_size,
+                    ));
+                }
+            }
+        }
+
+        if self.quarantine_capacity() == 0 {
+            // Safety: Caller is required to ensure objects are allocated, and originated from this slab manager.
+            unsafe {
+                self.release_objects(object_ptrs.iter().copied(), release_slab);
+            }
+
+            return;
+        }
+
+        let mut quarantine = self.quarantine.lock();
+        let evicted_object_ptrs = object_ptrs
+            .iter()
+            .filter_map(|object_ptr| quarantine.push(*object_ptr));
+
+        // Safety: Evicted objects originated from this slab manager.
+        unsafe {
+            self.release_objects(evicted_object_ptrs, release_slab);
+        }
+    }
+
+    /// Marks an object free in its slab, calling `release_slab` on any slab released as a result.
+    ///
+    /// # Safety
+    ///
+    /// - `object_ptr` must point to an allocated object that originated from this slab manager.
+    unsafe fn release_object(&self, object_ptr: NonNull<u8>, release_slab: impl Fn(&Slab)) {
+        // Safety: Caller is required to uphold the safety invariants.
+        unsafe {
+            self.release_objects([object_ptr], release_slab);
+        }
+    }
+
+    /// Marks objects free in their slabs, calling `release_slab` on any slab released as a result.
+    ///
+    /// # Safety
+    ///
+    /// - Every pointer of `object_ptrs` must point to a distinct allocated object that originated from this slab
+    ///   manager.
+    unsafe fn release_objects(
+        &self,
+        object_ptrs: impl IntoIterator<Item = NonNull<u8>>,
+        release_slab: impl Fn(&Slab),
+    ) {
+        let mut lists = None;
+        let mut released_count = 0;
+        let mut emptied_slab = false;
+
+        for object_ptr in object_ptrs {
+            let header = if self.external_headers {
+                // External headers can only be found with the lists locked, which are then kept locked for the batch.
+                let lists = lists.get_or_insert_with(|| self.lists());
+
+                let Some(header) = lists.external_header_at(self.slab_address_of(object_ptr))
+                else {
+                    (self.corruption_handler)(Corruption::ForeignPointer { object_ptr });
+                    continue;
+                };
+
+                header
+            } else {
+                // Safety: Caller is required to ensure object belongs to this slab manager, and so to one of its
+                //         slabs.
+                unsafe { SlabHeader::containing(object_ptr, self.slab_size()) }
+            };
+
+            // Safety: The slab can't be released while the object is allocated.
+            let header = unsafe { header.as_ref() };
+
+            // The slab can't be released while the object is allocated, so it must be marked before the object is
+            // returned.
+            header.begin_return();
+
+            // Safety: Object belongs to this slab.
+            match unsafe { header.return_object(object_ptr) } {
+                Ok(occupancy_changed) => {
+                    released_count += 1;
+
+                    if occupancy_changed {
+                        let lists = lists.get_or_insert_with(|| self.lists());
+
+                        // Safety: Slab belongs to this manager.
+                        unsafe {
+                            lists.relist(header);
+                        }
+
+                        emptied_slab |= header.list() == SlabList::Empty;
+                    }
+
+                    header.end_return();
+                }
+
+                Err(corruption) => {
+                    header.end_return();
+                    (self.corruption_handler)(corruption);
+                }
+            }
+        }
+
+        self.remaining_object_count
+            .fetch_add(released_count, Ordering::Relaxed);
+
+        // The lists stay locked once a slab is relisted, so slabs emptied by these frees can't be released elsewhere
+        // before the retention count is applied.
+        if emptied_slab && let Some(lists) = &mut lists {
+            let now = lists.clock.map(Clock::now);
+
+            self.release_empty_slabs(
+                lists,
+                self.empty_slab_retention(),
+                usize::MAX,
+                now,
+                release_slab,
+            );
+        }
+    }
+}
+
+impl<A: Allocator + Clone, R: RawMutex, const PAGE_SIZE: usize> SlabManager<A, R, PAGE_SIZE> {
+    /// Creates a manager for lock shard `shard` of the same size class, with the settings of this manager.
+    ///
+    /// # Errors
+    ///
+    /// - If the quarantine can't be allocated.
+    pub(crate) fn new_shard(&self, shard: u8) -> Result<Self, AllocError> {
+        let mut new_shard = Self::new_in(self.object_size, self.inner.clone());
+        new_shard.empty_slab_retention = AtomicUsize::new(self.empty_slab_retention());
+        new_shard.slab_limit = AtomicUsize::new(self.slab_limit());
+        new_shard.slab_decay = self.slab_decay;
+        new_shard.lists.get_mut().clock = self.lists().clock;
+        new_shard.lists.get_mut().wipe = self.lists().wipe;
+        new_shard.lists.get_mut().hooks = self.lists().hooks;
+        new_shard.slab_page_count = self.slab_page_count;
+        new_shard.shard = shard;
+        new_shard.lists.get_mut().metadata = self.lists().metadata;
+
+        if self.external_headers {
+            new_shard.set_external_headers();
+        }
+
+        new_shard.random = self.random;
+        new_shard.slab_selection = self.slab_selection;
+        new_shard.memory_limit = self.memory_limit;
+        new_shard.corruption_handler = self.corruption_handler;
+        new_shard.oom_handler = self.oom_handler;
+        #[cfg(feature = "poison")]
+        {
+            new_shard.poison = self.poison;
+        }
+        new_shard.set_quarantine_capacity(self.quarantine_capacity())?;
+
+        Ok(new_shard)
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> Drop for SlabManager<A, R, PAGE_SIZE> {
+    fn drop(&mut self) {
+        let lists = self.lists.get_mut();
+        let slab_count = lists.slab_count();
+
+        // Safety: The manager is being dropped, so none of its objects are used again, and every slab and the
+        //         quarantine were allocated from its backing allocator.
+        unsafe {
+            lists.destroy_all(&self.inner);
+            self.quarantine.get_mut().release_in(&self.inner);
+        }
+
+        self.release_memory(slab_count * self.slab_size());
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> core::fmt::Debug
+    for SlabManager<A, R, PAGE_SIZE>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut debug = f.debug_struct("SlabManager");
+        debug
+            .field("object_size", &self.object_size)
+            .field("slab_page_count", &self.slab_page_count)
+            .field("remaining_object_count", &self.remaining_object_count());
+
+        // The lists may be locked by the context being debugged, such as from a panic handler.
+        match self.lists.try_lock() {
+            Some(lists) => debug.field("lists", &*lists),
+            None => debug.field("lists", &format_args!("<locked>")),
+        };
+
+        debug.finish()
+    }
+}
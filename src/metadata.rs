@@ -0,0 +1,33 @@
+use crate::alloc_api::{AllocError, Allocator};
+use core::{alloc::Layout, ptr::NonNull};
+
+/// Allocator of the bookkeeping a manager grows as it creates slabs: their headers, if they are external.
+#[derive(Clone)]
+pub(crate) enum MetadataAllocator<A: Allocator> {
+    /// The backing allocator the slabs are allocated from.
+    Backing(A),
+
+    /// An allocator distinct from the backing allocator, so bookkeeping never competes with slabs for its memory.
+    Separate(&'static (dyn Allocator + Sync)),
+}
+
+// Safety: Allocations are served by the backing or separate allocator, which is never changed once memory is
+//         allocated.
+unsafe impl<A: Allocator> Allocator for MetadataAllocator<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        match self {
+            Self::Backing(allocator) => allocator.allocate(layout),
+            Self::Separate(allocator) => allocator.allocate(layout),
+        }
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Safety: Caller is required to maintain safety invariants.
+        unsafe {
+            match self {
+                Self::Backing(allocator) => allocator.deallocate(ptr, layout),
+                Self::Separate(allocator) => allocator.deallocate(ptr, layout),
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{
+    DEFAULT_PAGE_SIZE, DefaultRawMutex, SlabAllocator,
+    alloc_api::{AllocError, Allocator, Vec},
+    backing::BackingRef,
+};
+use core::{alloc::Layout, mem::ManuallyDrop, ptr::NonNull};
+use lock_api::RawMutex;
+
+/// Describes the NUMA nodes an allocator is shared between.
+pub trait NumaTopology: Sync {
+    /// Total number of nodes.
+    fn node_count(&self) -> usize;
+
+    /// Index of the node the caller is executing on.
+    ///
+    /// The returned value must always be less than [`NumaTopology::node_count`].
+    fn current_node(&self) -> usize;
+
+    /// Index of the node whose memory holds `ptr`.
+    ///
+    /// For every pointer allocated from a node's backing allocator, this must return that node.
+    fn node_of(&self, ptr: NonNull<u8>) -> usize;
+}
+
+/// A set of [`SlabAllocator`]s, one per NUMA node, each backed by node-local memory.
+///
+/// Allocations are served by the node the caller is executing on, and frees are returned to the node whose memory
+/// holds the object, so objects never migrate between nodes. Objects resized by [`Allocator::grow`] or
+/// [`Allocator::shrink`] stay on the node that holds them.
+pub struct NumaSlabAllocator<
+    A: Allocator,
+    R: RawMutex = DefaultRawMutex,
+    const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE,
+> {
+    topology: &'static dyn NumaTopology,
+
+    /// Slab allocators of each node, held in memory from node zero's backing allocator, so node zero is dropped
+    /// last.
+    nodes: ManuallyDrop<Vec<SlabAllocator<A, R, PAGE_SIZE>, BackingRef<A>>>,
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> NumaSlabAllocator<A, R, PAGE_SIZE> {
+    /// Creates an allocator with a slab allocator for each node, backed by the allocator `node_allocator` returns for
+    /// that node.
+    ///
+    /// # Panics
+    ///
+    /// - If the topology has no nodes.
+    pub fn new_in(
+        topology: &'static dyn NumaTopology,
+        mut node_allocator: impl FnMut(usize) -> A,
+    ) -> Self {
+        Self::with_nodes(topology, |node| SlabAllocator::new_in(node_allocator(node)))
+    }
+
+    /// Creates an allocator with the slab allocator `new_node` returns for each node, allowing each node's slab
+    /// allocator to be configured.
+    ///
+    /// Each node's slab allocator must be backed by memory local to that node, as reported by
+    /// [`NumaTopology::node_of`]. The nodes are held in memory from node zero's backing allocator.
+    ///
+    /// # Panics
+    ///
+    /// - If the topology has no nodes.
+    pub fn with_nodes(
+        topology: &'static dyn NumaTopology,
+        mut new_node: impl FnMut(usize) -> SlabAllocator<A, R, PAGE_SIZE>,
+    ) -> Self {
+        let node_count = topology.node_count();
+        assert!(node_count > 0);
+
+        let first_node = new_node(0);
+        let mut nodes = Vec::with_capacity_in(node_count, first_node.inner.handle());
+        nodes.push(first_node);
+        nodes.extend((1..node_count).map(new_node));
+
+        Self {
+            topology,
+            nodes: ManuallyDrop::new(nodes),
+        }
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> Drop
+    for NumaSlabAllocator<A, R, PAGE_SIZE>
+{
+    fn drop(&mut self) {
+        // Safety: The nodes aren't used again.
+        let mut nodes = unsafe { ManuallyDrop::take(&mut self.nodes) };
+
+        // Node zero holds the allocator the nodes are held in, so outlives their memory.
+        let first_node = nodes.remove(0);
+        drop(nodes);
+        drop(first_node);
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> NumaSlabAllocator<A, R, PAGE_SIZE> {
+    /// Number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The slab allocator of `node`, for inspecting or configuring it.
+    ///
+    /// # Panics
+    ///
+    /// - If `node` is not less than [`NumaSlabAllocator::node_count`].
+    pub fn node(&self, node: usize) -> &SlabAllocator<A, R, PAGE_SIZE> {
+        &self.nodes[node]
+    }
+
+    /// Releases every node's empty slabs and cached pages to their backing allocators, returning the number of bytes
+    /// released.
+    pub fn trim(&self) -> usize {
+        self.nodes.iter().map(SlabAllocator::trim).sum()
+    }
+
+    /// The slab allocator of the node the caller is executing on.
+    fn current_node(&self) -> &SlabAllocator<A, R, PAGE_SIZE> {
+        &self.nodes[self.topology.current_node()]
+    }
+
+    /// The slab allocator of the node holding `ptr`.
+    fn owning_node(&self, ptr: NonNull<u8>) -> &SlabAllocator<A, R, PAGE_SIZE> {
+        &self.nodes[self.topology.node_of(ptr)]
+    }
+}
+
+// Safety: Allocations are served by each node's `SlabAllocator`, which upholds the `Allocator` contract, and are
+//         always returned to the node that served them.
+unsafe impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> Allocator
+    for NumaSlabAllocator<A, R, PAGE_SIZE>
+{
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.current_node().allocate(layout)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.current_node().allocate_zeroed(layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: Caller is required to maintain safety invariants, and the object was allocated by its node.
+        unsafe { self.owning_node(ptr).grow(ptr, old_layout, new_layout) }
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: Caller is required to maintain safety invariants, and the object was allocated by its node.
+        unsafe {
+            self.owning_node(ptr)
+                .grow_zeroed(ptr, old_layout, new_layout)
+        }
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: Caller is required to maintain safety invariants, and the object was allocated by its node.
+        unsafe { self.owning_node(ptr).shrink(ptr, old_layout, new_layout) }
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Safety: Caller is required to maintain safety invariants, and the object was allocated by its node.
+        unsafe {
+            self.owning_node(ptr).deallocate(ptr, layout);
+        }
+    }
+}
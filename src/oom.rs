@@ -0,0 +1,30 @@
+use core::fmt;
+
+/// Failure of the backing allocator to provide memory for a size class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct OutOfMemory {
+    /// Object size of the size class the memory was requested for.
+    pub object_size: usize,
+
+    /// Number of bytes requested of the backing allocator.
+    pub size: usize,
+}
+
+impl fmt::Display for OutOfMemory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "backing allocator failed to provide {} bytes for the {}-byte size class",
+            self.size, self.object_size
+        )
+    }
+}
+
+/// Called when the backing allocator fails to provide memory for a slab or page, before the allocation fails.
+///
+/// The handler is called with the size class' lock held, so it must not allocate from or free to the allocator that
+/// called it. It may release memory held elsewhere, log the failure, or panic with its context. Failures caused by a
+/// slab limit or memory limit are not reported.
+pub type OomHandler = fn(OutOfMemory);
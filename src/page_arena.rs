@@ -0,0 +1,132 @@
+use crate::{
+    DEFAULT_PAGE_SIZE, DefaultRawMutex, PageSource,
+    alloc_api::{AllocError, Allocator, Vec},
+};
+use core::{alloc::Layout, ptr::NonNull};
+use lock_api::Mutex;
+
+/// Page source handing out the frames of an arena allocated from a backing allocator, for tests of code built on
+/// [`PageSourceAllocator`](crate::PageSourceAllocator).
+///
+/// The physical address of each frame is its offset into the arena, so the start of the arena,
+/// [`PageArena::mapping`], maps every frame while keeping the arena's provenance, and allocators over it run clean
+/// under Miri's strict provenance checks. Contiguous frames are aligned as mapped, rather than by their physical
+/// addresses.
+///
+/// ```ignore
+/// let arena = PageArena::<_>::new_in(16, Global).unwrap();
+/// let slab_allocator = unsafe { SlabAllocator::<_>::from_page_mapping(&arena, arena.mapping()) };
+/// ```
+#[derive(Debug)]
+pub struct PageArena<A: Allocator, const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE> {
+    arena: NonNull<u8>,
+    /// Whether each frame is allocated.
+    frames: Mutex<DefaultRawMutex, Vec<bool, A>>,
+}
+
+// Safety: The arena is owned, and its frames are only handed out through the lock on `frames`.
+unsafe impl<A: Allocator + Send, const PAGE_SIZE: usize> Send for PageArena<A, PAGE_SIZE> {}
+
+// Safety: The arena is owned, and its frames are only handed out through the lock on `frames`.
+unsafe impl<A: Allocator + Sync, const PAGE_SIZE: usize> Sync for PageArena<A, PAGE_SIZE> {}
+
+impl<A: Allocator, const PAGE_SIZE: usize> PageArena<A, PAGE_SIZE> {
+    /// Allocates an arena of `frame_count` frames from `allocator`, with every frame free.
+    ///
+    /// # Errors
+    ///
+    /// - If the arena, or the table of its frames, can't be allocated.
+    pub fn new_in(frame_count: usize, allocator: A) -> Result<Self, AllocError> {
+        let layout = Self::layout(frame_count)?;
+        let arena = allocator.allocate(layout)?.cast::<u8>();
+
+        let mut frames = Vec::new_in(allocator);
+        if frames.try_reserve_exact(frame_count).is_err() {
+            // Safety: Arena was just allocated with `layout`, and is unused.
+            unsafe {
+                frames.allocator().deallocate(arena, layout);
+            }
+
+            return Err(AllocError);
+        }
+
+        frames.resize(frame_count, false);
+
+        Ok(Self {
+            arena,
+            frames: Mutex::new(frames),
+        })
+    }
+
+    fn layout(frame_count: usize) -> Result<Layout, AllocError> {
+        frame_count
+            .checked_mul(PAGE_SIZE)
+            .and_then(|size| Layout::from_size_align(size, PAGE_SIZE).ok())
+            .ok_or(AllocError)
+    }
+
+    /// Start of the arena, at which the frame of physical address zero is mapped.
+    pub fn mapping(&self) -> NonNull<u8> {
+        self.arena
+    }
+
+    /// Number of frames in the arena.
+    pub fn frame_count(&self) -> usize {
+        self.frames.lock().len()
+    }
+
+    /// Number of frames currently allocated.
+    pub fn allocated_frame_count(&self) -> usize {
+        self.frames
+            .lock()
+            .iter()
+            .filter(|is_allocated| **is_allocated)
+            .count()
+    }
+}
+
+impl<A: Allocator, const PAGE_SIZE: usize> PageSource for PageArena<A, PAGE_SIZE> {
+    fn allocate_frame(&self) -> Option<usize> {
+        self.allocate_contiguous_frames(1, PAGE_SIZE)
+    }
+
+    unsafe fn free_frame(&self, address: usize) {
+        // Safety: Caller is required to maintain safety invariants.
+        unsafe {
+            self.free_contiguous_frames(address, 1);
+        }
+    }
+
+    fn allocate_contiguous_frames(&self, frame_count: usize, align: usize) -> Option<usize> {
+        let mut frames = self.frames.lock();
+
+        let first_index = (0..=frames.len().checked_sub(frame_count)?)
+            .filter(|index| (self.arena.addr().get() + (index * PAGE_SIZE)).is_multiple_of(align))
+            .find(|index| !frames[*index..][..frame_count].contains(&true))?;
+
+        frames[first_index..][..frame_count].fill(true);
+
+        Some(first_index * PAGE_SIZE)
+    }
+
+    unsafe fn free_contiguous_frames(&self, address: usize, frame_count: usize) {
+        let mut frames = self.frames.lock();
+        let frames = &mut frames[(address / PAGE_SIZE)..][..frame_count];
+
+        debug_assert!(frames.iter().all(|is_allocated| *is_allocated));
+        frames.fill(false);
+    }
+}
+
+impl<A: Allocator, const PAGE_SIZE: usize> Drop for PageArena<A, PAGE_SIZE> {
+    fn drop(&mut self) {
+        let frames = self.frames.get_mut();
+
+        // Safety: Arena was allocated with the layout of its frames, which are no longer handed out.
+        unsafe {
+            frames
+                .allocator()
+                .deallocate(self.arena, Self::layout(frames.len()).unwrap());
+        }
+    }
+}
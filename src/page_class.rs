@@ -0,0 +1,330 @@
+use crate::{
+    LeakHandler, MemoryLimit, OomHandler, OutOfMemory, SizeClassStats, TryAllocError,
+    alloc_api::{AllocError, Allocator, Vec},
+    leak,
+    slab::{self, DEFAULT_PAGE_SIZE, is_valid_page_size},
+    stats::Counters,
+};
+use core::{
+    alloc::Layout,
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use lock_api::{Mutex, RawMutex};
+
+/// Maximum number of free pages kept by the page size class by default.
+pub const DEFAULT_PAGE_CACHE_CAPACITY: usize = 64;
+
+/// Free pages kept for reuse.
+struct FreePages<A: Allocator> {
+    pages: Vec<NonNull<u8>, A>,
+}
+
+// Safety: Free pages aren't referenced by anything else.
+unsafe impl<A: Allocator + Send> Send for FreePages<A> {}
+
+/// Size class serving whole pages, which recycles freed pages rather than returning them to the backing allocator.
+///
+/// Pages can't hold an in-page slab header, so each page is handed out whole, and freed pages are kept in a cache of
+/// bounded capacity.
+pub(crate) struct PageClass<A: Allocator, R: RawMutex, const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE> {
+    free_pages: Mutex<R, FreePages<A>>,
+    capacity: AtomicUsize,
+
+    /// Pages currently held, whether allocated or cached.
+    page_count: AtomicUsize,
+    page_creation_count: AtomicUsize,
+    peak_page_count: AtomicUsize,
+    counters: Counters,
+    memory_limit: Option<&'static MemoryLimit>,
+    oom_handler: Option<OomHandler>,
+    leak_handler: Option<LeakHandler>,
+    wipe: bool,
+    inner: A,
+}
+
+impl<A: Allocator + Clone, R: RawMutex, const PAGE_SIZE: usize> PageClass<A, R, PAGE_SIZE> {
+    pub fn new_in(allocator: A) -> Self {
+        Self::with_cache_allocator_in(allocator.clone(), allocator)
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> PageClass<A, R, PAGE_SIZE> {
+    /// Creates a page size class whose cache of free pages grows in `cache_allocator`, which is a copy of the backing
+    /// `allocator`, so it can be created in a const context.
+    pub const fn with_cache_allocator_in(cache_allocator: A, allocator: A) -> Self {
+        const { assert!(is_valid_page_size(PAGE_SIZE)) };
+
+        Self {
+            free_pages: Mutex::new(FreePages {
+                pages: Vec::new_in(cache_allocator),
+            }),
+            capacity: AtomicUsize::new(DEFAULT_PAGE_CACHE_CAPACITY),
+            page_count: AtomicUsize::new(0),
+            page_creation_count: AtomicUsize::new(0),
+            peak_page_count: AtomicUsize::new(0),
+            counters: Counters::new(),
+            memory_limit: None,
+            oom_handler: None,
+            leak_handler: None,
+            wipe: false,
+            inner: allocator,
+        }
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> PageClass<A, R, PAGE_SIZE> {
+    // Safety: Layout is known to be valid.
+    const LAYOUT: Layout = unsafe { Layout::from_size_align_unchecked(PAGE_SIZE, PAGE_SIZE) };
+
+    /// Counts every page against `limit`, failing to take pages that would exceed it.
+    pub fn set_memory_limit(&mut self, limit: &'static MemoryLimit) {
+        self.memory_limit = Some(limit);
+    }
+
+    /// Reports failures of the backing allocator to provide a page to `handler`.
+    pub fn set_oom_handler(&mut self, handler: OomHandler) {
+        self.oom_handler = Some(handler);
+    }
+
+    /// Reports pages still allocated as the page size class is dropped to `handler`.
+    pub fn set_leak_handler(&mut self, handler: LeakHandler) {
+        self.leak_handler = Some(handler);
+    }
+
+    /// Zeroes every page before it is released to the backing allocator.
+    pub fn set_secure_wipe(&mut self) {
+        self.wipe = true;
+    }
+
+    pub fn leak_handler(&self) -> Option<LeakHandler> {
+        self.leak_handler
+    }
+
+    /// Takes a page, reusing a cached page if there is one.
+    pub fn allocate(&self, zeroed: bool) -> Result<NonNull<[u8]>, AllocError> {
+        let cached_page = self.free_pages.lock().pages.pop();
+        self.allocate_from(cached_page, zeroed)
+    }
+
+    /// Takes a page without waiting on the cache's lock, reusing a cached page if there is one.
+    pub fn try_allocate(&self, zeroed: bool) -> Result<NonNull<[u8]>, TryAllocError> {
+        let cached_page = self
+            .free_pages
+            .try_lock()
+            .ok_or(TryAllocError::WouldBlock)?
+            .pages
+            .pop();
+
+        Ok(self.allocate_from(cached_page, zeroed)?)
+    }
+
+    /// Takes `cached_page`, or a new page if there is none.
+    fn allocate_from(
+        &self,
+        cached_page: Option<NonNull<u8>>,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let page = match cached_page {
+            Some(page) => {
+                if zeroed {
+                    // Safety: Page was just taken from the cache, and so isn't referenced.
+                    unsafe {
+                        page.write_bytes(0, PAGE_SIZE);
+                    }
+                }
+
+                NonNull::slice_from_raw_parts(page, PAGE_SIZE)
+            }
+
+            None => {
+                if let Some(memory_limit) = self.memory_limit {
+                    memory_limit.acquire(PAGE_SIZE)?;
+                }
+
+                let page = if zeroed {
+                    self.inner.allocate_zeroed(Self::LAYOUT)
+                } else {
+                    self.inner.allocate(Self::LAYOUT)
+                }
+                .inspect_err(|_| {
+                    self.release_memory();
+
+                    if let Some(oom_handler) = self.oom_handler {
+                        oom_handler(OutOfMemory {
+                            object_size: PAGE_SIZE,
+                            size: PAGE_SIZE,
+                        });
+                    }
+                })?;
+
+                let page_count = self.page_count.fetch_add(1, Ordering::Relaxed) + 1;
+                self.page_creation_count.fetch_add(1, Ordering::Relaxed);
+                self.peak_page_count
+                    .fetch_max(page_count, Ordering::Relaxed);
+
+                page
+            }
+        };
+
+        self.counters.record_allocation();
+
+        Ok(page)
+    }
+
+    /// Returns a page, caching it for reuse if the cache has room.
+    ///
+    /// # Safety
+    ///
+    /// - `page` must point to a page that originated from this size class.
+    pub unsafe fn deallocate(&self, page: NonNull<u8>) {
+        self.counters.record_deallocations(1);
+
+        // Safety: Caller is required to ensure the page is allocated, so it is no longer referenced.
+        #[cfg(feature = "zeroize-on-free")]
+        unsafe {
+            page.write_bytes(0, PAGE_SIZE);
+        }
+
+        {
+            let mut free_pages = self.free_pages.lock();
+
+            if free_pages.pages.len() < self.capacity() && free_pages.pages.try_reserve(1).is_ok() {
+                free_pages.pages.push(page);
+                return;
+            }
+        }
+
+        // Safety: Caller is required to ensure the page originated from this size class, and so from `self.inner`.
+        unsafe {
+            self.release_page(page);
+        }
+    }
+
+    /// Maximum number of free pages kept for reuse.
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Sets the maximum number of free pages kept for reuse, releasing any cached pages beyond it.
+    pub fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        self.release_cached_pages(capacity);
+    }
+
+    /// Number of free pages currently kept for reuse.
+    pub fn cached_page_count(&self) -> usize {
+        self.free_pages.lock().pages.len()
+    }
+
+    /// Number of bytes allocated for the page cache.
+    pub fn metadata_bytes(&self) -> usize {
+        self.free_pages.lock().pages.capacity() * size_of::<NonNull<u8>>()
+    }
+
+    /// Releases every cached page to the backing allocator, returning the number of bytes released.
+    pub fn trim(&self) -> usize {
+        self.release_cached_pages(0) * PAGE_SIZE
+    }
+
+    /// Releases cached pages to the backing allocator until at least `target_bytes` have been released, or none are
+    /// left, returning the number of bytes released.
+    pub fn reclaim(&self, target_bytes: usize) -> usize {
+        let released_count = target_bytes.div_ceil(PAGE_SIZE);
+        let retained_count = self.cached_page_count().saturating_sub(released_count);
+
+        self.release_cached_pages(retained_count) * PAGE_SIZE
+    }
+
+    /// Records `wasted_bytes` lost to rounding allocations up to a page.
+    pub fn record_waste(&self, wasted_bytes: usize) {
+        self.counters.record_waste(wasted_bytes);
+    }
+
+    /// Zeroes the cumulative counters, and lowers the peaks to the current number of pages.
+    pub fn reset_stats(&self) {
+        self.counters.reset();
+        self.page_creation_count.store(0, Ordering::Relaxed);
+        self.peak_page_count
+            .store(self.page_count.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> SizeClassStats {
+        let page_count = self.page_count.load(Ordering::Relaxed);
+
+        SizeClassStats {
+            object_size: PAGE_SIZE,
+            live_object_count: page_count.saturating_sub(self.cached_page_count()),
+            object_capacity: page_count,
+            slab_count: page_count,
+            page_count,
+            allocation_count: self.counters.allocation_count(),
+            deallocation_count: self.counters.deallocation_count(),
+            wasted_bytes: self.counters.wasted_bytes(),
+            peak_live_object_count: self.counters.peak_live_object_count(),
+            peak_page_count: self.peak_page_count.load(Ordering::Relaxed),
+            slab_creation_count: self.page_creation_count.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Releases cached pages until at most `retained_count` remain, returning the number of pages released.
+    fn release_cached_pages(&self, retained_count: usize) -> usize {
+        let mut free_pages = self.free_pages.lock();
+        let retained_count = retained_count.min(free_pages.pages.len());
+        let released_count = free_pages.pages.len() - retained_count;
+
+        for page in free_pages.pages.drain(retained_count..) {
+            // Safety: Cached pages originated from this size class, and aren't referenced.
+            unsafe {
+                self.release_page(page);
+            }
+        }
+
+        released_count
+    }
+
+    /// # Safety
+    ///
+    /// - `page` must point to a free page that originated from this size class.
+    unsafe fn release_page(&self, page: NonNull<u8>) {
+        self.page_count.fetch_sub(1, Ordering::Relaxed);
+        self.release_memory();
+
+        // Safety: Caller is required to ensure the page was allocated by `self.inner` with the page layout, and is
+        //         free.
+        unsafe {
+            if self.wipe {
+                slab::wipe(NonNull::slice_from_raw_parts(page, PAGE_SIZE));
+            }
+
+            self.inner.deallocate(page, Self::LAYOUT);
+        }
+    }
+
+    /// Uncounts a released page from the memory limit, if there is one.
+    fn release_memory(&self) {
+        if let Some(memory_limit) = self.memory_limit {
+            memory_limit.release(PAGE_SIZE);
+        }
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> Drop for PageClass<A, R, PAGE_SIZE> {
+    fn drop(&mut self) {
+        self.trim();
+
+        let page_count = self.page_count.load(Ordering::Relaxed);
+        leak::report_leak(self.leak_handler, PAGE_SIZE, page_count);
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> core::fmt::Debug
+    for PageClass<A, R, PAGE_SIZE>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PageClass")
+            .field("page_count", &self.page_count.load(Ordering::Relaxed))
+            .field("capacity", &self.capacity())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{
+    DEFAULT_PAGE_SIZE,
+    alloc_api::{AllocError, Allocator},
+};
+use core::{alloc::Layout, ptr::NonNull};
+
+/// Source of physical page frames, such as a kernel's frame allocator.
+///
+/// Frames are `PAGE_SIZE` bytes, matching the page size of the [`PageSourceAllocator`] they back, and are identified
+/// by their physical address.
+pub trait PageSource {
+    /// Allocates a single frame, returning its physical address, or `None` if no frames are left.
+    fn allocate_frame(&self) -> Option<usize>;
+
+    /// Frees a frame allocated by [`PageSource::allocate_frame`].
+    ///
+    /// # Safety
+    ///
+    /// - `address` must be the physical address of a frame allocated by [`PageSource::allocate_frame`], which is no
+    ///   longer in use.
+    unsafe fn free_frame(&self, address: usize);
+
+    /// Allocates `frame_count` physically contiguous frames, the first aligned to `align` bytes, returning the
+    /// physical address of the first frame.
+    ///
+    /// Sources that can't allocate contiguous frames needn't implement this, in which case only allocations of a
+    /// single frame are served, and slabs can only span a single page.
+    fn allocate_contiguous_frames(&self, frame_count: usize, align: usize) -> Option<usize> {
+        let _ = (frame_count, align);
+        None
+    }
+
+    /// Frees frames allocated by [`PageSource::allocate_contiguous_frames`].
+    ///
+    /// # Safety
+    ///
+    /// - `address` and `frame_count` must describe frames allocated by [`PageSource::allocate_contiguous_frames`],
+    ///   which are no longer in use.
+    unsafe fn free_contiguous_frames(&self, address: usize, frame_count: usize) {
+        let _ = (address, frame_count);
+        unreachable!("contiguous frames are never allocated by default");
+    }
+}
+
+impl<S: PageSource + ?Sized> PageSource for &S {
+    fn allocate_frame(&self) -> Option<usize> {
+        (**self).allocate_frame()
+    }
+
+    unsafe fn free_frame(&self, address: usize) {
+        // Safety: Caller is required to maintain safety invariants.
+        unsafe {
+            (**self).free_frame(address);
+        }
+    }
+
+    fn allocate_contiguous_frames(&self, frame_count: usize, align: usize) -> Option<usize> {
+        (**self).allocate_contiguous_frames(frame_count, align)
+    }
+
+    unsafe fn free_contiguous_frames(&self, address: usize, frame_count: usize) {
+        // Safety: Caller is required to maintain safety invariants.
+        unsafe {
+            (**self).free_contiguous_frames(address, frame_count);
+        }
+    }
+}
+
+/// How frames are reached from their physical addresses.
+#[derive(Debug, Clone, Copy)]
+enum FrameMapping {
+    /// At a fixed offset above their physical addresses, through provenance exposed by whatever mapped them.
+    Offset(usize),
+
+    /// At their physical addresses as offsets from the start of a mapping, keeping the mapping's provenance.
+    Base(NonNull<u8>),
+}
+
+/// Backing allocator serving page-granular allocations from a [`PageSource`].
+///
+/// Frames are accessed through a virtual mapping of their physical addresses, either at a fixed offset from them, such
+/// as a higher-half direct map, or from the pointer to the start of the mapping. Pointers made from an offset take
+/// the provenance exposed by whatever mapped the frames, so only allocators created with
+/// [`PageSourceAllocator::with_mapping`] keep strict provenance. Every allocation is rounded up to whole frames;
+/// allocations of more than one frame, or aligned to more than a frame, require contiguous frames from the source.
+#[derive(Debug, Clone)]
+pub struct PageSourceAllocator<S: PageSource, const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE> {
+    source: S,
+    mapping: FrameMapping,
+}
+
+// Safety: The mapping's pointer is only offset to reach the frames allocated from the source, which are distinct.
+unsafe impl<S: PageSource + Send, const PAGE_SIZE: usize> Send
+    for PageSourceAllocator<S, PAGE_SIZE>
+{
+}
+
+// Safety: The mapping's pointer is only offset to reach the frames allocated from the source, which are distinct.
+unsafe impl<S: PageSource + Sync, const PAGE_SIZE: usize> Sync
+    for PageSourceAllocator<S, PAGE_SIZE>
+{
+}
+
+impl<S: PageSource, const PAGE_SIZE: usize> PageSourceAllocator<S, PAGE_SIZE> {
+    /// Creates an allocator over the frames of `source`, which are mapped at `virtual_offset` bytes above their
+    /// physical addresses.
+    ///
+    /// # Safety
+    ///
+    /// - Every frame `source` allocates must be mapped, readable and writable, at `virtual_offset` bytes above its
+    ///   physical address, for as long as it is allocated, and the mapping's provenance must be exposed.
+    pub const unsafe fn new(source: S, virtual_offset: usize) -> Self {
+        Self {
+            source,
+            mapping: FrameMapping::Offset(virtual_offset),
+        }
+    }
+
+    /// Creates an allocator over the frames of `source`, which are mapped at their physical addresses as offsets from
+    /// `mapping`.
+    ///
+    /// Allocations whose frames aren't aligned as requested once mapped, as `mapping` is less aligned than the frames'
+    /// physical addresses, fail.
+    ///
+    /// # Safety
+    ///
+    /// - Every frame `source` allocates must be readable and writable through `mapping`, at its physical address as
+    ///   an offset from it, for as long as it is allocated.
+    pub const unsafe fn with_mapping(source: S, mapping: NonNull<u8>) -> Self {
+        Self {
+            source,
+            mapping: FrameMapping::Base(mapping),
+        }
+    }
+
+    /// The source of this allocator's frames.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Number of frames backing an allocation of `layout`.
+    fn frame_count_of(layout: Layout) -> usize {
+        layout.size().div_ceil(PAGE_SIZE).max(1)
+    }
+
+    /// Frees the `frame_count` frames at `physical_address`, allocated for `layout`.
+    ///
+    /// # Safety
+    ///
+    /// - The frames must have been allocated from the source for an allocation of `layout`, and be unused.
+    unsafe fn free_frames(&self, physical_address: usize, frame_count: usize, layout: Layout) {
+        // Safety: Caller is required to ensure the frames were allocated in the way they're freed.
+        unsafe {
+            if frame_count == 1 && layout.align() <= PAGE_SIZE {
+                self.source.free_frame(physical_address);
+            } else {
+                self.source
+                    .free_contiguous_frames(physical_address, frame_count);
+            }
+        }
+    }
+
+    /// Pointer to the frame at `physical_address`.
+    fn frame_ptr(&self, physical_address: usize) -> *mut u8 {
+        match self.mapping {
+            FrameMapping::Offset(virtual_offset) => core::ptr::with_exposed_provenance_mut(
+                physical_address.wrapping_add(virtual_offset),
+            ),
+            FrameMapping::Base(mapping) => mapping.as_ptr().wrapping_add(physical_address),
+        }
+    }
+
+    /// Physical address of the frame at `ptr`.
+    fn physical_address_of(&self, ptr: NonNull<u8>) -> usize {
+        match self.mapping {
+            FrameMapping::Offset(virtual_offset) => ptr.addr().get().wrapping_sub(virtual_offset),
+            FrameMapping::Base(mapping) => ptr.addr().get().wrapping_sub(mapping.addr().get()),
+        }
+    }
+}
+
+// Safety: Frames are distinct and stay mapped while allocated, as required by `PageSourceAllocator::new` and
+//         `PageSourceAllocator::with_mapping`.
+unsafe impl<S: PageSource, const PAGE_SIZE: usize> Allocator for PageSourceAllocator<S, PAGE_SIZE> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let frame_count = Self::frame_count_of(layout);
+
+        let physical_address = if frame_count == 1 && layout.align() <= PAGE_SIZE {
+            self.source.allocate_frame()
+        } else {
+            self.source
+                .allocate_contiguous_frames(frame_count, layout.align().max(PAGE_SIZE))
+        }
+        .ok_or(AllocError)?;
+
+        let ptr = NonNull::new(self.frame_ptr(physical_address))
+            .filter(|ptr| ptr.addr().get().is_multiple_of(layout.align()));
+
+        let Some(ptr) = ptr else {
+            // Safety: Frames were just allocated from the source in the same way, and are unused.
+            unsafe {
+                self.free_frames(physical_address, frame_count, layout);
+            }
+
+            return Err(AllocError);
+        };
+
+        Ok(NonNull::slice_from_raw_parts(ptr, frame_count * PAGE_SIZE))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Safety: Caller is required to ensure `ptr` was allocated by this allocator with `layout`, so its frames
+        //         were allocated from the source in the same way.
+        unsafe {
+            self.free_frames(
+                self.physical_address_of(ptr),
+                Self::frame_count_of(layout),
+                layout,
+            );
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::alloc_api::{AllocError, Allocator};
+use core::{alloc::Layout, ptr::NonNull};
+
+/// Ring of recently freed objects, held back from reuse until they are evicted by later frees.
+///
+/// The ring is allocated from the backing allocator of its manager, which is passed in whenever the ring is resized
+/// or released, so the manager holds the only copy of it.
+pub(crate) struct Quarantine {
+    objects: NonNull<NonNull<u8>>,
+    len: usize,
+    capacity: usize,
+
+    /// Index of the oldest object, once the ring is full.
+    oldest_index: usize,
+}
+
+// Safety: Quarantined objects aren't referenced by anything else.
+unsafe impl Send for Quarantine {}
+
+impl Quarantine {
+    pub const fn new() -> Self {
+        Self {
+            objects: NonNull::dangling(),
+            len: 0,
+            capacity: 0,
+            oldest_index: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Number of bytes allocated to hold objects.
+    pub fn metadata_bytes(&self) -> usize {
+        self.capacity * size_of::<NonNull<u8>>()
+    }
+
+    /// Sets how many objects are held, first evicting every held object to `evict`.
+    ///
+    /// # Safety
+    ///
+    /// - `allocator` must be the allocator the ring was last resized with, if any.
+    pub unsafe fn set_capacity(
+        &mut self,
+        capacity: usize,
+        evict: impl FnMut(NonNull<u8>),
+        allocator: &impl Allocator,
+    ) -> Result<(), AllocError> {
+        self.drain(evict);
+
+        // Safety: Caller is required to ensure the ring was allocated from `allocator`.
+        unsafe {
+            self.release_in(allocator);
+        }
+
+        if capacity == 0 {
+            return Ok(());
+        }
+
+        // Allocating up-front ensures holding objects never allocates. If it fails, nothing is held.
+        let layout = Layout::array::<NonNull<u8>>(capacity).map_err(|_| AllocError)?;
+        self.objects = allocator.allocate(layout)?.cast();
+        self.capacity = capacity;
+
+        Ok(())
+    }
+
+    /// Holds `object_ptr`, returning the object it evicts, if any.
+    pub fn push(&mut self, object_ptr: NonNull<u8>) -> Option<NonNull<u8>> {
+        if self.capacity == 0 {
+            Some(object_ptr)
+        } else if self.len < self.capacity {
+            // Safety: The ring holds `capacity` objects, of which `len` are held.
+            unsafe {
+                self.objects.add(self.len).write(object_ptr);
+            }
+            self.len += 1;
+
+            None
+        } else {
+            // Safety: The ring is full, and the oldest index is always less than its capacity.
+            let evicted_ptr = unsafe { self.objects.add(self.oldest_index).replace(object_ptr) };
+            self.oldest_index = (self.oldest_index + 1) % self.capacity;
+
+            Some(evicted_ptr)
+        }
+    }
+
+    /// Evicts every held object to `evict`.
+    pub fn drain(&mut self, mut evict: impl FnMut(NonNull<u8>)) {
+        for index in 0..core::mem::take(&mut self.len) {
+            // Safety: The first `len` objects of the ring are held.
+            evict(unsafe { self.objects.add(index).read() });
+        }
+
+        self.oldest_index = 0;
+    }
+
+    /// Frees the ring, discarding any held objects.
+    ///
+    /// # Safety
+    ///
+    /// - `allocator` must be the allocator the ring was last resized with, if any.
+    pub unsafe fn release_in(&mut self, allocator: &impl Allocator) {
+        if self.capacity > 0 {
+            // Safety: Caller is required to ensure the ring was allocated from `allocator`, with the layout of its
+            //         capacity.
+            unsafe {
+                allocator.deallocate(
+                    self.objects.cast(),
+                    Layout::array::<NonNull<u8>>(self.capacity).unwrap_unchecked(),
+                );
+            }
+        }
+
+        *self = Self::new();
+    }
+}
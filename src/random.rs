@@ -0,0 +1,7 @@
+/// Source of random numbers, used to randomize the order objects are handed out in.
+///
+/// Values need not be cryptographically secure, but predictable values make heap layouts predictable.
+pub trait RandomSource: Sync {
+    /// Returns the next random value.
+    fn next_u64(&self) -> u64;
+}
@@ -0,0 +1,44 @@
+use crate::{SlabAllocator, alloc_api::Allocator};
+use lock_api::RawMutex;
+
+/// Allocator able to give memory back to its backing allocator on demand, such as when a kernel's memory-pressure
+/// subsystem asks every registered allocator to shrink.
+///
+/// ```ignore
+/// static RECLAIMERS: [&(dyn MemoryReclaim + Sync); 2] = [&KERNEL_HEAP, &DMA_HEAP];
+///
+/// fn on_memory_pressure(mut needed_bytes: usize) {
+///     for reclaimer in RECLAIMERS {
+///         needed_bytes = needed_bytes.saturating_sub(reclaimer.reclaim(needed_bytes));
+///     }
+/// }
+/// ```
+pub trait MemoryReclaim {
+    /// Releases memory that isn't in use until at least `target_bytes` have been released, or nothing more can be,
+    /// returning the number of bytes released.
+    ///
+    /// More than `target_bytes` may be released, as memory is released in whole slabs or pages.
+    fn reclaim(&self, target_bytes: usize) -> usize;
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator> MemoryReclaim
+    for SlabAllocator<A, R, PAGE_SIZE, F>
+{
+    /// Releases cached pages, then empty slabs of each size class in turn, until `target_bytes` have been released.
+    ///
+    /// Objects cached in a size class' magazine layer or quarantine are flushed to its slabs before its slabs are
+    /// released, so they don't keep slabs alive.
+    fn reclaim(&self, target_bytes: usize) -> usize {
+        let mut released_bytes = self.pages.reclaim(target_bytes);
+
+        for size_class in self.size_class_chunks().iter().flatten() {
+            if released_bytes >= target_bytes {
+                break;
+            }
+
+            released_bytes += size_class.reclaim(target_bytes - released_bytes);
+        }
+
+        released_bytes
+    }
+}
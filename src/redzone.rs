@@ -0,0 +1,89 @@
+use crate::Corruption;
+use core::{alloc::Layout, cmp::max, num::NonZero, ptr::NonNull};
+
+/// Minimum number of canary bytes on either side of an object.
+pub const REDZONE_SIZE: usize = 16;
+
+/// Byte that redzones are filled with.
+pub const REDZONE_BYTE: u8 = 0xCC;
+
+/// Offset of an object of `layout` within its slot, keeping the object aligned.
+fn leading_size(layout: Layout) -> usize {
+    max(REDZONE_SIZE, layout.align())
+}
+
+/// Size of the slot needed to hold an object of `layout` between its redzones.
+pub fn slot_size(layout: Layout) -> usize {
+    leading_size(layout) + layout.size() + REDZONE_SIZE
+}
+
+/// The slot holding an object of `layout`, assuming it was returned by [`guard`].
+pub fn slot_of(object_ptr: NonNull<u8>, layout: Layout) -> NonNull<u8> {
+    object_ptr.map_addr(|address| {
+        NonZero::new(address.get().wrapping_sub(leading_size(layout))).unwrap_or(address)
+    })
+}
+
+/// Fills the redzones of a freshly allocated slot, returning the object held within.
+///
+/// Every byte of the slot outside of the object is a canary, so even a single byte of overflow is detected.
+///
+/// # Safety
+///
+/// - `slot` must be an unreferenced allocation of at least [`slot_size`] bytes.
+pub unsafe fn guard(slot: NonNull<[u8]>, layout: Layout) -> NonNull<[u8]> {
+    let leading_size = leading_size(layout);
+    let trailing_size = slot.len() - leading_size - layout.size();
+
+    // Safety: Caller is required to ensure the slot is large enough to hold the object and its redzones.
+    unsafe {
+        let slot_ptr = slot.cast::<u8>();
+        let object_ptr = slot_ptr.add(leading_size);
+
+        slot_ptr.write_bytes(REDZONE_BYTE, leading_size);
+        object_ptr
+            .add(layout.size())
+            .write_bytes(REDZONE_BYTE, trailing_size);
+
+        NonNull::slice_from_raw_parts(object_ptr, layout.size())
+    }
+}
+
+/// Verifies the redzones around an object are intact, returning the slot holding it.
+///
+/// # Safety
+///
+/// - `object_ptr` must have been returned by [`guard`] with the same `layout` and `slot_size`.
+///
+/// # Errors
+///
+/// - [`Corruption::RedzoneOverwritten`] if either redzone has been written to.
+pub unsafe fn check(
+    object_ptr: NonNull<u8>,
+    slot_size: usize,
+    layout: Layout,
+) -> Result<NonNull<u8>, Corruption> {
+    let leading_size = leading_size(layout);
+    let trailing_size = slot_size - leading_size - layout.size();
+
+    // Safety: Caller is required to ensure the object lies `leading_size` bytes into its slot.
+    let slot_ptr = unsafe { object_ptr.sub(leading_size) };
+
+    // Safety: Redzones were initialized by `guard`, and lie within the slot.
+    let (leading, trailing) = unsafe {
+        (
+            NonNull::slice_from_raw_parts(slot_ptr, leading_size).as_ref(),
+            NonNull::slice_from_raw_parts(object_ptr.add(layout.size()), trailing_size).as_ref(),
+        )
+    };
+
+    if leading
+        .iter()
+        .chain(trailing)
+        .all(|byte| *byte == REDZONE_BYTE)
+    {
+        Ok(slot_ptr)
+    } else {
+        Err(Corruption::RedzoneOverwritten { object_ptr })
+    }
+}
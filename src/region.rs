@@ -0,0 +1,224 @@
+use crate::{
+    DEFAULT_PAGE_SIZE, DefaultRawMutex,
+    alloc_api::{AllocError, Allocator},
+};
+use core::{alloc::Layout, cmp::max, ptr::NonNull, slice};
+use lock_api::{Mutex, RawMutex};
+
+/// Pages of a region, tracked by a bitmap with a bit set for every allocated page.
+struct RegionPages {
+    bitmap: NonNull<u64>,
+    first_page: NonNull<u8>,
+    page_count: usize,
+    free_page_count: usize,
+}
+
+// Safety: The bitmap and pages are only accessed through the region's lock.
+unsafe impl Send for RegionPages {}
+
+impl RegionPages {
+    fn bitmap(&mut self) -> &mut [u64] {
+        // Safety: The bitmap covers every page, and is only accessed with the region locked.
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.bitmap.as_ptr(),
+                self.page_count.div_ceil(u64::BITS as usize),
+            )
+        }
+    }
+
+    fn is_allocated(&mut self, page_index: usize) -> bool {
+        (self.bitmap()[page_index / u64::BITS as usize] & (1 << (page_index % u64::BITS as usize)))
+            > 0
+    }
+
+    fn set_allocated(&mut self, page_indices: core::ops::Range<usize>, allocated: bool) {
+        let bitmap = self.bitmap();
+
+        for page_index in page_indices {
+            let word = &mut bitmap[page_index / u64::BITS as usize];
+            let page_bit = 1 << (page_index % u64::BITS as usize);
+
+            if allocated {
+                *word |= page_bit;
+            } else {
+                *word &= !page_bit;
+            }
+        }
+    }
+
+    /// Finds the first run of `page_count` free pages whose first page is aligned to `align`, marking it allocated.
+    fn allocate(
+        &mut self,
+        page_count: usize,
+        page_size: usize,
+        align: usize,
+    ) -> Option<NonNull<u8>> {
+        let align_page_count = max(align / page_size, 1);
+        let first_page_index = self.first_page.addr().get() / page_size;
+
+        // Index of the first page aligned to `align`. Every aligned page follows it at a multiple of the alignment.
+        let aligned_index = first_page_index.next_multiple_of(align_page_count) - first_page_index;
+        let mut page_index = aligned_index;
+
+        while page_index + page_count <= self.page_count {
+            let page_indices = page_index..(page_index + page_count);
+
+            if let Some(allocated_index) = page_indices
+                .clone()
+                .rev()
+                .find(|page_index| self.is_allocated(*page_index))
+            {
+                // Skip past the allocated page, to the next aligned page.
+                page_index = aligned_index
+                    + (allocated_index + 1 - aligned_index).next_multiple_of(align_page_count);
+                continue;
+            }
+
+            self.set_allocated(page_indices, true);
+            self.free_page_count -= page_count;
+
+            // Safety: Page lies within the region.
+            return Some(unsafe { self.first_page.add(page_index * page_size) });
+        }
+
+        None
+    }
+}
+
+/// Shared state of a region, stored at its start.
+struct RegionState<R: RawMutex> {
+    pages: Mutex<R, RegionPages>,
+}
+
+/// Backing allocator carving page-granular allocations out of a fixed memory region.
+///
+/// This allows a [`SlabAllocator`](crate::SlabAllocator) to be used without any other allocator, such as over the
+/// fixed RAM window of embedded systems, or during early boot. Every allocation is rounded up to whole pages of
+/// `PAGE_SIZE` bytes. The region's bookkeeping is kept at its start, and clones of the allocator share the region.
+pub struct RegionAllocator<
+    R: RawMutex = DefaultRawMutex,
+    const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE,
+> {
+    state: NonNull<RegionState<R>>,
+}
+
+impl<R: RawMutex, const PAGE_SIZE: usize> RegionAllocator<R, PAGE_SIZE> {
+    /// Creates an allocator over `region`.
+    ///
+    /// # Safety
+    ///
+    /// - `region` must be valid for reads and writes, and must not be accessed other than through this allocator
+    ///   for as long as it or any of its clones or allocations are in use.
+    ///
+    /// # Panics
+    ///
+    /// - If `region` can't hold its bookkeeping and at least one page.
+    pub unsafe fn new(region: NonNull<[u8]>) -> Self {
+        let region_start = region.cast::<u8>();
+        let region_end = region_start.addr().get() + region.len();
+
+        let state_offset = region_start.align_offset(align_of::<RegionState<R>>());
+        // The state may be less aligned than the bitmap's words, such as on 32-bit targets.
+        let state_end = region_start.addr().get() + state_offset + size_of::<RegionState<R>>();
+        let bitmap_offset =
+            state_end.next_multiple_of(align_of::<u64>()) - region_start.addr().get();
+
+        // The bitmap is sized for every page the region could hold, though the bookkeeping takes some of them.
+        let word_count = (region.len() / PAGE_SIZE).div_ceil(u64::BITS as usize);
+        let bookkeeping_end =
+            region_start.addr().get() + bitmap_offset + (word_count * size_of::<u64>());
+        let first_page_address = bookkeeping_end.next_multiple_of(PAGE_SIZE);
+        let page_count = region_end.saturating_sub(first_page_address) / PAGE_SIZE;
+
+        assert!(page_count > 0, "region is too small to hold a page");
+
+        // Safety: Offsets lie within the region, which is valid for writes, and the state and bitmap offsets are
+        //         rounded up to their alignment.
+        unsafe {
+            let state = region_start.add(state_offset).cast::<RegionState<R>>();
+            let bitmap = region_start.add(bitmap_offset).cast::<u64>();
+
+            for word_index in 0..word_count {
+                bitmap.add(word_index).write(0);
+            }
+
+            state.write(RegionState {
+                pages: Mutex::new(RegionPages {
+                    bitmap,
+                    first_page: region_start.add(first_page_address - region_start.addr().get()),
+                    page_count,
+                    free_page_count: page_count,
+                }),
+            });
+
+            Self { state }
+        }
+    }
+
+    /// Number of pages the region can hand out.
+    pub fn page_count(&self) -> usize {
+        self.pages().lock().page_count
+    }
+
+    /// Number of pages not currently allocated.
+    pub fn free_page_count(&self) -> usize {
+        self.pages().lock().free_page_count
+    }
+
+    fn pages(&self) -> &Mutex<R, RegionPages> {
+        // Safety: State was initialized as the allocator was created, and the region outlives the allocator.
+        unsafe { &self.state.as_ref().pages }
+    }
+
+    /// Number of pages backing an allocation of `layout`.
+    fn page_count_of(layout: Layout) -> usize {
+        max(layout.size().div_ceil(PAGE_SIZE), 1)
+    }
+}
+
+impl<R: RawMutex, const PAGE_SIZE: usize> Clone for RegionAllocator<R, PAGE_SIZE> {
+    fn clone(&self) -> Self {
+        Self { state: self.state }
+    }
+}
+
+// Safety: The region is only accessed through its lock.
+unsafe impl<R: RawMutex + Send + Sync, const PAGE_SIZE: usize> Send
+    for RegionAllocator<R, PAGE_SIZE>
+{
+}
+
+// Safety: The region is only accessed through its lock.
+unsafe impl<R: RawMutex + Send + Sync, const PAGE_SIZE: usize> Sync
+    for RegionAllocator<R, PAGE_SIZE>
+{
+}
+
+// Safety: Allocations are distinct runs of pages within the region, which are only freed when deallocated.
+unsafe impl<R: RawMutex, const PAGE_SIZE: usize> Allocator for RegionAllocator<R, PAGE_SIZE> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let page_count = Self::page_count_of(layout);
+        let memory = self
+            .pages()
+            .lock()
+            .allocate(page_count, PAGE_SIZE, layout.align())
+            .ok_or(AllocError)?;
+
+        Ok(NonNull::slice_from_raw_parts(
+            memory,
+            page_count * PAGE_SIZE,
+        ))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        let page_count = Self::page_count_of(layout);
+        let mut pages = self.pages().lock();
+
+        // Safety: Caller is required to ensure `ptr` was allocated from this region, and so lies after its first page.
+        let page_index = unsafe { ptr.byte_offset_from_unsigned(pages.first_page) } / PAGE_SIZE;
+
+        pages.set_allocated(page_index..(page_index + page_count), false);
+        pages.free_page_count += page_count;
+    }
+}
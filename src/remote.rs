@@ -0,0 +1,87 @@
+use core::{
+    ptr::{NonNull, null_mut},
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+};
+
+/// Lock-free queue of freed objects awaiting their return to the slabs.
+///
+/// Any processor may push objects, linking them through their first word, while the whole queue is taken at once
+/// by a drain. As objects are never popped individually, the queue is immune to ABA.
+pub(crate) struct RemoteFreeQueue {
+    head: AtomicPtr<u8>,
+
+    /// Number of queued objects. Incremented before an object is published, so never undercounts.
+    len: AtomicUsize,
+}
+
+impl RemoteFreeQueue {
+    pub const fn new() -> Self {
+        Self {
+            head: AtomicPtr::new(null_mut()),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Queues a freed object.
+    ///
+    /// # Safety
+    ///
+    /// - `object_ptr` must point to a free object that is no longer referenced, and is large enough and suitably
+    ///   aligned to hold a pointer.
+    pub unsafe fn push(&self, object_ptr: NonNull<u8>) {
+        self.len.fetch_add(1, Ordering::Relaxed);
+
+        let link = object_ptr.cast::<*mut u8>();
+        let mut head = self.head.load(Ordering::Relaxed);
+
+        loop {
+            // Safety: Caller is required to ensure the object is unreferenced, and can hold a pointer.
+            unsafe {
+                link.write(head);
+            }
+
+            match self.head.compare_exchange_weak(
+                head,
+                object_ptr.as_ptr(),
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(current_head) => head = current_head,
+            }
+        }
+    }
+
+    /// Takes every queued object, passing each to `f`, and returns the number of objects taken.
+    pub fn drain(&self, mut f: impl FnMut(NonNull<u8>)) -> usize {
+        // Avoid contending on the queue when there is nothing to take.
+        if self.head.load(Ordering::Relaxed).is_null() {
+            return 0;
+        }
+
+        let mut next = self.head.swap(null_mut(), Ordering::Acquire);
+        let mut drained_count = 0;
+
+        while let Some(object_ptr) = NonNull::new(next) {
+            // Safety: Queued objects hold the link to the next object, written before they were published.
+            next = unsafe { object_ptr.cast::<*mut u8>().read() };
+
+            // Safety: Object was taken from the queue, and so isn't referenced.
+            #[cfg(feature = "zeroize-on-free")]
+            unsafe {
+                object_ptr.cast::<*mut u8>().write(null_mut());
+            }
+
+            f(object_ptr);
+            drained_count += 1;
+        }
+
+        self.len.fetch_sub(drained_count, Ordering::Relaxed);
+
+        drained_count
+    }
+}
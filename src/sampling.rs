@@ -0,0 +1,76 @@
+use crate::Clock;
+use core::{
+    alloc::Layout,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+/// Allocation sampled by [`SlabAllocator::with_sampling`](crate::SlabAllocator::with_sampling).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AllocationSample {
+    /// Layout the allocation was made with.
+    pub layout: Layout,
+
+    /// Object size of the size class that served the allocation, or `None` if it was served by the fallback
+    /// allocator.
+    pub size_class: Option<usize>,
+
+    /// Time the allocation was made, as measured by the sampling clock.
+    pub timestamp: Duration,
+}
+
+/// Called with every sampled allocation, after it has been made.
+///
+/// The handler is called on the allocating path, though with none of the allocator's locks held, so it should only
+/// record the sample, such as into a lock-free ring buffer. It must not allocate from the allocator that called it.
+pub type SamplingHandler = fn(AllocationSample);
+
+/// Reports every `period`th allocation to a [`SamplingHandler`].
+pub(crate) struct Sampler {
+    period: usize,
+    allocation_count: AtomicUsize,
+    clock: &'static dyn Clock,
+    handler: SamplingHandler,
+}
+
+impl Sampler {
+    pub const fn new(period: usize, clock: &'static dyn Clock, handler: SamplingHandler) -> Self {
+        Self {
+            period,
+            allocation_count: AtomicUsize::new(0),
+            clock,
+            handler,
+        }
+    }
+
+    /// Counts `object_count` allocations of `layout`, served by the size class of `size_class`, reporting a sample
+    /// for each that falls on the sampling period.
+    pub fn record(&self, layout: Layout, size_class: Option<usize>, object_count: usize) {
+        let previous_count = self
+            .allocation_count
+            .fetch_add(object_count, Ordering::Relaxed);
+        // Counted within the current period, so samples are still counted as the count wraps.
+        let sample_count = ((previous_count % self.period) + object_count) / self.period;
+
+        for _ in 0..sample_count {
+            (self.handler)(AllocationSample {
+                layout,
+                size_class,
+                timestamp: self.clock.now(),
+            });
+        }
+    }
+}
+
+impl core::fmt::Debug for Sampler {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Sampler")
+            .field("period", &self.period)
+            .field(
+                "allocation_count",
+                &self.allocation_count.load(Ordering::Relaxed),
+            )
+            .finish_non_exhaustive()
+    }
+}
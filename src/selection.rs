@@ -0,0 +1,13 @@
+/// Policy choosing the slab that serves allocations once the active slab is exhausted.
+///
+/// Partial slabs are always preferred over empty slabs, so empty slabs stay reclaimable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SlabSelection {
+    /// The partial slab most recently relisted, whose memory is the most likely to still be cached.
+    #[default]
+    MostRecent,
+
+    /// The partial slab with the fewest free objects, concentrating live objects in as few slabs as possible, so
+    /// other slabs drain to empty and can be released. Finding it scans every partial slab of the size class.
+    Fullest,
+}
@@ -0,0 +1,250 @@
+use crate::{
+    DEFAULT_PAGE_SIZE, DefaultRawMutex, SlabAllocator,
+    alloc_api::{AllocError, Allocator},
+};
+use core::{
+    alloc::Layout,
+    ops::Deref,
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering, fence},
+};
+use lock_api::RawMutex;
+
+/// A value, with the number of handles sharing it.
+struct Counted<T> {
+    handle_count: AtomicUsize,
+    value: T,
+}
+
+/// Handle to a reference-counted `T`, kept in memory of the allocator backing it, which is reached through the value
+/// itself, so is never cloned.
+struct CountedRef<T> {
+    counted: NonNull<Counted<T>>,
+}
+
+impl<T> CountedRef<T> {
+    const LAYOUT: Layout = Layout::new::<Counted<T>>();
+
+    /// Moves `value` into memory of the allocator `backing` reaches from it.
+    ///
+    /// # Errors
+    ///
+    /// - If the memory can't be allocated.
+    fn new<B: Allocator>(value: T, backing: impl Fn(&T) -> &B) -> Result<Self, AllocError> {
+        let counted = backing(&value).allocate(Self::LAYOUT)?.cast::<Counted<T>>();
+
+        // Safety: Memory was just allocated with the layout of `Counted`.
+        unsafe {
+            counted.write(Counted {
+                handle_count: AtomicUsize::new(1),
+                value,
+            });
+        }
+
+        Ok(Self { counted })
+    }
+
+    fn counted(&self) -> &Counted<T> {
+        // Safety: The value is only dropped along with the last handle to it.
+        unsafe { self.counted.as_ref() }
+    }
+
+    fn get(&self) -> &T {
+        &self.counted().value
+    }
+
+    fn handle_count(&self) -> usize {
+        self.counted().handle_count.load(Ordering::Relaxed)
+    }
+
+    fn share(&self) -> Self {
+        // New handles are made from an existing handle, so need no ordering with the value's accesses.
+        self.counted().handle_count.fetch_add(1, Ordering::Relaxed);
+
+        Self {
+            counted: self.counted,
+        }
+    }
+
+    /// Drops the handle, dropping the value and freeing its memory with the allocator `backing` reaches from it if
+    /// this was the last handle.
+    ///
+    /// # Safety
+    ///
+    /// - `backing` must reach the same allocator as when the value was moved in.
+    /// - The handle must not be used afterwards.
+    unsafe fn release<B: Allocator>(&mut self, backing: impl Fn(&T) -> &B) {
+        if self.counted().handle_count.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+
+        // Every access made through the other handles happens before the value is dropped.
+        fence(Ordering::Acquire);
+
+        // Safety: This was the last handle, so nothing else references the value. It is moved out of its memory, so
+        //         the allocator it holds, or is, can still free that memory.
+        let value = unsafe { self.counted.read().value };
+
+        // Safety: Caller is required to ensure `backing` reaches the allocator the memory was allocated by.
+        unsafe {
+            backing(&value).deallocate(self.counted.cast(), Self::LAYOUT);
+        }
+    }
+}
+
+/// Cheaply cloned handle to a [`SlabAllocator`], which is dropped along with the last handle to it.
+///
+/// The slab allocator is moved into memory taken from its own backing allocator, and reference counted there, so it
+/// can be handed to several subsystems without being kept in a `static`, nor needing a global allocator as `Arc`
+/// would. Every handle allocates from, and may free the allocations of, the same slab allocator.
+///
+/// ```ignore
+/// let allocator = SharedSlabAllocator::new_in(PageAllocator::new())?;
+/// let mut entries = Vec::new_in(allocator.clone());
+/// ```
+pub struct SharedSlabAllocator<
+    A: Allocator,
+    R: RawMutex = DefaultRawMutex,
+    const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE,
+    F: Allocator = A,
+> {
+    shared: CountedRef<SlabAllocator<A, R, PAGE_SIZE, F>>,
+}
+
+// Safety: Handles only give shared access to the slab allocator, which is dropped by whichever handle is last.
+unsafe impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator> Send
+    for SharedSlabAllocator<A, R, PAGE_SIZE, F>
+where
+    SlabAllocator<A, R, PAGE_SIZE, F>: Send + Sync,
+{
+}
+
+// Safety: Handles only give shared access to the slab allocator.
+unsafe impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator> Sync
+    for SharedSlabAllocator<A, R, PAGE_SIZE, F>
+where
+    SlabAllocator<A, R, PAGE_SIZE, F>: Send + Sync,
+{
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize> SharedSlabAllocator<A, R, PAGE_SIZE> {
+    /// Creates a shared slab allocator over `allocator`, with the default size classes.
+    ///
+    /// # Errors
+    ///
+    /// - If the slab allocator can't be moved into memory of `allocator`.
+    pub fn new_in(allocator: A) -> Result<Self, AllocError> {
+        Self::new(SlabAllocator::new_in(allocator))
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator>
+    SharedSlabAllocator<A, R, PAGE_SIZE, F>
+{
+    /// Shares `allocator`, such as one configured by a [`SlabAllocatorBuilder`](crate::SlabAllocatorBuilder).
+    ///
+    /// # Errors
+    ///
+    /// - If `allocator` can't be moved into memory of its backing allocator.
+    pub fn new(allocator: SlabAllocator<A, R, PAGE_SIZE, F>) -> Result<Self, AllocError> {
+        Ok(Self {
+            shared: CountedRef::new(allocator, |allocator| allocator.inner.get())?,
+        })
+    }
+
+    /// Number of handles sharing the slab allocator.
+    pub fn handle_count(this: &Self) -> usize {
+        this.shared.handle_count()
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator> Clone
+    for SharedSlabAllocator<A, R, PAGE_SIZE, F>
+{
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.share(),
+        }
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator> Drop
+    for SharedSlabAllocator<A, R, PAGE_SIZE, F>
+{
+    fn drop(&mut self) {
+        // Safety: The slab allocator was moved into memory of its backing allocator, and the handle is being dropped.
+        unsafe {
+            self.shared.release(|allocator| allocator.inner.get());
+        }
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator> Deref
+    for SharedSlabAllocator<A, R, PAGE_SIZE, F>
+{
+    type Target = SlabAllocator<A, R, PAGE_SIZE, F>;
+
+    fn deref(&self) -> &Self::Target {
+        self.shared.get()
+    }
+}
+
+// Safety: Every handle allocates from the same slab allocator, so memory blocks are valid across clones, and are not
+//         freed until the last handle is dropped.
+unsafe impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator> Allocator
+    for SharedSlabAllocator<A, R, PAGE_SIZE, F>
+{
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        (**self).allocate(layout)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        (**self).allocate_zeroed(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Safety: Caller is required to uphold the safety invariants.
+        unsafe { (**self).deallocate(ptr, layout) }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: Caller is required to uphold the safety invariants.
+        unsafe { (**self).grow(ptr, old_layout, new_layout) }
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: Caller is required to uphold the safety invariants.
+        unsafe { (**self).grow_zeroed(ptr, old_layout, new_layout) }
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: Caller is required to uphold the safety invariants.
+        unsafe { (**self).shrink(ptr, old_layout, new_layout) }
+    }
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator> core::fmt::Debug
+    for SharedSlabAllocator<A, R, PAGE_SIZE, F>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SharedSlabAllocator")
+            .field("handle_count", &Self::handle_count(self))
+            .field("allocator", &**self)
+            .finish()
+    }
+}
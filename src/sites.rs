@@ -0,0 +1,222 @@
+use crate::{
+    TryAllocError,
+    alloc_api::{AllocError, Allocator, Vec},
+};
+use core::{fmt, panic::Location, ptr::NonNull};
+use lock_api::{Mutex, RawMutex};
+
+/// Where an allocation was made, as recorded by [allocation-site
+/// tracking](crate::SlabAllocator::with_allocation_sites).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocationSite {
+    /// Source location of the call to the allocator.
+    Location(&'static Location<'static>),
+
+    /// Tag supplied with the allocation by [`SlabAllocator::allocate_at`](crate::SlabAllocator::allocate_at).
+    Tag(&'static str),
+}
+
+impl AllocationSite {
+    /// Source location of the call to the function this is called from, or of its caller if that is itself
+    /// `#[track_caller]`.
+    #[track_caller]
+    pub fn caller() -> Self {
+        Self::Location(Location::caller())
+    }
+}
+
+impl fmt::Display for AllocationSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Location(location) => write!(f, "{location}"),
+            Self::Tag(tag) => f.write_str(tag),
+        }
+    }
+}
+
+/// Live allocations of a single [`AllocationSite`], as reported by
+/// [`SlabAllocator::allocation_sites`](crate::SlabAllocator::allocation_sites).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationSiteStats {
+    /// Site the allocations were made at.
+    pub site: AllocationSite,
+
+    /// Allocations currently live.
+    pub live_allocation_count: usize,
+
+    /// Bytes currently live, as requested by the allocations' layouts.
+    pub live_bytes: usize,
+}
+
+impl fmt::Display for AllocationSiteStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} allocations, {} bytes",
+            self.site, self.live_allocation_count, self.live_bytes
+        )
+    }
+}
+
+/// Live allocation, as recorded by a [`SiteRegistry`].
+struct SiteRecord {
+    address: usize,
+    size: usize,
+    site: AllocationSite,
+}
+
+/// Side table of the site of every live allocation, so live allocations can be reported by where they were made.
+///
+/// Records are kept ordered by address, so the record of a pointer is found by binary search.
+pub(crate) struct SiteRegistry<A: Allocator, R: RawMutex> {
+    records: Mutex<R, Vec<SiteRecord, A>>,
+}
+
+impl<A: Allocator, R: RawMutex> SiteRegistry<A, R> {
+    pub fn new_in(allocator: A) -> Self {
+        Self {
+            records: Mutex::new(Vec::new_in(allocator)),
+        }
+    }
+
+    /// Records the allocation at `ptr` of `size` bytes as made at `site`, replacing any record of `ptr`.
+    ///
+    /// # Errors
+    ///
+    /// - If the table can't grow to hold the record.
+    pub fn insert(
+        &self,
+        ptr: NonNull<u8>,
+        size: usize,
+        site: AllocationSite,
+    ) -> Result<(), AllocError> {
+        Self::insert_into(&mut self.records.lock(), ptr, size, site)
+    }
+
+    /// Records the allocation at `ptr` as [`SiteRegistry::insert`] does, without waiting on the table's lock.
+    ///
+    /// # Errors
+    ///
+    /// - [`TryAllocError::WouldBlock`] if the table's lock is held.
+    /// - [`TryAllocError::Exhausted`] if the table can't grow to hold the record.
+    pub fn try_insert(
+        &self,
+        ptr: NonNull<u8>,
+        size: usize,
+        site: AllocationSite,
+    ) -> Result<(), TryAllocError> {
+        let mut records = self.records.try_lock().ok_or(TryAllocError::WouldBlock)?;
+        Ok(Self::insert_into(&mut records, ptr, size, site)?)
+    }
+
+    fn insert_into(
+        records: &mut Vec<SiteRecord, A>,
+        ptr: NonNull<u8>,
+        size: usize,
+        site: AllocationSite,
+    ) -> Result<(), AllocError> {
+        let address = ptr.addr().get();
+        let record = SiteRecord {
+            address,
+            size,
+            site,
+        };
+
+        match records.binary_search_by_key(&address, |record| record.address) {
+            Ok(index) => records[index] = record,
+
+            Err(index) => {
+                records.try_reserve(1).map_err(|_| AllocError)?;
+                records.insert(index, record);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Forgets the allocation at `ptr` as it is freed.
+    pub fn remove(&self, ptr: NonNull<u8>) {
+        Self::remove_from(&mut self.records.lock(), ptr);
+    }
+
+    fn remove_from(records: &mut Vec<SiteRecord, A>, ptr: NonNull<u8>) -> Option<AllocationSite> {
+        let index = records
+            .binary_search_by_key(&ptr.addr().get(), |record| record.address)
+            .ok()?;
+
+        Some(records.remove(index).site)
+    }
+
+    /// Site the allocation at `ptr` was made at, if it is recorded.
+    pub fn site_of(&self, ptr: NonNull<u8>) -> Option<AllocationSite> {
+        let records = self.records.lock();
+        let index = records
+            .binary_search_by_key(&ptr.addr().get(), |record| record.address)
+            .ok()?;
+
+        Some(records[index].site)
+    }
+
+    /// Replaces the record of the allocation at `old_ptr` with its reallocation at `new_ptr` of `new_size` bytes, made
+    /// at `site`.
+    ///
+    /// # Errors
+    ///
+    /// - If the table can't grow to hold the record.
+    pub fn replace(
+        &self,
+        old_ptr: NonNull<u8>,
+        new_ptr: NonNull<u8>,
+        new_size: usize,
+        site: AllocationSite,
+    ) -> Result<(), AllocError> {
+        let mut records = self.records.lock();
+        Self::remove_from(&mut records, old_ptr);
+        Self::insert_into(&mut records, new_ptr, new_size, site)
+    }
+
+    /// Number of bytes allocated to hold the table.
+    pub fn metadata_bytes(&self) -> usize {
+        self.records.lock().capacity() * size_of::<SiteRecord>()
+    }
+
+    /// Groups the live allocations by site, in descending order of live bytes, into a list allocated from
+    /// `allocator`.
+    ///
+    /// # Errors
+    ///
+    /// - If the list can't be allocated.
+    pub fn stats_in<B: Allocator>(
+        &self,
+        allocator: B,
+    ) -> Result<Vec<AllocationSiteStats, B>, AllocError> {
+        let mut sites = Vec::<AllocationSiteStats, B>::new_in(allocator);
+
+        for record in self.records.lock().iter() {
+            match sites.iter_mut().find(|stats| stats.site == record.site) {
+                Some(stats) => {
+                    stats.live_allocation_count += 1;
+                    stats.live_bytes += record.size;
+                }
+
+                None => {
+                    sites.try_reserve(1).map_err(|_| AllocError)?;
+                    sites.push(AllocationSiteStats {
+                        site: record.site,
+                        live_allocation_count: 1,
+                        live_bytes: record.size,
+                    });
+                }
+            }
+        }
+
+        sites.sort_unstable_by(|a, b| b.live_bytes.cmp(&a.live_bytes));
+        Ok(sites)
+    }
+}
+
+impl<A: Allocator, R: RawMutex> fmt::Debug for SiteRegistry<A, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SiteRegistry").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,36 @@
+use crate::DEFAULT_SIZE_CLASSES;
+
+/// Set of size classes fixed at compile time, usually declared with [`size_classes!`](crate::size_classes).
+///
+/// Allocators created by [`with_const_size_classes_in`](crate::SlabAllocator::with_const_size_classes_in) have
+/// only these size classes, which are checked as they are compiled rather than as they are created.
+pub trait SizeClasses {
+    /// Object sizes of the size classes, in strictly ascending order.
+    const OBJECT_SIZES: &'static [usize];
+}
+
+/// The [`DEFAULT_SIZE_CLASSES`].
+pub struct DefaultSizeClasses;
+
+impl SizeClasses for DefaultSizeClasses {
+    const OBJECT_SIZES: &'static [usize] = DEFAULT_SIZE_CLASSES;
+}
+
+/// Declares a type implementing [`SizeClasses`].
+///
+/// ```ignore
+/// slab_allocator::size_classes!(pub struct NetworkSizeClasses = [64, 128, 512, 2048]);
+///
+/// let allocator = SlabAllocator::with_const_size_classes_in::<NetworkSizeClasses>(frames);
+/// ```
+#[macro_export]
+macro_rules! size_classes {
+    ($(#[$attr:meta])* $vis:vis struct $name:ident = [$($object_size:expr),+ $(,)?] $(;)?) => {
+        $(#[$attr])*
+        $vis struct $name;
+
+        impl $crate::SizeClasses for $name {
+            const OBJECT_SIZES: &'static [usize] = &[$($object_size),+];
+        }
+    };
+}
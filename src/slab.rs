@@ -0,0 +1,1211 @@
+use crate::{
+    Corruption, RandomSource,
+    alloc_api::{AllocError, Allocator},
+};
+use core::{
+    alloc::Layout,
+    cell::Cell,
+    num::NonZero,
+    ptr::NonNull,
+    slice,
+    sync::atomic::{AtomicU32, AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// Size of the smallest objects a slab can hold.
+pub(crate) const MIN_OBJECT_SIZE: usize = 8;
+
+/// Page size used unless another is given, matching the 4 KiB pages of most architectures.
+pub const DEFAULT_PAGE_SIZE: usize = 0x1000;
+
+/// Maximum number of contiguous pages a slab can span.
+pub const MAX_SLAB_PAGE_COUNT: usize = 64;
+
+/// Size of a cache line, the granularity the first object of successive slabs is offset by, and the alignment of
+/// each size class.
+pub(crate) const CACHE_LINE_SIZE: usize = 64;
+
+/// Whether `page_size` is supported: a power of two of at least 4 KiB, small enough that the largest slab's size
+/// fits `u32`.
+pub(crate) const fn is_valid_page_size(page_size: usize) -> bool {
+    page_size.is_power_of_two()
+        && page_size >= DEFAULT_PAGE_SIZE
+        && page_size <= (1 << 31) / MAX_SLAB_PAGE_COUNT
+}
+
+/// Flag of [`SlabHeader::slab_size_log2`] set when the slab's memory was aligned within an over-sized allocation, the
+/// start of which is stored directly after the slab's memory.
+const UNALIGNED_MEMORY: u8 = 1 << 7;
+
+/// Number of words in each of a slab's bitmaps.
+const fn bitmap_word_count(object_size: usize, slab_size: usize) -> usize {
+    (slab_size / object_size).div_ceil(u64::BITS as usize)
+}
+
+/// Size of the slab header, including its bitmaps.
+const fn header_size(object_size: usize, slab_size: usize) -> usize {
+    size_of::<SlabHeader>()
+        + (2 * bitmap_word_count(object_size, slab_size) * size_of::<AtomicU64>())
+}
+
+/// Size of the part of a slab's memory taken by its header: none, if the header is external.
+const fn in_slab_header_size(object_size: usize, slab_size: usize, external_header: bool) -> usize {
+    if external_header {
+        0
+    } else {
+        header_size(object_size, slab_size)
+    }
+}
+
+/// Number of allocatable objects in a slab of `slab_size` bytes, whose header is kept outside of its memory if
+/// `external_header`.
+pub(crate) const fn object_count(
+    object_size: usize,
+    slab_size: usize,
+    external_header: bool,
+) -> usize {
+    (slab_size - in_slab_header_size(object_size, slab_size, external_header)) / object_size
+}
+
+/// Number of pages spanned by each slab of `object_size`-byte objects unless configured otherwise: the fewest whose
+/// header and leftover bytes together take no more than an eighth of the slab.
+///
+/// Slabs of small objects fit their header in the leftover bytes of a single page, while larger objects would lose a
+/// whole object of each page to the header, and are instead amortized over slabs of several pages.
+pub(crate) const fn default_slab_page_count(
+    object_size: usize,
+    page_size: usize,
+    external_header: bool,
+) -> usize {
+    let mut page_count = 1;
+    while page_count < MAX_SLAB_PAGE_COUNT {
+        let slab_size = page_count * page_size;
+        let wasted_size =
+            slab_size - (object_count(object_size, slab_size, external_header) * object_size);
+        if wasted_size <= slab_size / 8 {
+            break;
+        }
+
+        page_count *= 2;
+    }
+
+    page_count
+}
+
+/// Offset of an uncolored slab's first object from the start of its memory.
+///
+/// Objects are packed against the end of the slab, leaving any remainder of the slab between the header and the
+/// first object. As the page size is a multiple of every object size's alignment, objects stay aligned.
+pub(crate) const fn first_object_offset(
+    object_size: usize,
+    slab_size: usize,
+    external_header: bool,
+) -> usize {
+    slab_size - (object_count(object_size, slab_size, external_header) * object_size)
+}
+
+/// Distance between successive colors of a slab: a cache line, or the objects' alignment if larger, so colored
+/// objects stay aligned.
+const fn color_step(object_size: usize) -> usize {
+    let object_align = 1 << object_size.trailing_zeros();
+
+    if object_align > CACHE_LINE_SIZE {
+        object_align
+    } else {
+        CACHE_LINE_SIZE
+    }
+}
+
+/// Number of colors of a slab: offsets its objects can be moved towards the header by, using the remainder of the
+/// slab left over by the objects. Bounded by the colors a `u8` can hold.
+pub(crate) const fn color_count(
+    object_size: usize,
+    slab_size: usize,
+    external_header: bool,
+) -> usize {
+    let leftover_size = first_object_offset(object_size, slab_size, external_header)
+        - in_slab_header_size(object_size, slab_size, external_header);
+    let color_count = (leftover_size / color_step(object_size)) + 1;
+
+    if color_count > (u8::MAX as usize) + 1 {
+        (u8::MAX as usize) + 1
+    } else {
+        color_count
+    }
+}
+
+/// Bitmap word with a bit set for every allocatable object covered by the word at `word_index`.
+const fn object_mask(
+    object_size: usize,
+    slab_size: usize,
+    external_header: bool,
+    word_index: usize,
+) -> u64 {
+    let bit_count = object_count(object_size, slab_size, external_header)
+        .saturating_sub(word_index * u64::BITS as usize);
+
+    // Bit count is clamped to the bits of a word, so will never overflow `u32`.
+    #[allow(clippy::cast_possible_truncation, clippy::as_conversions)]
+    if bit_count >= u64::BITS as usize {
+        u64::MAX
+    } else {
+        (1u64 << bit_count as u32) - 1
+    }
+}
+
+/// Splits an object index into the index of the bitmap word covering it, and the object's bit within that word.
+const fn bitmap_position(object_index: usize) -> (usize, u64) {
+    (
+        object_index / u64::BITS as usize,
+        1 << (object_index % u64::BITS as usize),
+    )
+}
+
+/// Zeroes `memory` with volatile writes, so the zeroing isn't elided as the memory is released.
+///
+/// # Safety
+///
+/// - `memory` must be valid for writes, aligned to and a multiple of the pointer size, and not be referenced.
+pub(crate) unsafe fn wipe(memory: NonNull<[u8]>) {
+    let words = memory.cast::<usize>();
+
+    for index in 0..(memory.len() / size_of::<usize>()) {
+        // Safety: Caller is required to ensure the memory is valid for writes, and aligned.
+        unsafe {
+            words.add(index).write_volatile(0);
+        }
+    }
+}
+
+/// Byte that free objects are filled with, so writes made after an object is freed can be detected.
+#[cfg(feature = "poison")]
+pub(crate) const POISON_BYTE: u8 = 0x6B;
+
+/// Fills a free object with [`POISON_BYTE`].
+///
+/// # Safety
+///
+/// - `object` must be a free object that is no longer referenced.
+#[cfg(feature = "poison")]
+pub(crate) unsafe fn poison_object(object: NonNull<[u8]>) {
+    // Safety: Caller is required to ensure the object is not referenced.
+    unsafe {
+        object.cast::<u8>().write_bytes(POISON_BYTE, object.len());
+    }
+}
+
+/// Verifies a poisoned object is intact as it is handed out again.
+///
+/// # Safety
+///
+/// - `object` must have been poisoned when freed, and be exclusively owned by the caller.
+///
+/// # Errors
+///
+/// - [`Corruption::WriteAfterFree`] if the object was written to while it was free.
+#[cfg(feature = "poison")]
+pub(crate) unsafe fn check_poison(object: NonNull<[u8]>) -> Result<(), Corruption> {
+    // Safety: Caller is required to ensure the object is initialized and exclusively owned.
+    let bytes = unsafe { object.as_ref() };
+
+    if bytes.iter().all(|byte| *byte == POISON_BYTE) {
+        Ok(())
+    } else {
+        Err(Corruption::WriteAfterFree {
+            object_ptr: object.cast(),
+        })
+    }
+}
+
+/// Where a slab is currently held by its manager.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SlabList {
+    /// The slab is serving allocations.
+    Active,
+
+    /// Every object is allocated.
+    Full,
+
+    /// Some, but not all, objects are allocated.
+    Partial,
+
+    /// No objects are allocated.
+    Empty,
+}
+
+/// Slab metadata, stored at the start of the slab's memory.
+///
+/// Slabs are aligned to their size, so keeping the header in-slab allows the owning slab of any object to be found
+/// by masking the object's address down to its slab boundary. Slabs whose memory must hold nothing but objects, such
+/// as memory visible to devices, instead keep their header in a separate allocation, found through the
+/// [`SlabTree`](crate::tree::SlabTree) of their manager.
+///
+/// The header is directly followed by its two bitmaps, each of [`bitmap_word_count`] words: the free bitmap, with a
+/// bit set for every free object, and the clean bitmap, with a bit set for every object known to be zeroed, as it
+/// hasn't been handed out since the slab was created. External headers hold a pointer to the slab's memory between
+/// the header and its bitmaps.
+///
+/// Every field is kept as narrow as its bounds allow, so the header, with the links of the list and tree it is held
+/// in, fits a single cache line.
+pub(crate) struct SlabHeader {
+    bitmaps: NonNull<AtomicU64>,
+
+    // List membership is only ever accessed with the owning manager's lists locked.
+    prev: Cell<Option<NonNull<SlabHeader>>>,
+    next: Cell<Option<NonNull<SlabHeader>>>,
+
+    // As is membership of the manager's tree of slabs by address.
+    left: Cell<Option<NonNull<SlabHeader>>>,
+    right: Cell<Option<NonNull<SlabHeader>>>,
+
+    /// Time the slab was last put on the empty list, in nanoseconds, if its manager has a clock.
+    empty_since: Cell<u64>,
+
+    /// Size of each object, in bytes. Always smaller than the slab's size, so always fits `u32`.
+    object_size: u32,
+
+    /// Number of free objects not yet reserved by an allocation. Never exceeds the number of bits set in the free
+    /// bitmap, so always fits `u32`.
+    free_count: AtomicU32,
+
+    /// Number of frees that may still access this header, preventing the slab from being released under them. Bounded
+    /// by the number of concurrently freeing threads.
+    pending_returns: AtomicU32,
+
+    /// Size of the slab's memory, as a power of two, with [`UNALIGNED_MEMORY`] set if the slab's memory was aligned
+    /// within an over-sized allocation. Slab sizes fit `u32`, so never reach the flag's bit.
+    slab_size_log2: u8,
+
+    /// Number of color steps the objects are moved towards the header by, so objects at the same index of
+    /// successive slabs map to different cache sets.
+    color: u8,
+
+    /// Index of the lock shard of the size class the slab belongs to.
+    shard: u8,
+
+    list: Cell<SlabList>,
+}
+
+impl SlabHeader {
+    /// # Safety
+    ///
+    /// - `object_ptr` must point to an object that originated from a slab of `slab_size` bytes, whose header isn't
+    ///   external.
+    pub unsafe fn containing(object_ptr: NonNull<u8>, slab_size: usize) -> NonNull<Self> {
+        debug_assert!(slab_size.is_power_of_two());
+
+        object_ptr
+            .map_addr(|address| {
+                // Safety: Masking a non-zero address down to its slab boundary never yields zero, as page zero is
+                //         never a valid slab.
+                unsafe { NonZero::new_unchecked(address.get() & !(slab_size - 1)) }
+            })
+            .cast()
+    }
+
+    /// Bitmap with a bit set for every free object.
+    fn free_bitmap(&self) -> &[AtomicU64] {
+        // Safety: The free bitmap directly follows the header, and lives as long as it.
+        unsafe { slice::from_raw_parts(self.bitmaps.as_ptr(), self.bitmap_word_count()) }
+    }
+
+    /// Bitmap with a bit set for every object known to be zeroed.
+    fn clean_bitmap(&self) -> &[AtomicU64] {
+        let word_count = self.bitmap_word_count();
+
+        // Safety: The clean bitmap directly follows the free bitmap, and lives as long as the header.
+        unsafe { slice::from_raw_parts(self.bitmaps.add(word_count).as_ptr(), word_count) }
+    }
+
+    /// Size of each object in this slab.
+    pub fn object_size(&self) -> usize {
+        // Object sizes always fit `u32`, so will never truncate.
+        #[allow(clippy::as_conversions)]
+        {
+            self.object_size as usize
+        }
+    }
+
+    /// Size of this slab's memory.
+    pub fn slab_size(&self) -> usize {
+        1 << (self.slab_size_log2 & !UNALIGNED_MEMORY)
+    }
+
+    /// Whether the slab's memory was aligned within an over-sized allocation, as the backing allocator didn't align it
+    /// itself.
+    fn has_unaligned_memory(&self) -> bool {
+        self.slab_size_log2 & UNALIGNED_MEMORY != 0
+    }
+
+    /// Start of the slab's memory, which holds the header unless it is external.
+    fn memory(&self) -> NonNull<u8> {
+        if self.is_external() {
+            // Safety: External headers hold the pointer to the slab's memory directly before their bitmaps, in the
+            //         same allocation.
+            unsafe {
+                self.bitmaps
+                    .cast::<NonNull<u8>>()
+                    .byte_sub(size_of::<AtomicU64>())
+                    .read()
+            }
+        } else {
+            // Safety: The bitmaps of in-slab headers were derived from the slab's memory, which the header starts.
+            unsafe { self.bitmaps.cast::<u8>().byte_sub(size_of::<Self>()) }
+        }
+    }
+
+    fn bitmap_word_count(&self) -> usize {
+        bitmap_word_count(self.object_size(), self.slab_size())
+    }
+
+    /// Number of allocatable objects in this slab.
+    pub fn object_count(&self) -> usize {
+        object_count(self.object_size(), self.slab_size(), self.is_external())
+    }
+
+    /// Whether the header is kept outside of the slab's memory.
+    pub fn is_external(&self) -> bool {
+        // Only external headers are separated from their bitmaps, by the pointer to the slab's memory.
+        self.bitmaps.addr().get() != NonNull::from(self).addr().get() + size_of::<Self>()
+    }
+
+    fn first_object_offset(&self) -> usize {
+        first_object_offset(self.object_size(), self.slab_size(), self.is_external())
+            - (usize::from(self.color) * color_step(self.object_size()))
+    }
+
+    /// Index of the lock shard of the size class the slab belongs to.
+    pub fn shard(&self) -> usize {
+        usize::from(self.shard)
+    }
+
+    /// Whether `offset` from the start of the slab is the offset of one of its objects.
+    pub fn is_object_offset(&self, offset: usize) -> bool {
+        offset
+            .checked_sub(self.first_object_offset())
+            .is_some_and(|object_offset| {
+                object_offset.is_multiple_of(self.object_size())
+                    && (object_offset / self.object_size()) < self.object_count()
+            })
+    }
+
+    /// Index of the object at `object_ptr`, which must point to one of the slab's objects.
+    pub fn object_index_of(&self, object_ptr: NonNull<u8>) -> usize {
+        let object_offset =
+            object_ptr.addr().get() - self.memory().addr().get() - self.first_object_offset();
+
+        object_offset / self.object_size()
+    }
+
+    /// Currently remaining (free) objects in this slab.
+    pub fn remaining_object_count(&self) -> usize {
+        self.free_count(Ordering::Relaxed)
+    }
+
+    fn free_count(&self, order: Ordering) -> usize {
+        // Free counts are bounded by the object count, so widening them is lossless.
+        #[allow(clippy::as_conversions)]
+        {
+            self.free_count.load(order) as usize
+        }
+    }
+
+    fn occupancy_of(&self, free_count: usize) -> SlabList {
+        if free_count == 0 {
+            SlabList::Full
+        } else if free_count == self.object_count() {
+            SlabList::Empty
+        } else {
+            SlabList::Partial
+        }
+    }
+
+    /// The list this slab belongs on, given its current occupancy.
+    pub fn occupancy(&self) -> SlabList {
+        self.occupancy_of(self.free_count(Ordering::Acquire))
+    }
+
+    pub fn list(&self) -> SlabList {
+        self.list.get()
+    }
+
+    /// # Safety
+    ///
+    /// - The owning manager's lists must be locked.
+    pub unsafe fn set_list(&self, list: SlabList) {
+        self.list.set(list);
+    }
+
+    /// Header of the previous slab on the list holding this slab.
+    pub fn prev(&self) -> Option<NonNull<Self>> {
+        self.prev.get()
+    }
+
+    /// Header of the next slab on the list holding this slab.
+    pub fn next(&self) -> Option<NonNull<Self>> {
+        self.next.get()
+    }
+
+    /// # Safety
+    ///
+    /// - The owning manager's lists must be locked.
+    pub unsafe fn set_links(&self, prev: Option<NonNull<Self>>, next: Option<NonNull<Self>>) {
+        self.prev.set(prev);
+        self.next.set(next);
+    }
+
+    /// Header of the child slab at lower addresses in the tree holding this slab.
+    pub fn left(&self) -> Option<NonNull<Self>> {
+        self.left.get()
+    }
+
+    /// Header of the child slab at higher addresses in the tree holding this slab.
+    pub fn right(&self) -> Option<NonNull<Self>> {
+        self.right.get()
+    }
+
+    /// Link to the child slab at lower addresses, which may only be set with the owning manager's lists locked.
+    pub fn left_link(&self) -> &Cell<Option<NonNull<Self>>> {
+        &self.left
+    }
+
+    /// Link to the child slab at higher addresses, which may only be set with the owning manager's lists locked.
+    pub fn right_link(&self) -> &Cell<Option<NonNull<Self>>> {
+        &self.right
+    }
+
+    /// Base address of the slab's memory.
+    pub fn address(&self) -> usize {
+        self.memory().addr().get()
+    }
+
+    /// Time the slab was last put on the empty list, as measured by its manager's clock.
+    pub fn empty_since(&self) -> Duration {
+        Duration::from_nanos(self.empty_since.get())
+    }
+
+    /// # Safety
+    ///
+    /// - The owning manager's lists must be locked.
+    pub unsafe fn set_empty_since(&self, time: Duration) {
+        self.empty_since
+            .set(u64::try_from(time.as_nanos()).unwrap_or(u64::MAX));
+    }
+
+    /// Atomically claims a free object, returning its index.
+    ///
+    /// The lowest free object is claimed, unless `random` is provided, in which case a random free object is.
+    fn claim_object(&self, random: Option<&dyn RandomSource>) -> Option<usize> {
+        // Reserve an object first, so a free bit is guaranteed to be found below.
+        self.free_count
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |free_count| {
+                free_count.checked_sub(1)
+            })
+            .ok()?;
+
+        let random_value = random.map(RandomSource::next_u64);
+
+        loop {
+            if let Some(object_index) = self.try_claim_bit(random_value) {
+                return Some(object_index);
+            }
+        }
+    }
+
+    /// Attempts to clear a set bit of the free bitmap, returning the index of its object.
+    ///
+    /// With `random_value`, the bit is chosen at random from those set, rather than being the lowest.
+    fn try_claim_bit(&self, random_value: Option<u64>) -> Option<usize> {
+        let free_bitmap = self.free_bitmap();
+
+        let mut skip_count = match random_value {
+            Some(random_value) => {
+                let free_bit_count: u32 = free_bitmap
+                    .iter()
+                    .map(|word| word.load(Ordering::Relaxed).count_ones())
+                    .sum();
+
+                random_value % u64::from(free_bit_count.max(1))
+            }
+
+            None => 0,
+        };
+
+        for (word_index, word) in free_bitmap.iter().enumerate() {
+            let bits = word.load(Ordering::Relaxed);
+            let bit_count = u64::from(bits.count_ones());
+
+            if skip_count >= bit_count {
+                skip_count -= bit_count;
+                continue;
+            }
+
+            // Clear the lowest set bits, leaving the chosen free object as the lowest.
+            let candidates =
+                (0..skip_count).fold(bits, |candidates, _| candidates & (candidates - 1));
+            let object_bit = 1 << candidates.trailing_zeros();
+
+            // Clear the bit in the bitmap, starting over if the word changed.
+            if word
+                .compare_exchange_weak(
+                    bits,
+                    bits & !object_bit,
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                )
+                .is_err()
+            {
+                return None;
+            }
+
+            // `u64::trailing_zeros()` will never overflow a `usize`.
+            #[allow(clippy::cast_possible_truncation, clippy::as_conversions)]
+            return Some((word_index * u64::BITS as usize) + candidates.trailing_zeros() as usize);
+        }
+
+        None
+    }
+
+    /// The object at `object_index` within the slab headed by `header`.
+    ///
+    /// # Safety
+    ///
+    /// - `header` must point to the header of a live slab.
+    /// - `object_index` must be less than the slab's object count.
+    pub unsafe fn object(header: NonNull<Self>, object_index: usize) -> NonNull<[u8]> {
+        // Safety: Caller is required to ensure `header` is valid.
+        let header_ref = unsafe { header.as_ref() };
+        let object_size = header_ref.object_size();
+        let object_offset = header_ref.first_object_offset() + (object_index * object_size);
+
+        // Safety: Caller is required to ensure the object lies within the slab's memory.
+        let object_ptr = unsafe { header_ref.memory().add(object_offset) };
+
+        NonNull::slice_from_raw_parts(object_ptr, object_size)
+    }
+
+    /// Marks a claimed object as no longer clean, returning whether it was.
+    fn take_clean(&self, object_index: usize) -> bool {
+        let (word_index, object_bit) = bitmap_position(object_index);
+        let word = &self.clean_bitmap()[word_index];
+
+        // Objects only ever become dirty, so the bitmap needn't be modified once they have been handed out.
+        (word.load(Ordering::Relaxed) & object_bit) > 0
+            && (word.fetch_and(!object_bit, Ordering::Relaxed) & object_bit) > 0
+    }
+
+    /// Claims a free object from the slab headed by `header`, chosen at random if `random` is provided.
+    ///
+    /// Returns the object, and whether it is known to be zeroed.
+    ///
+    /// # Safety
+    ///
+    /// - `header` must point to the header of a live slab.
+    pub unsafe fn next_object(
+        header: NonNull<Self>,
+        random: Option<&dyn RandomSource>,
+    ) -> Option<(NonNull<[u8]>, bool)> {
+        // Safety: Caller is required to ensure `header` is valid.
+        let header_ref = unsafe { header.as_ref() };
+
+        let object_index = header_ref.claim_object(random)?;
+        let is_clean = header_ref.take_clean(object_index);
+
+        // Safety: Claimed objects always lie within the slab.
+        Some((unsafe { Self::object(header, object_index) }, is_clean))
+    }
+
+    /// Claims a free object aligned to `align` from the slab headed by `header`, which may be more than the objects'
+    /// own alignment, if it has one.
+    ///
+    /// Returns the object, and whether it is known to be zeroed.
+    ///
+    /// # Safety
+    ///
+    /// - `header` must point to the header of a live slab.
+    pub unsafe fn next_aligned_object(
+        header: NonNull<Self>,
+        align: usize,
+    ) -> Option<(NonNull<[u8]>, bool)> {
+        // Safety: Caller is required to ensure `header` is valid.
+        let header_ref = unsafe { header.as_ref() };
+        let object_size = header_ref.object_size();
+        let object_count = header_ref.object_count();
+        let first_object_address =
+            header_ref.memory().addr().get() + header_ref.first_object_offset();
+
+        // Objects aligned to `align` are a whole number of `align` bytes apart, as every object is a multiple of its
+        // own alignment.
+        let stride = align / align.min(1 << object_size.trailing_zeros());
+        let first_index = (0..stride.min(object_count)).find(|object_index| {
+            (first_object_address + (object_index * object_size)).is_multiple_of(align)
+        })?;
+
+        // Reserve an object first, as allocations claiming from the slab do.
+        header_ref
+            .free_count
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |free_count| {
+                free_count.checked_sub(1)
+            })
+            .ok()?;
+
+        let free_bitmap = header_ref.free_bitmap();
+        let claimed_index = (first_index..object_count)
+            .step_by(stride)
+            .find(|object_index| {
+                let (word_index, object_bit) = bitmap_position(*object_index);
+                let word = &free_bitmap[word_index];
+
+                (word.load(Ordering::Relaxed) & object_bit) > 0
+                    && (word.fetch_and(!object_bit, Ordering::Acquire) & object_bit) > 0
+            });
+
+        let Some(object_index) = claimed_index else {
+            header_ref.free_count.fetch_add(1, Ordering::Release);
+            return None;
+        };
+
+        let is_clean = header_ref.take_clean(object_index);
+
+        // Safety: Claimed objects always lie within the slab.
+        Some((unsafe { Self::object(header, object_index) }, is_clean))
+    }
+
+    /// Marks the start of a free which may access this header after returning its object.
+    pub fn begin_return(&self) {
+        self.pending_returns.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Marks the end of a free started by [`SlabHeader::begin_return`].
+    ///
+    /// The header must not be accessed by the caller afterwards, as the slab may be released.
+    pub fn end_return(&self) {
+        self.pending_returns.fetch_sub(1, Ordering::Release);
+    }
+
+    /// Whether the slab can be released without any free still accessing its header.
+    pub fn is_releasable(&self) -> bool {
+        self.pending_returns.load(Ordering::Acquire) == 0
+    }
+
+    /// Atomically marks an object free, returning whether the slab's occupancy changed as a result.
+    ///
+    /// # Safety
+    ///
+    /// - `object_ptr` must point to an object that originated from this slab.
+    ///
+    /// # Errors
+    ///
+    /// - [`Corruption::DoubleFree`] if the object is already free, in which case the slab is left unchanged.
+    pub unsafe fn return_object(&self, object_ptr: NonNull<u8>) -> Result<bool, Corruption> {
+        // Safety: `object`, lying within the slab's memory, points to the same allocation as the slab's memory.
+        let byte_offset = unsafe { object_ptr.byte_offset_from_unsigned(self.memory()) };
+        debug_assert!(byte_offset >= self.first_object_offset());
+
+        let object_index = (byte_offset - self.first_object_offset()) / self.object_size();
+
+        let (word_index, object_bit) = bitmap_position(object_index);
+
+        // Set the bit in the bitmap. Setting an already set bit leaves the bitmap unchanged.
+        let previous_bits = self.free_bitmap()[word_index].fetch_or(object_bit, Ordering::Release);
+
+        if (previous_bits & object_bit) > 0 {
+            return Err(Corruption::DoubleFree { object_ptr });
+        }
+
+        // Free counts are bounded by the object count, so widening them is lossless.
+        #[allow(clippy::as_conversions)]
+        let previous_free_count = self.free_count.fetch_add(1, Ordering::Release) as usize;
+
+        Ok(self.occupancy_of(previous_free_count) != self.occupancy_of(previous_free_count + 1))
+    }
+}
+
+/// Handle to a slab, held by its manager's lists through its header.
+///
+/// Slabs are handed to the callbacks of a [`SlabManager`](crate::SlabManager) as they are created and released, such
+/// as to construct or inspect their objects. Slabs aren't released when their handle is dropped, but explicitly by
+/// their manager, so that handles can be rebuilt from the headers linked into the lists.
+pub struct Slab {
+    header: NonNull<SlabHeader>,
+}
+
+impl Slab {
+    /// Layout of the memory of a slab spanning `page_count` pages of `PAGE_SIZE` bytes, aligned to its size.
+    ///
+    /// # Panics
+    ///
+    /// - If `page_count` isn't a power of two, or exceeds [`MAX_SLAB_PAGE_COUNT`].
+    pub(crate) const fn layout_of<const PAGE_SIZE: usize>(page_count: usize) -> Layout {
+        const { assert!(is_valid_page_size(PAGE_SIZE)) };
+        assert!(page_count.is_power_of_two() && page_count <= MAX_SLAB_PAGE_COUNT);
+
+        let slab_size = page_count * PAGE_SIZE;
+
+        // Safety: Slab size is a non-zero power of two, bounded below `isize::MAX`.
+        unsafe { Layout::from_size_align_unchecked(slab_size, slab_size) }
+    }
+
+    /// Layout of the over-sized allocation a slab of `layout` is aligned within, if the backing allocator doesn't honor
+    /// its alignment: large enough to hold the slab at any alignment the allocator may return, followed by the start of
+    /// the allocation.
+    fn unaligned_layout(layout: Layout) -> Layout {
+        // Twice the slab's size is bounded by twice the largest slab, so is always valid.
+        Layout::from_size_align(2 * layout.size(), align_of::<NonNull<u8>>()).unwrap()
+    }
+
+    /// Allocates zeroed memory for a slab of `layout` from `allocator`, along with whether it had to be aligned within
+    /// an over-sized allocation, as `allocator` didn't honor the slab's alignment.
+    fn allocate_memory(
+        layout: Layout,
+        allocator: &impl Allocator,
+    ) -> Result<(NonNull<u8>, bool), AllocError> {
+        let memory = allocator.allocate_zeroed(layout)?.cast::<u8>();
+        if memory.addr().get().is_multiple_of(layout.align()) {
+            return Ok((memory, false));
+        }
+
+        // Safety: Memory was just allocated with `layout`, and isn't referenced.
+        unsafe {
+            allocator.deallocate(memory, layout);
+        }
+
+        let unaligned_memory = allocator
+            .allocate_zeroed(Self::unaligned_layout(layout))?
+            .cast::<u8>();
+
+        // Safety: The over-sized allocation is pointer-aligned, and twice the slab's size, so the slab aligned within it
+        //         is always followed by room for a pointer.
+        unsafe {
+            let memory = unaligned_memory.add(unaligned_memory.align_offset(layout.align()));
+            memory
+                .add(layout.size())
+                .cast::<NonNull<u8>>()
+                .write(unaligned_memory);
+
+            Ok((memory, true))
+        }
+    }
+
+    /// Deallocates slab `memory` of `layout` to `allocator`, or the over-sized allocation it was aligned within if
+    /// `unaligned`.
+    ///
+    /// # Safety
+    ///
+    /// - `memory` and `unaligned` must have been returned together by [`Self::allocate_memory`] with `layout` and
+    ///   `allocator`, and the memory mustn't be used again.
+    unsafe fn deallocate_memory(
+        memory: NonNull<u8>,
+        unaligned: bool,
+        layout: Layout,
+        allocator: &impl Allocator,
+    ) {
+        // Safety: Caller is required to ensure the memory was allocated this way, and is no longer used. Over-sized
+        //         allocations store their start directly after the slab's memory.
+        unsafe {
+            if unaligned {
+                let unaligned_memory = memory.add(layout.size()).cast::<NonNull<u8>>().read();
+                allocator.deallocate(unaligned_memory, Self::unaligned_layout(layout));
+            } else {
+                allocator.deallocate(memory, layout);
+            }
+        }
+    }
+
+    /// Layout of the separate allocation holding the header of a slab of `object_size` objects and `slab_size`
+    /// bytes, when the header is external: the header, the pointer to the slab's memory, then the bitmaps.
+    fn external_header_layout(object_size: usize, slab_size: usize) -> Layout {
+        // Header sizes are bounded by the bitmaps of the largest slab, so are always valid.
+        Layout::from_size_align(
+            header_size(object_size, slab_size) + size_of::<AtomicU64>(),
+            align_of::<SlabHeader>(),
+        )
+        .unwrap()
+    }
+
+    /// Creates a slab of `object_size` objects spanning `page_count` contiguous pages of `PAGE_SIZE` bytes, with its
+    /// objects offset by the given `color`, for the given lock `shard` of its size class.
+    ///
+    /// If `external_header`, the header is allocated separately from `header_allocator`, leaving the slab's memory to
+    /// its objects.
+    ///
+    /// Should `allocator` not align the slab's memory to its size, twice the memory is allocated, and the slab aligned
+    /// within it.
+    ///
+    /// # Panics
+    ///
+    /// - If `color` isn't below the slab's [`color_count`].
+    pub(crate) fn new_in<const PAGE_SIZE: usize>(
+        object_size: usize,
+        page_count: usize,
+        color: usize,
+        shard: u8,
+        external_header: bool,
+        allocator: &impl Allocator,
+        header_allocator: &impl Allocator,
+    ) -> Result<Self, AllocError> {
+        let layout = Self::layout_of::<PAGE_SIZE>(page_count);
+
+        assert!(object_size >= MIN_OBJECT_SIZE);
+        assert!(object_size < PAGE_SIZE);
+        assert!(object_size.is_multiple_of(size_of::<usize>()));
+        let color = u8::try_from(color)
+            .ok()
+            .filter(|color| {
+                usize::from(*color) < color_count(object_size, layout.size(), external_header)
+            })
+            .expect("slab color out of range");
+
+        // Zeroed memory allows objects to be handed out zeroed without clearing them again.
+        let (memory, unaligned) = Self::allocate_memory(layout, allocator)?;
+
+        let header = if external_header {
+            match header_allocator
+                .allocate(Self::external_header_layout(object_size, layout.size()))
+            {
+                Ok(header) => header.cast::<SlabHeader>(),
+                Err(error) => {
+                    // Safety: Memory was just allocated with `layout`, and isn't referenced.
+                    unsafe {
+                        Self::deallocate_memory(memory, unaligned, layout, allocator);
+                    }
+
+                    return Err(error);
+                }
+            }
+        } else {
+            memory.cast::<SlabHeader>()
+        };
+
+        let bitmaps = if external_header {
+            // Safety: The header's own allocation holds the pointer to the slab's memory after the header, then the
+            //         bitmaps.
+            unsafe {
+                let memory_ptr = header.add(1).cast::<NonNull<u8>>();
+                memory_ptr.write(memory);
+
+                memory_ptr
+                    .byte_add(size_of::<AtomicU64>())
+                    .cast::<AtomicU64>()
+            }
+        } else {
+            // Safety: The bitmaps directly follow the header, within the slab.
+            unsafe { header.add(1).cast::<AtomicU64>() }
+        };
+        let word_count = bitmap_word_count(object_size, layout.size());
+
+        // Safety: Header memory was just allocated, and is suitably aligned.
+        unsafe {
+            header.write(SlabHeader {
+                bitmaps,
+                prev: Cell::new(None),
+                next: Cell::new(None),
+                left: Cell::new(None),
+                right: Cell::new(None),
+                empty_since: Cell::new(0),
+                // Object size is smaller than the slab size, which is bounded by `MAX_SLAB_PAGE_COUNT` pages of a
+                // valid page size, so will never truncate. Neither will the object count.
+                #[allow(clippy::cast_possible_truncation, clippy::as_conversions)]
+                object_size: object_size as u32,
+                #[allow(clippy::cast_possible_truncation, clippy::as_conversions)]
+                free_count: AtomicU32::new(
+                    object_count(object_size, layout.size(), external_header) as u32,
+                ),
+                pending_returns: AtomicU32::new(0),
+                // Trailing zeros of a `usize` will never truncate a `u8`.
+                #[allow(clippy::cast_possible_truncation)]
+                slab_size_log2: layout.size().trailing_zeros() as u8
+                    | if unaligned { UNALIGNED_MEMORY } else { 0 },
+                color,
+                shard,
+                list: Cell::new(SlabList::Empty),
+            });
+
+            // Every allocatable object starts both free and clean.
+            for word_index in 0..word_count {
+                let mask = object_mask(object_size, layout.size(), external_header, word_index);
+
+                bitmaps.add(word_index).write(AtomicU64::new(mask));
+                bitmaps
+                    .add(word_count + word_index)
+                    .write(AtomicU64::new(mask));
+            }
+        }
+
+        Ok(Self { header })
+    }
+
+    /// Rebuilds the handle of the slab headed by `header`.
+    ///
+    /// # Safety
+    ///
+    /// - `header` must point to the header of a live slab.
+    pub(crate) unsafe fn from_header(header: NonNull<SlabHeader>) -> Self {
+        Self { header }
+    }
+
+    /// Deallocates the slab's memory to `allocator`, and its header to `header_allocator` if it is external. If
+    /// `wipe`, the slab's memory is zeroed first, so the data of its objects doesn't persist in the backing memory.
+    ///
+    /// # Safety
+    ///
+    /// - The slab must have been created with `allocator` and `header_allocator`, and none of its objects or handles
+    ///   may be used again.
+    pub(crate) unsafe fn release_in(
+        self,
+        allocator: &impl Allocator,
+        header_allocator: &impl Allocator,
+        wipe: bool,
+    ) {
+        let memory = self.header().memory();
+        let unaligned = self.header().has_unaligned_memory();
+        let layout = self.layout();
+        let header_layout = self.has_external_header().then(|| {
+            Self::external_header_layout(self.header().object_size(), self.header().slab_size())
+        });
+
+        // Safety: Caller is required to ensure the slab was allocated from these allocators, and is no longer used. The
+        //         slab's header was read above, so may be wiped with the slab's memory.
+        unsafe {
+            if wipe {
+                self::wipe(NonNull::slice_from_raw_parts(memory, layout.size()));
+            }
+
+            Self::deallocate_memory(memory, unaligned, layout, allocator);
+
+            if let Some(header_layout) = header_layout {
+                header_allocator.deallocate(self.header.cast(), header_layout);
+            }
+        }
+    }
+
+    pub(crate) fn header_ptr(&self) -> NonNull<SlabHeader> {
+        self.header
+    }
+
+    pub(crate) fn header(&self) -> &SlabHeader {
+        // Safety: Header is initialized, and lives as long as `self`.
+        unsafe { self.header.as_ref() }
+    }
+
+    /// Size of each of the slab's objects.
+    pub fn object_size(&self) -> usize {
+        self.header().object_size()
+    }
+
+    /// Number of objects held in the slab, whether free or not.
+    pub fn object_count(&self) -> usize {
+        self.header().object_count()
+    }
+
+    /// Layout of the memory covered by this slab.
+    pub fn layout(&self) -> Layout {
+        let slab_size = self.header().slab_size();
+
+        // Safety: Slab was allocated with this layout, so it is valid.
+        unsafe { Layout::from_size_align_unchecked(slab_size, slab_size) }
+    }
+
+    /// Memory covered by this slab, including its header unless it is external.
+    pub fn memory(&self) -> NonNull<[u8]> {
+        NonNull::slice_from_raw_parts(self.header().memory(), self.header().slab_size())
+    }
+
+    /// Whether the slab's header is kept outside of its memory.
+    pub fn has_external_header(&self) -> bool {
+        self.header().is_external()
+    }
+
+    /// Size of the separate allocation holding the slab's header, if it is external.
+    pub(crate) fn external_header_size(&self) -> usize {
+        if self.has_external_header() {
+            Self::external_header_layout(self.header().object_size(), self.header().slab_size())
+                .size()
+        } else {
+            0
+        }
+    }
+
+    /// Currently remaining (free) objects in this slab.
+    pub fn remaining_object_count(&self) -> usize {
+        self.header().remaining_object_count()
+    }
+
+    /// Whether every object in the slab is allocated.
+    pub fn is_full(&self) -> bool {
+        self.remaining_object_count() == 0
+    }
+
+    fn object(&self, object_index: usize) -> NonNull<[u8]> {
+        debug_assert!(object_index < self.header().object_count());
+
+        // Safety: `self.header` is the header of this live slab.
+        unsafe { SlabHeader::object(self.header, object_index) }
+    }
+
+    /// Writes the slab's objects as rows of a character each, `#` if allocated or `.` if free, with a row for every
+    /// word of its bitmap.
+    pub fn write_map(&self, writer: &mut impl core::fmt::Write) -> core::fmt::Result {
+        let free_bitmap = self.header().free_bitmap();
+
+        for object_index in 0..self.header().object_count() {
+            let (word_index, object_bit) = bitmap_position(object_index);
+
+            if object_index > 0 && object_bit == 1 {
+                writer.write_char('\n')?;
+            }
+
+            let is_free = (free_bitmap[word_index].load(Ordering::Relaxed) & object_bit) > 0;
+            writer.write_char(if is_free { '.' } else { '#' })?;
+        }
+
+        writer.write_char('\n')
+    }
+
+    /// Iterates every object in this slab, whether free or not.
+    pub fn objects(&self) -> impl Iterator<Item = NonNull<[u8]>> {
+        (0..self.header().object_count()).map(|object_index| self.object(object_index))
+    }
+
+    /// Iterates the objects in this slab which are currently free.
+    pub fn free_objects(&self) -> impl Iterator<Item = NonNull<[u8]>> {
+        self.objects_where(true)
+    }
+
+    /// Iterates the objects in this slab which are currently allocated.
+    pub fn allocated_objects(&self) -> impl Iterator<Item = NonNull<[u8]>> {
+        self.objects_where(false)
+    }
+
+    fn objects_where(&self, is_free: bool) -> impl Iterator<Item = NonNull<[u8]>> {
+        let free_bitmap = self.header().free_bitmap();
+
+        (0..self.header().object_count())
+            .filter(move |object_index| {
+                let (word_index, object_bit) = bitmap_position(*object_index);
+                ((free_bitmap[word_index].load(Ordering::Acquire) & object_bit) > 0) == is_free
+            })
+            .map(|object_index| self.object(object_index))
+    }
+
+    /// Words of the slab's free bitmap, with a bit set for every free object.
+    pub(crate) fn free_bitmap_words(&self) -> impl Iterator<Item = u64> {
+        self.header()
+            .free_bitmap()
+            .iter()
+            .map(|word| word.load(Ordering::Acquire))
+    }
+
+    /// Number of objects marked free in the slab's free bitmap, and whether any bit past its last object is set.
+    pub(crate) fn count_free_bits(&self) -> (usize, bool) {
+        let header = self.header();
+
+        header.free_bitmap().iter().enumerate().fold(
+            (0, false),
+            |(count, has_stray_bits), (word_index, word)| {
+                let word = word.load(Ordering::Acquire);
+                let mask = object_mask(
+                    header.object_size(),
+                    header.slab_size(),
+                    header.is_external(),
+                    word_index,
+                );
+
+                // Bit counts of a word always fit `usize`.
+                #[allow(clippy::as_conversions)]
+                let free_bits = (word & mask).count_ones() as usize;
+
+                (count + free_bits, has_stray_bits || (word & !mask) != 0)
+            },
+        )
+    }
+
+    /// Iterates the objects in this slab which are currently allocated, but are free in `free_bitmap`, an earlier
+    /// copy of the slab's free bitmap, or every allocated object if there is none.
+    pub(crate) fn objects_allocated_since(
+        &self,
+        free_bitmap: Option<&[u64]>,
+    ) -> impl Iterator<Item = NonNull<[u8]>> {
+        let current_free_bitmap = self.header().free_bitmap();
+
+        (0..self.header().object_count())
+            .filter(move |object_index| {
+                let (word_index, object_bit) = bitmap_position(*object_index);
+                let is_allocated =
+                    (current_free_bitmap[word_index].load(Ordering::Acquire) & object_bit) == 0;
+
+                is_allocated
+                    && free_bitmap.is_none_or(|free_bitmap| {
+                        free_bitmap
+                            .get(word_index)
+                            .is_some_and(|word| (word & object_bit) > 0)
+                    })
+            })
+            .map(|object_index| self.object(object_index))
+    }
+
+    /// Marks every object free at once, returning the number of objects that were allocated.
+    ///
+    /// # Safety
+    ///
+    /// - No object of the slab may be referenced, nor the slab be accessed concurrently.
+    pub(crate) unsafe fn reset(&self) -> usize {
+        let header = self.header();
+        let slab_size = header.slab_size();
+
+        for (word_index, word) in header.free_bitmap().iter().enumerate() {
+            let mask = object_mask(
+                header.object_size(),
+                slab_size,
+                header.is_external(),
+                word_index,
+            );
+
+            word.store(mask, Ordering::Release);
+        }
+
+        let object_count = header.object_count();
+        let allocated_count = object_count - header.free_count(Ordering::Acquire);
+
+        // Object counts are bounded by the slab's size, so always fit `u32`.
+        #[allow(clippy::cast_possible_truncation, clippy::as_conversions)]
+        header
+            .free_count
+            .store(object_count as u32, Ordering::Release);
+
+        allocated_count
+    }
+}
+
+// Safety: The slab exclusively owns its memory.
+unsafe impl Send for Slab {}
+
+// Safety: Shared references to a slab only permit atomic updates of its state.
+unsafe impl Sync for Slab {}
+
+impl core::fmt::Debug for Slab {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Slab")
+            .field("object_size", &self.header().object_size())
+            .field("color", &self.header().color)
+            .field("remaining_object_count", &self.remaining_object_count())
+            .field("memory", &self.memory())
+            .field("free_bitmap", &BitmapWords(self.header().free_bitmap()))
+            .finish()
+    }
+}
+
+/// Formats the words of a bitmap in hexadecimal.
+struct BitmapWords<'a>(&'a [AtomicU64]);
+
+impl core::fmt::Debug for BitmapWords<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut words = f.debug_list();
+
+        for word in self.0 {
+            words.entry(&format_args!("{:#018x}", word.load(Ordering::Relaxed)));
+        }
+
+        words.finish()
+    }
+}
@@ -0,0 +1,354 @@
+use crate::{DEFAULT_SIZE_CLASSES, MAX_SIZE_CLASS_COUNT};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of size classes of a [`SlabAllocator`](crate::SlabAllocator) with the default size classes, including its
+/// page size class.
+pub const SIZE_CLASS_COUNT: usize = DEFAULT_SIZE_CLASSES.len() + 1;
+
+/// Snapshot of a single size class' state and activity.
+///
+/// Cumulative counts and peaks cover the time since the allocator was created, or since its statistics were last
+/// reset by [`SlabAllocator::reset_stats`](crate::SlabAllocator::reset_stats).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SizeClassStats {
+    /// Size of the objects served by the size class.
+    pub object_size: usize,
+
+    /// Objects currently allocated, excluding those cached in the magazine layer.
+    pub live_object_count: usize,
+
+    /// Total objects held by the size class' slabs, whether allocated or not.
+    pub object_capacity: usize,
+
+    /// Slabs currently held by the size class.
+    pub slab_count: usize,
+
+    /// Pages of memory currently held by the size class.
+    pub page_count: usize,
+
+    /// Cumulative number of allocations served.
+    pub allocation_count: usize,
+
+    /// Cumulative number of deallocations.
+    pub deallocation_count: usize,
+
+    /// Cumulative number of slabs created.
+    pub slab_creation_count: usize,
+
+    /// Cumulative number of bytes lost to rounding allocations up to the object size, including any redzones.
+    pub wasted_bytes: usize,
+
+    /// Peak number of objects allocated at once.
+    pub peak_live_object_count: usize,
+
+    /// Peak number of pages of memory held at once.
+    pub peak_page_count: usize,
+}
+
+/// Snapshot of the allocations served by the fallback allocator of a [`SlabAllocator`](crate::SlabAllocator), taken
+/// by [`SlabAllocator::fallback_stats`](crate::SlabAllocator::fallback_stats) if they are
+/// [tracked](crate::SlabAllocator::with_fallback_tracking).
+///
+/// Cumulative counts and peaks cover the time since tracking began, or since the allocator's statistics were last
+/// reset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FallbackStats {
+    /// Allocations currently held from the fallback allocator.
+    pub live_allocation_count: usize,
+
+    /// Bytes currently held from the fallback allocator.
+    pub live_bytes: usize,
+
+    /// Cumulative number of allocations served. Reallocations within the fallback allocator aren't counted.
+    pub allocation_count: usize,
+
+    /// Cumulative number of deallocations.
+    pub deallocation_count: usize,
+
+    /// Peak number of bytes held from the fallback allocator at once.
+    pub peak_live_bytes: usize,
+}
+
+/// Snapshot of every size class of a [`SlabAllocator`](crate::SlabAllocator).
+///
+/// With the `serde` feature, the snapshot is serialized as the sequence of its size classes' statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlabAllocatorStats {
+    size_classes: [SizeClassStats; MAX_SIZE_CLASS_COUNT + 1],
+    size_class_count: usize,
+}
+
+impl SlabAllocatorStats {
+    pub(crate) const fn new() -> Self {
+        Self {
+            size_classes: [SizeClassStats {
+                object_size: 0,
+                live_object_count: 0,
+                object_capacity: 0,
+                slab_count: 0,
+                page_count: 0,
+                allocation_count: 0,
+                deallocation_count: 0,
+                slab_creation_count: 0,
+                wasted_bytes: 0,
+                peak_live_object_count: 0,
+                peak_page_count: 0,
+            }; MAX_SIZE_CLASS_COUNT + 1],
+            size_class_count: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, stats: SizeClassStats) {
+        self.size_classes[self.size_class_count] = stats;
+        self.size_class_count += 1;
+    }
+
+    /// Statistics of each size class, in ascending order of object size.
+    pub fn size_classes(&self) -> &[SizeClassStats] {
+        &self.size_classes[..self.size_class_count]
+    }
+
+    /// Statistics of the size class of `object_size`, if there is one.
+    pub fn size_class(&self, object_size: usize) -> Option<&SizeClassStats> {
+        self.size_classes()
+            .iter()
+            .find(|size_class| size_class.object_size == object_size)
+    }
+
+    /// Publishes the snapshot to the `metrics` facade's recorder, labelling each size class' metrics with its
+    /// `object_size`.
+    ///
+    /// Current state is published as the gauges `slab_allocator_live_objects`, `slab_allocator_object_capacity`,
+    /// `slab_allocator_slabs`, and `slab_allocator_pages`. Cumulative activity is published as the counters
+    /// `slab_allocator_allocations`, `slab_allocator_deallocations`, `slab_allocator_slab_creations`, and
+    /// `slab_allocator_wasted_bytes`, from which rates are derived by the recorder. The counters restart from zero as
+    /// the allocator's statistics are reset.
+    #[cfg(feature = "metrics")]
+    pub fn record_metrics(&self) {
+        use alloc::string::ToString;
+
+        for size_class in self.size_classes() {
+            let labels = [("object_size", size_class.object_size.to_string())];
+
+            let gauges = [
+                ("slab_allocator_live_objects", size_class.live_object_count),
+                ("slab_allocator_object_capacity", size_class.object_capacity),
+                ("slab_allocator_slabs", size_class.slab_count),
+                ("slab_allocator_pages", size_class.page_count),
+            ];
+
+            for (name, value) in gauges {
+                metrics::gauge!(name, &labels).set(value as f64);
+            }
+
+            let counters = [
+                ("slab_allocator_allocations", size_class.allocation_count),
+                (
+                    "slab_allocator_deallocations",
+                    size_class.deallocation_count,
+                ),
+                (
+                    "slab_allocator_slab_creations",
+                    size_class.slab_creation_count,
+                ),
+                ("slab_allocator_wasted_bytes", size_class.wasted_bytes),
+            ];
+
+            for (name, value) in counters {
+                metrics::counter!(name, &labels).absolute(value as u64);
+            }
+        }
+    }
+}
+
+/// Number of slabs of a size class by the share of their objects that are allocated, computed on demand by
+/// [`SlabAllocator::occupancy_histogram`](crate::SlabAllocator::occupancy_histogram).
+///
+/// Many slabs in the lower buckets mean memory is held by slabs that are nearly empty, which reclamation could
+/// return if their objects were freed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OccupancyHistogram {
+    /// Slabs with no allocated objects.
+    pub empty: usize,
+
+    /// Slabs with up to a quarter of their objects allocated.
+    pub quarter: usize,
+
+    /// Slabs with more than a quarter, and up to half, of their objects allocated.
+    pub half: usize,
+
+    /// Slabs with more than half, and up to three quarters, of their objects allocated.
+    pub three_quarters: usize,
+
+    /// Slabs with more than three quarters, but not all, of their objects allocated.
+    pub nearly_full: usize,
+
+    /// Slabs with every object allocated.
+    pub full: usize,
+}
+
+impl OccupancyHistogram {
+    /// Counts a slab with `allocated_count` of its `object_count` objects allocated.
+    pub(crate) fn record(&mut self, allocated_count: usize, object_count: usize) {
+        let bucket = if allocated_count == 0 {
+            &mut self.empty
+        } else if allocated_count == object_count {
+            &mut self.full
+        } else {
+            match (allocated_count * 4).div_ceil(object_count) {
+                1 => &mut self.quarter,
+                2 => &mut self.half,
+                3 => &mut self.three_quarters,
+                _ => &mut self.nearly_full,
+            }
+        };
+
+        *bucket += 1;
+    }
+
+    /// Total number of slabs counted.
+    pub fn slab_count(&self) -> usize {
+        self.empty + self.quarter + self.half + self.three_quarters + self.nearly_full + self.full
+    }
+}
+
+/// Cumulative activity counters of a size class.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    allocation_count: AtomicUsize,
+    deallocation_count: AtomicUsize,
+    wasted_bytes: AtomicUsize,
+    live_object_count: AtomicUsize,
+    peak_live_object_count: AtomicUsize,
+}
+
+impl Counters {
+    pub const fn new() -> Self {
+        Self {
+            allocation_count: AtomicUsize::new(0),
+            deallocation_count: AtomicUsize::new(0),
+            wasted_bytes: AtomicUsize::new(0),
+            live_object_count: AtomicUsize::new(0),
+            peak_live_object_count: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn record_allocation(&self) {
+        self.record_allocations(1);
+    }
+
+    pub fn record_allocations(&self, count: usize) {
+        self.allocation_count.fetch_add(count, Ordering::Relaxed);
+
+        let live_object_count = self.live_object_count.fetch_add(count, Ordering::Relaxed) + count;
+        self.peak_live_object_count
+            .fetch_max(live_object_count, Ordering::Relaxed);
+    }
+
+    pub fn record_deallocations(&self, count: usize) {
+        self.deallocation_count.fetch_add(count, Ordering::Relaxed);
+        self.live_object_count.fetch_sub(count, Ordering::Relaxed);
+    }
+
+    pub fn record_waste(&self, wasted_bytes: usize) {
+        self.wasted_bytes.fetch_add(wasted_bytes, Ordering::Relaxed);
+    }
+
+    /// Zeroes the cumulative counters, and lowers the peak to the current number of live objects.
+    pub fn reset(&self) {
+        self.allocation_count.store(0, Ordering::Relaxed);
+        self.deallocation_count.store(0, Ordering::Relaxed);
+        self.wasted_bytes.store(0, Ordering::Relaxed);
+        self.peak_live_object_count.store(
+            self.live_object_count.load(Ordering::Relaxed),
+            Ordering::Relaxed,
+        );
+    }
+
+    pub fn allocation_count(&self) -> usize {
+        self.allocation_count.load(Ordering::Relaxed)
+    }
+
+    pub fn deallocation_count(&self) -> usize {
+        self.deallocation_count.load(Ordering::Relaxed)
+    }
+
+    pub fn wasted_bytes(&self) -> usize {
+        self.wasted_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn peak_live_object_count(&self) -> usize {
+        self.peak_live_object_count.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for SlabAllocatorStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SlabAllocatorStats {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "SlabAllocatorStats {{ size_classes: {} }}",
+            self.size_classes()
+        );
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SlabAllocatorStats {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.size_classes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SlabAllocatorStats {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StatsVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for StatsVisitor {
+            type Value = SlabAllocatorStats;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(
+                    f,
+                    "a sequence of at most {} size class statistics",
+                    MAX_SIZE_CLASS_COUNT + 1
+                )
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut stats = SlabAllocatorStats::new();
+
+                while let Some(size_class) = seq.next_element()? {
+                    if stats.size_class_count == stats.size_classes.len() {
+                        return Err(serde::de::Error::invalid_length(
+                            stats.size_class_count + 1,
+                            &self,
+                        ));
+                    }
+
+                    stats.push(size_class);
+                }
+
+                Ok(stats)
+            }
+        }
+
+        deserializer.deserialize_seq(StatsVisitor)
+    }
+}
@@ -0,0 +1,105 @@
+use core::ptr::NonNull;
+
+/// Hardware memory tagging, such as ARM's Memory Tagging Extension, applied to the objects of size classes so that
+/// accesses through dangling pointers fault.
+///
+/// Each object's memory is tagged afresh as it is handed out, and again as it is freed, so pointers to the object
+/// from before it was freed no longer match its tag. The allocator itself reaches objects and their slabs through
+/// untagged pointers, which must access memory whatever its tag, such as kernel pointers under a match-all tag.
+/// Allocations served by the fallback allocator are handed out as it returns them.
+pub trait MemoryTagger: Sync {
+    /// Tags the memory of `object`, reached through an untagged pointer, with a fresh tag, returning a pointer to it
+    /// carrying the tag.
+    fn tag(&self, object: NonNull<[u8]>) -> NonNull<u8>;
+
+    /// Strips the tag from `ptr`, returning the untagged pointer its memory was tagged through.
+    fn untag(&self, ptr: NonNull<u8>) -> NonNull<u8>;
+
+    /// Returns the untagged `ptr`, carrying the tag its memory currently holds.
+    fn load_tag(&self, ptr: NonNull<u8>) -> NonNull<u8>;
+}
+
+/// Memory tagging through the instructions of ARM's Memory Tagging Extension.
+///
+/// Tags are drawn with `irg`, so tag checking, and the tags excluded from random draws, are configured by the
+/// platform. Objects that don't span whole 16-byte granules, such as those of the 8-byte size class, share granules
+/// with their neighbours, so are handed out with the tag their memory already holds.
+#[cfg(all(target_arch = "aarch64", target_feature = "mte"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Mte;
+
+#[cfg(all(target_arch = "aarch64", target_feature = "mte"))]
+impl Mte {
+    /// Number of bytes sharing each tag.
+    const GRANULE_SIZE: usize = 16;
+
+    /// Bits of a pointer holding its tag.
+    const TAG_MASK: usize = 0xF << 56;
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "mte"))]
+impl MemoryTagger for Mte {
+    fn tag(&self, object: NonNull<[u8]>) -> NonNull<u8> {
+        let ptr = object.cast::<u8>();
+        if !ptr.addr().get().is_multiple_of(Self::GRANULE_SIZE)
+            || !object.len().is_multiple_of(Self::GRANULE_SIZE)
+        {
+            return self.load_tag(ptr);
+        }
+
+        let tagged_ptr: *mut u8;
+
+        // Safety: Drawing a tag only computes a pointer, without accessing the memory it points to.
+        #[allow(clippy::pointers_in_nomem_asm_block)]
+        unsafe {
+            core::arch::asm!(
+                "irg {tagged_ptr}, {ptr}",
+                tagged_ptr = out(reg) tagged_ptr,
+                ptr = in(reg) ptr.as_ptr(),
+                options(nomem, nostack, preserves_flags),
+            );
+        }
+
+        for offset in (0..object.len()).step_by(Self::GRANULE_SIZE) {
+            // Safety: The granule lies within the object, whose memory is held by the allocator.
+            unsafe {
+                core::arch::asm!(
+                    "stg {granule}, [{granule}]",
+                    granule = in(reg) tagged_ptr.wrapping_add(offset),
+                    options(nostack, preserves_flags),
+                );
+            }
+        }
+
+        NonNull::new(tagged_ptr).unwrap_or(ptr)
+    }
+
+    fn untag(&self, ptr: NonNull<u8>) -> NonNull<u8> {
+        // Untagged pointers fill the tag's bits with bit 55, as with the rest of their top byte.
+        ptr.map_addr(|address| {
+            let untagged_bits = if address.get() & (1 << 55) == 0 {
+                0
+            } else {
+                Self::TAG_MASK
+            };
+
+            core::num::NonZero::new((address.get() & !Self::TAG_MASK) | untagged_bits)
+                .unwrap_or(address)
+        })
+    }
+
+    fn load_tag(&self, ptr: NonNull<u8>) -> NonNull<u8> {
+        let mut tagged_ptr = ptr.as_ptr();
+
+        // Safety: Loading a tag only reads the tag memory of the granule `ptr` lies in, which the allocator holds.
+        unsafe {
+            core::arch::asm!(
+                "ldg {tagged_ptr}, [{tagged_ptr}]",
+                tagged_ptr = inout(reg) tagged_ptr,
+                options(readonly, nostack, preserves_flags),
+            );
+        }
+
+        NonNull::new(tagged_ptr).unwrap_or(ptr)
+    }
+}
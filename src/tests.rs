@@ -1,104 +1,4009 @@
-use crate::{Slab, SlabAllocator, SlabManager};
-use core::alloc::Layout;
-use std::alloc::{Allocator, Global};
+use crate::{
+    ConsistencyReport, Corruption, DEFAULT_PAGE_SIZE, DefaultRawMutex, DefaultSizeClasses,
+    GlobalSlabAllocator, Leak, LocalSlabAllocator, MAGAZINE_CAPACITY, MemoryLimit, MemoryTagger,
+    NumaSlabAllocator, NumaTopology, ObjectCache, ObjectHandle, OccupancyHistogram, PageProtector,
+    PageSource, PageSourceAllocator, RandomSource, RegionAllocator, SIZE_CLASS_COUNT,
+    SharedSlabAllocator, SizeClassStats, SizeClasses, Slab, SlabAllocator, SlabAllocatorBuilder,
+    SlabArena, SlabBox, SlabManager, SlabSelection, Topology, TryAllocError,
+    alloc_api::{AllocError, Allocator, Global, Vec},
+    list::SlabChain,
+    slab::{SlabHeader, SlabList},
+    tree::SlabTree,
+};
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    mem::MaybeUninit,
+    ptr::NonNull,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+};
+use lock_api::{GuardSend, RawMutex};
 
-const LAYOUT_64: Layout = Layout::new::<[u8; 64]>();
-const LAYOUT_128: Layout = Layout::new::<[u8; 128]>();
-const LAYOUT_256: Layout = Layout::new::<[u8; 256]>();
-const LAYOUT_512: Layout = Layout::new::<[u8; 512]>();
-const LAYOUT_1024: Layout = Layout::new::<[u8; 1024]>();
-const LAYOUT_2048: Layout = Layout::new::<[u8; 2048]>();
+struct SingleCpu;
+
+impl Topology for SingleCpu {
+    fn cpu_count(&self) -> usize {
+        1
+    }
+
+    fn current_cpu(&self) -> usize {
+        0
+    }
+}
+
+/// Pointer that can be handed between test threads.
+#[derive(Clone, Copy)]
+struct SendPtr(NonNull<u8>);
+
+// Safety: Objects are only ever accessed by one thread at a time.
+unsafe impl Send for SendPtr {}
+
+// Safety: Objects are only ever accessed by one thread at a time.
+unsafe impl Sync for SendPtr {}
+
+/// Layout of the largest allocation served by the size class of `object_size`.
+const fn class_layout(object_size: usize) -> Layout {
+    #[cfg(feature = "redzone")]
+    let object_size = object_size - (2 * crate::redzone::REDZONE_SIZE);
+
+    match Layout::from_size_align(object_size, 1) {
+        Ok(layout) => layout,
+        Err(_) => unreachable!(),
+    }
+}
+
+const LAYOUT_64: Layout = class_layout(64);
+const LAYOUT_128: Layout = class_layout(128);
+const LAYOUT_256: Layout = class_layout(256);
+const LAYOUT_512: Layout = class_layout(512);
+const LAYOUT_1024: Layout = class_layout(1024);
+const LAYOUT_2048: Layout = class_layout(2048);
 
 #[test]
 pub fn slab_allocate() {
-    let mut slab = Slab::<64, Global>::new_in(Global).unwrap();
-    assert!(slab.remaining_object_count() == 64);
+    let slab = Slab::new_in::<0x1000>(64, 1, 0, 0, false, &Global, &Global).unwrap();
+    // The first two object slots hold the slab header and its bitmaps.
+    assert!(slab.remaining_object_count() == 62);
+
+    // Safety: `slab` is live.
+    let (object, is_clean) = unsafe { SlabHeader::next_object(slab.header_ptr(), None).unwrap() };
+    assert!(is_clean);
+    assert!(slab.remaining_object_count() == 61);
+    let memory = slab.memory();
+    assert!(
+        (memory.addr().get()..(memory.addr().get() + memory.len())).contains(&object.addr().get())
+    );
+
+    // Safety: Object originated from `slab`.
+    unsafe {
+        let header = SlabHeader::containing(object.cast(), 0x1000);
+        assert!(header == slab.header_ptr());
 
-    let object = slab.next_object().unwrap();
-    assert!(slab.remaining_object_count() == 63);
+        assert!(header.as_ref().return_object(object.cast()).is_ok());
+    }
+    assert!(slab.remaining_object_count() == 62);
 
     // Safety: Object originated from `slab`.
+    let result = unsafe { slab.header().return_object(object.cast()) };
+    assert!(
+        result
+            == Err(Corruption::DoubleFree {
+                object_ptr: object.cast()
+            })
+    );
+    assert!(slab.remaining_object_count() == 62);
+
+    // Objects are no longer known to be zeroed once they have been handed out.
+    // Safety: `slab` is live.
+    let (_, is_clean) = unsafe { SlabHeader::next_object(slab.header_ptr(), None).unwrap() };
+    assert!(!is_clean);
+
+    // Safety: Slab was created with `Global`, and is no longer used.
     unsafe {
-        slab.return_object(object.as_non_null_ptr());
+        slab.release_in(&Global, &Global, false);
     }
-    assert!(slab.remaining_object_count() == 64);
 }
 
 #[test]
 pub fn slab_manager_allocate() {
-    let mut slab_manager = SlabManager::<64, Global>::new_in(Global);
+    let slab_manager = SlabManager::<_, DefaultRawMutex>::new_in(64, Global);
     assert!(slab_manager.remaining_object_count() == 0);
 
     let object = slab_manager.next_object().unwrap();
-    assert!(slab_manager.remaining_object_count == 63);
+    assert!(slab_manager.remaining_object_count() == 61);
+
+    // Safety: Object originated from `slab_manager`.
+    unsafe {
+        slab_manager.return_object(object.cast());
+    }
+    assert!(slab_manager.remaining_object_count() == 62);
+}
+
+#[test]
+pub fn slab_chain() {
+    let slabs =
+        [0, 1, 2].map(|_| Slab::new_in::<0x1000>(64, 1, 0, 0, false, &Global, &Global).unwrap());
+    let mut chain = SlabChain::new();
+
+    for slab in &slabs {
+        // Safety: Slab is live, and not linked into any list.
+        unsafe {
+            chain.push_front(slab.header_ptr());
+        }
+    }
+    assert!(chain.len() == 3);
+
+    // Slabs are unlinked from the middle of the list without disturbing their neighbours.
+    // Safety: Slab is linked into `chain`.
+    let header = unsafe { chain.remove(slabs[1].header()) };
+    assert!(header == slabs[1].header_ptr());
+    assert!(chain.len() == 2);
+    assert!(
+        chain
+            .iter()
+            .map(|slab| slab.header_ptr())
+            .eq([slabs[2].header_ptr(), slabs[0].header_ptr()])
+    );
+
+    assert!(chain.pop_front() == Some(slabs[2].header_ptr()));
+    assert!(chain.pop_front() == Some(slabs[0].header_ptr()));
+    assert!(chain.pop_front().is_none());
+
+    for slab in slabs {
+        // Safety: Slab was created with `Global`, and is no longer used.
+        unsafe {
+            slab.release_in(&Global, &Global, false);
+        }
+    }
+}
+
+#[test]
+pub fn slab_manager_lists() {
+    let mut slab_manager = SlabManager::<_, DefaultRawMutex>::new_in(2048, Global);
+    slab_manager.set_slab_page_count(1);
+
+    // Each single-page slab of 2048-byte objects holds a single object, and so is immediately full.
+    let object_1 = slab_manager.next_object().unwrap();
+    let object_2 = slab_manager.next_object().unwrap();
+    assert!(slab_manager.lists().len(SlabList::Active) == 1);
+    assert!(slab_manager.lists().len(SlabList::Full) == 1);
+
+    // Safety: Objects originated from `slab_manager`.
+    unsafe {
+        slab_manager.return_object(object_1.cast());
+    }
+    assert!(slab_manager.lists().len(SlabList::Full) == 0);
+    assert!(slab_manager.lists().len(SlabList::Empty) == 1);
+
+    // The empty slab is reused, rather than a new slab being created.
+    let object_3 = slab_manager.next_object().unwrap();
+    assert!(slab_manager.lists().slab_count() == 2);
+    assert!(slab_manager.lists().len(SlabList::Full) == 1);
+    assert!(slab_manager.lists().len(SlabList::Empty) == 0);
+
+    // Safety: Objects originated from `slab_manager`.
+    unsafe {
+        slab_manager.return_object(object_2.cast());
+        slab_manager.return_object(object_3.cast());
+    }
+    assert!(slab_manager.lists().len(SlabList::Empty) == 1);
+
+    let slab_manager = SlabManager::<_, DefaultRawMutex>::new_in(64, Global);
+
+    // Partial slabs are preferred to empty slabs when the active slab is exhausted.
+    let objects = (0..=(62 * 2))
+        .map(|_| slab_manager.next_object().unwrap())
+        .collect::<Vec<_>>();
+    assert!(slab_manager.lists().len(SlabList::Full) == 2);
 
     // Safety: Object originated from `slab_manager`.
     unsafe {
-        slab_manager.return_object(object.as_non_null_ptr());
+        slab_manager.return_object(objects[0].cast());
+    }
+    assert!(slab_manager.lists().len(SlabList::Full) == 1);
+    assert!(slab_manager.lists().len(SlabList::Partial) == 1);
+
+    for object in &objects[1..] {
+        // Safety: Object originated from `slab_manager`.
+        unsafe {
+            slab_manager.return_object(object.cast());
+        }
     }
-    assert!(slab_manager.remaining_object_count == 64);
+    assert!(slab_manager.lists().len(SlabList::Empty) == 2);
+    assert!(slab_manager.remaining_object_count() == 62 * 3);
+}
+
+#[test]
+pub fn slab_manager_fullest_slab_selection() {
+    let mut slab_manager = SlabManager::<_, DefaultRawMutex>::new_in(64, Global);
+    slab_manager.set_slab_selection(SlabSelection::Fullest);
+
+    // Fill three slabs, the last of which is left active.
+    let objects = (0..(62 * 3))
+        .map(|_| slab_manager.next_object().unwrap())
+        .collect::<Vec<_>>();
+    let slab_address_of = |object: NonNull<[u8]>| object.cast::<u8>().addr().get() & !0xFFF;
+
+    // Free more objects of the first slab than of the second, the first last, so it is the most recently relisted.
+    for object in objects[62..67].iter().chain(&objects[..10]) {
+        // Safety: Object originated from `slab_manager`.
+        unsafe {
+            slab_manager.return_object(object.cast());
+        }
+    }
+    assert!(slab_manager.lists().len(SlabList::Partial) == 2);
+
+    // The fuller second slab serves allocations once the active slab is exhausted.
+    let object = slab_manager.next_object().unwrap();
+    assert!(slab_address_of(object) == slab_address_of(objects[62]));
+
+    for object in objects[10..62]
+        .iter()
+        .chain(&objects[67..])
+        .chain([&object])
+    {
+        // Safety: Object originated from `slab_manager`, and is still allocated.
+        unsafe {
+            slab_manager.return_object(object.cast());
+        }
+    }
+}
+
+#[test]
+pub fn slab_manager_concurrent() {
+    const THREADS: usize = 4;
+    const OBJECTS_PER_THREAD: usize = 100;
+
+    let slab_manager = SlabManager::<_, DefaultRawMutex>::new_in(128, Global);
+
+    let objects = std::thread::scope(|scope| {
+        let threads = (0..THREADS)
+            .map(|_| {
+                scope.spawn(|| {
+                    (0..OBJECTS_PER_THREAD)
+                        .map(|_| SendPtr(slab_manager.next_object().unwrap().cast()))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        threads
+            .into_iter()
+            .flat_map(|thread| thread.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    // Every object handed out must be unique.
+    assert!(
+        objects
+            .iter()
+            .map(|object| object.0.addr())
+            .collect::<std::collections::BTreeSet<_>>()
+            .len()
+            == THREADS * OBJECTS_PER_THREAD
+    );
+
+    let slab_count = slab_manager.lists().slab_count();
+    assert!(slab_manager.remaining_object_count() == (slab_count * 31) - objects.len());
+
+    std::thread::scope(|scope| {
+        for objects in objects.chunks(OBJECTS_PER_THREAD) {
+            let slab_manager = &slab_manager;
+            scope.spawn(move || {
+                for object in objects {
+                    // Safety: Object originated from `slab_manager`.
+                    unsafe {
+                        slab_manager.return_object(object.0);
+                    }
+                }
+            });
+        }
+    });
+
+    assert!(slab_manager.remaining_object_count() == slab_count * 31);
+    assert!(slab_manager.lists().len(SlabList::Empty) + 1 == slab_count);
 }
 
 #[test]
 pub fn slab_allocator_allocate_one() {
-    let slab_allocator = SlabAllocator::new_in(Global);
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
 
     let allocate_64 = slab_allocator.allocate(LAYOUT_64).unwrap();
-    assert!(allocate_64.len() == 64);
+    assert!(allocate_64.len() == LAYOUT_64.size());
 
     let allocate_128 = slab_allocator.allocate(LAYOUT_128).unwrap();
-    assert!(allocate_128.len() == 128);
+    assert!(allocate_128.len() == LAYOUT_128.size());
 
     let allocate_256 = slab_allocator.allocate(LAYOUT_256).unwrap();
-    assert!(allocate_256.len() == 256);
+    assert!(allocate_256.len() == LAYOUT_256.size());
 
     let allocate_512 = slab_allocator.allocate(LAYOUT_512).unwrap();
-    assert!(allocate_512.len() == 512);
+    assert!(allocate_512.len() == LAYOUT_512.size());
 
     let allocate_1024 = slab_allocator.allocate(LAYOUT_1024).unwrap();
-    assert!(allocate_1024.len() == 1024);
+    assert!(allocate_1024.len() == LAYOUT_1024.size());
 
     let allocate_2048 = slab_allocator.allocate(LAYOUT_2048).unwrap();
-    assert!(allocate_2048.len() == 2048);
+    assert!(allocate_2048.len() == LAYOUT_2048.size());
 
     // Safety: Allocations are returned identically to their allocator.
     unsafe {
-        slab_allocator.deallocate(allocate_64.as_non_null_ptr(), LAYOUT_64);
-        slab_allocator.deallocate(allocate_128.as_non_null_ptr(), LAYOUT_128);
-        slab_allocator.deallocate(allocate_256.as_non_null_ptr(), LAYOUT_256);
-        slab_allocator.deallocate(allocate_512.as_non_null_ptr(), LAYOUT_512);
-        slab_allocator.deallocate(allocate_1024.as_non_null_ptr(), LAYOUT_1024);
-        slab_allocator.deallocate(allocate_2048.as_non_null_ptr(), LAYOUT_2048);
+        slab_allocator.deallocate(allocate_64.cast(), LAYOUT_64);
+        slab_allocator.deallocate(allocate_128.cast(), LAYOUT_128);
+        slab_allocator.deallocate(allocate_256.cast(), LAYOUT_256);
+        slab_allocator.deallocate(allocate_512.cast(), LAYOUT_512);
+        slab_allocator.deallocate(allocate_1024.cast(), LAYOUT_1024);
+        slab_allocator.deallocate(allocate_2048.cast(), LAYOUT_2048);
     }
 }
 
 #[test]
 pub fn slab_allocator_allocate_extra() {
-    let slab_allocator = SlabAllocator::new_in(Global);
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
     assert!(slab_allocator.remaining_object_count::<2048>() == 0);
 
-    let allocation_1 = slab_allocator.allocate(LAYOUT_2048).unwrap();
-    assert!(slab_allocator.remaining_object_count::<2048>() == 1);
-    let allocation_2 = slab_allocator.allocate(LAYOUT_2048).unwrap();
-    assert!(slab_allocator.remaining_object_count::<2048>() == 0);
-    let allocation_3 = slab_allocator.allocate(LAYOUT_2048).unwrap();
+    // Slabs of 2048-byte objects span four pages, holding seven objects beside their header, so the eighth
+    // allocation creates another slab.
+    assert!(slab_allocator.slab_page_count::<2048>() == 4);
+    let allocations = [6, 5, 4, 3, 2, 1, 0, 6].map(|remaining_object_count| {
+        let allocation = slab_allocator.allocate(LAYOUT_2048).unwrap();
+        assert!(slab_allocator.remaining_object_count::<2048>() == remaining_object_count);
+        allocation
+    });
+
+    for (allocation, remaining_object_count) in allocations.into_iter().zip(7..) {
+        // Safety: Allocations are returned identically to their allocator.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), LAYOUT_2048);
+        }
+        assert!(slab_allocator.remaining_object_count::<2048>() == remaining_object_count);
+    }
+}
+
+#[test]
+pub fn slab_allocator_magazine_reuse() {
+    let slab_allocator = SlabAllocator::<_>::with_magazines_in(Global, &SingleCpu);
+
+    let allocation_1 = slab_allocator.allocate(LAYOUT_64).unwrap();
+    assert!(slab_allocator.remaining_object_count::<64>() == 61);
+
+    // Safety: Allocation is returned identically to its allocator.
+    unsafe {
+        slab_allocator.deallocate(allocation_1.cast(), LAYOUT_64);
+    }
+    assert!(slab_allocator.remaining_object_count::<64>() == 61);
+    assert!(slab_allocator.cached_object_count::<64>() == 1);
+
+    let allocation_2 = slab_allocator.allocate(LAYOUT_64).unwrap();
+    assert!(allocation_1.cast::<u8>() == allocation_2.cast());
+    assert!(slab_allocator.cached_object_count::<64>() == 0);
+
+    // Safety: Allocation is returned identically to its allocator.
+    unsafe {
+        slab_allocator.deallocate(allocation_2.cast(), LAYOUT_64);
+    }
+
+    slab_allocator.flush_magazines();
+    assert!(slab_allocator.cached_object_count::<64>() == 0);
+    assert!(slab_allocator.remaining_object_count::<64>() == 62);
+}
+
+#[test]
+pub fn slab_allocator_magazine_depot() {
+    let slab_allocator = SlabAllocator::<_>::with_magazines_in(Global, &SingleCpu);
+
+    // Enough objects to fill both per-CPU magazines and spill into the depot.
+    let allocations = (0..(MAGAZINE_CAPACITY * 4))
+        .map(|_| slab_allocator.allocate(LAYOUT_128).unwrap())
+        .collect::<Vec<_>>();
+    let remaining_object_count = slab_allocator.remaining_object_count::<128>();
+
+    for allocation in &allocations {
+        // Safety: Allocation is returned identically to its allocator.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), LAYOUT_128);
+        }
+    }
+    assert!(slab_allocator.cached_object_count::<128>() == allocations.len());
+
+    let reallocations = (0..allocations.len())
+        .map(|_| slab_allocator.allocate(LAYOUT_128).unwrap())
+        .collect::<Vec<_>>();
+    assert!(slab_allocator.cached_object_count::<128>() == 0);
+    assert!(slab_allocator.remaining_object_count::<128>() == remaining_object_count);
+
+    for allocation in &reallocations {
+        // Safety: Allocation is returned identically to its allocator.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), LAYOUT_128);
+        }
+    }
+
+    slab_allocator.flush_magazines();
+    assert!(
+        slab_allocator.remaining_object_count::<128>()
+            == remaining_object_count + allocations.len()
+    );
+}
+
+#[test]
+pub fn object_cache_constructor_destructor() {
+    static CONSTRUCTED: AtomicUsize = AtomicUsize::new(0);
+    static DESTRUCTED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Object {
+        value: u64,
+        _padding: [u8; 100],
+    }
+
+    let object_cache = ObjectCache::<Object, Global>::with_constructor_in(
+        |object: &mut MaybeUninit<Object>| {
+            CONSTRUCTED.fetch_add(1, Ordering::Relaxed);
+            object.write(Object {
+                value: 0xCAFE,
+                _padding: [0; 100],
+            });
+        },
+        Some(|_: &mut Object| {
+            DESTRUCTED.fetch_add(1, Ordering::Relaxed);
+        }),
+        Global,
+    );
+
+    let object = object_cache.allocate().unwrap();
+    assert!(object.is_aligned());
+    // `Object` is served from the 128-byte class.
+    assert!(CONSTRUCTED.load(Ordering::Relaxed) == 31);
+    assert!(object_cache.remaining_object_count() == 30);
+
+    // Safety: Object was constructed by the cache, and isn't otherwise referenced.
+    unsafe {
+        assert!(object.as_ref().value == 0xCAFE);
+        object_cache.deallocate(object);
+    }
+
+    let object = object_cache.allocate().unwrap();
+    assert!(CONSTRUCTED.load(Ordering::Relaxed) == 31);
+
+    // Safety: Object is returned in its constructed state.
+    unsafe {
+        object_cache.deallocate(object);
+    }
+
+    drop(object_cache);
+    assert!(DESTRUCTED.load(Ordering::Relaxed) == 31);
+}
+
+#[test]
+pub fn slab_allocator_custom_lock() {
+    static LOCK_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    /// Raw mutex that counts how often it is locked.
+    struct CountingRawMutex(DefaultRawMutex);
+
+    // Safety: Locking is delegated to a valid raw mutex.
+    unsafe impl RawMutex for CountingRawMutex {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const INIT: Self = Self(DefaultRawMutex::INIT);
+
+        type GuardMarker = GuardSend;
+
+        fn lock(&self) {
+            LOCK_COUNT.fetch_add(1, Ordering::Relaxed);
+            RawMutex::lock(&self.0);
+        }
+
+        fn try_lock(&self) -> bool {
+            LOCK_COUNT.fetch_add(1, Ordering::Relaxed);
+            RawMutex::try_lock(&self.0)
+        }
+
+        unsafe fn unlock(&self) {
+            // Safety: Caller is required to hold the lock.
+            unsafe {
+                RawMutex::unlock(&self.0);
+            }
+        }
+    }
+
+    let slab_allocator = SlabAllocator::<Global, CountingRawMutex>::new_in(Global);
+
+    // Creating the first slab requires the size class' lock.
+    let allocation = slab_allocator.allocate(LAYOUT_256).unwrap();
+    assert!(LOCK_COUNT.load(Ordering::Relaxed) > 0);
+
+    // Safety: Allocation is returned identically to its allocator.
+    unsafe {
+        slab_allocator.deallocate(allocation.cast(), LAYOUT_256);
+    }
+}
+
+#[test]
+pub fn global_slab_allocator_allocate() {
+    static GLOBAL_ALLOCATOR: GlobalSlabAllocator<Global> = GlobalSlabAllocator::new(|| Global);
+
+    // Safety: Layout is non-zero in size.
+    let allocation = unsafe { GLOBAL_ALLOCATOR.alloc(LAYOUT_128) };
+    assert!(!allocation.is_null());
+    assert!(GLOBAL_ALLOCATOR.allocator().remaining_object_count::<128>() == 30);
+
+    // Safety: Allocation is returned identically to its allocator.
+    unsafe {
+        GLOBAL_ALLOCATOR.dealloc(allocation, LAYOUT_128);
+    }
+
+    assert!(GLOBAL_ALLOCATOR.allocator().remaining_object_count::<128>() == 31);
+}
+
+#[test]
+pub fn slab_allocator_trim() {
+    // Single-page slabs of 2048-byte objects hold a single object each.
+    let slab_allocator =
+        SlabAllocator::<_>::with_magazines_in(Global, &SingleCpu).with_slab_page_count::<2048>(1);
+
+    let allocations = (0..3)
+        .map(|_| slab_allocator.allocate(LAYOUT_2048).unwrap())
+        .collect::<Vec<_>>();
+
+    for allocation in &allocations {
+        // Safety: Allocations are returned identically to their allocator.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), LAYOUT_2048);
+        }
+    }
+    assert!(slab_allocator.cached_object_count::<2048>() == 3);
+
+    // Cached objects are flushed, and every empty slab but the active slab is released.
+    assert!(slab_allocator.trim() == 2 * 0x1000);
+    assert!(slab_allocator.cached_object_count::<2048>() == 0);
     assert!(slab_allocator.remaining_object_count::<2048>() == 1);
-    let allocation_4 = slab_allocator.allocate(LAYOUT_2048).unwrap();
-    assert!(slab_allocator.remaining_object_count::<2048>() == 0);
-    let allocation_5 = slab_allocator.allocate(LAYOUT_2048).unwrap();
+
+    // Nothing is left to release.
+    assert!(slab_allocator.trim() == 0);
+}
+
+#[test]
+pub fn slab_allocator_maintain() {
+    // Single-page slabs of 2048-byte objects hold a single object each.
+    let slab_allocator =
+        SlabAllocator::<_>::with_magazines_in(Global, &SingleCpu).with_slab_page_count::<2048>(1);
+
+    let allocations = (0..40)
+        .map(|_| slab_allocator.allocate(LAYOUT_2048).unwrap())
+        .collect::<Vec<_>>();
+
+    for allocation in &allocations {
+        // Safety: Allocations are returned identically to their allocator.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), LAYOUT_2048);
+        }
+    }
+
+    // Both of the processor's magazines are full, so a magazine of objects was moved to the depot.
+    assert!(slab_allocator.cached_object_count::<2048>() == 40);
+    slab_allocator.set_empty_slab_retention::<2048>(2);
+
+    // The depot's objects empty their slabs, of which only the retained slabs are kept.
+    assert!(slab_allocator.maintain() == (MAGAZINE_CAPACITY - 2) * 0x1000);
+    assert!(slab_allocator.cached_object_count::<2048>() == 40 - MAGAZINE_CAPACITY);
+    assert!(slab_allocator.remaining_object_count::<2048>() == 2);
+
+    // Nothing is left to do.
+    assert!(slab_allocator.maintain() == 0);
+}
+
+#[test]
+pub fn slab_allocator_slab_decay() {
+    use crate::Clock;
+    use core::time::Duration;
+
+    struct ManualClock(AtomicU64);
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Duration {
+            Duration::from_secs(self.0.load(Ordering::Relaxed))
+        }
+    }
+
+    static CLOCK: ManualClock = ManualClock(AtomicU64::new(0));
+
+    // Single-page slabs of 2048-byte objects hold a single object each.
+    let slab_allocator = SlabAllocator::<_>::new_in(Global)
+        .with_slab_page_count::<2048>(1)
+        .with_slab_decay(&CLOCK, Duration::from_secs(10));
+    slab_allocator.set_empty_slab_retention::<2048>(0);
+
+    let allocations = (0..3)
+        .map(|_| slab_allocator.allocate(LAYOUT_2048).unwrap())
+        .collect::<Vec<_>>();
+
+    for allocation in &allocations {
+        // Safety: Allocations are returned identically to their allocator.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), LAYOUT_2048);
+        }
+    }
+
+    // Emptied slabs are kept until they have decayed.
+    assert!(slab_allocator.remaining_object_count::<2048>() == 3);
+    CLOCK.0.store(9, Ordering::Relaxed);
+    assert!(slab_allocator.maintain() == 0);
+
+    CLOCK.0.store(10, Ordering::Relaxed);
+    assert!(slab_allocator.maintain() == 2 * 0x1000);
     assert!(slab_allocator.remaining_object_count::<2048>() == 1);
+}
+
+#[test]
+pub fn slab_allocator_memory_reclaim() {
+    use crate::MemoryReclaim;
+
+    // Single-page slabs of 2048-byte objects hold a single object each.
+    let slab_allocator = SlabAllocator::<_>::new_in(Global).with_slab_page_count::<2048>(1);
+
+    let allocations = (0..5)
+        .map(|_| slab_allocator.allocate(LAYOUT_2048).unwrap())
+        .collect::<Vec<_>>();
+
+    for allocation in &allocations {
+        // Safety: Allocations are returned identically to their allocator.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), LAYOUT_2048);
+        }
+    }
+
+    // Whole slabs are released until the target is met.
+    let reclaimer: &dyn MemoryReclaim = &slab_allocator;
+    assert!(reclaimer.reclaim(0) == 0);
+    assert!(reclaimer.reclaim(1) == 0x1000);
+    assert!(reclaimer.reclaim(0x1800) == 2 * 0x1000);
+
+    // Every empty slab but the active slab is released, short of the target.
+    assert!(reclaimer.reclaim(usize::MAX) == 0x1000);
+    assert!(reclaimer.reclaim(usize::MAX) == 0);
+}
+
+#[test]
+pub fn slab_allocator_empty_slab_retention() {
+    // Single-page slabs of 2048-byte objects hold a single object each.
+    let slab_allocator = SlabAllocator::<_>::new_in(Global).with_slab_page_count::<2048>(1);
+    assert!(slab_allocator.empty_slab_retention::<2048>() == usize::MAX);
+    slab_allocator.set_empty_slab_retention::<2048>(1);
+
+    let allocations = (0..4)
+        .map(|_| slab_allocator.allocate(LAYOUT_2048).unwrap())
+        .collect::<Vec<_>>();
+
+    for allocation in &allocations {
+        // Safety: Allocations are returned identically to their allocator.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), LAYOUT_2048);
+        }
+    }
+
+    // Only a single empty slab is kept, alongside the active slab.
+    assert!(slab_allocator.empty_slab_count::<2048>() == 1);
+    assert!(slab_allocator.remaining_object_count::<2048>() == 2);
+}
+
+#[test]
+pub fn slab_allocator_slab_limit() {
+    // Single-page slabs of 2048-byte objects hold a single object each.
+    let slab_allocator = SlabAllocator::<_>::new_in(Global).with_slab_page_count::<2048>(1);
+    assert!(slab_allocator.slab_limit::<2048>() == usize::MAX);
+    slab_allocator.set_slab_limit::<2048>(2);
+
+    let allocations = (0..2)
+        .map(|_| slab_allocator.allocate(LAYOUT_2048).unwrap())
+        .collect::<Vec<_>>();
+
+    // The size class is at its limit, but others are unaffected.
+    assert!(slab_allocator.allocate(LAYOUT_2048).is_err());
+    let allocation = slab_allocator.allocate(LAYOUT_1024).unwrap();
+
+    // Safety: Allocation was allocated with `LAYOUT_2048`.
+    unsafe {
+        slab_allocator.deallocate(allocations[0].cast(), LAYOUT_2048);
+    }
+
+    // Freed objects can be allocated again without creating a slab.
+    let reallocation = slab_allocator.allocate(LAYOUT_2048).unwrap();
+    assert!(slab_allocator.stats().size_class(2048).unwrap().slab_count == 2);
+
+    // Safety: Allocations are returned identically to their allocator.
+    unsafe {
+        slab_allocator.deallocate(allocation.cast(), LAYOUT_1024);
+        slab_allocator.deallocate(allocations[1].cast(), LAYOUT_2048);
+        slab_allocator.deallocate(reallocation.cast(), LAYOUT_2048);
+    }
+}
+
+#[test]
+pub fn slab_allocator_reserve() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    let object_count = 2 * crate::slab::object_count(256, 0x1000, false) + 1;
+
+    slab_allocator.reserve::<256>(object_count).unwrap();
+    assert!(slab_allocator.remaining_object_count::<256>() >= object_count);
+    assert!(
+        slab_allocator
+            .stats()
+            .size_class(256)
+            .unwrap()
+            .slab_creation_count
+            == 3
+    );
+
+    // Reserved objects are allocated without creating slabs.
+    let allocations = (0..object_count)
+        .map(|_| slab_allocator.allocate(LAYOUT_256).unwrap())
+        .collect::<Vec<_>>();
+    assert!(
+        slab_allocator
+            .stats()
+            .size_class(256)
+            .unwrap()
+            .slab_creation_count
+            == 3
+    );
+
+    // Once the slab limit is reached, reservations needing new slabs fail.
+    slab_allocator.set_slab_limit::<256>(3);
+    slab_allocator.reserve::<256>(0).unwrap();
+    assert!(slab_allocator.reserve::<256>(usize::MAX).is_err());
+
+    for allocation in allocations {
+        // Safety: Allocation was allocated with `LAYOUT_256`.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), LAYOUT_256);
+        }
+    }
+}
+
+#[test]
+pub fn slab_allocator_try_allocate() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+
+    // Without an active slab, one must be created with the size class' lock held.
+    {
+        let _lists = slab_allocator.expect_size_class(64).slabs.lists();
+        assert!(slab_allocator.try_allocate(LAYOUT_64) == Err(TryAllocError::WouldBlock));
+    }
+
+    let object = slab_allocator.try_allocate(LAYOUT_64).unwrap();
+
+    // Objects of the active slab are claimed without locking.
+    let other_object = {
+        let _lists = slab_allocator.expect_size_class(64).slabs.lists();
+        slab_allocator.try_allocate(LAYOUT_64).unwrap()
+    };
+
+    // Safety: Objects were allocated with `LAYOUT_64`.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), LAYOUT_64);
+        slab_allocator.deallocate(other_object.cast(), LAYOUT_64);
+    }
+}
+
+#[cfg(feature = "critical-section")]
+#[test]
+pub fn slab_allocator_critical_section() {
+    use crate::CriticalSectionRawMutex;
+
+    let slab_allocator = SlabAllocator::<_, CriticalSectionRawMutex>::new_in(Global);
+
+    // A lock held by the interrupted context is reported rather than waited on.
+    {
+        let _lists = slab_allocator.expect_size_class(64).slabs.lists();
+        assert!(slab_allocator.try_allocate(LAYOUT_64) == Err(TryAllocError::WouldBlock));
+    }
+
+    let allocations = (0..256)
+        .map(|_| slab_allocator.allocate(LAYOUT_64).unwrap())
+        .collect::<Vec<_>>();
+
+    for allocation in allocations {
+        // Safety: Allocation is returned identically to its allocator.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), LAYOUT_64);
+        }
+    }
+
+    let stats = *slab_allocator.stats().size_class(64).unwrap();
+    assert!(stats.live_object_count == 0);
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+pub fn tracking_allocator() {
+    use crate::{TrackedAllocation, TrackingAllocator, TrackingEvent};
+
+    let tracking_allocator = TrackingAllocator::new(SlabAllocator::<_>::new_in(Global));
+
+    let object = tracking_allocator.allocate(LAYOUT_64).unwrap().cast::<u8>();
+    let large_object = tracking_allocator
+        .allocate(Layout::from_size_align(0x2000, 8).unwrap())
+        .unwrap();
+
+    // Safety: Object was allocated with `LAYOUT_64`.
+    let grown_object = unsafe { tracking_allocator.grow(object, LAYOUT_64, LAYOUT_256) }
+        .unwrap()
+        .cast::<u8>();
+
+    let live_allocations = tracking_allocator.live_allocations();
+    assert!(live_allocations.len() == 2);
+    assert!(live_allocations[0].size_class.is_none());
+    assert!(
+        live_allocations[1]
+            == TrackedAllocation {
+                ptr: grown_object,
+                layout: LAYOUT_256,
+                size_class: Some(256),
+            }
+    );
 
     // Safety: Allocations are returned identically to their allocator.
     unsafe {
-        slab_allocator.deallocate(allocation_1.as_non_null_ptr(), LAYOUT_2048);
-        assert!(slab_allocator.remaining_object_count::<2048>() == 2);
-        slab_allocator.deallocate(allocation_2.as_non_null_ptr(), LAYOUT_2048);
-        assert!(slab_allocator.remaining_object_count::<2048>() == 3);
-        slab_allocator.deallocate(allocation_3.as_non_null_ptr(), LAYOUT_2048);
-        assert!(slab_allocator.remaining_object_count::<2048>() == 4);
-        slab_allocator.deallocate(allocation_4.as_non_null_ptr(), LAYOUT_2048);
-        assert!(slab_allocator.remaining_object_count::<2048>() == 5);
-        slab_allocator.deallocate(allocation_5.as_non_null_ptr(), LAYOUT_2048);
-        assert!(slab_allocator.remaining_object_count::<2048>() == 6);
+        tracking_allocator.deallocate(grown_object, LAYOUT_256);
+        tracking_allocator.deallocate(
+            large_object.cast(),
+            Layout::from_size_align(0x2000, 8).unwrap(),
+        );
+    }
+
+    tracking_allocator.assert_no_leaks();
+
+    let events = tracking_allocator.events();
+    assert!(events.len() == 6);
+    assert!(matches!(events[2], TrackingEvent::Deallocate(allocation) if allocation.ptr == object));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+#[should_panic = "freed while not allocated"]
+pub fn tracking_allocator_double_free() {
+    use crate::TrackingAllocator;
+
+    let tracking_allocator = TrackingAllocator::new(SlabAllocator::<_>::new_in(Global));
+    let object = tracking_allocator.allocate(LAYOUT_64).unwrap().cast::<u8>();
+
+    // Safety: The second deallocation is caught before it reaches the allocator.
+    unsafe {
+        tracking_allocator.deallocate(object, LAYOUT_64);
+        tracking_allocator.deallocate(object, LAYOUT_64);
+    }
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+pub fn failing_allocator() {
+    use crate::FailingAllocator;
+
+    let failing_allocator = FailingAllocator::new(Global);
+    let slab_allocator = SlabAllocator::<_>::new_in(&failing_allocator);
+
+    // The slab can't be created, so the allocation fails, but the size class is left usable.
+    failing_allocator.fail_nth(1);
+    assert!(slab_allocator.allocate(LAYOUT_64).is_err());
+    let object = slab_allocator.allocate(LAYOUT_64).unwrap();
+
+    // Allocations no size class serves fail as the fallback allocator fails.
+    let large_layout = Layout::from_size_align(0x2000, 8).unwrap();
+    failing_allocator.fail_nth(1);
+    assert!(slab_allocator.allocate(large_layout).is_err());
+    assert!(failing_allocator.failure_count() == 2);
+
+    // Safety: Object was allocated with `LAYOUT_64`.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), LAYOUT_64);
+    }
+
+    // Random failures follow the seed.
+    let failures = |seed| {
+        let failing_allocator = FailingAllocator::new(Global);
+        failing_allocator.fail_randomly(seed, 4);
+
+        (0..64)
+            .map(|_| {
+                let allocation = failing_allocator.allocate(LAYOUT_64).ok()?;
+
+                // Safety: Allocation is returned identically to its allocator.
+                unsafe {
+                    failing_allocator.deallocate(allocation.cast(), LAYOUT_64);
+                }
+
+                Some(())
+            })
+            .map(|allocation| allocation.is_none())
+            .collect::<Vec<_>>()
+    };
+
+    assert!(failures(7) == failures(7));
+    assert!(failures(7).contains(&true) && failures(7).contains(&false));
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+pub fn slab_allocator_oom_handler() {
+    use crate::{FailingAllocator, OutOfMemory};
+
+    static FAILED_OBJECT_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+    fn record_oom(oom: OutOfMemory) {
+        FAILED_OBJECT_SIZE.store(oom.object_size, Ordering::Relaxed);
     }
+
+    let failing_allocator = FailingAllocator::new(Global);
+    let slab_allocator =
+        SlabAllocator::<_>::new_in(&failing_allocator).with_oom_handler(record_oom);
+
+    // Once the slab lists have room, the next backing allocation is the slab's own.
+    slab_allocator.reserve::<128>(1).unwrap();
+    let object_count = slab_allocator.total_object_count::<128>() + 1;
+    failing_allocator.fail_nth(1);
+    assert!(slab_allocator.reserve::<128>(object_count).is_err());
+    assert!(FAILED_OBJECT_SIZE.load(Ordering::Relaxed) == 128);
+
+    // Pages report the page size.
+    failing_allocator.fail_nth(1);
+    assert!(slab_allocator.allocate(class_layout(4096)).is_err());
+    assert!(FAILED_OBJECT_SIZE.load(Ordering::Relaxed) == 4096);
+}
+
+#[test]
+pub fn slab_allocator_size_class_for() {
+    let slab_allocator = SlabAllocator::<_>::with_size_classes_in(Global, &[64, 192, 512]);
+
+    assert!(slab_allocator.size_class_for(Layout::from_size_align(100, 8).unwrap()) == Some(192));
+    assert!(slab_allocator.size_class_for(LAYOUT_64) == Some(64));
+    assert!(slab_allocator.size_class_for(Layout::from_size_align(100, 128).unwrap()) == Some(512));
+    assert!(slab_allocator.size_class_for(Layout::from_size_align(3000, 8).unwrap()) == Some(4096));
+    assert!(
+        slab_allocator
+            .size_class_for(Layout::from_size_align(5000, 8).unwrap())
+            .is_none()
+    );
+}
+
+#[test]
+pub fn slab_allocator_usable_size() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global).with_lazy_shrink();
+
+    let layout = Layout::from_size_align(100, 8).unwrap();
+    let object = slab_allocator.allocate(layout).unwrap();
+    let large_layout = Layout::from_size_align(0x2000, 8).unwrap();
+    let large_object = slab_allocator.allocate(large_layout).unwrap();
+
+    // Safety: Allocations were made with the given layouts.
+    unsafe {
+        assert!(slab_allocator.usable_size(object.cast(), layout) == object.len());
+        assert!(slab_allocator.usable_size(large_object.cast(), large_layout) == 0x2000);
+    }
+
+    // Objects shrunk in place keep their slot.
+    let shrunk_layout = Layout::from_size_align(40, 8).unwrap();
+    // Safety: Object was allocated with `layout`.
+    let object = unsafe { slab_allocator.shrink(object.cast(), layout, shrunk_layout) }.unwrap();
+
+    // Safety: Allocations are returned identically to their allocator.
+    unsafe {
+        #[cfg(not(feature = "redzone"))]
+        assert!(slab_allocator.usable_size(object.cast(), shrunk_layout) == 128);
+
+        slab_allocator.deallocate(object.cast(), shrunk_layout);
+        slab_allocator.deallocate(large_object.cast(), large_layout);
+    }
+}
+
+#[test]
+pub fn slab_allocator_object_counts() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    assert!(slab_allocator.total_object_count::<256>() == 0);
+
+    let objects = (0..3)
+        .map(|_| slab_allocator.allocate(LAYOUT_256).unwrap())
+        .collect::<Vec<_>>();
+
+    let total_object_count = slab_allocator.total_object_count::<256>();
+    assert!(slab_allocator.allocated_object_count::<256>() == 3);
+    assert!(
+        slab_allocator.remaining_object_count::<256>()
+            == total_object_count - slab_allocator.allocated_object_count::<256>()
+    );
+
+    for object in objects {
+        // Safety: Object was allocated with `LAYOUT_256`.
+        unsafe {
+            slab_allocator.deallocate(object.cast(), LAYOUT_256);
+        }
+    }
+
+    assert!(slab_allocator.allocated_object_count::<256>() == 0);
+    assert!(slab_allocator.remaining_object_count::<256>() == total_object_count);
+}
+
+#[test]
+pub fn slab_allocator_total_backing_bytes() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+
+    // The size classes themselves are held from the backing allocator.
+    let metadata_bytes = slab_allocator.total_backing_bytes();
+    assert!(metadata_bytes > 0);
+
+    let object = slab_allocator.allocate(LAYOUT_64).unwrap();
+    assert!(slab_allocator.total_backing_bytes() >= metadata_bytes + 0x1000);
+
+    // Safety: Object was allocated with `LAYOUT_64`.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), LAYOUT_64);
+    }
+}
+
+#[test]
+pub fn slab_allocator_owns() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+
+    let object = slab_allocator.allocate(LAYOUT_256).unwrap();
+    let page = slab_allocator
+        .allocate(Layout::from_size_align(4096, 4096).unwrap())
+        .unwrap();
+    let other_object = Global.allocate(LAYOUT_256).unwrap();
+
+    assert!(slab_allocator.owns(object.cast()));
+    assert!(
+        slab_allocator.owns(
+            object
+                .cast::<u8>()
+                .map_addr(|address| address.saturating_add(8))
+        )
+    );
+    assert!(!slab_allocator.owns(page.cast()));
+    assert!(!slab_allocator.owns(other_object.cast()));
+
+    // Safety: Allocations are returned identically to their allocators.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), LAYOUT_256);
+        slab_allocator.deallocate(page.cast(), Layout::from_size_align(4096, 4096).unwrap());
+        Global.deallocate(other_object.cast(), LAYOUT_256);
+    }
+}
+
+#[test]
+pub fn slab_allocator_owns_across_slabs() {
+    // Single-page slabs of 2048-byte objects hold one object each, so every allocation is found in a slab of its own.
+    let slab_allocator = SlabAllocator::<_>::new_in(Global).with_slab_page_count::<2048>(1);
+    let objects = (0..16)
+        .map(|_| slab_allocator.allocate(LAYOUT_2048).unwrap())
+        .collect::<Vec<_>>();
+    assert!(
+        objects
+            .iter()
+            .all(|object| slab_allocator.owns(object.cast()))
+    );
+
+    for object in &objects {
+        // Safety: Object was allocated with `LAYOUT_2048`.
+        unsafe {
+            slab_allocator.deallocate(object.cast(), LAYOUT_2048);
+        }
+    }
+
+    // Released slabs are no longer owned.
+    slab_allocator.trim();
+    let owned_count = objects
+        .iter()
+        .filter(|object| slab_allocator.owns(object.cast()))
+        .count();
+    assert!(owned_count == slab_allocator.stats().size_class(2048).unwrap().slab_count);
+}
+
+#[test]
+pub fn slab_tree() {
+    let mut slabs = (0..32)
+        .map(|_| Slab::new_in::<0x1000>(64, 1, 0, 0, false, &Global, &Global).unwrap())
+        .collect::<alloc::vec::Vec<_>>();
+    slabs.sort_by_key(|slab| slab.memory().addr());
+    let address_of = |slab: &Slab| slab.memory().addr().get();
+
+    let mut tree = SlabTree::new();
+    for slab in &slabs {
+        // Safety: Slab is live, not linked into any tree, and doesn't share its memory with any other slab.
+        unsafe {
+            tree.insert(slab.header_ptr());
+        }
+    }
+
+    // Every other slab is unlinked, without disturbing the order of those remaining.
+    for slab in slabs.iter().step_by(2) {
+        // Safety: Slab is linked into `tree`.
+        unsafe {
+            tree.remove(slab.header());
+        }
+    }
+
+    for (index, slab) in slabs.iter().enumerate() {
+        let address = address_of(slab);
+        let expected = (index % 2 == 1).then(|| slab.header_ptr());
+        assert!(tree.get(address) == expected);
+
+        let last_before = (0..index).rev().find(|index| index % 2 == 1);
+        assert!(tree.last_before(address) == last_before.map(|index| slabs[index].header_ptr()));
+
+        let first_after = ((index + 1)..slabs.len()).find(|index| index % 2 == 1);
+        assert!(tree.first_after(address) == first_after.map(|index| slabs[index].header_ptr()));
+    }
+
+    for slab in slabs.iter().skip(1).step_by(2) {
+        // Safety: Slab is linked into `tree`.
+        unsafe {
+            tree.remove(slab.header());
+        }
+    }
+    assert!(
+        slabs
+            .iter()
+            .all(|slab| tree.get(address_of(slab)).is_none())
+    );
+
+    for slab in slabs {
+        // Safety: Slab was created with `Global`, and is no longer used.
+        unsafe {
+            slab.release_in(&Global, &Global, false);
+        }
+    }
+}
+
+#[test]
+pub fn slab_allocator_external_headers() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global)
+        .with_external_headers()
+        .with_checked_deallocation();
+    let layout = class_layout(64);
+
+    let objects = (0..64)
+        .map(|_| slab_allocator.allocate(layout).unwrap())
+        .collect::<Vec<_>>();
+    assert!(slab_allocator.total_object_count::<64>() == 64);
+
+    // Objects are handed out past their leading redzone.
+    #[cfg(feature = "redzone")]
+    let slot_offset = crate::redzone::REDZONE_SIZE;
+    #[cfg(not(feature = "redzone"))]
+    let slot_offset = 0;
+
+    // Without a header, the slab's memory holds nothing but objects.
+    let slab_address = (objects[0].cast::<u8>().addr().get() - slot_offset) & !0xFFF;
+    let mut object_offsets = objects
+        .iter()
+        .map(|object| object.cast::<u8>().addr().get() - slot_offset - slab_address)
+        .collect::<Vec<_>>();
+    object_offsets.sort_unstable();
+    assert!(
+        object_offsets
+            == (0..64)
+                .map(|object_index| object_index * 64)
+                .collect::<Vec<_>>()
+    );
+
+    for object in &objects {
+        assert!(slab_allocator.owns(object.cast()));
+
+        // Safety: Object was allocated with `layout`.
+        unsafe {
+            slab_allocator.deallocate(object.cast(), layout);
+        }
+    }
+
+    assert!(slab_allocator.allocated_object_count::<64>() == 0);
+}
+
+#[test]
+pub fn slab_allocator_metadata_allocator() {
+    /// Allocator counting its live allocations.
+    struct CountingAllocator(AtomicUsize);
+
+    // Safety: Allocations are served by `Global`.
+    unsafe impl Allocator for CountingAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.0.fetch_sub(1, Ordering::Relaxed);
+
+            // Safety: Memory was allocated by `Global` with `layout`.
+            unsafe {
+                Global.deallocate(ptr, layout);
+            }
+        }
+    }
+
+    static METADATA: CountingAllocator = CountingAllocator(AtomicUsize::new(0));
+
+    let slab_allocator = SlabAllocator::<_>::new_in(Global)
+        .with_external_headers()
+        .with_metadata_allocator(&METADATA);
+    assert!(METADATA.0.load(Ordering::Relaxed) == 0);
+
+    // Only the slab's header is allocated from the metadata allocator, as slabs are found by address through their
+    // headers.
+    let object = slab_allocator.allocate(LAYOUT_64).unwrap();
+    assert!(METADATA.0.load(Ordering::Relaxed) == 1);
+
+    // Safety: Object was allocated with `LAYOUT_64`.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), LAYOUT_64);
+    }
+
+    drop(slab_allocator);
+    assert!(METADATA.0.load(Ordering::Relaxed) == 0);
+}
+
+#[test]
+pub fn slab_allocator_slab_coloring() {
+    // Slabs hold 12 320-byte objects, leaving 176 bytes unused, so successive slabs cycle through three colors.
+    let slab_allocator =
+        SlabAllocator::<_>::with_size_classes_in(Global, &[320]).with_checked_deallocation();
+    let layout = class_layout(320);
+    let objects_per_slab = 12;
+
+    let objects = (0..(5 * objects_per_slab))
+        .map(|_| slab_allocator.allocate(layout).unwrap())
+        .collect::<Vec<_>>();
+
+    // Objects are handed out past their leading redzone.
+    #[cfg(feature = "redzone")]
+    let slot_offset = crate::redzone::REDZONE_SIZE;
+    #[cfg(not(feature = "redzone"))]
+    let slot_offset = 0;
+
+    let first_object_offsets = objects
+        .chunks(objects_per_slab)
+        .map(|slab_objects| (slab_objects[0].cast::<u8>().addr().get() - slot_offset) % 0x1000)
+        .collect::<Vec<_>>();
+
+    assert!(first_object_offsets[..3] == [256, 192, 128]);
+    assert!(first_object_offsets[3] == first_object_offsets[0]);
+
+    // Colored objects stay aligned, and are freed by their offset within their own slab.
+    for object in objects {
+        assert!((object.cast::<u8>().addr().get() - slot_offset).is_multiple_of(64));
+
+        // Safety: Object was allocated with `layout`.
+        unsafe {
+            slab_allocator.deallocate(object.cast(), layout);
+        }
+    }
+}
+
+#[test]
+pub fn slab_allocator_size_classes_on_own_cache_lines() {
+    let allocator = SlabAllocator::<Global>::new_in(Global);
+
+    for size_class in allocator.size_class_chunks().iter().flatten() {
+        let address = core::ptr::from_ref(size_class).addr();
+
+        assert!(address % crate::slab::CACHE_LINE_SIZE == 0);
+    }
+}
+
+#[test]
+pub fn slab_allocator_lock_shards() {
+    static CURRENT_CPU: AtomicUsize = AtomicUsize::new(0);
+
+    struct TwoCpus;
+
+    impl Topology for TwoCpus {
+        fn cpu_count(&self) -> usize {
+            2
+        }
+
+        fn current_cpu(&self) -> usize {
+            CURRENT_CPU.load(Ordering::Relaxed)
+        }
+    }
+
+    let slab_allocator = SlabAllocator::<_>::new_in(Global).with_lock_shards::<64>(&TwoCpus, 2);
+    let layout = class_layout(64);
+
+    // Each processor allocates from a slab of its own shard.
+    CURRENT_CPU.store(0, Ordering::Relaxed);
+    let first = slab_allocator.allocate(layout).unwrap();
+    CURRENT_CPU.store(1, Ordering::Relaxed);
+    let second = slab_allocator.allocate(layout).unwrap();
+
+    assert!(slab_allocator.stats().size_class(64).unwrap().slab_count == 2);
+
+    // Objects freed on another processor are returned to the shard they were allocated from.
+    CURRENT_CPU.store(0, Ordering::Relaxed);
+    // Safety: Object was allocated with `layout`.
+    unsafe {
+        slab_allocator.deallocate(second.cast(), layout);
+    }
+
+    CURRENT_CPU.store(1, Ordering::Relaxed);
+    let reallocated = slab_allocator.allocate(layout).unwrap();
+    assert!(reallocated.cast::<u8>() == second.cast::<u8>());
+
+    // Safety: Objects were allocated with `layout`.
+    unsafe {
+        slab_allocator.deallocate(first.cast(), layout);
+        slab_allocator.deallocate(reallocated.cast(), layout);
+    }
+
+    let stats = *slab_allocator.stats().size_class(64).unwrap();
+    assert!(stats.live_object_count == 0 && stats.slab_count == 2);
+}
+
+#[test]
+pub fn slab_allocator_stats() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+
+    let allocations = (0..3)
+        .map(|_| slab_allocator.allocate(LAYOUT_256).unwrap())
+        .collect::<Vec<_>>();
+
+    // Safety: Allocation is returned identically to its allocator.
+    unsafe {
+        slab_allocator.deallocate(allocations[0].cast(), LAYOUT_256);
+    }
+
+    let stats = *slab_allocator.stats().size_class(256).unwrap();
+    assert!(stats.object_size == 256);
+    assert!(stats.live_object_count == 2);
+    assert!(stats.object_capacity == 15);
+    assert!(stats.slab_count == 1);
+    assert!(stats.page_count == 1);
+    assert!(stats.allocation_count == 3);
+    assert!(stats.deallocation_count == 1);
+    assert!(stats.slab_creation_count == 1);
+
+    // Other size classes are untouched.
+    assert!(
+        *slab_allocator.stats().size_class(64).unwrap()
+            == SizeClassStats {
+                object_size: 64,
+                ..Default::default()
+            }
+    );
+}
+
+#[test]
+pub fn slab_allocator_wasted_bytes() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+
+    let layout = Layout::from_size_align(200, 8).unwrap();
+    let size_class = slab_allocator.size_class_for(layout).unwrap();
+    let mut objects = [NonNull::slice_from_raw_parts(NonNull::dangling(), 0); 3];
+
+    let object = slab_allocator.allocate(layout).unwrap();
+    assert!(slab_allocator.allocate_many(layout, &mut objects) == objects.len());
+
+    let stats = *slab_allocator.stats().size_class(size_class).unwrap();
+    assert!(stats.wasted_bytes == (size_class - 200) * 4);
+
+    // Safety: Objects were allocated with `layout`.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), layout);
+        slab_allocator.deallocate_many(&objects.map(NonNull::cast), layout);
+    }
+
+    // Waste is cumulative, so freeing objects doesn't reduce it.
+    let stats = *slab_allocator.stats().size_class(size_class).unwrap();
+    assert!(stats.wasted_bytes == (size_class - 200) * 4);
+}
+
+#[test]
+pub fn slab_allocator_peak_stats() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+
+    let objects = (0..20)
+        .map(|_| slab_allocator.allocate(LAYOUT_256).unwrap())
+        .collect::<Vec<_>>();
+
+    for object in objects {
+        // Safety: Object was allocated with `LAYOUT_256`.
+        unsafe {
+            slab_allocator.deallocate(object.cast(), LAYOUT_256);
+        }
+    }
+
+    slab_allocator.trim();
+    let object = slab_allocator.allocate(LAYOUT_256).unwrap();
+
+    // Peaks outlast the objects and slabs that made them.
+    let stats = *slab_allocator.stats().size_class(256).unwrap();
+    assert!(stats.live_object_count == 1);
+    assert!(stats.peak_live_object_count == 20);
+    assert!(stats.page_count == 1);
+    assert!(stats.peak_page_count == 2);
+
+    // Safety: Object was allocated with `LAYOUT_256`.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), LAYOUT_256);
+    }
+}
+
+#[test]
+pub fn slab_allocator_reset_stats() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+
+    let objects = (0..20)
+        .map(|_| slab_allocator.allocate(LAYOUT_256).unwrap())
+        .collect::<Vec<_>>();
+
+    // Safety: Object was allocated with `LAYOUT_256`.
+    unsafe {
+        slab_allocator.deallocate(objects[0].cast(), LAYOUT_256);
+    }
+
+    slab_allocator.reset_stats();
+
+    let stats = *slab_allocator.stats().size_class(256).unwrap();
+    assert!(stats.allocation_count == 0);
+    assert!(stats.deallocation_count == 0);
+    assert!(stats.slab_creation_count == 0);
+    assert!(stats.live_object_count == 19);
+    assert!(stats.peak_live_object_count == 19);
+    assert!(stats.peak_page_count == stats.page_count);
+
+    for object in &objects[1..] {
+        // Safety: Object was allocated with `LAYOUT_256`.
+        unsafe {
+            slab_allocator.deallocate(object.cast(), LAYOUT_256);
+        }
+    }
+
+    let stats = *slab_allocator.stats().size_class(256).unwrap();
+    assert!(stats.deallocation_count == 19);
+    assert!(stats.peak_live_object_count == 19);
+}
+
+#[test]
+pub fn slab_allocator_occupancy_histogram() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    assert!(slab_allocator.occupancy_histogram::<256>() == OccupancyHistogram::default());
+
+    // Fill one slab, and a third of another.
+    let objects_per_slab = slab_allocator
+        .expect_size_class(256)
+        .slabs
+        .objects_per_slab();
+    let objects = (0..(objects_per_slab + (objects_per_slab / 3)))
+        .map(|_| slab_allocator.allocate(LAYOUT_256).unwrap())
+        .collect::<Vec<_>>();
+
+    let histogram = slab_allocator.occupancy_histogram::<256>();
+    assert!(histogram.full == 1);
+    assert!(histogram.half == 1);
+    assert!(histogram.slab_count() == 2);
+
+    for object in objects {
+        // Safety: Object was allocated with `LAYOUT_256`.
+        unsafe {
+            slab_allocator.deallocate(object.cast(), LAYOUT_256);
+        }
+    }
+
+    let histogram = slab_allocator.occupancy_histogram::<256>();
+    assert!(histogram.empty == 2);
+}
+
+#[test]
+pub fn slab_allocator_debug() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    let object = slab_allocator.allocate(LAYOUT_256).unwrap();
+
+    let debug = std::format!("{slab_allocator:?}");
+    assert!(debug.contains("object_size: 256"));
+    assert!(debug.contains("free_bitmap: [0x"));
+
+    // Locked slab lists are skipped rather than waited on.
+    let lists = slab_allocator.expect_size_class(256).slabs.lists();
+    assert!(std::format!("{slab_allocator:?}").contains("lists: <locked>"));
+    drop(lists);
+
+    // Safety: Object was allocated with `LAYOUT_256`.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), LAYOUT_256);
+    }
+}
+
+#[test]
+pub fn slab_allocator_check_consistency() {
+    let slab_allocator = SlabAllocator::<_>::with_size_classes_in(Global, &[64, 256]);
+
+    let objects = (0..3)
+        .map(|_| slab_allocator.allocate(LAYOUT_256).unwrap())
+        .collect::<Vec<_>>();
+    let object = slab_allocator.allocate(LAYOUT_64).unwrap();
+
+    // Safety: Object was allocated with `LAYOUT_256`.
+    unsafe {
+        slab_allocator.deallocate(objects[1].cast(), LAYOUT_256);
+    }
+
+    let report = slab_allocator.check_consistency();
+    assert!(report.is_consistent());
+    assert!(report.inconsistencies().next().is_none());
+    assert!(report.checked_slab_count() == 2);
+    assert!(report.locked_shard_count() == 0);
+
+    // Safety: Objects were allocated with their layouts.
+    unsafe {
+        slab_allocator.deallocate(objects[0].cast(), LAYOUT_256);
+        slab_allocator.deallocate(objects[2].cast(), LAYOUT_256);
+        slab_allocator.deallocate(object.cast(), LAYOUT_64);
+    }
+}
+
+#[test]
+pub fn slab_manager_check_consistency_skips_locked() {
+    let slab_manager = SlabManager::<_, DefaultRawMutex>::new_in(64, Global);
+    let object = slab_manager.next_object().unwrap();
+
+    let mut report = ConsistencyReport::new();
+    let lists = slab_manager.lists();
+    slab_manager.check_consistency(&mut report);
+    drop(lists);
+    assert!(report.locked_shard_count() == 1);
+    assert!(report.checked_slab_count() == 0);
+
+    slab_manager.check_consistency(&mut report);
+    assert!(report.is_consistent());
+    assert!(report.checked_slab_count() == 1);
+
+    // Safety: Object originated from `slab_manager`.
+    unsafe {
+        slab_manager.return_object(object.cast());
+    }
+}
+
+#[test]
+pub fn slab_allocator_dump_map() {
+    let slab_allocator = SlabAllocator::<_>::with_size_classes_in(Global, &[64, 256]);
+
+    let objects = (0..3)
+        .map(|_| slab_allocator.allocate(LAYOUT_256).unwrap())
+        .collect::<Vec<_>>();
+
+    // Safety: Object was allocated with `LAYOUT_256`.
+    unsafe {
+        slab_allocator.deallocate(objects[1].cast(), LAYOUT_256);
+    }
+
+    let mut map = std::string::String::new();
+    slab_allocator.dump_map(&mut map).unwrap();
+
+    let mut lines = map.lines();
+    assert!(lines.next() == Some("64: 0 slabs"));
+    assert!(lines.next() == Some("256: 1 slab"));
+
+    let slab_map = lines.next().unwrap();
+    assert!(slab_map.len() == slab_allocator.total_object_count::<256>());
+    assert!(slab_map.starts_with("#.#."));
+    assert!(lines.next().is_none());
+
+    // Safety: Objects were allocated with `LAYOUT_256`.
+    unsafe {
+        slab_allocator.deallocate(objects[0].cast(), LAYOUT_256);
+        slab_allocator.deallocate(objects[2].cast(), LAYOUT_256);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+pub fn slab_allocator_stats_serde() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    let object = slab_allocator.allocate(LAYOUT_256).unwrap();
+
+    let stats = slab_allocator.stats();
+    let json = serde_json::to_string(&stats).unwrap();
+    assert!(json.starts_with("[{\"object_size\":"));
+    assert!(serde_json::from_str::<crate::SlabAllocatorStats>(&json).unwrap() == stats);
+
+    let histogram = slab_allocator.occupancy_histogram::<256>();
+    let json = serde_json::to_string(&histogram).unwrap();
+    assert!(serde_json::from_str::<OccupancyHistogram>(&json).unwrap() == histogram);
+
+    // Snapshots can't hold more size classes than an allocator can have.
+    let size_class = serde_json::to_string(&SizeClassStats::default()).unwrap();
+    let too_many = std::format!("[{}]", std::vec![size_class; 64].join(","));
+    assert!(serde_json::from_str::<crate::SlabAllocatorStats>(&too_many).is_err());
+
+    // Safety: Object was allocated with `LAYOUT_256`.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), LAYOUT_256);
+    }
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+pub fn slab_allocator_record_metrics() {
+    use metrics::{
+        Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+    };
+    use std::sync::{Arc, Mutex};
+
+    /// Recorder keeping the last value of every metric, as the bits of a `f64` for gauges.
+    #[derive(Default)]
+    struct TestRecorder(Mutex<Vec<(Key, Arc<AtomicU64>)>>);
+
+    impl TestRecorder {
+        fn register(&self, key: &Key) -> Arc<AtomicU64> {
+            let mut metrics = self.0.lock().unwrap();
+            if let Some((_, value)) = metrics.iter().find(|(other, _)| other == key) {
+                return value.clone();
+            }
+
+            let value = Arc::new(AtomicU64::new(0));
+            metrics.push((key.clone(), value.clone()));
+            value
+        }
+
+        fn value(&self, name: &str, object_size: usize) -> u64 {
+            let object_size = std::format!("{object_size}");
+            let metrics = self.0.lock().unwrap();
+            let (_, value) = metrics
+                .iter()
+                .find(|(key, _)| {
+                    key.name() == name
+                        && key.labels().any(|label| {
+                            label.key() == "object_size" && label.value() == object_size
+                        })
+                })
+                .unwrap();
+
+            value.load(Ordering::Relaxed)
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.register(key))
+        }
+
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::from_arc(self.register(key))
+        }
+
+        fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    let layout = class_layout(64);
+    let objects = [(); 3].map(|()| slab_allocator.allocate(layout).unwrap());
+
+    let recorder = TestRecorder::default();
+    metrics::with_local_recorder(&recorder, || slab_allocator.record_metrics());
+
+    let live_objects = f64::from_bits(recorder.value("slab_allocator_live_objects", 64));
+    assert!(live_objects == 3.0);
+    assert!(f64::from_bits(recorder.value("slab_allocator_slabs", 64)) == 1.0);
+    assert!(recorder.value("slab_allocator_allocations", 64) == 3);
+    assert!(recorder.value("slab_allocator_allocations", 128) == 0);
+
+    for object in objects {
+        // Safety: Object was allocated with `layout`.
+        unsafe {
+            slab_allocator.deallocate(object.cast(), layout);
+        }
+    }
+
+    metrics::with_local_recorder(&recorder, || slab_allocator.record_metrics());
+    assert!(f64::from_bits(recorder.value("slab_allocator_live_objects", 64)) == 0.0);
+    assert!(recorder.value("slab_allocator_deallocations", 64) == 3);
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+pub fn slab_allocator_tracing() {
+    use std::sync::{Arc, Mutex};
+    use tracing::{Event, Metadata, Subscriber, span};
+
+    /// Subscriber recording the name of every span created.
+    #[derive(Default)]
+    struct SpanNames(Mutex<Vec<&'static str>>);
+
+    impl Subscriber for SpanNames {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            let mut names = self.0.lock().unwrap();
+            names.push(span.metadata().name());
+            span::Id::from_u64(names.len() as u64)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, _: &Event<'_>) {}
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    let subscriber = Arc::new(SpanNames::default());
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+
+    tracing::subscriber::with_default(subscriber.clone(), || {
+        // Enough objects to fill more than the active slab, so emptied slabs are left to reclaim.
+        let objects = (0..256)
+            .map(|_| slab_allocator.allocate(LAYOUT_64).unwrap())
+            .collect::<Vec<_>>();
+
+        let large_layout = Layout::from_size_align(0x2000, 8).unwrap();
+        let large_object = slab_allocator.allocate(large_layout).unwrap();
+
+        // Safety: Objects were allocated with the layouts they are freed with.
+        unsafe {
+            for object in objects {
+                slab_allocator.deallocate(object.cast(), LAYOUT_64);
+            }
+
+            slab_allocator.deallocate(large_object.cast(), large_layout);
+        }
+
+        assert!(slab_allocator.trim() > 0);
+    });
+
+    let names = subscriber.0.lock().unwrap();
+    assert!(names.contains(&"create_slab"));
+    assert!(names.contains(&"fallthrough"));
+    assert!(names.contains(&"reclaim"));
+}
+
+#[cfg(all(feature = "log", feature = "test-utils"))]
+#[test]
+pub fn slab_allocator_log() {
+    use log::{Level, LevelFilter, Log, Metadata, Record};
+    use std::{string::String, sync::Mutex};
+
+    /// Logger keeping every message logged by the allocator.
+    struct TestLogger(Mutex<Vec<(Level, String)>>);
+
+    impl Log for TestLogger {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record<'_>) {
+            if record.target().starts_with("slab_allocator") {
+                let message = std::format!("{}", record.args());
+                self.0.lock().unwrap().push((record.level(), message));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: TestLogger = TestLogger(Mutex::new(Vec::new()));
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Debug);
+
+    let failing = crate::FailingAllocator::new(Global);
+    let slab_allocator = SlabAllocator::<_>::new_in(&failing).with_fallback(Global);
+
+    let object = slab_allocator.allocate(LAYOUT_64).unwrap();
+    let large_layout = Layout::from_size_align(0x2000, 8).unwrap();
+    let large_object = slab_allocator.allocate(large_layout).unwrap();
+
+    // Once the slab lists have room, the next backing allocation is the slab's own.
+    slab_allocator.reserve::<128>(1).unwrap();
+    failing.fail_nth(1);
+    let object_count = slab_allocator.total_object_count::<128>() + 1;
+    assert!(slab_allocator.reserve::<128>(object_count).is_err());
+
+    // Safety: Objects were allocated with the layouts they are freed with.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), LAYOUT_64);
+        slab_allocator.deallocate(large_object.cast(), large_layout);
+    }
+
+    let messages = LOGGER.0.lock().unwrap();
+    let logged = |level, text: &str| {
+        messages
+            .iter()
+            .any(|(other_level, message)| *other_level == level && message.contains(text))
+    };
+
+    assert!(logged(Level::Debug, "created a slab of"));
+    assert!(logged(
+        Level::Debug,
+        "falls through to the fallback allocator"
+    ));
+    assert!(logged(
+        Level::Warn,
+        "failed to allocate a slab of 128-byte objects"
+    ));
+}
+
+#[cfg(feature = "defmt")]
+#[test]
+pub fn slab_allocator_defmt() {
+    // Formatting requires a global logger of the target, so only check every type can be formatted.
+    const fn assert_format<T: defmt::Format>() {}
+
+    assert_format::<SizeClassStats>();
+    assert_format::<crate::SlabAllocatorStats>();
+    assert_format::<OccupancyHistogram>();
+    assert_format::<TryAllocError>();
+    assert_format::<Corruption>();
+}
+
+#[test]
+#[cfg(feature = "zeroize-on-free")]
+pub fn slab_allocator_zeroize_on_free() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+
+    let allocation = slab_allocator.allocate(LAYOUT_64).unwrap();
+
+    // Safety: Allocation is valid for its length.
+    unsafe {
+        allocation.cast::<u8>().write_bytes(0xFF, allocation.len());
+        slab_allocator.deallocate(allocation.cast(), LAYOUT_64);
+    }
+
+    // The lowest free object is always claimed first, so the freed object is handed out again.
+    let allocation = slab_allocator.allocate(LAYOUT_64).unwrap();
+
+    // Safety: Allocation is valid for its length.
+    assert!(unsafe { allocation.as_ref() }.iter().all(|byte| *byte == 0));
+}
+
+#[test]
+#[cfg(feature = "poison")]
+#[should_panic = "written to after being freed"]
+pub fn slab_allocator_poison() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+
+    let allocation = slab_allocator.allocate(LAYOUT_64).unwrap();
+
+    // Safety: Allocation is returned identically to its allocator.
+    unsafe {
+        slab_allocator.deallocate(allocation.cast(), LAYOUT_64);
+    }
+
+    // Safety: The object is still backed by its slab, so the write itself is sound.
+    unsafe {
+        allocation.cast::<u8>().write(0);
+    }
+
+    // The lowest free object is always claimed first, so the corrupted object is handed out again.
+    let _ = slab_allocator.allocate(LAYOUT_64);
+}
+
+#[test]
+#[cfg(feature = "redzone")]
+#[should_panic = "redzone around object"]
+pub fn slab_allocator_redzone_overflow() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    let layout = Layout::new::<[u8; 24]>();
+
+    let allocation = slab_allocator.allocate(layout).unwrap();
+    assert!(allocation.len() == 24);
+
+    // Safety: Allocation is returned identically to its allocator, and the redzone is backed by its slot.
+    unsafe {
+        allocation.cast::<u8>().add(24).write(0);
+        slab_allocator.deallocate(allocation.cast(), layout);
+    }
+}
+
+#[test]
+pub fn slab_allocator_quarantine() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    slab_allocator.set_quarantine_capacity::<64>(2).unwrap();
+
+    let allocation_1 = slab_allocator.allocate(LAYOUT_64).unwrap();
+
+    // Safety: Allocation is returned identically to its allocator.
+    unsafe {
+        slab_allocator.deallocate(allocation_1.cast(), LAYOUT_64);
+    }
+    assert!(slab_allocator.quarantined_object_count::<64>() == 1);
+
+    // The quarantined object isn't handed out again.
+    let allocation_2 = slab_allocator.allocate(LAYOUT_64).unwrap();
+    let allocation_3 = slab_allocator.allocate(LAYOUT_64).unwrap();
+    assert!(allocation_2.cast::<u8>() != allocation_1.cast());
+    assert!(allocation_3.cast::<u8>() != allocation_1.cast());
+
+    // Safety: Allocations are returned identically to their allocator.
+    unsafe {
+        slab_allocator.deallocate(allocation_2.cast(), LAYOUT_64);
+        slab_allocator.deallocate(allocation_3.cast(), LAYOUT_64);
+    }
+
+    // The oldest object is evicted to make room.
+    assert!(slab_allocator.quarantined_object_count::<64>() == 2);
+    assert!(slab_allocator.remaining_object_count::<64>() == 60);
+
+    slab_allocator.flush_quarantine();
+    assert!(slab_allocator.quarantined_object_count::<64>() == 0);
+    assert!(slab_allocator.remaining_object_count::<64>() == 62);
+}
+
+#[test]
+pub fn slab_allocator_randomized_order() {
+    /// Deterministic xorshift generator, so the test is reproducible.
+    struct XorShift(AtomicU64);
+
+    impl RandomSource for XorShift {
+        fn next_u64(&self) -> u64 {
+            let mut value = self.0.load(Ordering::Relaxed);
+            value ^= value << 13;
+            value ^= value >> 7;
+            value ^= value << 17;
+            self.0.store(value, Ordering::Relaxed);
+
+            value
+        }
+    }
+
+    static RANDOM: XorShift = XorShift(AtomicU64::new(0x2545_F491_4F6C_DD1D));
+
+    let slab_allocator = SlabAllocator::<_>::new_in(Global).with_randomized_order(&RANDOM);
+
+    let mut allocations = (0..62)
+        .map(|_| slab_allocator.allocate(LAYOUT_64).unwrap().cast::<u8>())
+        .collect::<Vec<_>>();
+
+    // Every object of the slab is handed out, but not in address order.
+    assert!(!allocations.is_sorted());
+    allocations.sort();
+    allocations.dedup();
+    assert!(allocations.len() == 62);
+    assert!(slab_allocator.remaining_object_count::<64>() == 0);
+
+    for allocation in allocations {
+        // Safety: Allocations are returned identically to their allocator.
+        unsafe {
+            slab_allocator.deallocate(allocation, LAYOUT_64);
+        }
+    }
+}
+
+#[test]
+pub fn slab_allocator_double_free_handler() {
+    static DOUBLE_FREES: AtomicUsize = AtomicUsize::new(0);
+
+    // With both poisoning and redzones, the poisoned redzone is detected first.
+    let slab_allocator = SlabAllocator::<_>::new_in(Global).with_corruption_handler(|corruption| {
+        assert!(matches!(
+            corruption,
+            Corruption::DoubleFree { .. } | Corruption::RedzoneOverwritten { .. }
+        ));
+        DOUBLE_FREES.fetch_add(1, Ordering::Relaxed);
+    });
+
+    let allocation = slab_allocator.allocate(LAYOUT_64).unwrap();
+
+    // Safety: The second free is deliberate, and is reported rather than completed.
+    unsafe {
+        slab_allocator.deallocate(allocation.cast(), LAYOUT_64);
+        slab_allocator.deallocate(allocation.cast(), LAYOUT_64);
+    }
+
+    assert!(DOUBLE_FREES.load(Ordering::Relaxed) == 1);
+    assert!(slab_allocator.remaining_object_count::<64>() == 62);
+}
+
+#[test]
+pub fn slab_allocator_checked_deallocation() {
+    static FOREIGN_POINTERS: AtomicUsize = AtomicUsize::new(0);
+
+    let slab_allocator = SlabAllocator::<_>::new_in(Global)
+        .with_checked_deallocation()
+        .with_corruption_handler(|corruption| {
+            assert!(matches!(corruption, Corruption::ForeignPointer { .. }));
+            FOREIGN_POINTERS.fetch_add(1, Ordering::Relaxed);
+        });
+
+    let allocation = slab_allocator.allocate(LAYOUT_64).unwrap();
+    let foreign_allocation = Global.allocate(LAYOUT_64).unwrap();
+
+    // Safety: Foreign and misaligned pointers are deliberate, and are reported rather than freed.
+    unsafe {
+        slab_allocator.deallocate(foreign_allocation.cast(), LAYOUT_64);
+        slab_allocator.deallocate(allocation.cast::<u8>().add(1), LAYOUT_64);
+    }
+    assert!(FOREIGN_POINTERS.load(Ordering::Relaxed) == 2);
+
+    // Safety: Allocations are returned identically to their allocators.
+    unsafe {
+        slab_allocator.deallocate(allocation.cast(), LAYOUT_64);
+        Global.deallocate(foreign_allocation.cast(), LAYOUT_64);
+    }
+    assert!(FOREIGN_POINTERS.load(Ordering::Relaxed) == 2);
+    assert!(slab_allocator.remaining_object_count::<64>() == 62);
+}
+
+#[test]
+pub fn slab_allocator_allocate_zeroed() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+
+    // Objects of a fresh slab are already zeroed.
+    let allocation = slab_allocator.allocate_zeroed(LAYOUT_128).unwrap();
+
+    // Safety: Allocation is valid for its length.
+    unsafe {
+        assert!(allocation.as_ref().iter().all(|byte| *byte == 0));
+        allocation.cast::<u8>().write_bytes(0xFF, allocation.len());
+        slab_allocator.deallocate(allocation.cast(), LAYOUT_128);
+    }
+
+    // The lowest free object is always claimed first, so the dirtied object is handed out again, and must be
+    // zeroed.
+    let allocation = slab_allocator.allocate_zeroed(LAYOUT_128).unwrap();
+
+    // Safety: Allocation is valid for its length.
+    unsafe {
+        assert!(allocation.as_ref().iter().all(|byte| *byte == 0));
+        slab_allocator.deallocate(allocation.cast(), LAYOUT_128);
+    }
+}
+
+#[test]
+pub fn slab_allocator_grow() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    let small_layout = Layout::from_size_align(LAYOUT_128.size() - 8, 1).unwrap();
+    let large_layout = Layout::new::<[u8; 200]>();
+
+    let allocation = slab_allocator.allocate(small_layout).unwrap();
+
+    // Safety: Allocation is valid for its length.
+    unsafe {
+        allocation
+            .cast::<u8>()
+            .write_bytes(0xAB, small_layout.size());
+    }
+
+    // Growing within the size class keeps the object in place.
+    // Safety: Allocation was allocated with `small_layout`.
+    let grown_allocation = unsafe {
+        slab_allocator
+            .grow(allocation.cast(), small_layout, LAYOUT_128)
+            .unwrap()
+    };
+    assert!(grown_allocation.cast::<u8>() == allocation.cast());
+    assert!(grown_allocation.len() >= LAYOUT_128.size());
+
+    // Growing into another size class moves the object.
+    // Safety: Allocation was grown to `LAYOUT_128`.
+    let moved_allocation = unsafe {
+        slab_allocator
+            .grow(grown_allocation.cast(), LAYOUT_128, large_layout)
+            .unwrap()
+    };
+    assert!(moved_allocation.cast::<u8>() != allocation.cast());
+    assert!(slab_allocator.remaining_object_count::<128>() == 31);
+
+    // Safety: Allocation is valid for `large_layout`.
+    unsafe {
+        assert!(
+            moved_allocation.as_ref()[..small_layout.size()]
+                .iter()
+                .all(|byte| *byte == 0xAB)
+        );
+        slab_allocator.deallocate(moved_allocation.cast(), large_layout);
+    }
+}
+
+#[test]
+pub fn slab_allocator_shrink() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    let small_layout = Layout::from_size_align(LAYOUT_128.size() - 8, 1).unwrap();
+
+    let allocation = slab_allocator.allocate(LAYOUT_256).unwrap();
+
+    // Safety: Allocation is valid for its length.
+    unsafe {
+        allocation.cast::<u8>().write_bytes(0xAB, LAYOUT_256.size());
+    }
+
+    // Shrinking into another size class moves the object.
+    // Safety: Allocation was allocated with `LAYOUT_256`.
+    let moved_allocation = unsafe {
+        slab_allocator
+            .shrink(allocation.cast(), LAYOUT_256, LAYOUT_128)
+            .unwrap()
+    };
+    assert!(moved_allocation.cast::<u8>() != allocation.cast());
+    assert!(slab_allocator.remaining_object_count::<128>() == 30);
+
+    // Shrinking within the size class keeps the object in place.
+    // Safety: Allocation was shrunk to `LAYOUT_128`.
+    let shrunk_allocation = unsafe {
+        slab_allocator
+            .shrink(moved_allocation.cast(), LAYOUT_128, small_layout)
+            .unwrap()
+    };
+    assert!(shrunk_allocation.cast::<u8>() == moved_allocation.cast());
+
+    // Safety: Allocation is valid for `small_layout`.
+    unsafe {
+        assert!(
+            shrunk_allocation.as_ref()[..small_layout.size()]
+                .iter()
+                .all(|byte| *byte == 0xAB)
+        );
+        slab_allocator.deallocate(shrunk_allocation.cast(), small_layout);
+    }
+    assert!(slab_allocator.remaining_object_count::<128>() == 31);
+}
+
+#[test]
+pub fn slab_allocator_lazy_shrink() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global).with_lazy_shrink();
+
+    let allocation = slab_allocator.allocate(LAYOUT_256).unwrap();
+
+    // Safety: Allocation was allocated with `LAYOUT_256`.
+    let shrunk_allocation = unsafe {
+        slab_allocator
+            .shrink(allocation.cast(), LAYOUT_256, LAYOUT_64)
+            .unwrap()
+    };
+    assert!(shrunk_allocation.cast::<u8>() == allocation.cast());
+    assert!(slab_allocator.remaining_object_count::<64>() == 0);
+
+    // The object can grow back into its slot, and is returned to the size class it was allocated from.
+    // Safety: Allocation was shrunk to `LAYOUT_64`.
+    unsafe {
+        let grown_allocation = slab_allocator
+            .grow(shrunk_allocation.cast(), LAYOUT_64, LAYOUT_128)
+            .unwrap();
+        assert!(grown_allocation.cast::<u8>() == allocation.cast());
+
+        slab_allocator.deallocate(grown_allocation.cast(), LAYOUT_128);
+    }
+    assert!(slab_allocator.remaining_object_count::<128>() == 0);
+    assert!(slab_allocator.remaining_object_count::<256>() == 15);
+}
+
+#[test]
+pub fn slab_allocator_grow_zeroed() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    let small_layout = Layout::from_size_align(LAYOUT_128.size() - 8, 1).unwrap();
+    let large_layout = Layout::new::<[u8; 200]>();
+
+    let allocation = slab_allocator.allocate(small_layout).unwrap();
+
+    // Safety: Allocation is valid for its length.
+    unsafe {
+        allocation.cast::<u8>().write_bytes(0xAB, allocation.len());
+    }
+
+    // Growing within the size class only zeroes the bytes past the old layout.
+    // Safety: Allocation was allocated with `small_layout`.
+    let grown_allocation = unsafe {
+        slab_allocator
+            .grow_zeroed(allocation.cast(), small_layout, LAYOUT_128)
+            .unwrap()
+    };
+    assert!(grown_allocation.cast::<u8>() == allocation.cast());
+
+    // Safety: Allocation is valid for its length.
+    unsafe {
+        let (preserved, zeroed) = grown_allocation.as_ref().split_at(small_layout.size());
+        assert!(preserved.iter().all(|byte| *byte == 0xAB));
+        assert!(zeroed.iter().all(|byte| *byte == 0));
+    }
+
+    // Growing into another size class zeroes the new object beyond the copied bytes.
+    // Safety: Allocation was grown to `LAYOUT_128`.
+    let moved_allocation = unsafe {
+        slab_allocator
+            .grow_zeroed(grown_allocation.cast(), LAYOUT_128, large_layout)
+            .unwrap()
+    };
+    assert!(moved_allocation.cast::<u8>() != allocation.cast());
+
+    // Safety: Allocation is valid for its length.
+    unsafe {
+        let (preserved, zeroed) = moved_allocation.as_ref().split_at(small_layout.size());
+        assert!(preserved.iter().all(|byte| *byte == 0xAB));
+        assert!(zeroed.iter().all(|byte| *byte == 0));
+
+        slab_allocator.deallocate(moved_allocation.cast(), large_layout);
+    }
+}
+
+#[test]
+pub fn slab_allocator_allocate_many() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    let mut objects = [NonNull::slice_from_raw_parts(NonNull::dangling(), 0); 40];
+
+    // Allocating more objects than a slab holds spans slabs.
+    assert!(slab_allocator.allocate_many(LAYOUT_128, &mut objects) == objects.len());
+    assert!(slab_allocator.stats().size_class(128).unwrap().slab_count == 2);
+    assert!(slab_allocator.remaining_object_count::<128>() == 22);
+
+    for (index, object) in objects.iter().enumerate() {
+        assert!(object.len() >= LAYOUT_128.size());
+        assert!(
+            objects[..index]
+                .iter()
+                .all(|other_object| other_object.cast::<u8>() != object.cast())
+        );
+    }
+
+    for object in objects {
+        // Safety: Object was allocated with `LAYOUT_128`.
+        unsafe {
+            slab_allocator.deallocate(object.cast(), LAYOUT_128);
+        }
+    }
+    assert!(slab_allocator.remaining_object_count::<128>() == 62);
+}
+
+#[test]
+pub fn slab_allocator_deallocate_many() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    let mut objects = [NonNull::slice_from_raw_parts(NonNull::dangling(), 0); 80];
+
+    assert!(slab_allocator.allocate_many(LAYOUT_64, &mut objects) == objects.len());
+    let object_ptrs = objects.map(NonNull::cast::<u8>);
+
+    // Safety: Objects were allocated with `LAYOUT_64`.
+    unsafe {
+        slab_allocator.deallocate_many(&object_ptrs, LAYOUT_64);
+    }
+
+    let stats = *slab_allocator.stats().size_class(64).unwrap();
+    assert!(stats.live_object_count == 0);
+    assert!(stats.deallocation_count == objects.len());
+    assert!(slab_allocator.remaining_object_count::<64>() == stats.object_capacity);
+}
+
+#[test]
+#[cfg(all(feature = "small-size-classes", not(feature = "redzone")))]
+pub fn slab_allocator_small_size_classes() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    let layout = Layout::new::<u32>();
+    let object_count = crate::slab::object_count(8, 0x1000, false);
+
+    // A slab of 8-byte objects spans several bitmap words.
+    assert!(object_count > 64 * 7);
+
+    let mut objects = [NonNull::slice_from_raw_parts(NonNull::dangling(), 0); 200];
+    assert!(slab_allocator.allocate_many(layout, &mut objects) == objects.len());
+    assert!(slab_allocator.remaining_object_count::<8>() == object_count - objects.len());
+
+    for object in objects {
+        assert!(object.len() == 8);
+        assert!(object.cast::<u64>().is_aligned());
+    }
+
+    // Safety: Objects were allocated with `layout`.
+    unsafe {
+        slab_allocator.deallocate_many(&objects.map(NonNull::cast::<u8>), layout);
+    }
+    assert!(slab_allocator.remaining_object_count::<8>() == object_count);
+    assert!(slab_allocator.stats().size_class(8).unwrap().slab_count == 1);
+}
+
+#[test]
+#[cfg(not(feature = "redzone"))]
+pub fn slab_allocator_custom_size_classes() {
+    let slab_allocator = SlabAllocator::<_>::with_size_classes_in(Global, &[32, 64, 96, 256]);
+
+    let stats = slab_allocator.stats();
+    assert!(stats.size_classes().len() == 5);
+    assert!(stats.size_class(128).is_none());
+
+    // Allocations are served by the smallest size class whose objects fit and are aligned for them.
+    for (layout, object_size) in [
+        (Layout::from_size_align(40, 8).unwrap(), 64),
+        (Layout::from_size_align(96, 32).unwrap(), 96),
+        (Layout::from_size_align(96, 64).unwrap(), 256),
+        (Layout::from_size_align(100, 8).unwrap(), 256),
+    ] {
+        let object = slab_allocator.allocate(layout).unwrap();
+        assert!(object.len() == object_size);
+        assert!(
+            object
+                .cast::<u8>()
+                .addr()
+                .get()
+                .is_multiple_of(layout.align())
+        );
+        assert!(
+            slab_allocator
+                .stats()
+                .size_class(object_size)
+                .unwrap()
+                .live_object_count
+                == 1
+        );
+
+        // Safety: Object was allocated with `layout`.
+        unsafe {
+            slab_allocator.deallocate(object.cast(), layout);
+        }
+    }
+
+    // Allocations larger than every size class are served by the backing allocator.
+    let layout = Layout::from_size_align(0x800, 8).unwrap();
+    let object = slab_allocator.allocate(layout).unwrap();
+    assert!(object.len() == layout.size());
+    assert!(
+        slab_allocator
+            .stats()
+            .size_classes()
+            .iter()
+            .all(|size_class| size_class.live_object_count == 0)
+    );
+
+    // Safety: Object was allocated with `layout`.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), layout);
+    }
+}
+
+#[test]
+pub fn slab_allocator_const_size_classes() {
+    crate::size_classes!(struct TestSizeClasses = [48, 64, 192]);
+
+    let slab_allocator = SlabAllocator::<_>::with_const_size_classes_in::<TestSizeClasses>(Global);
+    assert!(slab_allocator.stats().size_classes().len() == TestSizeClasses::OBJECT_SIZES.len() + 1);
+
+    let object = slab_allocator.allocate(LAYOUT_64).unwrap();
+    assert!(
+        slab_allocator
+            .stats()
+            .size_class(64)
+            .unwrap()
+            .live_object_count
+            == 1
+    );
+
+    // Safety: Object was allocated with `LAYOUT_64`.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), LAYOUT_64);
+    }
+
+    let default_allocator =
+        SlabAllocator::<_>::with_const_size_classes_in::<DefaultSizeClasses>(Global);
+    assert!(default_allocator.stats().size_classes().len() == SIZE_CLASS_COUNT);
+}
+
+#[test]
+pub fn slab_allocator_intermediate_size_classes() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    let layout = class_layout(768);
+    let object_count = crate::slab::object_count(768, 0x1000, false);
+
+    // A page doesn't divide into 768-byte objects, so its remainder is left unused.
+    assert!(object_count == 5);
+
+    let allocation = slab_allocator.allocate(layout).unwrap();
+    assert!(allocation.len() >= layout.size());
+    assert!(slab_allocator.remaining_object_count::<768>() == object_count - 1);
+    assert!(slab_allocator.remaining_object_count::<1024>() == 0);
+
+    // Objects of intermediate size classes are only aligned to a quarter of the power-of-two size above them.
+    let aligned_layout = layout.align_to(512).unwrap();
+    let aligned_allocation = slab_allocator.allocate(aligned_layout).unwrap();
+    assert!(
+        aligned_allocation
+            .cast::<u8>()
+            .addr()
+            .get()
+            .is_multiple_of(512)
+    );
+    assert!(slab_allocator.remaining_object_count::<768>() == object_count - 1);
+
+    // Safety: Allocations were allocated with their layouts.
+    unsafe {
+        slab_allocator.deallocate(allocation.cast(), layout);
+        slab_allocator.deallocate(aligned_allocation.cast(), aligned_layout);
+    }
+    assert!(slab_allocator.remaining_object_count::<768>() == object_count);
+}
+
+#[test]
+pub fn slab_allocator_page_class() {
+    let slab_allocator = SlabAllocator::<_>::new_in(Global);
+    let layout = Layout::from_size_align(3000, 8).unwrap();
+
+    let allocation = slab_allocator.allocate(layout).unwrap();
+    assert!(allocation.len() >= layout.size());
+
+    // Safety: Allocation was allocated with `layout`.
+    unsafe {
+        slab_allocator.deallocate(allocation.cast(), layout);
+    }
+    assert!(slab_allocator.cached_page_count() == 1);
+
+    // Freed pages are recycled, rather than returned to the backing allocator.
+    let recycled_allocation = slab_allocator.allocate_zeroed(layout).unwrap();
+    assert!(recycled_allocation.cast::<u8>() == allocation.cast());
+    assert!(slab_allocator.cached_page_count() == 0);
+
+    // Safety: Allocation is valid for its length.
+    unsafe {
+        assert!(recycled_allocation.as_ref().iter().all(|byte| *byte == 0));
+        slab_allocator.deallocate(recycled_allocation.cast(), layout);
+    }
+
+    let stats = *slab_allocator.stats().size_class(0x1000).unwrap();
+    assert!(stats.live_object_count == 0);
+    assert!(stats.page_count == 1);
+    assert!(stats.allocation_count == 2);
+    assert!(stats.slab_creation_count == 1);
+
+    assert!(slab_allocator.trim() == 0x1000);
+    assert!(
+        slab_allocator
+            .stats()
+            .size_class(0x1000)
+            .unwrap()
+            .page_count
+            == 0
+    );
+}
+
+#[test]
+pub fn slab_allocator_multi_page_slabs() {
+    let slab_allocator = SlabAllocator::<Global>::new_in(Global).with_slab_page_count::<2048>(8);
+    assert!(slab_allocator.slab_page_count::<2048>() == 8);
+    // Other size classes keep their default slab size.
+    assert!(slab_allocator.slab_page_count::<1024>() == 2);
+
+    let object_count = crate::slab::object_count(2048, 8 * 0x1000, false);
+    assert!(object_count > 2);
+
+    // Fill the first slab, and take one object from a second slab.
+    let allocations = (0..=object_count)
+        .map(|_| slab_allocator.allocate(LAYOUT_2048).unwrap())
+        .collect::<Vec<_>>();
+    assert!(slab_allocator.remaining_object_count::<2048>() == object_count - 1);
+
+    let stats = *slab_allocator.stats().size_class(2048).unwrap();
+    assert!(stats.slab_count == 2);
+    assert!(stats.page_count == 16);
+    assert!(stats.object_capacity == 2 * object_count);
+
+    for allocation in allocations {
+        // Safety: Allocation was allocated with `LAYOUT_2048`.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), LAYOUT_2048);
+        }
+    }
+
+    assert!(slab_allocator.remaining_object_count::<2048>() == 2 * object_count);
+    // The active slab is kept.
+    assert!(slab_allocator.trim() == 8 * 0x1000);
+}
+
+#[test]
+pub fn slab_allocator_page_size() {
+    const PAGE_SIZE: usize = 0x4000;
+
+    let slab_allocator = SlabAllocator::<Global, DefaultRawMutex, PAGE_SIZE>::new_in(Global);
+
+    let allocation = slab_allocator.allocate(LAYOUT_2048).unwrap();
+    assert!(
+        slab_allocator.remaining_object_count::<2048>()
+            == crate::slab::object_count(2048, PAGE_SIZE, false) - 1
+    );
+
+    let stats = *slab_allocator.stats().size_class(2048).unwrap();
+    assert!(stats.page_count == 1);
+    assert!(stats.object_capacity > 2);
+
+    // Allocations of a whole page are served by the page size class, and smaller allocations larger than every slab
+    // size class by the backing allocator.
+    let page_layout = class_layout(PAGE_SIZE);
+    let page = slab_allocator.allocate(page_layout).unwrap();
+    let large_layout = class_layout(0x1000);
+    let large_allocation = slab_allocator.allocate(large_layout).unwrap();
+
+    let stats = slab_allocator.stats();
+    assert!(stats.size_class(PAGE_SIZE).unwrap().live_object_count == 1);
+    assert!(stats.size_class(0x1000).is_none());
+
+    // Safety: Allocations were allocated with their respective layouts.
+    unsafe {
+        slab_allocator.deallocate(allocation.cast(), LAYOUT_2048);
+        slab_allocator.deallocate(page.cast(), page_layout);
+        slab_allocator.deallocate(large_allocation.cast(), large_layout);
+    }
+
+    assert!(slab_allocator.trim() == PAGE_SIZE);
+}
+
+#[test]
+pub fn numa_slab_allocator() {
+    /// Memory ranges handed out by each node's backing allocator, as `(start, end, node)`.
+    static NODE_MEMORY: std::sync::Mutex<Vec<(usize, usize, usize)>> =
+        std::sync::Mutex::new(Vec::new());
+    static CURRENT_NODE: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Clone)]
+    struct NodeAllocator(usize);
+
+    // Safety: Allocations are served by `Global`.
+    unsafe impl Allocator for NodeAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let memory = Global.allocate(layout)?;
+            let start = memory.cast::<u8>().addr().get();
+            NODE_MEMORY
+                .lock()
+                .unwrap()
+                .push((start, start + memory.len(), self.0));
+
+            Ok(memory)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            NODE_MEMORY
+                .lock()
+                .unwrap()
+                .retain(|(start, _, _)| *start != ptr.addr().get());
+
+            // Safety: Memory was allocated by `Global` with `layout`.
+            unsafe {
+                Global.deallocate(ptr, layout);
+            }
+        }
+    }
+
+    struct TwoNodes;
+
+    impl NumaTopology for TwoNodes {
+        fn node_count(&self) -> usize {
+            2
+        }
+
+        fn current_node(&self) -> usize {
+            CURRENT_NODE.load(Ordering::Relaxed)
+        }
+
+        fn node_of(&self, ptr: NonNull<u8>) -> usize {
+            let address = ptr.addr().get();
+
+            NODE_MEMORY
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(start, end, _)| (*start..*end).contains(&address))
+                .map(|(_, _, node)| *node)
+                .unwrap()
+        }
+    }
+
+    static TOPOLOGY: TwoNodes = TwoNodes;
+
+    let numa_allocator = NumaSlabAllocator::<NodeAllocator>::new_in(&TOPOLOGY, NodeAllocator);
+    assert!(numa_allocator.node_count() == 2);
+
+    let node_0_allocation = numa_allocator.allocate(LAYOUT_128).unwrap();
+
+    // Objects freed from another node are returned to the node that holds them.
+    CURRENT_NODE.store(1, Ordering::Relaxed);
+    let node_1_allocation = numa_allocator.allocate(LAYOUT_128).unwrap();
+    assert!(TOPOLOGY.node_of(node_1_allocation.cast()) == 1);
+
+    // Safety: Allocation was allocated with `LAYOUT_128`.
+    unsafe {
+        numa_allocator.deallocate(node_0_allocation.cast(), LAYOUT_128);
+    }
+
+    let node_0_stats = *numa_allocator.node(0).stats().size_class(128).unwrap();
+    assert!(node_0_stats.allocation_count == 1);
+    assert!(node_0_stats.deallocation_count == 1);
+
+    let node_1_stats = *numa_allocator.node(1).stats().size_class(128).unwrap();
+    assert!(node_1_stats.allocation_count == 1);
+    assert!(node_1_stats.live_object_count == 1);
+
+    // Safety: Allocation was allocated with `LAYOUT_128`.
+    unsafe {
+        numa_allocator.deallocate(node_1_allocation.cast(), LAYOUT_128);
+    }
+}
+
+#[test]
+pub fn slab_allocator_remote_frees() {
+    let slab_allocator = SlabAllocator::<Global>::new_in(Global).with_remote_frees();
+
+    let allocation_1 = slab_allocator.allocate(LAYOUT_128).unwrap();
+    let allocation_2 = slab_allocator.allocate(LAYOUT_128).unwrap();
+    assert!(slab_allocator.remaining_object_count::<128>() == 29);
+
+    // Safety: Allocation was allocated with `LAYOUT_128`.
+    unsafe {
+        slab_allocator.deallocate(allocation_1.cast(), LAYOUT_128);
+    }
+
+    // Frees are queued, rather than returned to their slabs.
+    assert!(slab_allocator.remote_free_count::<128>() == 1);
+    assert!(slab_allocator.remaining_object_count::<128>() == 29);
+    assert!(
+        slab_allocator
+            .stats()
+            .size_class(128)
+            .unwrap()
+            .live_object_count
+            == 1
+    );
+
+    // The next allocation drains the queue before taking an object.
+    let allocation_3 = slab_allocator.allocate(LAYOUT_128).unwrap();
+    assert!(slab_allocator.remote_free_count::<128>() == 0);
+    assert!(slab_allocator.remaining_object_count::<128>() == 29);
+
+    // Safety: Allocations were allocated with `LAYOUT_128`.
+    unsafe {
+        slab_allocator.deallocate(allocation_2.cast(), LAYOUT_128);
+        slab_allocator.deallocate(allocation_3.cast(), LAYOUT_128);
+    }
+
+    assert!(slab_allocator.remote_free_count::<128>() == 2);
+
+    slab_allocator.trim();
+    assert!(slab_allocator.remote_free_count::<128>() == 0);
+    assert!(slab_allocator.remaining_object_count::<128>() == 31);
+}
+
+#[test]
+pub fn region_allocator() {
+    let region_layout = Layout::from_size_align(0x10000, 8).unwrap();
+    let region = Global.allocate(region_layout).unwrap();
+    let region_range =
+        region.cast::<u8>().addr().get()..(region.cast::<u8>().addr().get() + region.len());
+
+    // Safety: Region was just allocated, and is only accessed through the allocator.
+    let region_allocator = unsafe { RegionAllocator::<DefaultRawMutex>::new(region) };
+    let page_count = region_allocator.page_count();
+    assert!(page_count > 0 && page_count < 0x10);
+
+    let aligned_layout = Layout::from_size_align(0x2000, 0x2000).unwrap();
+    let aligned_allocation = region_allocator.allocate(aligned_layout).unwrap();
+    assert!(aligned_allocation.cast::<u8>().addr().get() % 0x2000 == 0);
+    assert!(region_range.contains(&aligned_allocation.cast::<u8>().addr().get()));
+    assert!(region_allocator.free_page_count() == page_count - 2);
+
+    // Safety: Allocation was allocated with `aligned_layout`.
+    unsafe {
+        region_allocator.deallocate(aligned_allocation.cast(), aligned_layout);
+    }
+
+    assert!(region_allocator.free_page_count() == page_count);
+
+    // Safety: Region was allocated with `region_layout`, and is no longer used.
+    unsafe {
+        Global.deallocate(region.cast(), region_layout);
+    }
+}
+
+#[test]
+pub fn slab_allocator_from_region() {
+    let region_layout = Layout::from_size_align(0x10000, 0x1000).unwrap();
+    let region = Global.allocate(region_layout).unwrap();
+    let region_range =
+        region.cast::<u8>().addr().get()..(region.cast::<u8>().addr().get() + region.len());
+
+    {
+        // Safety: Region was just allocated, and is only accessed through the allocator.
+        let slab_allocator = unsafe { SlabAllocator::<RegionAllocator>::from_region(region) };
+
+        // Slabs, and the lists holding them, are carved out of the region until it is exhausted.
+        let allocations =
+            core::iter::from_fn(|| slab_allocator.allocate(LAYOUT_2048).ok()).collect::<Vec<_>>();
+        assert!(!allocations.is_empty());
+        assert!(
+            allocations
+                .iter()
+                .all(|allocation| region_range.contains(&allocation.cast::<u8>().addr().get()))
+        );
+
+        for allocation in allocations {
+            // Safety: Allocation was allocated with `LAYOUT_2048`.
+            unsafe {
+                slab_allocator.deallocate(allocation.cast(), LAYOUT_2048);
+            }
+        }
+
+        assert!(slab_allocator.allocate(LAYOUT_2048).is_ok());
+    }
+
+    // Safety: Region was allocated with `region_layout`, and is no longer used.
+    unsafe {
+        Global.deallocate(region.cast(), region_layout);
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "frames are reached through exposed provenance")]
+pub fn slab_allocator_from_page_source() {
+    const VIRTUAL_OFFSET: usize = 0x10000;
+    const FRAME_LAYOUT: Layout = match Layout::from_size_align(0x1000, 0x1000) {
+        Ok(layout) => layout,
+        Err(_) => unreachable!(),
+    };
+
+    /// Hands out frames from `Global`, at physical addresses `VIRTUAL_OFFSET` below their actual addresses.
+    struct Frames {
+        allocated_count: AtomicUsize,
+    }
+
+    impl PageSource for Frames {
+        fn allocate_frame(&self) -> Option<usize> {
+            let frame = Global.allocate(FRAME_LAYOUT).ok()?;
+            self.allocated_count.fetch_add(1, Ordering::Relaxed);
+
+            Some(frame.cast::<u8>().expose_provenance().get() - VIRTUAL_OFFSET)
+        }
+
+        unsafe fn free_frame(&self, address: usize) {
+            let frame = core::ptr::with_exposed_provenance_mut::<u8>(address + VIRTUAL_OFFSET);
+            self.allocated_count.fetch_sub(1, Ordering::Relaxed);
+
+            // Safety: Frame was allocated by `Global` with `FRAME_LAYOUT`.
+            unsafe {
+                Global.deallocate(NonNull::new(frame).unwrap(), FRAME_LAYOUT);
+            }
+        }
+    }
+
+    static FRAMES: Frames = Frames {
+        allocated_count: AtomicUsize::new(0),
+    };
+
+    {
+        // Safety: Frames are accessible at `VIRTUAL_OFFSET` above their physical addresses.
+        let slab_allocator = unsafe {
+            SlabAllocator::<PageSourceAllocator<&Frames>>::from_page_source(&FRAMES, VIRTUAL_OFFSET)
+        };
+
+        let allocation = slab_allocator.allocate(LAYOUT_64).unwrap();
+        assert!(FRAMES.allocated_count.load(Ordering::Relaxed) > 0);
+
+        // Without contiguous frames, only allocations of a single frame can be served, though slabs of every size
+        // class span a single frame.
+        let large_layout = Layout::from_size_align(0x2000, 8).unwrap();
+        assert!(slab_allocator.allocate(large_layout).is_err());
+        let large_object = slab_allocator.allocate(LAYOUT_2048).unwrap();
+
+        // Safety: Allocations were allocated with their layouts.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), LAYOUT_64);
+            slab_allocator.deallocate(large_object.cast(), LAYOUT_2048);
+        }
+    }
+
+    assert!(FRAMES.allocated_count.load(Ordering::Relaxed) == 0);
+}
+
+#[test]
+#[cfg(feature = "test-utils")]
+pub fn slab_allocator_from_page_mapping() {
+    use crate::PageArena;
+
+    let arena = PageArena::<_>::new_in(16, Global).unwrap();
+
+    {
+        // Safety: Frames are accessible at their physical addresses as offsets from the arena's mapping.
+        let slab_allocator = unsafe {
+            SlabAllocator::<PageSourceAllocator<&PageArena<Global>>>::from_page_mapping(
+                &arena,
+                arena.mapping(),
+            )
+        };
+
+        let allocation = slab_allocator.allocate(LAYOUT_64).unwrap();
+        let allocated_frame_count = arena.allocated_frame_count();
+        assert!(allocated_frame_count > 0);
+
+        // Safety: Object is at least `LAYOUT_64.size()` bytes, and was just allocated.
+        unsafe {
+            allocation.cast::<u8>().write_bytes(0xA5, LAYOUT_64.size());
+        }
+
+        // Allocations of more than a frame are served from contiguous frames of the arena.
+        let large_layout = Layout::from_size_align(0x2000, 8).unwrap();
+        let large_allocation = slab_allocator.allocate(large_layout).unwrap();
+        assert!(arena.allocated_frame_count() == allocated_frame_count + 2);
+
+        // Safety: Allocations were allocated with their layouts.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), LAYOUT_64);
+            slab_allocator.deallocate(large_allocation.cast(), large_layout);
+        }
+    }
+
+    assert!(arena.allocated_frame_count() == 0);
+}
+
+#[test]
+pub fn slab_allocator_fallback() {
+    let region_layout = Layout::from_size_align(0x10000, 0x1000).unwrap();
+    let region = Global.allocate(region_layout).unwrap();
+    let region_range =
+        region.cast::<u8>().addr().get()..(region.cast::<u8>().addr().get() + region.len());
+
+    {
+        // Safety: Region was just allocated, and is only accessed through the allocator.
+        let slab_allocator =
+            unsafe { SlabAllocator::<RegionAllocator>::from_region(region) }.with_fallback(Global);
+
+        let small_allocation = slab_allocator.allocate(LAYOUT_64).unwrap();
+        assert!(region_range.contains(&small_allocation.cast::<u8>().addr().get()));
+
+        // Allocations no size class serves are made by the fallback allocator, rather than carved out of the region.
+        let large_layout = Layout::from_size_align(0x10000, 8).unwrap();
+        let large_allocation = slab_allocator.allocate(large_layout).unwrap();
+        assert!(!region_range.contains(&large_allocation.cast::<u8>().addr().get()));
+
+        // Safety: Allocations were allocated with their respective layouts.
+        unsafe {
+            slab_allocator.deallocate(small_allocation.cast(), LAYOUT_64);
+            slab_allocator.deallocate(large_allocation.cast(), large_layout);
+        }
+    }
+
+    // Safety: Region was allocated with `region_layout`, and is no longer used.
+    unsafe {
+        Global.deallocate(region.cast(), region_layout);
+    }
+}
+
+#[test]
+pub fn slab_allocator_memory_limit() {
+    const PAGE_LAYOUT: Layout = class_layout(0x1000);
+    static MEMORY_LIMIT: MemoryLimit = MemoryLimit::new(2 * 0x1000);
+
+    {
+        let slab_allocator = SlabAllocator::<_>::new_in(Global).with_memory_limit(&MEMORY_LIMIT);
+
+        let object = slab_allocator.allocate(LAYOUT_64).unwrap();
+        let page = slab_allocator.allocate(PAGE_LAYOUT).unwrap();
+        assert!(MEMORY_LIMIT.held_bytes() == MEMORY_LIMIT.limit());
+
+        // A new slab would exceed the limit.
+        assert!(slab_allocator.allocate(LAYOUT_512).is_err());
+
+        // Safety: Page was allocated with `PAGE_LAYOUT`.
+        unsafe {
+            slab_allocator.deallocate(page.cast(), PAGE_LAYOUT);
+        }
+        assert!(slab_allocator.cached_page_count() == 1);
+
+        // The cached page is released to make room for the new slab.
+        let new_object = slab_allocator.allocate(LAYOUT_512).unwrap();
+        assert!(MEMORY_LIMIT.held_bytes() == MEMORY_LIMIT.limit());
+        assert!(slab_allocator.cached_page_count() == 0);
+
+        // Safety: Objects were allocated with their layouts.
+        unsafe {
+            slab_allocator.deallocate(object.cast(), LAYOUT_64);
+            slab_allocator.deallocate(new_object.cast(), LAYOUT_512);
+        }
+    }
+
+    assert!(MEMORY_LIMIT.held_bytes() == 0);
+}
+
+#[test]
+pub fn slab_allocator_builder() {
+    let region_layout = Layout::from_size_align(0x10000, 0x1000).unwrap();
+    let region = Global.allocate(region_layout).unwrap();
+    let region_range =
+        region.cast::<u8>().addr().get()..(region.cast::<u8>().addr().get() + region.len());
+
+    {
+        // Safety: Region was just allocated, and is only accessed through the allocator.
+        let slab_allocator = SlabAllocatorBuilder::<RegionAllocator>::new_in(unsafe {
+            RegionAllocator::new(region)
+        })
+        .fallback(Global)
+        .size_classes(&[64, 2048])
+        .slab_page_count::<64>(2)
+        .empty_slab_retention::<64>(0)
+        .build()
+        .unwrap();
+
+        assert!(slab_allocator.slab_page_count::<64>() == 2);
+        assert!(slab_allocator.empty_slab_retention::<64>() == 0);
+
+        let allocation = slab_allocator.allocate(LAYOUT_64).unwrap();
+        assert!(region_range.contains(&allocation.cast::<u8>().addr().get()));
+
+        // Allocations between size classes are served by the next larger size class.
+        // Safety: Allocation was allocated with `LAYOUT_64`.
+        let allocation = unsafe {
+            slab_allocator
+                .grow(allocation.cast(), LAYOUT_64, LAYOUT_128)
+                .unwrap()
+        };
+        assert!(region_range.contains(&allocation.cast::<u8>().addr().get()));
+        assert!(
+            slab_allocator
+                .stats()
+                .size_class(2048)
+                .unwrap()
+                .live_object_count
+                == 1
+        );
+        assert!(slab_allocator.stats().size_classes().len() == 3);
+
+        // Allocations larger than every size class are served by the fallback allocator.
+        let large_layout = Layout::from_size_align(0x2000, 8).unwrap();
+
+        // Safety: Allocation was grown to `LAYOUT_128`.
+        let allocation = unsafe {
+            slab_allocator
+                .grow(allocation.cast(), LAYOUT_128, large_layout)
+                .unwrap()
+        };
+        assert!(!region_range.contains(&allocation.cast::<u8>().addr().get()));
+
+        // Safety: Allocation was grown to `large_layout`.
+        unsafe {
+            slab_allocator.deallocate(allocation.cast(), large_layout);
+        }
+    }
+
+    // Safety: Region was allocated with `region_layout`, and is no longer used.
+    unsafe {
+        Global.deallocate(region.cast(), region_layout);
+    }
+}
+
+#[test]
+pub fn slab_allocator_const_new() {
+    static SLAB_ALLOCATOR: SlabAllocator<Global> = SlabAllocator::const_new_in(&Global);
+
+    assert!(SLAB_ALLOCATOR.stats().size_classes().len() == 1);
+    assert!(SLAB_ALLOCATOR.try_allocate(LAYOUT_64) == Err(TryAllocError::WouldBlock));
+
+    // The first allocation creates the size classes.
+    let allocation = SLAB_ALLOCATOR.allocate(LAYOUT_64).unwrap();
+    assert!(SLAB_ALLOCATOR.stats().size_classes().len() == SIZE_CLASS_COUNT);
+    assert!(SLAB_ALLOCATOR.allocated_object_count::<64>() == 1);
+
+    // Safety: Allocation was allocated with `LAYOUT_64`.
+    unsafe {
+        SLAB_ALLOCATOR.deallocate(allocation.cast(), LAYOUT_64);
+    }
+
+    assert!(SLAB_ALLOCATOR.allocated_object_count::<64>() == 0);
+}
+
+#[test]
+pub fn local_slab_allocator() {
+    let allocator = LocalSlabAllocator::<Global>::new_in(Global);
+
+    let allocations = [LAYOUT_64, LAYOUT_128, LAYOUT_64].map(|layout| {
+        let allocation = allocator.allocate(layout).unwrap();
+        (allocation, layout)
+    });
+    assert!(allocator.allocated_object_count::<64>() == 2);
+    assert!(allocator.allocated_object_count::<128>() == 1);
+
+    for (allocation, layout) in allocations {
+        // Safety: Allocation was allocated with `layout`.
+        unsafe {
+            allocator.deallocate(allocation.cast(), layout);
+        }
+    }
+
+    assert!(allocator.allocated_object_count::<64>() == 0);
+}
+
+/// Number of objects allocated from every size class of `allocator`.
+fn live_object_count(allocator: &SlabAllocator<Global>) -> usize {
+    allocator
+        .stats()
+        .size_classes()
+        .iter()
+        .map(|stats| stats.live_object_count)
+        .sum()
+}
+
+#[test]
+pub fn slab_box() {
+    let allocator = SlabAllocator::<Global>::new_in(Global);
+
+    let mut slab_box = SlabBox::new_in([1_u64; 8], &allocator);
+    slab_box[7] = 2;
+    assert!(live_object_count(&allocator) == 1);
+    assert!(allocator.owns(NonNull::from(&*slab_box).cast()));
+
+    let value = SlabBox::into_inner(slab_box);
+    assert!(value[0] == 1 && value[7] == 2);
+    assert!(live_object_count(&allocator) == 0);
+
+    // Values are dropped along with their boxes.
+    let counter = AtomicUsize::new(0);
+
+    struct DropCounter<'a>(&'a AtomicUsize);
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    drop(SlabBox::new_in(DropCounter(&counter), &allocator));
+    assert!(counter.load(Ordering::Relaxed) == 1);
+    assert!(live_object_count(&allocator) == 0);
+}
+
+#[test]
+pub fn slab_box_pinned() {
+    struct Node {
+        this: *const Node,
+        _pinned: core::marker::PhantomPinned,
+    }
+
+    let allocator = SlabAllocator::<Global>::new_in(Global);
+
+    let node = SlabBox::try_pin_with_in(
+        |object| {
+            let this = object.as_ptr();
+            object.write(Node {
+                this,
+                _pinned: core::marker::PhantomPinned,
+            })
+        },
+        &allocator,
+    )
+    .unwrap();
+    assert!(core::ptr::eq(node.this, &raw const *node));
+    assert!(live_object_count(&allocator) == 1);
+
+    let value = allocator.allocate_pinned(7_u64).unwrap();
+    assert!(*value == 7);
+    assert!(live_object_count(&allocator) == 2);
+
+    drop((node, value));
+    assert!(live_object_count(&allocator) == 0);
+}
+
+#[test]
+pub fn slab_arena() {
+    let allocator = SlabAllocator::<Global>::new_in(Global);
+    let mut arena = SlabArena::new_in(&allocator);
+
+    let value = arena.allocate_value(7_u64).unwrap();
+    *value += 1;
+    assert!(*value == 8);
+
+    for _ in 0..100 {
+        arena.allocate(LAYOUT_128).unwrap();
+    }
+    assert!(arena.allocation_count() == 101);
+    assert!(live_object_count(&allocator) >= 101);
+
+    // Resetting frees every allocation, though not the record of them.
+    arena.reset();
+    assert!(arena.allocation_count() == 0);
+    assert!(live_object_count(&allocator) == 1);
+
+    arena.allocate(LAYOUT_64).unwrap();
+    drop(arena);
+    assert!(live_object_count(&allocator) == 0);
+}
+
+#[test]
+pub fn slab_allocator_handles() {
+    let allocator = SlabAllocator::<Global>::new_in(Global);
+
+    let handles = [LAYOUT_64, LAYOUT_128, LAYOUT_64].map(|layout| {
+        let handle = allocator.allocate_handle(layout).unwrap();
+        let object = allocator.resolve(handle).unwrap();
+        assert!(object.len() >= layout.size());
+
+        // Safety: Object is allocated, and large enough for a `u32`.
+        unsafe {
+            object.cast::<u32>().write(handle.into_raw());
+        }
+
+        handle
+    });
+    assert!(handles[0] != handles[2]);
+    assert!(live_object_count(&allocator) == 3);
+
+    for handle in handles {
+        // Handles round-trip through their raw values.
+        let handle = ObjectHandle::from_raw(handle.into_raw());
+
+        // Safety: Object was written above.
+        assert!(
+            unsafe { allocator.resolve(handle).unwrap().cast::<u32>().read() } == handle.into_raw()
+        );
+
+        // Safety: Handle was allocated above, and is freed once.
+        unsafe {
+            allocator.deallocate_handle(handle);
+        }
+    }
+
+    assert!(live_object_count(&allocator) == 0);
+}
+
+#[test]
+pub fn slab_allocator_stale_handles() {
+    static STALE_HANDLES: AtomicUsize = AtomicUsize::new(0);
+
+    let allocator = SlabAllocator::<Global>::new_in(Global).with_corruption_handler(|corruption| {
+        assert!(matches!(corruption, Corruption::StaleHandle { .. }));
+        STALE_HANDLES.fetch_add(1, Ordering::Relaxed);
+    });
+
+    let handle = allocator.allocate_handle(LAYOUT_64).unwrap();
+
+    // Safety: Handle was just allocated, and is freed once.
+    unsafe {
+        allocator.deallocate_handle(handle);
+    }
+
+    // The freed object's slot is handed out again, under a new generation.
+    let new_handle = allocator.allocate_handle(LAYOUT_64).unwrap();
+    assert!(new_handle != handle);
+    assert!(allocator.resolve(new_handle).is_some());
+
+    assert!(allocator.resolve(handle).is_none());
+
+    // Safety: Handle is stale, so nothing is freed.
+    unsafe {
+        allocator.deallocate_handle(handle);
+    }
+    assert!(STALE_HANDLES.load(Ordering::Relaxed) == 2);
+    assert!(live_object_count(&allocator) == 1);
+
+    // Safety: Handle was allocated above, and is freed once.
+    unsafe {
+        allocator.deallocate_handle(new_handle);
+    }
+    assert!(live_object_count(&allocator) == 0);
+}
+
+#[test]
+pub fn slab_allocator_leak_handler() {
+    static LEAKED_OBJECTS: AtomicUsize = AtomicUsize::new(0);
+
+    let allocator = SlabAllocator::<Global>::new_in(Global).with_leak_handler(|leak: Leak| {
+        LEAKED_OBJECTS.fetch_add(leak.object_count, Ordering::Relaxed);
+    });
+
+    let allocations =
+        [LAYOUT_64, LAYOUT_64, LAYOUT_128].map(|layout| allocator.allocate(layout).unwrap());
+
+    // Safety: Allocation was allocated with `LAYOUT_64`.
+    unsafe {
+        allocator.deallocate(allocations[0].cast(), LAYOUT_64);
+    }
+
+    drop(allocator);
+    assert!(LEAKED_OBJECTS.load(Ordering::Relaxed) == 2);
+}
+
+#[test]
+pub fn slab_allocator_is_fully_free() {
+    let allocator = SlabAllocator::<Global>::new_in(Global);
+    assert!(allocator.is_fully_free());
+
+    let page_layout = Layout::from_size_align(4096, 4096).unwrap();
+    let allocations = [LAYOUT_64, LAYOUT_128, page_layout]
+        .map(|layout| (allocator.allocate(layout).unwrap(), layout));
+    assert!(!allocator.is_fully_free());
+    assert!(allocator.outstanding_allocation_count() == live_object_count(&allocator));
+
+    for (allocation, layout) in allocations {
+        // Safety: Allocation was allocated with `layout`.
+        unsafe {
+            allocator.deallocate(allocation.cast(), layout);
+        }
+    }
+
+    assert!(allocator.outstanding_allocation_count() == 0);
+    assert!(allocator.is_fully_free());
+}
+
+#[test]
+pub fn slab_allocator_reset() {
+    let allocator = SlabAllocator::<Global>::new_in(Global);
+
+    let allocations = (0..200)
+        .map(|index| {
+            let layout = if index % 2 == 0 {
+                LAYOUT_64
+            } else {
+                LAYOUT_128
+            };
+            (allocator.allocate(layout).unwrap(), layout)
+        })
+        .collect::<std::vec::Vec<_>>();
+
+    // Objects freed before the reset are cached, rather than allocated.
+    for &(allocation, layout) in &allocations[..10] {
+        // Safety: Allocation was allocated with `layout`.
+        unsafe {
+            allocator.deallocate(allocation.cast(), layout);
+        }
+    }
+
+    allocator.allocate_handle(LAYOUT_64).unwrap();
+    assert!(!allocator.is_fully_free());
+
+    // Safety: No allocation is referenced after the reset.
+    unsafe {
+        allocator.reset();
+    }
+
+    assert!(allocator.is_fully_free());
+
+    let allocation = allocator.allocate(LAYOUT_64).unwrap();
+    assert!(allocator.outstanding_allocation_count() == 1);
+
+    // Safety: Allocation was allocated with `LAYOUT_64`.
+    unsafe {
+        allocator.deallocate(allocation.cast(), LAYOUT_64);
+    }
+
+    assert!(allocator.trim() > 0);
+}
+
+#[test]
+pub fn slab_allocator_checkpoint() {
+    let allocator = SlabAllocator::<Global>::new_in(Global);
+
+    let kept = allocator.allocate(LAYOUT_64).unwrap().cast::<u64>();
+    // Safety: Object is allocated, and large enough to hold a `u64`.
+    unsafe {
+        kept.write(0xDEAD_BEEF);
+    }
+
+    let checkpoint = allocator.checkpoint().unwrap();
+
+    for index in 0..100 {
+        let layout = if index % 2 == 0 {
+            LAYOUT_64
+        } else {
+            LAYOUT_128
+        };
+        allocator.allocate(layout).unwrap();
+    }
+
+    // Safety: No object allocated since the checkpoint is referenced.
+    unsafe {
+        allocator.rollback(&checkpoint);
+    }
+
+    assert!(allocator.outstanding_allocation_count() == 1);
+    // Safety: Object allocated before the checkpoint is still allocated.
+    assert!(unsafe { kept.read() } == 0xDEAD_BEEF);
+
+    // Only the size class of `LAYOUT_64` is rolled back.
+    let checkpoint = allocator.checkpoint_of(&[LAYOUT_64]).unwrap();
+    allocator.allocate(LAYOUT_64).unwrap();
+    let other = allocator.allocate(LAYOUT_128).unwrap();
+
+    // Safety: No object allocated since the checkpoint is referenced.
+    unsafe {
+        allocator.rollback(&checkpoint);
+    }
+
+    assert!(allocator.outstanding_allocation_count() == 2);
+
+    // Safety: Objects were allocated with their layouts.
+    unsafe {
+        allocator.deallocate(other.cast(), LAYOUT_128);
+        allocator.deallocate(kept.cast(), LAYOUT_64);
+    }
+
+    assert!(allocator.is_fully_free());
+}
+
+#[test]
+pub fn shared_slab_allocator() {
+    let allocator = SharedSlabAllocator::<Global>::new_in(Global).unwrap();
+    let other = allocator.clone();
+    assert!(SharedSlabAllocator::handle_count(&allocator) == 2);
+
+    let mut values = Vec::new_in(other.clone());
+    values.extend(0..16u64);
+
+    let object = allocator.allocate(LAYOUT_64).unwrap();
+    assert!(other.outstanding_allocation_count() == 2);
+
+    // Safety: Object was allocated by a handle to the same allocator, with `LAYOUT_64`.
+    unsafe {
+        other.deallocate(object.cast(), LAYOUT_64);
+    }
+
+    drop(values);
+    drop(other);
+    assert!(SharedSlabAllocator::handle_count(&allocator) == 1);
+    assert!(allocator.is_fully_free());
+}
+
+#[test]
+pub fn unique_backing_allocator() {
+    /// Backing allocator that can't be cloned, counting the bytes it has outstanding.
+    struct UniqueAllocator {
+        allocated_bytes: &'static AtomicUsize,
+    }
+
+    // Safety: Allocations are served by `Global`.
+    unsafe impl Allocator for UniqueAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.allocated_bytes
+                .fetch_add(layout.size(), Ordering::Relaxed);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.allocated_bytes
+                .fetch_sub(layout.size(), Ordering::Relaxed);
+
+            // Safety: Caller is required to ensure `ptr` was allocated by `Global` with `layout`.
+            unsafe { Global.deallocate(ptr, layout) }
+        }
+    }
+
+    static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+    let allocator = SlabAllocator::<_>::new_in(UniqueAllocator {
+        allocated_bytes: &ALLOCATED_BYTES,
+    });
+    let object = allocator.allocate(LAYOUT_64).unwrap();
+    assert!(ALLOCATED_BYTES.load(Ordering::Relaxed) > 0);
+
+    // The size classes share the backing allocator, so the slab allocator can be moved without moving it.
+    let moved_allocator = allocator;
+
+    // Safety: Object was allocated with `LAYOUT_64`.
+    unsafe {
+        moved_allocator.deallocate(object.cast(), LAYOUT_64);
+    }
+
+    drop(moved_allocator);
+    assert!(ALLOCATED_BYTES.load(Ordering::Relaxed) == 0);
+}
+
+#[test]
+pub fn slab_allocator_aligned_objects() {
+    const LAYOUT_64_PAGE_ALIGNED: Layout = match Layout::from_size_align(64, 0x1000) {
+        Ok(layout) => layout,
+        Err(_) => unreachable!(),
+    };
+
+    let slab_allocator = SlabAllocator::<Global>::new_in(Global).with_slab_page_count::<64>(4);
+    // Redzones push the object into a size class whose slabs hold too few objects to be aligned.
+    #[cfg(not(feature = "redzone"))]
+    assert!(slab_allocator.size_class_for(LAYOUT_64_PAGE_ALIGNED) == Some(64));
+
+    let allocations = (0..6)
+        .map(|_| slab_allocator.allocate(LAYOUT_64_PAGE_ALIGNED).unwrap())
+        .collect::<Vec<_>>();
+    assert!(
+        allocations
+            .iter()
+            .all(|object| object.addr().get().is_multiple_of(0x1000))
+    );
+    assert!(allocations.iter().enumerate().all(|(index, object)| {
+        allocations[..index]
+            .iter()
+            .all(|other| other.addr() != object.addr())
+    }));
+
+    for object in allocations {
+        // Safety: Object was allocated with `LAYOUT_64_PAGE_ALIGNED`.
+        unsafe {
+            slab_allocator.deallocate(object.cast(), LAYOUT_64_PAGE_ALIGNED);
+        }
+    }
+    assert!(slab_allocator.outstanding_allocation_count() == 0);
+}
+
+#[test]
+pub fn slab_allocator_unaligned_backing() {
+    /// Allocator that only aligns its allocations to a cache line, however large the requested alignment.
+    #[derive(Clone)]
+    struct MisalignedAllocator;
+
+    const MISALIGNMENT: usize = 64;
+
+    fn misaligned_layout(layout: Layout) -> Layout {
+        Layout::from_size_align(
+            layout.size() + MISALIGNMENT,
+            layout.align().max(MISALIGNMENT),
+        )
+        .unwrap()
+    }
+
+    // Safety: Allocations are offset within allocations of `Global`, which are valid until deallocated.
+    unsafe impl Allocator for MisalignedAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let memory = Global.allocate(misaligned_layout(layout))?.cast::<u8>();
+
+            // Safety: The allocation has room for the misalignment before the requested size.
+            Ok(NonNull::slice_from_raw_parts(
+                unsafe { memory.add(MISALIGNMENT) },
+                layout.size(),
+            ))
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            // Safety: Caller is required to ensure `ptr` was allocated by `allocate` with `layout`.
+            unsafe { Global.deallocate(ptr.sub(MISALIGNMENT), misaligned_layout(layout)) }
+        }
+    }
+
+    let slab_allocator = SlabAllocator::<_>::new_in(MisalignedAllocator);
+
+    // Span several slabs, each of which the backing allocator misaligns.
+    let allocations = (0..200)
+        .map(|index| {
+            let object = slab_allocator.allocate(LAYOUT_64).unwrap().cast::<u8>();
+
+            // Safety: Object fits `LAYOUT_64`, and is exclusively owned.
+            unsafe {
+                object.write_bytes(index, LAYOUT_64.size());
+            }
+
+            (index, object)
+        })
+        .collect::<Vec<_>>();
+
+    for (index, object) in allocations {
+        // Safety: Object was written with its index above.
+        assert!(unsafe { object.add(LAYOUT_64.size() - 1).read() } == index);
+
+        // Safety: Object was allocated with `LAYOUT_64`.
+        unsafe {
+            slab_allocator.deallocate(object, LAYOUT_64);
+        }
+    }
+    assert!(slab_allocator.outstanding_allocation_count() == 0);
+
+    // Guarded objects aren't aligned within over-sized allocations, so fail rather than land on misaligned pages.
+    let slab_allocator =
+        SlabAllocator::<_>::new_in(MisalignedAllocator).with_poisoned_guard_pages(1024);
+    assert!(
+        slab_allocator
+            .allocate(Layout::from_size_align(1024, 8).unwrap())
+            .is_err()
+    );
+}
+
+#[test]
+pub fn slab_allocator_zero_size() {
+    let zero_size = Layout::from_size_align(0, 256).unwrap();
+    let slab_allocator = SlabAllocator::<Global>::new_in(Global);
+
+    let object = slab_allocator.allocate(zero_size).unwrap();
+    assert!(object.is_empty());
+    assert!(object.cast::<u8>().addr().get() == 256);
+    // No slot was taken for the object.
+    assert!(slab_allocator.outstanding_allocation_count() == 0);
+
+    // Safety: Object was allocated with `zero_size`.
+    let object = unsafe { slab_allocator.grow(object.cast(), zero_size, LAYOUT_64) }.unwrap();
+    assert!(slab_allocator.outstanding_allocation_count() == 1);
+
+    // Safety: Object was grown to `LAYOUT_64`.
+    let object = unsafe { slab_allocator.shrink(object.cast(), LAYOUT_64, zero_size) }.unwrap();
+    assert!(object.is_empty());
+    assert!(slab_allocator.outstanding_allocation_count() == 0);
+
+    // Safety: Object was shrunk to `zero_size`.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), zero_size);
+    }
+}
+
+#[test]
+pub fn slab_allocator_fallback_tracking() {
+    static LEAKED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+    let large_layout = Layout::from_size_align(0x3000, 8).unwrap();
+    let allocator = SlabAllocator::<Global>::new_in(Global)
+        .with_leak_handler(|leak: Leak| {
+            LEAKED_BYTES.fetch_add(leak.object_size * leak.object_count, Ordering::Relaxed);
+        })
+        .with_fallback_tracking();
+    assert!(allocator.size_class_for(large_layout).is_none());
+
+    let leaked = allocator.allocate(large_layout).unwrap().cast::<u8>();
+    let object = allocator.allocate(large_layout).unwrap().cast::<u8>();
+    // Safety: Offset lies within the allocation.
+    assert!(allocator.owns(unsafe { object.add(0x1234) }));
+    assert!(allocator.fallback_stats().unwrap().live_bytes == 2 * 0x3000);
+
+    let grown_layout = Layout::from_size_align(0x5000, 8).unwrap();
+    // Safety: Object was allocated with `large_layout`.
+    let object = unsafe { allocator.grow(object, large_layout, grown_layout) }
+        .unwrap()
+        .cast::<u8>();
+    // Safety: Offset lies within the grown allocation.
+    assert!(allocator.owns(unsafe { object.add(0x4000) }));
+
+    // Safety: Object was grown to `grown_layout`.
+    unsafe {
+        allocator.deallocate(object, grown_layout);
+    }
+
+    let stats = allocator.fallback_stats().unwrap();
+    assert!(stats.live_allocation_count == 1);
+    assert!(stats.live_bytes == 0x3000);
+    assert!(stats.peak_live_bytes == 0x3000 + 0x5000);
+    assert!(stats.allocation_count == 2 && stats.deallocation_count == 1);
+
+    drop(allocator);
+    assert!(LEAKED_BYTES.load(Ordering::Relaxed) == 0x3000);
+
+    // Safety: The leaked allocation was made by `Global`, the fallback allocator, with `large_layout`.
+    unsafe {
+        Global.deallocate(leaked, large_layout);
+    }
+}
+
+#[test]
+pub fn slab_allocator_memory_tagging() {
+    /// Counts the objects tagged and the pointers untagged, without changing either.
+    struct CountingTagger {
+        tag_count: AtomicUsize,
+        untag_count: AtomicUsize,
+    }
+
+    impl MemoryTagger for CountingTagger {
+        fn tag(&self, object: NonNull<[u8]>) -> NonNull<u8> {
+            self.tag_count.fetch_add(1, Ordering::Relaxed);
+            object.cast()
+        }
+
+        fn untag(&self, ptr: NonNull<u8>) -> NonNull<u8> {
+            self.untag_count.fetch_add(1, Ordering::Relaxed);
+            ptr
+        }
+
+        fn load_tag(&self, ptr: NonNull<u8>) -> NonNull<u8> {
+            ptr
+        }
+    }
+
+    static TAGGER: CountingTagger = CountingTagger {
+        tag_count: AtomicUsize::new(0),
+        untag_count: AtomicUsize::new(0),
+    };
+
+    let slab_allocator = SlabAllocator::<_>::new_in(Global).with_memory_tagging(&TAGGER);
+
+    // Objects are tagged as they're handed out, and again as they're freed.
+    let object = slab_allocator.allocate(LAYOUT_64).unwrap();
+    assert!(TAGGER.tag_count.load(Ordering::Relaxed) == 1);
+
+    // Safety: Object was allocated with `LAYOUT_64`.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), LAYOUT_64);
+    }
+    assert!(TAGGER.tag_count.load(Ordering::Relaxed) == 2);
+    assert!(TAGGER.untag_count.load(Ordering::Relaxed) == 1);
+
+    // Allocations served by the fallback allocator are neither tagged nor untagged.
+    let large_layout = Layout::from_size_align(0x4000, 8).unwrap();
+    let large_allocation = slab_allocator.allocate(large_layout).unwrap();
+
+    // Safety: Allocation was allocated with `large_layout`.
+    unsafe {
+        slab_allocator.deallocate(large_allocation.cast(), large_layout);
+    }
+    assert!(TAGGER.tag_count.load(Ordering::Relaxed) == 2);
+    assert!(TAGGER.untag_count.load(Ordering::Relaxed) == 1);
+}
+
+#[test]
+pub fn slab_allocator_guard_pages() {
+    /// Counts the pages protected and unprotected, without changing their access.
+    struct CountingProtector {
+        protect_count: AtomicUsize,
+        unprotect_count: AtomicUsize,
+    }
+
+    impl PageProtector for CountingProtector {
+        unsafe fn protect(&self, page: NonNull<[u8]>) {
+            assert!(page.len() == DEFAULT_PAGE_SIZE);
+            self.protect_count.fetch_add(1, Ordering::Relaxed);
+        }
+
+        unsafe fn unprotect(&self, page: NonNull<[u8]>) {
+            assert!(page.len() == DEFAULT_PAGE_SIZE);
+            self.unprotect_count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    static PROTECTOR: CountingProtector = CountingProtector {
+        protect_count: AtomicUsize::new(0),
+        unprotect_count: AtomicUsize::new(0),
+    };
+
+    let slab_allocator = SlabAllocator::<_>::new_in(Global).with_guard_pages(1024, &PROTECTOR);
+    let layout = Layout::from_size_align(1024, 8).unwrap();
+    let object_size = slab_allocator.size_class_for(layout).unwrap();
+
+    // Guarded objects end their page, with the guard page protected after it.
+    let object = slab_allocator.allocate(layout).unwrap();
+    let page_offset = object.cast::<u8>().addr().get() % DEFAULT_PAGE_SIZE;
+    assert!(page_offset + object.len() <= DEFAULT_PAGE_SIZE);
+    assert!(page_offset >= DEFAULT_PAGE_SIZE - object_size);
+    assert!(PROTECTOR.protect_count.load(Ordering::Relaxed) == 1);
+    assert!(
+        slab_allocator
+            .stats()
+            .size_class(object_size)
+            .unwrap()
+            .live_object_count
+            == 0
+    );
+    assert!(slab_allocator.outstanding_allocation_count() == 1);
+
+    // Smaller size classes are still served by slabs.
+    let small_object = slab_allocator.allocate(LAYOUT_64).unwrap();
+    assert!(PROTECTOR.protect_count.load(Ordering::Relaxed) == 1);
+
+    // Safety: Objects were allocated with their layouts.
+    unsafe {
+        slab_allocator.deallocate(object.cast(), layout);
+        slab_allocator.deallocate(small_object.cast(), LAYOUT_64);
+    }
+    assert!(PROTECTOR.unprotect_count.load(Ordering::Relaxed) == 1);
+    assert!(slab_allocator.is_fully_free());
+}
+
+#[test]
+pub fn slab_allocator_poisoned_guard_pages() {
+    static OVERWRITES: AtomicUsize = AtomicUsize::new(0);
+
+    let slab_allocator = SlabAllocator::<_>::new_in(Global)
+        .with_poisoned_guard_pages(1024)
+        .with_corruption_handler(|corruption| {
+            assert!(matches!(
+                corruption,
+                Corruption::GuardPageOverwritten { .. }
+            ));
+            OVERWRITES.fetch_add(1, Ordering::Relaxed);
+        });
+    let layout = Layout::from_size_align(1000, 8).unwrap();
+
+    // Objects are placed by their requested size, so even an overflow of a single byte reaches the guard page.
+    let object = slab_allocator.allocate(layout).unwrap().cast::<u8>();
+    #[cfg(not(feature = "redzone"))]
+    assert!(DEFAULT_PAGE_SIZE - (object.addr().get() % DEFAULT_PAGE_SIZE) == layout.size());
+    // Safety: Object was allocated with the layout.
+    assert!(unsafe { slab_allocator.usable_size(object, layout) } == layout.size());
+
+    // Guarded objects are moved as they grow, so they still end against their guard page.
+    let grown_layout = Layout::from_size_align(1016, 8).unwrap();
+    // Safety: Object was allocated with the layout.
+    let grown_object = unsafe { slab_allocator.grow(object, layout, grown_layout) }
+        .unwrap()
+        .cast::<u8>();
+    assert!(grown_object != object);
+    #[cfg(not(feature = "redzone"))]
+    assert!(
+        DEFAULT_PAGE_SIZE - (grown_object.addr().get() % DEFAULT_PAGE_SIZE) == grown_layout.size()
+    );
+
+    // Safety: Object was allocated with the grown layout.
+    unsafe {
+        slab_allocator.deallocate(grown_object, grown_layout);
+    }
+
+    let object = slab_allocator.allocate(layout).unwrap().cast::<u8>();
+    let guard_offset = DEFAULT_PAGE_SIZE - (object.addr().get() % DEFAULT_PAGE_SIZE);
+
+    // Safety: The overflow into the guard page is deliberate, and is reported rather than freed.
+    unsafe {
+        let guard_byte = object.add(guard_offset).read();
+        object.add(guard_offset).write(!guard_byte);
+        slab_allocator.deallocate(object, layout);
+        assert!(OVERWRITES.load(Ordering::Relaxed) == 1);
+
+        // Once the guard page is restored, the object is freed.
+        object.add(guard_offset).write(guard_byte);
+        slab_allocator.deallocate(object, layout);
+    }
+    assert!(OVERWRITES.load(Ordering::Relaxed) == 1);
+    assert!(slab_allocator.is_fully_free());
+}
+
+#[test]
+pub fn slab_allocator_secure_wipe() {
+    /// Counts the slabs and pages released to it, and those of them not zeroed.
+    struct WipeCheckingAllocator {
+        released_count: AtomicUsize,
+        unwiped_count: AtomicUsize,
+    }
+
+    unsafe impl Allocator for WipeCheckingAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            // Slabs and pages are the only page-aligned memory of the allocator.
+            if layout.align() >= DEFAULT_PAGE_SIZE {
+                self.released_count.fetch_add(1, Ordering::Relaxed);
+
+                // Safety: Memory is valid for `layout` until it is deallocated.
+                let memory = unsafe { NonNull::slice_from_raw_parts(ptr, layout.size()).as_ref() };
+                if memory.iter().any(|byte| *byte != 0) {
+                    self.unwiped_count.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+
+            // Safety: Memory was allocated by `Global` with `layout`.
+            unsafe {
+                Global.deallocate(ptr, layout);
+            }
+        }
+    }
+
+    static BACKING: WipeCheckingAllocator = WipeCheckingAllocator {
+        released_count: AtomicUsize::new(0),
+        unwiped_count: AtomicUsize::new(0),
+    };
+
+    let slab_allocator = SlabAllocator::<_>::new_in(&BACKING).with_secure_wipe();
+    let page_layout = Layout::from_size_align(DEFAULT_PAGE_SIZE, DEFAULT_PAGE_SIZE).unwrap();
+
+    // With redzones, pages are served by the fallback allocator, which isn't wiped.
+    let layouts = [LAYOUT_64, page_layout]
+        .into_iter()
+        .filter(|layout| slab_allocator.size_class_for(*layout).is_some())
+        .collect::<std::vec::Vec<_>>();
+
+    for layout in layouts.iter().copied() {
+        let object = slab_allocator.allocate(layout).unwrap();
+
+        // Safety: Object was just allocated with `layout`, and is written before it is freed.
+        unsafe {
+            object.cast::<u8>().write_bytes(0xA5, object.len());
+            slab_allocator.deallocate(object.cast(), layout);
+        }
+    }
+
+    // The slab and the cached page are released as the allocator is dropped.
+    drop(slab_allocator);
+    assert!(BACKING.released_count.load(Ordering::Relaxed) == layouts.len());
+    assert!(BACKING.unwiped_count.load(Ordering::Relaxed) == 0);
+}
+
+#[cfg(feature = "allocation-sites")]
+#[test]
+pub fn slab_allocator_allocation_sites() {
+    use crate::{AllocationSite, AllocationSiteStats};
+
+    let slab_allocator = SlabAllocator::<_>::new_in(Global).with_allocation_sites();
+
+    let mut objects = [NonNull::slice_from_raw_parts(NonNull::dangling(), 0); 2];
+    for object in &mut objects {
+        *object = slab_allocator.allocate(LAYOUT_64).unwrap();
+    }
+    let tagged_object = slab_allocator
+        .allocate_at(LAYOUT_256, AllocationSite::Tag("buffers"))
+        .unwrap();
+
+    let sites = slab_allocator.allocation_sites().unwrap();
+    assert!(sites.len() == 2);
+    assert!(
+        sites[0]
+            == AllocationSiteStats {
+                site: AllocationSite::Tag("buffers"),
+                live_allocation_count: 1,
+                live_bytes: LAYOUT_256.size(),
+            }
+    );
+    assert!(
+        matches!(sites[1].site, AllocationSite::Location(location) if location.file() == file!())
+    );
+    assert!(sites[1].live_allocation_count == 2);
+    assert!(sites[1].live_bytes == 2 * LAYOUT_64.size());
+
+    // Reallocated objects keep the site they were first allocated at.
+    // Safety: Object was allocated with `LAYOUT_256`.
+    let tagged_object =
+        unsafe { slab_allocator.grow(tagged_object.cast(), LAYOUT_256, LAYOUT_1024) }.unwrap();
+
+    let sites = slab_allocator.allocation_sites().unwrap();
+    assert!(sites[0].site == AllocationSite::Tag("buffers"));
+    assert!(sites[0].live_bytes == LAYOUT_1024.size());
+
+    // Safety: Objects were allocated with their layouts.
+    unsafe {
+        slab_allocator.deallocate_many(&objects.map(NonNull::cast), LAYOUT_64);
+        slab_allocator.deallocate(tagged_object.cast(), LAYOUT_1024);
+    }
+    assert!(slab_allocator.allocation_sites().unwrap().is_empty());
+}
+
+#[test]
+pub fn slab_allocator_sampling() {
+    use crate::{AllocationSample, Clock};
+    use core::time::Duration;
+
+    struct FixedClock;
+
+    impl Clock for FixedClock {
+        fn now(&self) -> Duration {
+            Duration::from_secs(42)
+        }
+    }
+
+    static SAMPLE_COUNT: AtomicUsize = AtomicUsize::new(0);
+    static SLAB_SAMPLE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    fn record_sample(sample: AllocationSample) {
+        assert!(sample.timestamp == Duration::from_secs(42));
+
+        if sample.size_class.is_some() {
+            assert!(sample.layout == LAYOUT_64);
+            SLAB_SAMPLE_COUNT.fetch_add(1, Ordering::Relaxed);
+        } else {
+            assert!(sample.layout.size() == 4 * DEFAULT_PAGE_SIZE);
+        }
+
+        SAMPLE_COUNT.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let slab_allocator =
+        SlabAllocator::<_>::new_in(Global).with_sampling(4, &FixedClock, record_sample);
+
+    // Every 4th of the 11 slab allocations is sampled, whether allocated singly or in bulk.
+    let objects = (0..6)
+        .map(|_| slab_allocator.allocate(LAYOUT_64).unwrap())
+        .collect::<Vec<_>>();
+    let mut many_objects = [NonNull::slice_from_raw_parts(NonNull::dangling(), 0); 5];
+    assert!(slab_allocator.allocate_many(LAYOUT_64, &mut many_objects) == 5);
+    assert!(SAMPLE_COUNT.load(Ordering::Relaxed) == 2);
+
+    // The 12th is served by the fallback allocator.
+    let fallback_layout = Layout::from_size_align(4 * DEFAULT_PAGE_SIZE, 8).unwrap();
+    let fallback_object = slab_allocator.allocate(fallback_layout).unwrap();
+    assert!(SAMPLE_COUNT.load(Ordering::Relaxed) == 3);
+    assert!(SLAB_SAMPLE_COUNT.load(Ordering::Relaxed) == 2);
+
+    // Safety: Allocations are returned identically to their allocator.
+    unsafe {
+        for object in objects.iter().chain(&many_objects) {
+            slab_allocator.deallocate(object.cast(), LAYOUT_64);
+        }
+        slab_allocator.deallocate(fallback_object.cast(), fallback_layout);
+    }
+
+    // Frees aren't sampled.
+    assert!(SAMPLE_COUNT.load(Ordering::Relaxed) == 3);
+}
+
+#[test]
+pub fn slab_allocator_hooks() {
+    use crate::AllocatorHooks;
+
+    struct CountingHooks {
+        created_slab_count: AtomicUsize,
+        destroyed_slab_count: AtomicUsize,
+        fallthrough_count: AtomicUsize,
+        failure_count: AtomicUsize,
+    }
+
+    impl AllocatorHooks for CountingHooks {
+        fn on_slab_created(&self, _: usize, _: NonNull<[u8]>) {
+            self.created_slab_count.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_slab_destroyed(&self, _: usize, _: NonNull<[u8]>) {
+            self.destroyed_slab_count.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_fallthrough(&self, _: Layout) {
+            self.fallthrough_count.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_alloc_failure(&self, _: Layout) {
+            self.failure_count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    static HOOKS: CountingHooks = CountingHooks {
+        created_slab_count: AtomicUsize::new(0),
+        destroyed_slab_count: AtomicUsize::new(0),
+        fallthrough_count: AtomicUsize::new(0),
+        failure_count: AtomicUsize::new(0),
+    };
+
+    // Only a single slab fits the limit.
+    static MEMORY_LIMIT: MemoryLimit = MemoryLimit::new(DEFAULT_PAGE_SIZE);
+
+    let slab_allocator = SlabAllocator::<_>::new_in(Global)
+        .with_memory_limit(&MEMORY_LIMIT)
+        .with_hooks(&HOOKS);
+
+    let object = slab_allocator.allocate(LAYOUT_64).unwrap();
+    assert!(HOOKS.created_slab_count.load(Ordering::Relaxed) == 1);
+
+    let fallback_layout = Layout::from_size_align(4 * DEFAULT_PAGE_SIZE, 8).unwrap();
+    let fallback_object = slab_allocator.allocate(fallback_layout).unwrap();
+    assert!(HOOKS.fallthrough_count.load(Ordering::Relaxed) == 1);
+    assert!(HOOKS.failure_count.load(Ordering::Relaxed) == 0);
+
+    // Allocations fail once the slab is full, as another would exceed the limit.
+    let objects = core::iter::from_fn(|| slab_allocator.allocate(LAYOUT_64).ok())
+        .take(DEFAULT_PAGE_SIZE)
+        .collect::<Vec<_>>();
+    assert!(objects.len() < DEFAULT_PAGE_SIZE);
+    assert!(HOOKS.created_slab_count.load(Ordering::Relaxed) == 1);
+    assert!(HOOKS.failure_count.load(Ordering::Relaxed) == 1);
+
+    // Safety: Allocations are returned identically to their allocator.
+    unsafe {
+        for object in objects.iter().chain([&object]) {
+            slab_allocator.deallocate(object.cast(), LAYOUT_64);
+        }
+        slab_allocator.deallocate(fallback_object.cast(), fallback_layout);
+    }
+
+    drop(slab_allocator);
+    assert!(HOOKS.destroyed_slab_count.load(Ordering::Relaxed) == 1);
+}
+
+#[test]
+pub fn slab_manager_standalone() {
+    let mut slab_manager = SlabManager::<_, DefaultRawMutex>::new_in(256, Global);
+    slab_manager.set_slab_page_count(2);
+
+    // Slabs are handed to the caller as they are created, before any of their objects.
+    let created_object_count = AtomicUsize::new(0);
+    let object = slab_manager
+        .next_object_with(|slab| {
+            assert!(slab.object_size() == 256);
+            assert!(slab.layout().size() == 2 * DEFAULT_PAGE_SIZE);
+            assert!(slab.allocated_objects().count() == 0);
+            created_object_count.fetch_add(slab.object_count(), Ordering::Relaxed);
+        })
+        .unwrap();
+    assert!(created_object_count.load(Ordering::Relaxed) == slab_manager.objects_per_slab());
+    assert!(slab_manager.remaining_object_count() == slab_manager.objects_per_slab() - 1);
+    assert!(slab_manager.owns(object.cast()));
+
+    // Safety: Object was taken from `slab_manager`, and is no longer used.
+    unsafe {
+        slab_manager.return_object(object.cast());
+    }
+
+    assert!(slab_manager.remaining_object_count() == slab_manager.objects_per_slab());
+
+    // Reserved slabs are held empty until they are reclaimed.
+    slab_manager
+        .reserve(2 * slab_manager.objects_per_slab())
+        .unwrap();
+    assert!(slab_manager.empty_slab_count() == 1);
+
+    let released_slab_count = AtomicUsize::new(0);
+    let released_bytes = slab_manager.reclaim_empty_with(|slab| {
+        assert!(slab.free_objects().count() == slab.object_count());
+        released_slab_count.fetch_add(1, Ordering::Relaxed);
+    });
+    assert!(released_slab_count.load(Ordering::Relaxed) == 1);
+    assert!(released_bytes == 2 * DEFAULT_PAGE_SIZE);
+    assert!(slab_manager.remaining_object_count() == slab_manager.objects_per_slab());
+}
+
+#[test]
+#[should_panic = "invalid object size"]
+pub fn slab_manager_invalid_object_size() {
+    let _ = SlabManager::<_, DefaultRawMutex>::new_in(DEFAULT_PAGE_SIZE, Global);
 }
@@ -0,0 +1,233 @@
+use crate::{
+    DEFAULT_PAGE_SIZE, DefaultRawMutex, SlabAllocator,
+    alloc_api::{AllocError, Allocator, Vec},
+};
+use core::{alloc::Layout, ptr::NonNull};
+use lock_api::{Mutex, RawMutex};
+
+/// Allocation recorded by a [`TrackingAllocator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackedAllocation {
+    /// Address of the allocation.
+    pub ptr: NonNull<u8>,
+
+    /// Layout the allocation was made with.
+    pub layout: Layout,
+
+    /// Object size of the size class the layout maps to, or `None` if it is served by the fallback allocator.
+    pub size_class: Option<usize>,
+}
+
+/// Allocation or deallocation recorded by a [`TrackingAllocator`], in the order they were made.
+///
+/// Growing or shrinking an allocation is recorded as the deallocation of the old allocation, then the allocation of
+/// the new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackingEvent {
+    /// An allocation was made.
+    Allocate(TrackedAllocation),
+
+    /// An allocation was freed.
+    Deallocate(TrackedAllocation),
+}
+
+/// Allocations and events of a tracking allocator.
+struct Shadow {
+    live_allocations: Vec<TrackedAllocation>,
+    events: Vec<TrackingEvent>,
+}
+
+// Safety: Recorded pointers are only compared, never dereferenced.
+unsafe impl Send for Shadow {}
+
+/// [`SlabAllocator`] wrapper recording every allocation and deallocation made through it, for tests of code built on
+/// the allocator.
+///
+/// Freeing a pointer that isn't allocated, or with a layout other than it was allocated with, panics before the
+/// pointer reaches the allocator, so double frees and mismatched layouts are caught where they happen. Leaks are
+/// caught by [`TrackingAllocator::assert_no_leaks`]. Records are kept in the global allocator, so the tracked
+/// allocator can be backed by anything.
+pub struct TrackingAllocator<
+    A: Allocator,
+    R: RawMutex = DefaultRawMutex,
+    const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE,
+    F: Allocator = A,
+> {
+    slab_allocator: SlabAllocator<A, R, PAGE_SIZE, F>,
+    shadow: Mutex<R, Shadow>,
+}
+
+impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator>
+    TrackingAllocator<A, R, PAGE_SIZE, F>
+{
+    /// Tracks allocations made through `slab_allocator`.
+    pub fn new(slab_allocator: SlabAllocator<A, R, PAGE_SIZE, F>) -> Self {
+        Self {
+            slab_allocator,
+            shadow: Mutex::new(Shadow {
+                live_allocations: Vec::new(),
+                events: Vec::new(),
+            }),
+        }
+    }
+
+    /// The tracked allocator.
+    pub fn slab_allocator(&self) -> &SlabAllocator<A, R, PAGE_SIZE, F> {
+        &self.slab_allocator
+    }
+
+    /// Stops tracking, returning the tracked allocator.
+    pub fn into_inner(self) -> SlabAllocator<A, R, PAGE_SIZE, F> {
+        self.slab_allocator
+    }
+
+    /// Allocations that haven't been freed, in the order they were made.
+    pub fn live_allocations(&self) -> Vec<TrackedAllocation> {
+        self.shadow.lock().live_allocations.clone()
+    }
+
+    /// Every allocation and deallocation made, in order.
+    pub fn events(&self) -> Vec<TrackingEvent> {
+        self.shadow.lock().events.clone()
+    }
+
+    /// Asserts every allocation has been freed.
+    ///
+    /// # Panics
+    ///
+    /// - If any allocation hasn't been freed.
+    pub fn assert_no_leaks(&self) {
+        let shadow = self.shadow.lock();
+
+        assert!(
+            shadow.live_allocations.is_empty(),
+            "{} allocations were leaked: {:?}",
+            shadow.live_allocations.len(),
+            shadow.live_allocations
+        );
+    }
+
+    /// Records an allocation of `layout` at `ptr`.
+    fn record_allocation(&self, ptr: NonNull<u8>, layout: Layout) {
+        let allocation = TrackedAllocation {
+            ptr,
+            layout,
+            size_class: self.slab_allocator.size_class_for(layout),
+        };
+
+        let mut shadow = self.shadow.lock();
+        shadow.live_allocations.push(allocation);
+        shadow.events.push(TrackingEvent::Allocate(allocation));
+    }
+
+    /// Records the deallocation of `ptr`, which is about to be freed with `layout`.
+    ///
+    /// # Panics
+    ///
+    /// - If `ptr` isn't allocated, or was allocated with a layout other than `layout`.
+    fn record_deallocation(&self, ptr: NonNull<u8>, layout: Layout) {
+        let mut shadow = self.shadow.lock();
+
+        let index = shadow
+            .live_allocations
+            .iter()
+            .position(|allocation| allocation.ptr == ptr)
+            .unwrap_or_else(|| {
+                panic!("{ptr:?} freed while not allocated, such as by a double free")
+            });
+
+        let allocation = shadow.live_allocations.remove(index);
+
+        assert!(
+            allocation.layout == layout,
+            "{ptr:?} allocated with {:?} but freed with {layout:?}",
+            allocation.layout
+        );
+
+        shadow.events.push(TrackingEvent::Deallocate(allocation));
+    }
+
+    /// Records the result of resizing `ptr` from `old_layout` to `new_layout`, whose deallocation was already
+    /// recorded. On failure the old allocation is left in place, so it is recorded again.
+    fn record_resize(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        memory: Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        match memory {
+            Ok(memory) => self.record_allocation(memory.cast(), new_layout),
+            Err(AllocError) => self.record_allocation(ptr, old_layout),
+        }
+
+        memory
+    }
+}
+
+// Safety: Every call is forwarded to the tracked allocator, which upholds the allocator's invariants.
+unsafe impl<A: Allocator, R: RawMutex, const PAGE_SIZE: usize, F: Allocator> Allocator
+    for TrackingAllocator<A, R, PAGE_SIZE, F>
+{
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = self.slab_allocator.allocate(layout)?;
+        self.record_allocation(memory.cast(), layout);
+
+        Ok(memory)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = self.slab_allocator.allocate_zeroed(layout)?;
+        self.record_allocation(memory.cast(), layout);
+
+        Ok(memory)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.record_deallocation(ptr, old_layout);
+
+        // Safety: Caller is required to maintain safety invariants.
+        let memory = unsafe { self.slab_allocator.grow(ptr, old_layout, new_layout) };
+        self.record_resize(ptr, old_layout, new_layout, memory)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.record_deallocation(ptr, old_layout);
+
+        // Safety: Caller is required to maintain safety invariants.
+        let memory = unsafe { self.slab_allocator.grow_zeroed(ptr, old_layout, new_layout) };
+        self.record_resize(ptr, old_layout, new_layout, memory)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.record_deallocation(ptr, old_layout);
+
+        // Safety: Caller is required to maintain safety invariants.
+        let memory = unsafe { self.slab_allocator.shrink(ptr, old_layout, new_layout) };
+        self.record_resize(ptr, old_layout, new_layout, memory)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.record_deallocation(ptr, layout);
+
+        // Safety: Caller is required to maintain safety invariants.
+        unsafe {
+            self.slab_allocator.deallocate(ptr, layout);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use crate::slab::SlabHeader;
+use core::{cell::Cell, ptr::NonNull};
+
+/// Search tree of slabs ordered by address, threaded through their headers, so slabs can be found by address without
+/// allocating.
+///
+/// The tree is a treap: ordered by address, and heap-ordered by a hash of each address, so its shape is that of a
+/// tree built from randomly ordered insertions, and lookups take logarithmic time on average. As the priorities are
+/// derived from the addresses, headers need only hold the links to their children.
+pub(crate) struct SlabTree {
+    root: Cell<Option<NonNull<SlabHeader>>>,
+}
+
+// Safety: Headers are only linked and unlinked with the owning manager's lists locked.
+unsafe impl Send for SlabTree {}
+
+/// Priority of the slab at `address`, drawn from the bits of its address by a 64-bit finalizer, as slab addresses
+/// differ only in their higher bits.
+fn priority(address: usize) -> u64 {
+    let mut hash = address as u64;
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+    hash ^ (hash >> 33)
+}
+
+/// # Safety
+///
+/// - `header` must be linked into a tree, whose owning manager's lists are locked.
+unsafe fn header_ref<'a>(header: NonNull<SlabHeader>) -> &'a SlabHeader {
+    // Safety: Caller is required to ensure the header is linked into a tree, so belongs to a live slab.
+    unsafe { header.as_ref() }
+}
+
+impl SlabTree {
+    pub const fn new() -> Self {
+        Self {
+            root: Cell::new(None),
+        }
+    }
+
+    /// Header of the slab starting at `address`, if it is linked into the tree.
+    pub fn get(&self, address: usize) -> Option<NonNull<SlabHeader>> {
+        let mut node = self.root.get();
+
+        while let Some(header) = node {
+            // Safety: Headers linked into the tree are live.
+            let header_ref = unsafe { header_ref(header) };
+
+            node = match address.cmp(&header_ref.address()) {
+                core::cmp::Ordering::Less => header_ref.left(),
+                core::cmp::Ordering::Greater => header_ref.right(),
+                core::cmp::Ordering::Equal => return Some(header),
+            };
+        }
+
+        None
+    }
+
+    /// Header of the last slab starting before `address`, if any.
+    pub fn last_before(&self, address: usize) -> Option<NonNull<SlabHeader>> {
+        let mut node = self.root.get();
+        let mut last = None;
+
+        while let Some(header) = node {
+            // Safety: Headers linked into the tree are live.
+            let header_ref = unsafe { header_ref(header) };
+
+            node = if header_ref.address() < address {
+                last = Some(header);
+                header_ref.right()
+            } else {
+                header_ref.left()
+            };
+        }
+
+        last
+    }
+
+    /// Header of the first slab starting after `address`, if any.
+    pub fn first_after(&self, address: usize) -> Option<NonNull<SlabHeader>> {
+        let mut node = self.root.get();
+        let mut first = None;
+
+        while let Some(header) = node {
+            // Safety: Headers linked into the tree are live.
+            let header_ref = unsafe { header_ref(header) };
+
+            node = if header_ref.address() > address {
+                first = Some(header);
+                header_ref.left()
+            } else {
+                header_ref.right()
+            };
+        }
+
+        first
+    }
+
+    /// Links the slab headed by `header` into the tree.
+    ///
+    /// # Safety
+    ///
+    /// - `header` must point to the header of a live slab that isn't linked into any tree, and whose memory doesn't
+    ///   start at the address of any slab linked into this tree.
+    pub unsafe fn insert(&mut self, header: NonNull<SlabHeader>) {
+        // Safety: Caller is required to ensure `header` is valid.
+        let new = unsafe { header_ref(header) };
+        let address = new.address();
+        let new_priority = priority(address);
+
+        // Descend to the first slab of lower priority, which the new slab takes the place of.
+        let mut link = &self.root;
+        while let Some(node) = link.get() {
+            // Safety: Headers linked into the tree are live, and the tree is locked, as it is borrowed mutably.
+            let node = unsafe { header_ref(node) };
+            if priority(node.address()) < new_priority {
+                break;
+            }
+
+            link = if address < node.address() {
+                node.left_link()
+            } else {
+                node.right_link()
+            };
+        }
+
+        // Split the displaced subtree around the new slab's address, into its left and right subtrees.
+        let (mut left_link, mut right_link) = (new.left_link(), new.right_link());
+        let mut node = link.get();
+
+        while let Some(header) = node {
+            // Safety: Headers linked into the tree are live, and the tree is locked, as it is borrowed mutably.
+            let header_ref = unsafe { header_ref(header) };
+
+            if header_ref.address() < address {
+                node = header_ref.right();
+                left_link.set(Some(header));
+                left_link = header_ref.right_link();
+            } else {
+                node = header_ref.left();
+                right_link.set(Some(header));
+                right_link = header_ref.left_link();
+            }
+        }
+
+        left_link.set(None);
+        right_link.set(None);
+        link.set(Some(header));
+    }
+
+    /// Unlinks the slab headed by `header`.
+    ///
+    /// # Safety
+    ///
+    /// - `header` must be linked into this tree.
+    pub unsafe fn remove(&mut self, header: &SlabHeader) {
+        let address = header.address();
+
+        let mut link = &self.root;
+        loop {
+            let node = link.get().expect("slab is not linked into the tree");
+            // Safety: Headers linked into the tree are live, and the tree is locked, as it is borrowed mutably.
+            let node = unsafe { header_ref(node) };
+
+            link = match address.cmp(&node.address()) {
+                core::cmp::Ordering::Less => node.left_link(),
+                core::cmp::Ordering::Greater => node.right_link(),
+                core::cmp::Ordering::Equal => break,
+            };
+        }
+
+        // Merge the slab's subtrees in its place, keeping the slab of higher priority above at each step.
+        let (mut left, mut right) = (header.left(), header.right());
+        loop {
+            match (left, right) {
+                (None, remaining) | (remaining, None) => {
+                    link.set(remaining);
+                    break;
+                }
+
+                (Some(left_header), Some(right_header)) => {
+                    // Safety: Headers linked into the tree are live, and the tree is locked, as it is borrowed
+                    //         mutably.
+                    let (left_ref, right_ref) =
+                        unsafe { (header_ref(left_header), header_ref(right_header)) };
+
+                    if priority(left_ref.address()) > priority(right_ref.address()) {
+                        link.set(Some(left_header));
+                        link = left_ref.right_link();
+                        left = left_ref.right();
+                    } else {
+                        link.set(Some(right_header));
+                        link = right_ref.left_link();
+                        right = right_ref.left();
+                    }
+                }
+            }
+        }
+
+        header.left_link().set(None);
+        header.right_link().set(None);
+    }
+}
+
+impl core::fmt::Debug for SlabTree {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SlabTree")
+            .field("root", &self.root.get())
+            .finish()
+    }
+}